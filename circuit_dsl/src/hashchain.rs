@@ -0,0 +1,32 @@
+//! Hash chain, proving the knowledge of preimages to the tail of a Poseidon hash chain with a
+//! configurable number of elements per node. The state is the current tail, the preimage of step
+//! `i` is derived from `i` and the seed.
+use crate::{mix, Builder, Step};
+use num_bigint::BigUint;
+
+#[derive(Clone, Copy, Debug)]
+pub struct HashChain {
+    pub num_elts_per_step: usize,
+    pub seed: u64,
+}
+
+impl HashChain {
+    /// `k`-th preimage element of step `i`.
+    pub fn preimage(&self, i: usize, k: usize) -> u64 {
+        mix(mix(self.seed) ^ (i * self.num_elts_per_step + k) as u64)
+    }
+}
+
+impl Step for HashChain {
+    const ARITY: usize = 1;
+
+    fn synthesize<B: Builder>(&self, b: &mut B, i: usize, z: &[B::Var]) -> Result<Vec<B::Var>, B::Error> {
+        // z provides the running digest
+        let mut m = z.to_vec();
+        for k in 0..self.num_elts_per_step {
+            m.push(b.alloc(|| BigUint::from(self.preimage(i, k)))?);
+        }
+
+        Ok(vec![b.poseidon(&m)?])
+    }
+}
@@ -0,0 +1,192 @@
+//! Write-once description of step circuits. A workload is a [`Step`] written against the
+//! [`Builder`] operations, which the `dsl` module of the Nova, Sonobe and Protostar crates lowers
+//! to a bellpepper `StepCircuit`, an arkworks `FCircuit` and a halo2 step circuit, along with a
+//! native builder computing the next state.
+//!
+//! Values cross the builders as canonical integers, so a step does not depend on the field types
+//! of any framework. Poseidon lowers to the native sponge of each framework, whose parameters
+//! differ, so hash outputs only agree within a framework.
+//!
+//! The MinRoot, hash chain and range check benches of each crate run the DSL lowerings. The
+//! hand-written circuits of these workloads are only kept as constraint-count baselines in the
+//! tests of the `dsl` modules, except for the degree-5 MinRoot gate of Protostar, which stays
+//! hand-written as the DSL has no custom gates. The other workloads (signatures, SHA-256, Keccak,
+//! Merkle updates, random circuits) stay hand-written, as they need gadgets the DSL does not offer.
+
+use num_bigint::BigUint;
+use std::fmt::Debug;
+
+pub mod hashchain;
+pub mod minroot;
+pub mod range_check;
+
+/// Operations a step circuit is written with.
+pub trait Builder {
+    type Var: Clone;
+    type Error;
+
+    /// Modulus of the field the step is built over.
+    fn modulus(&self) -> BigUint;
+
+    fn constant(&mut self, value: &BigUint) -> Result<Self::Var, Self::Error>;
+
+    /// Allocates a witness, `value` is only called when the witness is computed.
+    fn alloc(&mut self, value: impl FnOnce() -> BigUint) -> Result<Self::Var, Self::Error>;
+
+    /// Allocates a witness computed from the values of `inputs`, without constraining it.
+    fn hint(
+        &mut self,
+        inputs: &[Self::Var],
+        f: impl FnOnce(&[BigUint]) -> BigUint,
+    ) -> Result<Self::Var, Self::Error>;
+
+    fn add(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error>;
+
+    fn mul(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error>;
+
+    fn enforce_eq(&mut self, a: &Self::Var, b: &Self::Var) -> Result<(), Self::Error>;
+
+    /// Absorbs `inputs` in the Poseidon sponge of the framework and squeezes a single element.
+    fn poseidon(&mut self, inputs: &[Self::Var]) -> Result<Self::Var, Self::Error>;
+
+    /// Checks that `a` fits in `num_bits` bits. The default decomposes it into boolean witnesses
+    /// with the other operations, frameworks with a cheaper gadget override it.
+    fn range_check(&mut self, a: &Self::Var, num_bits: usize) -> Result<(), Self::Error> {
+        let mut sum: Option<Self::Var> = None;
+        for k in 0..num_bits {
+            let bit = self.hint(std::slice::from_ref(a), |values| (&values[0] >> k) & BigUint::from(1u8))?;
+            let square = self.mul(&bit, &bit)?;
+            self.enforce_eq(&square, &bit)?;
+
+            let term = match k {
+                0 => bit,
+                _ => {
+                    let power = self.constant(&(BigUint::from(1u8) << k))?;
+                    self.mul(&bit, &power)?
+                }
+            };
+            sum = Some(match sum {
+                Some(sum) => self.add(&sum, &term)?,
+                None => term,
+            });
+        }
+
+        match sum {
+            Some(sum) => self.enforce_eq(&sum, a),
+            None => {
+                let zero = self.constant(&BigUint::from(0u8))?;
+                self.enforce_eq(&zero, a)
+            }
+        }
+    }
+}
+
+/// A step circuit written once for every framework.
+pub trait Step: Clone + Debug + Send + Sync {
+    /// Number of elements of the state.
+    const ARITY: usize;
+
+    /// Constraints of step `i` from the state `z`, returns the next state. Advice that is not a
+    /// function of `z` must be derived from `i`, as some folding schemes keep a single instance
+    /// of the circuit across steps.
+    fn synthesize<B: Builder>(&self, b: &mut B, i: usize, z: &[B::Var]) -> Result<Vec<B::Var>, B::Error>;
+}
+
+/// splitmix64 finalizer, enough to get well-spread advice without a rng in the circuit.
+pub fn mix(x: u64) -> u64 {
+    let mut x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+#[test]
+fn mix_test() {
+    // first output of splitmix64 seeded with 0
+    assert_eq!(mix(0), 0xe220a8397b1dcdaf);
+    assert_ne!(mix(1), mix(0));
+}
+
+#[test]
+fn native_step_test() {
+    use crate::{hashchain::HashChain, minroot::MinRoot, range_check::RangeCheck};
+
+    // the operations on integers modulo p, counting the equalities
+    struct ModBuilder {
+        p: BigUint,
+        num_eqs: usize,
+    }
+
+    impl Builder for ModBuilder {
+        type Var = BigUint;
+        type Error = String;
+
+        fn modulus(&self) -> BigUint {
+            self.p.clone()
+        }
+
+        fn constant(&mut self, value: &BigUint) -> Result<BigUint, String> {
+            Ok(value % &self.p)
+        }
+
+        fn alloc(&mut self, value: impl FnOnce() -> BigUint) -> Result<BigUint, String> {
+            Ok(value() % &self.p)
+        }
+
+        fn hint(&mut self, inputs: &[BigUint], f: impl FnOnce(&[BigUint]) -> BigUint) -> Result<BigUint, String> {
+            Ok(f(inputs) % &self.p)
+        }
+
+        fn add(&mut self, a: &BigUint, b: &BigUint) -> Result<BigUint, String> {
+            Ok((a + b) % &self.p)
+        }
+
+        fn mul(&mut self, a: &BigUint, b: &BigUint) -> Result<BigUint, String> {
+            Ok((a * b) % &self.p)
+        }
+
+        fn enforce_eq(&mut self, a: &BigUint, b: &BigUint) -> Result<(), String> {
+            self.num_eqs += 1;
+            match a == b {
+                true => Ok(()),
+                false => Err(format!("{} != {}", a, b)),
+            }
+        }
+
+        // a stand-in for the sponge, the steps only pass it their inputs
+        fn poseidon(&mut self, inputs: &[BigUint]) -> Result<BigUint, String> {
+            Ok(inputs.iter().fold(BigUint::from(0u8), |acc, x| (acc * 3u32 + x) % &self.p))
+        }
+    }
+
+    // Pallas scalar field
+    let p = BigUint::parse_bytes(b"40000000000000000000000000000000224698fc094cf91b992d30ed00000001", 16).unwrap();
+    let mut b = ModBuilder { p: p.clone(), num_eqs: 0 };
+    let n = |x: u64| BigUint::from(x);
+
+    // the default range check spends one equality per bit and one for the sum
+    b.range_check(&n(5), 3).unwrap();
+    assert_eq!(b.num_eqs, 4);
+    assert!(b.range_check(&n(8), 3).is_err());
+
+    let z = MinRoot { num_iters_per_step: 4 }.synthesize(&mut b, 0, &[n(0), n(0), n(1)]).unwrap();
+    let exp = minroot::fifth_root_exponent(&p);
+    let (mut x, mut y) = (n(0), n(1));
+    for i in 0..4u64 {
+        (x, y) = ((&x + &y).modpow(&exp, &p), &x + i);
+    }
+    assert_eq!(z, vec![n(4), x, y]);
+
+    let step = HashChain { num_elts_per_step: 3, seed: 7 };
+    let z = step.synthesize(&mut b, 2, &[n(11)]).unwrap();
+    let preimage = (0..3).map(|k| n(step.preimage(2, k)));
+    assert_eq!(z, vec![b.poseidon(&[vec![n(11)], preimage.collect()].concat()).unwrap()]);
+
+    let step = RangeCheck { num_adds_per_step: 3 };
+    let z = step.synthesize(&mut b, 5, &[n(u64::MAX)]).unwrap();
+    let acc = (0..3).fold(u64::MAX, |acc, j| acc.wrapping_add(step.addend(5, j)));
+    assert_eq!(z, vec![n(acc)]);
+
+    // an accumulator out of range carries more than one bit
+    assert!(step.synthesize(&mut b, 5, &[n(u64::MAX) << 2]).is_err());
+}
@@ -0,0 +1,61 @@
+//! MinRoot, executing a configurable number of iterations of the `MinRoot` function per step:
+//! `x_{i+1} = (x_i + y_i)^{1/5}`, `y_{i+1} = x_i + i`, checked as `x_{i+1}^5 = x_i + y_i` through
+//! two squarings and a multiplication. The fifth root is a hint, the state is `(i, x_i, y_i)`.
+use crate::{Builder, Step};
+use num_bigint::BigUint;
+
+#[derive(Clone, Copy, Debug)]
+pub struct MinRoot {
+    pub num_iters_per_step: usize,
+}
+
+/// Exponent of the fifth root modulo `p`, the inverse of 5 modulo `p - 1`.
+pub fn fifth_root_exponent(p: &BigUint) -> BigUint {
+    let p_minus_one = p - 1u32;
+    (1u32..5)
+        .map(|t| &p_minus_one * t + 1u32)
+        .find(|n| (n % 5u32) == BigUint::from(0u32))
+        .expect("5 must not divide p - 1")
+        / 5u32
+}
+
+impl Step for MinRoot {
+    const ARITY: usize = 3;
+
+    fn synthesize<B: Builder>(&self, b: &mut B, _i: usize, z: &[B::Var]) -> Result<Vec<B::Var>, B::Error> {
+        let p = b.modulus();
+        let exp = fifth_root_exponent(&p);
+        let one = b.constant(&BigUint::from(1u8))?;
+
+        let [mut i, mut x, mut y] = [z[0].clone(), z[1].clone(), z[2].clone()];
+        for _ in 0..self.num_iters_per_step {
+            let x_next = b.hint(&[x.clone(), y.clone()], |values| ((&values[0] + &values[1]) % &p).modpow(&exp, &p))?;
+
+            // x_next^5 = x + y
+            let sq = b.mul(&x_next, &x_next)?;
+            let quad = b.mul(&sq, &sq)?;
+            let fifth = b.mul(&quad, &x_next)?;
+            let sum = b.add(&x, &y)?;
+            b.enforce_eq(&fifth, &sum)?;
+
+            let y_next = b.add(&x, &i)?;
+            i = b.add(&i, &one)?;
+            x = x_next;
+            y = y_next;
+        }
+
+        Ok(vec![i, x, y])
+    }
+}
+
+#[test]
+fn fifth_root_exponent_test() {
+    // 5 * 41 = 2 * 102 + 1
+    assert_eq!(fifth_root_exponent(&BigUint::from(103u32)), BigUint::from(41u32));
+
+    let p = BigUint::from(103u32);
+    for x in 0u32..103 {
+        let x = BigUint::from(x);
+        assert_eq!(x.modpow(&BigUint::from(5u32), &p).modpow(&fifth_root_exponent(&p), &p), x);
+    }
+}
@@ -0,0 +1,48 @@
+//! Range-check chain: each step performs a configurable number of u64 additions with carry,
+//! `acc + a_j = acc_{j+1} + carry_j * 2^64`, range-checking every `a_j` and `acc_{j+1}` to 64 bits.
+//! The state is the running accumulator, the addends are derived from the step index as in the
+//! Sonobe version.
+use crate::{mix, Builder, Step};
+use num_bigint::BigUint;
+
+pub const NUM_BITS: usize = 64;
+
+#[derive(Clone, Copy, Debug)]
+pub struct RangeCheck {
+    pub num_adds_per_step: usize,
+}
+
+impl RangeCheck {
+    /// `j`-th addend of step `i`.
+    pub fn addend(&self, i: usize, j: usize) -> u64 {
+        mix((i * self.num_adds_per_step + j) as u64)
+    }
+}
+
+impl Step for RangeCheck {
+    const ARITY: usize = 1;
+
+    fn synthesize<B: Builder>(&self, b: &mut B, i: usize, z: &[B::Var]) -> Result<Vec<B::Var>, B::Error> {
+        let two_pow_64 = b.constant(&(BigUint::from(1u8) << NUM_BITS))?;
+
+        let mut acc = z[0].clone();
+        for j in 0..self.num_adds_per_step {
+            let a = b.alloc(|| BigUint::from(self.addend(i, j)))?;
+            b.range_check(&a, NUM_BITS)?;
+
+            let acc_next = b.hint(&[acc.clone(), a.clone()], |values| (&values[0] + &values[1]) % (BigUint::from(1u8) << NUM_BITS))?;
+            let carry = b.hint(&[acc.clone(), a.clone()], |values| (&values[0] + &values[1]) >> NUM_BITS)?;
+            b.range_check(&acc_next, NUM_BITS)?;
+            b.range_check(&carry, 1)?;
+
+            let lhs = b.add(&acc, &a)?;
+            let carry_term = b.mul(&carry, &two_pow_64)?;
+            let rhs = b.add(&acc_next, &carry_term)?;
+            b.enforce_eq(&lhs, &rhs)?;
+
+            acc = acc_next;
+        }
+
+        Ok(vec![acc])
+    }
+}
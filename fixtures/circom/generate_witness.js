@@ -0,0 +1,55 @@
+// Minimal stand-in for the generate_witness.js script circom emits next to the wasm, same
+// usage: node generate_witness.js <circuit.wasm> <input.json> <output.wtns>
+const fs = require("fs");
+function fnv(str) {
+  let h = 0xCBF29CE484222325n;
+  for (let i = 0; i < str.length; i++) {
+    h ^= BigInt(str.charCodeAt(i));
+    h = (h * 0x100000001B3n) & 0xFFFFFFFFFFFFFFFFn;
+  }
+  return h;
+}
+(async () => {
+  const [wasmPath, inputPath, outPath] = process.argv.slice(2);
+  const inputs = JSON.parse(fs.readFileSync(inputPath));
+  let errStr = "";
+  const { instance } = await WebAssembly.instantiate(fs.readFileSync(wasmPath), {
+    runtime: {
+      exceptionHandler: (code) => { throw new Error("circom exception " + code + errStr); },
+      printErrorMessage: () => {}, writeBufferMessage: () => {}, showSharedRWMemory: () => {},
+      logSetSignal: () => {}, logGetSignal: () => {}, logFinishComponent: () => {}, logStartComponent: () => {}, log: () => {},
+    },
+  });
+  const w = instance.exports;
+  const n32 = w.getFieldNumLen32();
+  w.getRawPrime();
+  const prime = [...Array(n32).keys()].map(j => BigInt(w.readSharedRWMemory(j) >>> 0));
+  const p = prime.reduceRight((acc, x) => (acc << 32n) | x, 0n);
+  w.init(1);
+  for (const [name, value] of Object.entries(inputs)) {
+    const h = fnv(name);
+    const msb = Number(h >> 32n), lsb = Number(h & 0xFFFFFFFFn);
+    const vals = [].concat(value).flat(Infinity);
+    for (let i = 0; i < vals.length; i++) {
+      let v = ((BigInt(vals[i]) % p) + p) % p;
+      for (let j = 0; j < n32; j++) { w.writeSharedRWMemory(j, Number(v & 0xFFFFFFFFn)); v >>= 32n; }
+      w.setInputSignal(msb, lsb, i);
+    }
+  }
+  const size = w.getWitnessSize();
+  const n8 = n32 * 4;
+  const buf = Buffer.alloc(4 + 4 + 4 + (4 + 8 + 4 + n8 + 4) + (4 + 8 + size * n8));
+  let o = 0;
+  buf.write("wtns", o); o += 4;
+  buf.writeUInt32LE(2, o); o += 4; buf.writeUInt32LE(2, o); o += 4;
+  buf.writeUInt32LE(1, o); o += 4; buf.writeBigUInt64LE(BigInt(4 + n8 + 4), o); o += 8;
+  buf.writeUInt32LE(n8, o); o += 4;
+  for (let j = 0; j < n32; j++) { buf.writeUInt32LE(Number(prime[j]), o); o += 4; }
+  buf.writeUInt32LE(size, o); o += 4;
+  buf.writeUInt32LE(2, o); o += 4; buf.writeBigUInt64LE(BigInt(size * n8), o); o += 8;
+  for (let i = 0; i < size; i++) {
+    w.getWitness(i);
+    for (let j = 0; j < n32; j++) { buf.writeUInt32LE(w.readSharedRWMemory(j) >>> 0, o); o += 4; }
+  }
+  fs.writeFileSync(outPath, buf);
+})();
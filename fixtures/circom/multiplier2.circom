@@ -0,0 +1,13 @@
+pragma circom 2.0.0;
+
+// Step circuit of the Circom import fixtures: the state `a` is multiplied by the private input
+// `b`, so `c` is the next state.
+template Multiplier2() {
+    signal input a;
+    signal input b;
+    signal output c;
+
+    c <== a * b;
+}
+
+component main = Multiplier2();
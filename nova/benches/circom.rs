@@ -0,0 +1,87 @@
+use nova::circom::{fixture, nova_ivc, CircomCircuit, FIXTURE_STEPS};
+use nova_snark::{
+    provider::{Bn256EngineKZG, GrumpkinEngine},
+    traits::{
+      circuit::TrivialCircuit,
+      snark::RelaxedR1CSSNARKTrait,
+      Engine,
+    },
+    PublicParams,
+  };
+  use std::{collections::HashMap, fs::File, sync::Arc, time::{Duration, Instant}};
+  use std::io::Write;
+
+  type E1 = Bn256EngineKZG;
+  type E2 = GrumpkinEngine;
+  type EE1 = nova_snark::provider::hyperkzg::EvaluationEngine<E1>;
+  type EE2 = nova_snark::provider::ipa_pc::EvaluationEngine<E2>;
+  type S1 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E1, EE1>; // non-preprocessing SNARK
+  type S2 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E2, EE2>; // non-preprocessing SNARK
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+fn bench_nova_ivc(c: &mut Criterion) {
+    // the bundled multiplier, point `fixture` to the .r1cs and witnesses of another circuit to
+    // benchmark it instead
+    let (r1cs, generator) = fixture::<<E1 as Engine>::Scalar>().expect("failed to load the circom fixture");
+    let r1cs = Arc::new(r1cs);
+    let z0_primary = vec![<E1 as Engine>::Scalar::from(3)];
+    let num_steps = FIXTURE_STEPS;
+
+    let circuit_primary = CircomCircuit::new(r1cs.clone(), None);
+    let circuit_secondary = TrivialCircuit::default();
+    let pp = PublicParams::<
+      E1,
+      E2,
+      CircomCircuit<<E1 as Engine>::GE>,
+      TrivialCircuit<<E2 as Engine>::Scalar>,
+    >::setup(
+    &circuit_primary,
+    &circuit_secondary,
+    &*S1::ck_floor(),
+    &*S2::ck_floor(),
+    )
+    .unwrap();
+
+    let mut group = c.benchmark_group("NOVA IVC");
+
+    group.sample_size(10);
+
+    let mut time = HashMap::new();
+    let test_name = format!("entire_process_{}", r1cs.constraints.len());
+    let benchmark_id = BenchmarkId::new(test_name, r1cs.num_wires);
+    group.bench_function(benchmark_id, |b| {
+      b.iter_custom(|iters| {
+          let start = Instant::now();
+          for _ in 0..iters {
+              black_box(nova_ivc(num_steps, r1cs.clone(), &generator, z0_primary.clone(), pp.clone(), circuit_secondary.clone()));
+          }
+          let elapsed = start.elapsed();
+          let _ = *time.entry(0)
+              .and_modify(|e| *e += elapsed)
+              .or_insert(elapsed);
+          elapsed
+      })
+    });
+
+    let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+    let total_duration = time.entry(0).or_insert(Duration::ZERO).as_millis();
+    let average_execution_time = total_duration / iterations;
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/nova_circom.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Num Constraints | Num Wires | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-----------------|-----------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    writeln!(
+        file,
+        "| {}         | {}               | {}         | {:?} ms             | {:?}                | {:?}                  |",
+        num_steps, r1cs.constraints.len(), r1cs.num_wires, average_execution_time, pp.num_constraints().0, pp.num_constraints().1
+    ).expect("Failed to write to file");
+}
+
+fn circom_nova(c: &mut Criterion) {
+    bench_nova_ivc(c);
+}
+
+criterion_group!(benches, circom_nova);
+criterion_main!(benches);
@@ -1,4 +1,5 @@
-use nova::hashchain::{nova_ivc, HashChainCircuit};
+use circuit_dsl::hashchain::HashChain;
+use nova::dsl::{nova_ivc, DslCircuit};
 use nova_snark::{
     provider::{Bn256EngineKZG, GrumpkinEngine},
     traits::{
@@ -17,6 +18,7 @@ use nova_snark::{
   type EE2 = nova_snark::provider::ipa_pc::EvaluationEngine<E2>;
   type S1 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E1, EE1>; // non-preprocessing SNARK
   type S2 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E2, EE2>; // non-preprocessing SNARK
+  type F = <E1 as Engine>::Scalar;
   
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 fn bench_nova_ivc(c: &mut Criterion) {
@@ -26,13 +28,13 @@ fn bench_nova_ivc(c: &mut Criterion) {
     let num_steps = 10;
     let num_elts_per_step = vec![1400, 3200, 6600, 14000, 28000];
     for num_elts_per_step in &num_elts_per_step {
-      let circuit_primary = HashChainCircuit::new(*num_elts_per_step);
+      let circuit_primary = DslCircuit::new(HashChain { num_elts_per_step: *num_elts_per_step, seed: 0 }, 0);
 
       let circuit_secondary = TrivialCircuit::default();
       let pp = PublicParams::<
         E1,
         E2,
-        HashChainCircuit<<E1 as Engine>::GE>,
+        DslCircuit<F, HashChain>,
         TrivialCircuit<<E2 as Engine>::Scalar>,
       >::setup(
       &circuit_primary,
@@ -60,7 +62,7 @@ fn bench_nova_ivc(c: &mut Criterion) {
           b.iter_custom(|iters| {
               let start = Instant::now();
               for _ in 0..iters {
-                  black_box(nova_ivc(num_steps, *num_elts_per_step, pp_vec[i].clone(), secondary_circuits[i].clone()));
+                  black_box(nova_ivc(num_steps, primary_circuits[i].step, vec![F::from(0)], pp_vec[i].clone(), secondary_circuits[i].clone()));
               }
               let elapsed = start.elapsed();
               let _ = *time.entry(*num_elts_per_step)
@@ -0,0 +1,101 @@
+use nova::matvec::{nova_ivc, random_input, DenseLayerCircuit};
+use nova_snark::{
+    provider::{Bn256EngineKZG, GrumpkinEngine},
+    traits::{
+      circuit::TrivialCircuit,
+      snark::RelaxedR1CSSNARKTrait,
+      Engine, 
+    },
+    PublicParams,
+  };
+  use std::{collections::HashMap, fs::File, time::{Duration, Instant}};
+  use std::io::Write;
+  use rand::{rngs::StdRng, SeedableRng};
+
+  type E1 = Bn256EngineKZG;
+  type E2 = GrumpkinEngine;
+  type EE1 = nova_snark::provider::hyperkzg::EvaluationEngine<E1>;
+  type EE2 = nova_snark::provider::ipa_pc::EvaluationEngine<E2>;
+  type S1 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E1, EE1>; // non-preprocessing SNARK
+  type S2 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E2, EE2>; // non-preprocessing SNARK
+  
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+fn bench_nova_ivc(c: &mut Criterion) {
+    let mut primary_circuits = Vec::new();
+    let mut secondary_circuits = Vec::new();
+    let mut pp_vec = Vec::new();
+    let num_steps = 10;
+    // a layer of width w costs w^2 multiplications and w range checks
+    let widths = vec![16, 32, 64, 128];
+    let mut rng = StdRng::seed_from_u64(0);
+    for width in &widths {
+      let circuit_primary = DenseLayerCircuit::new(random_input(*width, &mut rng), &mut rng);
+
+      let circuit_secondary = TrivialCircuit::default();
+      let pp = PublicParams::<
+        E1,
+        E2,
+        DenseLayerCircuit<<E1 as Engine>::GE>,
+        TrivialCircuit<<E2 as Engine>::Scalar>,
+      >::setup(
+      &circuit_primary,
+      &circuit_secondary,
+      &*S1::ck_floor(),
+      &*S2::ck_floor(),
+      )
+      .unwrap();
+    
+      pp_vec.push(pp);
+      primary_circuits.push(circuit_primary);
+      secondary_circuits.push(circuit_secondary);
+    }
+
+    let mut group = c.benchmark_group("NOVA IVC");
+
+    group.sample_size(10);
+
+    let mut results = Vec::new();
+    for (i, width) in widths.iter().enumerate() {
+      let mut time = HashMap::new();
+        let test_name = format!("entire_process_{}", width);
+        let benchmark_id = BenchmarkId::new(test_name, width);  
+        group.bench_function(benchmark_id, |b| {
+          b.iter_custom(|iters| {
+              let start = Instant::now();
+              for _ in 0..iters {
+                  black_box(nova_ivc(num_steps, *width, pp_vec[i].clone(), secondary_circuits[i].clone()));
+              }
+              let elapsed = start.elapsed();
+              let _ = *time.entry(*width)
+                  .and_modify(|e| *e += elapsed)
+                  .or_insert(elapsed);
+              elapsed
+          })
+      });
+
+      let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+      let total_duration = time.entry(*width).or_insert(Duration::ZERO).as_millis();
+      let average_execution_time = total_duration / iterations;
+      results.push((*width, average_execution_time));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/nova_matvec.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Width              | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (i, (width, duration)) in results.iter().enumerate() {
+        writeln!(
+            file,
+            "| {}         | {}               | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, width, duration, pp_vec[i].num_constraints().0, pp_vec[i].num_constraints().1
+        ).expect("Failed to write to file");
+    }
+}
+
+fn matvec_nova(c: &mut Criterion) {
+    bench_nova_ivc(c);
+}
+
+criterion_group!(benches, matvec_nova);
+criterion_main!(benches);
@@ -1,4 +1,5 @@
-use nova::minroot::{nova_ivc, MinRootCircuit, MinRootIteration};
+use circuit_dsl::minroot::MinRoot;
+use nova::dsl::{nova_ivc, DslCircuit};
 use nova_snark::{
     provider::{Bn256EngineKZG, GrumpkinEngine},
     traits::{
@@ -17,6 +18,7 @@ use nova_snark::{
   type EE2 = nova_snark::provider::ipa_pc::EvaluationEngine<E2>;
   type S1 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E1, EE1>; // non-preprocessing SNARK
   type S2 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E2, EE2>; // non-preprocessing SNARK
+  type F = <E1 as Engine>::Scalar;
   
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 fn bench_nova_ivc(c: &mut Criterion) {
@@ -25,25 +27,13 @@ fn bench_nova_ivc(c: &mut Criterion) {
     let mut pp_vec = Vec::new();
     let num_iters_per_step = vec![1000, 9000, 25000, 58000, 100000];
     for num_iters in &num_iters_per_step {
-      let circuit_primary = MinRootCircuit {
-          seq: vec![
-        MinRootIteration {
-          i: <E1 as Engine>::Scalar::zero(),
-          x_i: <E1 as Engine>::Scalar::zero(),
-          y_i: <E1 as Engine>::Scalar::zero(),
-          i_plus_1: <E1 as Engine>::Scalar::zero(),
-          x_i_plus_1: <E1 as Engine>::Scalar::zero(),
-          y_i_plus_1: <E1 as Engine>::Scalar::zero(),
-            };
-            *num_iters
-          ],
-        };
+      let circuit_primary = DslCircuit::new(MinRoot { num_iters_per_step: *num_iters }, 0);
 
       let circuit_secondary = TrivialCircuit::default();
       let pp = PublicParams::<
         E1,
         E2,
-        MinRootCircuit<<E1 as Engine>::GE>,
+        DslCircuit<F, MinRoot>,
         TrivialCircuit<<E2 as Engine>::Scalar>,
       >::setup(
       &circuit_primary,
@@ -72,7 +62,7 @@ fn bench_nova_ivc(c: &mut Criterion) {
           b.iter_custom(|iters| {
               let start = Instant::now();
               for _ in 0..iters {
-                  black_box(nova_ivc(num_steps, *num_iters, pp_vec[i].clone(), secondary_circuits[i].clone()));
+                  black_box(nova_ivc(num_steps, primary_circuits[i].step, vec![F::from(0), F::from(0), F::from(1)], pp_vec[i].clone(), secondary_circuits[i].clone()));
               }
               let elapsed = start.elapsed();
               let _ = *time.entry(*num_iters)
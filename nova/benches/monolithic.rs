@@ -0,0 +1,94 @@
+use nova::hashchain::HashChainCircuit;
+use nova::minroot::minroot_circuits;
+use nova::monolithic::{spartan_prove, spartan_setup, MonolithicCircuit};
+use nova_snark::{
+    provider::Bn256EngineKZG,
+    traits::{circuit::StepCircuit, Engine},
+  };
+  use std::{collections::HashMap, fs::File, time::Duration};
+  use std::io::Write;
+
+  type E1 = Bn256EngineKZG;
+  type F = <E1 as Engine>::Scalar;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const NUM_STEPS: usize = 10;
+
+// (workload, num iters per step, execution time, circuit size)
+type ResultRow = (&'static str, usize, u128, usize);
+
+// proves `num_steps * num_iters` iterations of a workload as a single Spartan proof
+fn bench_spartan<SC: StepCircuit<F>>(
+    c: &mut Criterion,
+    workload: &'static str,
+    num_iters_per_step: &[usize],
+    circuit: impl Fn(usize) -> MonolithicCircuit<F, SC>,
+) -> Vec<ResultRow> {
+    let params = num_iters_per_step
+      .iter()
+      .map(|num_iters| spartan_setup(circuit(*num_iters)))
+      .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group(format!("SPARTAN {}", workload));
+
+    group.sample_size(10);
+
+    let mut results = Vec::new();
+    for (i, num_iters) in num_iters_per_step.iter().enumerate() {
+      let mut time = HashMap::new();
+        let test_name = format!("entire_process_{}", num_iters);
+        let benchmark_id = BenchmarkId::new(test_name, num_iters);
+        group.bench_function(benchmark_id, |b| {
+          b.iter_custom(|iters| {
+              let mut elapsed = Duration::ZERO;
+              for _ in 0..iters {
+                  // the advice is produced outside of the measured prover time
+                  let circuit = circuit(*num_iters);
+                  elapsed += black_box(spartan_prove(&params[i], circuit));
+              }
+              let _ = *time.entry(*num_iters)
+                  .and_modify(|e| *e += elapsed)
+                  .or_insert(elapsed);
+              elapsed
+          })
+      });
+
+      let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+      let total_duration = time.entry(*num_iters).or_insert(Duration::ZERO).as_millis();
+      let average_execution_time = total_duration / iterations;
+      results.push((workload, *num_iters, average_execution_time, params[i].num_constraints()));
+    }
+
+    group.finish();
+    results
+}
+
+fn monolithic_nova(c: &mut Criterion) {
+    // the largest IVC configurations are left out, a single R1CS of that size does not fit in memory
+    let mut results = bench_spartan(c, "MinRoot", &[1000, 9000, 25000], |num_iters| {
+        let (z0, steps) = minroot_circuits(NUM_STEPS, num_iters);
+        MonolithicCircuit::new(z0, steps)
+    });
+    results.extend(bench_spartan(c, "HashChain", &[1400, 3200, 6600], |num_elts| {
+        let steps = (0..NUM_STEPS)
+          .map(|_| HashChainCircuit::<<E1 as Engine>::GE>::new(num_elts))
+          .collect::<Vec<_>>();
+        MonolithicCircuit::new(vec![F::from(0)], steps)
+    }));
+
+    // same schema as the IVC tables, a monolithic proof has no secondary circuit
+    let mut file = File::create("../benchmark_results/nova_monolithic_spartan.md").expect("Failed to create file");
+    writeln!(file, "| Workload   | Num Steps  | Num Iters per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (workload, num_iters, duration, size) in results {
+        writeln!(
+            file,
+            "| {}    | {}         | {}               | {:?} ms             | {:?}                | -                      |",
+            workload, NUM_STEPS, num_iters, duration, size
+        ).expect("Failed to write to file");
+    }
+}
+
+criterion_group!(benches, monolithic_nova);
+criterion_main!(benches);
@@ -0,0 +1,117 @@
+use nova::supernova::{
+    opcode_num_constraints, supernova_ivc, universal_nova_ivc, universal_num_constraints, Opcode,
+    Program, UniversalCircuit,
+  };
+use nova_snark::{
+    provider::{Bn256EngineKZG, GrumpkinEngine},
+    supernova::PublicParams as SuperNovaPublicParams,
+    traits::{
+      circuit::TrivialCircuit,
+      snark::RelaxedR1CSSNARKTrait,
+      Engine,
+    },
+    PublicParams,
+  };
+  use std::{collections::HashMap, fs::File, time::{Duration, Instant}};
+  use std::io::Write;
+
+  type E1 = Bn256EngineKZG;
+  type E2 = GrumpkinEngine;
+  type EE1 = nova_snark::provider::hyperkzg::EvaluationEngine<E1>;
+  type EE2 = nova_snark::provider::ipa_pc::EvaluationEngine<E2>;
+  type S1 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E1, EE1>; // non-preprocessing SNARK
+  type S2 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E2, EE2>; // non-preprocessing SNARK
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+fn bench_supernova_ivc(c: &mut Criterion) {
+    let num_steps = 20;
+    let num_iters_per_step = vec![1, 16, 64, 256];
+    // the same random program is proven with both schemes
+    let programs = num_iters_per_step
+      .iter()
+      .map(|num_iters| Program::random(num_steps, *num_iters, 0))
+      .collect::<Vec<_>>();
+
+    let mut supernova_pp_vec = Vec::new();
+    let mut universal_pp_vec = Vec::new();
+    let circuit_secondary = TrivialCircuit::default();
+    for program in &programs {
+      let supernova_pp = SuperNovaPublicParams::setup(program, &*S1::ck_floor(), &*S2::ck_floor());
+
+      let universal_pp = PublicParams::<
+        E1,
+        E2,
+        UniversalCircuit<<E1 as Engine>::GE>,
+        TrivialCircuit<<E2 as Engine>::Scalar>,
+      >::setup(
+      &program.universal_circuits::<<E1 as Engine>::GE>()[0],
+      &circuit_secondary,
+      &*S1::ck_floor(),
+      &*S2::ck_floor(),
+      )
+      .unwrap();
+
+      supernova_pp_vec.push(supernova_pp);
+      universal_pp_vec.push(universal_pp);
+    }
+
+    let mut group = c.benchmark_group("SUPERNOVA IVC");
+
+    group.sample_size(10);
+
+    let mut results = Vec::new();
+    for (i, num_iters) in num_iters_per_step.iter().enumerate() {
+      let mut time = HashMap::new();
+      for scheme in ["supernova", "universal"] {
+        let test_name = format!("{}_{}", scheme, num_iters);
+        let benchmark_id = BenchmarkId::new(test_name, num_iters);
+        group.bench_function(benchmark_id, |b| {
+          b.iter_custom(|iters| {
+              let start = Instant::now();
+              for _ in 0..iters {
+                  if scheme == "supernova" {
+                    black_box(supernova_ivc(&programs[i], &supernova_pp_vec[i]));
+                  } else {
+                    black_box(universal_nova_ivc(&programs[i], universal_pp_vec[i].clone(), circuit_secondary.clone()));
+                  }
+              }
+              let elapsed = start.elapsed();
+              let _ = *time.entry(scheme)
+                  .and_modify(|e| *e += elapsed)
+                  .or_insert(elapsed);
+              elapsed
+          })
+        });
+      }
+
+      let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+      let supernova_time = time.entry("supernova").or_insert(Duration::ZERO).as_millis() / iterations;
+      let universal_time = time.entry("universal").or_insert(Duration::ZERO).as_millis() / iterations;
+      results.push((*num_iters, supernova_time, universal_time));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/nova_supernova.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Num Iters per step | SuperNova Time (ms) | Universal Nova Time (ms) | Add / Mul / Hash / MinRoot step size | Universal step size |").expect("Failed to write to file");
+    writeln!(file, "|------------|--------------------|---------------------|--------------------------|--------------------------------------|---------------------|").expect("Failed to write to file");
+    for (num_iters, supernova_time, universal_time) in results.iter() {
+        let opcode_sizes = Opcode::ALL
+          .iter()
+          .map(|op| opcode_num_constraints(*op, *num_iters).to_string())
+          .collect::<Vec<_>>()
+          .join(" / ");
+        writeln!(
+            file,
+            "| {}         | {}               | {:?} ms             | {:?} ms                  | {}                  | {}                  |",
+            num_steps, num_iters, supernova_time, universal_time, opcode_sizes, universal_num_constraints(*num_iters)
+        ).expect("Failed to write to file");
+    }
+}
+
+fn supernova_nova(c: &mut Criterion) {
+    bench_supernova_ivc(c);
+}
+
+criterion_group!(benches, supernova_nova);
+criterion_main!(benches);
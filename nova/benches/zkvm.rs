@@ -0,0 +1,121 @@
+use nova::zkvm::{
+    execute, nova_ivc, opcode_num_constraints, supernova_ivc, uniform_num_constraints, Machine, Opcode,
+    Program, ZkvmCircuit,
+  };
+use nova_snark::{
+    provider::{Bn256EngineKZG, GrumpkinEngine},
+    supernova::PublicParams as SuperNovaPublicParams,
+    traits::{
+      circuit::TrivialCircuit,
+      snark::RelaxedR1CSSNARKTrait,
+      Engine,
+    },
+    PublicParams,
+  };
+  use std::{collections::HashMap, fs::File, time::{Duration, Instant}};
+  use std::io::Write;
+
+  type E1 = Bn256EngineKZG;
+  type E2 = GrumpkinEngine;
+  type EE1 = nova_snark::provider::hyperkzg::EvaluationEngine<E1>;
+  type EE2 = nova_snark::provider::ipa_pc::EvaluationEngine<E2>;
+  type S1 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E1, EE1>; // non-preprocessing SNARK
+  type S2 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E2, EE2>; // non-preprocessing SNARK
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+fn bench_zkvm_ivc(c: &mut Criterion) {
+    let programs = vec![
+      ("fibonacci", Program::fibonacci(16)),
+      ("bubble_sort", Program::bubble_sort(&[7, 3, 9, 1, 5, 2])),
+    ];
+    // every program is proven up to and including its final Halt
+    let num_steps = programs
+      .iter()
+      .map(|(_, program)| Machine::run(program, 10_000).1)
+      .collect::<Vec<_>>();
+
+    let mut supernova_pp_vec = Vec::new();
+    let mut universal_pp_vec = Vec::new();
+    let circuit_secondary = TrivialCircuit::default();
+    for (i, (_, program)) in programs.iter().enumerate() {
+      let execution = execute::<<E1 as Engine>::Scalar>(program, num_steps[i]);
+      let supernova_pp = SuperNovaPublicParams::setup(&execution, &*S1::ck_floor(), &*S2::ck_floor());
+
+      let universal_pp = PublicParams::<
+        E1,
+        E2,
+        ZkvmCircuit<<E1 as Engine>::GE>,
+        TrivialCircuit<<E2 as Engine>::Scalar>,
+      >::setup(
+      &execution.uniform_circuits::<<E1 as Engine>::GE>()[0],
+      &circuit_secondary,
+      &*S1::ck_floor(),
+      &*S2::ck_floor(),
+      )
+      .unwrap();
+
+      supernova_pp_vec.push(supernova_pp);
+      universal_pp_vec.push(universal_pp);
+    }
+
+    let mut group = c.benchmark_group("ZKVM IVC");
+
+    group.sample_size(10);
+
+    let mut results = Vec::new();
+    for (i, (name, program)) in programs.iter().enumerate() {
+      let mut time = HashMap::new();
+      for scheme in ["supernova", "universal"] {
+        let test_name = format!("{}_{}", scheme, name);
+        let benchmark_id = BenchmarkId::new(test_name, num_steps[i]);
+        group.bench_function(benchmark_id, |b| {
+          b.iter_custom(|iters| {
+              let start = Instant::now();
+              for _ in 0..iters {
+                  if scheme == "supernova" {
+                    black_box(supernova_ivc(program, num_steps[i], &supernova_pp_vec[i]));
+                  } else {
+                    black_box(nova_ivc(program, num_steps[i], universal_pp_vec[i].clone(), circuit_secondary.clone()));
+                  }
+              }
+              let elapsed = start.elapsed();
+              let _ = *time.entry(scheme)
+                  .and_modify(|e| *e += elapsed)
+                  .or_insert(elapsed);
+              elapsed
+          })
+        });
+      }
+
+      let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+      let supernova_time = time.entry("supernova").or_insert(Duration::ZERO).as_millis() / iterations;
+      let universal_time = time.entry("universal").or_insert(Duration::ZERO).as_millis() / iterations;
+      results.push((name, num_steps[i], supernova_time, universal_time));
+    }
+
+    group.finish();
+
+    let opcode_sizes = Opcode::ALL
+      .iter()
+      .map(|op| format!("{:?}: {}", op, opcode_num_constraints(*op)))
+      .collect::<Vec<_>>()
+      .join(", ");
+    let mut file = File::create("../benchmark_results/nova_zkvm.md").expect("Failed to create file");
+    writeln!(file, "| Program      | Num Steps  | SuperNova Time (ms) | Universal Nova Time (ms) | Universal step size |").expect("Failed to write to file");
+    writeln!(file, "|--------------|------------|---------------------|--------------------------|---------------------|").expect("Failed to write to file");
+    for (name, num_steps, supernova_time, universal_time) in results.iter() {
+        writeln!(
+            file,
+            "| {}  | {}         | {:?} ms             | {:?} ms                  | {}                  |",
+            name, num_steps, supernova_time, universal_time, uniform_num_constraints()
+        ).expect("Failed to write to file");
+    }
+    writeln!(file, "\nOpcode step sizes: {}", opcode_sizes).expect("Failed to write to file");
+}
+
+fn zkvm_nova(c: &mut Criterion) {
+    bench_zkvm_ivc(c);
+}
+
+criterion_group!(benches, zkvm_nova);
+criterion_main!(benches);
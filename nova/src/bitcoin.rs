@@ -0,0 +1,384 @@
+//! Bitcoin header chain, the light-client workload: each step takes a configurable number of
+//! 80-byte block headers, checks that every header commits to the previous block hash, hashes it
+//! with double SHA-256 and checks that the hash, read as a little-endian integer, is at most the
+//! target encoded by the header's compact `nBits` field. The state is the tip hash, as two
+//! 128-bit big-endian halves of the raw digest, and the compact target.
+//!
+//! Difficulty retargeting is out of scope: `nBits` is carried unchanged in the state, so every
+//! header of a run must share the target of the first one, as the headers of the bundled fixture
+//! do. Targets are supported for exponents in `MIN_EXPONENT..=MAX_EXPONENT`, which covers every
+//! target below the mainnet proof-of-work limit.
+use crate::sha256::{digest_to_scalars, enforce_packing, DIGEST_BYTES};
+use bellpepper::gadgets::sha256::sha256;
+use bellpepper_core::{
+  boolean::{AllocatedBit, Boolean},
+  num::AllocatedNum,
+  ConstraintSystem, LinearCombination, SynthesisError, Variable,
+};
+use ff::{Field, PrimeField};
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+pub const HEADER_BYTES: usize = 80;
+pub const MIN_EXPONENT: usize = 3;
+pub const MAX_EXPONENT: usize = 29;
+// targets are below 2^TARGET_BITS, so are the hashes meeting them
+const TARGET_BITS: usize = 8 * MAX_EXPONENT;
+
+const PREV_HASH: usize = 4;
+const BITS: usize = 72;
+
+pub type Header = [u8; HEADER_BYTES];
+
+/// Bitcoin mainnet headers from the genesis block on, bundled so the workload runs offline.
+pub fn fixture() -> Vec<Header> {
+  include_str!("../../fixtures/bitcoin_headers.txt")
+    .lines()
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .map(|line| {
+      let bytes = (0..line.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&line[i..i + 2], 16).expect("invalid hex"))
+        .collect::<Vec<_>>();
+      bytes.try_into().expect("a header is 80 bytes")
+    })
+    .collect()
+}
+
+pub fn block_hash(header: &Header) -> [u8; DIGEST_BYTES] {
+  Sha256::digest(Sha256::digest(header)).into()
+}
+
+pub fn prev_hash(header: &Header) -> [u8; DIGEST_BYTES] {
+  header[PREV_HASH..PREV_HASH + DIGEST_BYTES].try_into().unwrap()
+}
+
+pub fn compact_target(header: &Header) -> u32 {
+  u32::from_le_bytes(header[BITS..BITS + 4].try_into().unwrap())
+}
+
+/// Expands `nBits`, a 24-bit mantissa scaled by `256^(exponent - 3)`.
+pub fn target(bits: u32) -> BigUint {
+  let exponent = (bits >> 24) as usize;
+  assert!((MIN_EXPONENT..=MAX_EXPONENT).contains(&exponent), "unsupported target exponent");
+  assert_eq!(bits & 0x0080_0000, 0, "negative target");
+  BigUint::from(bits & 0x007f_ffff) << (8 * (exponent - MIN_EXPONENT))
+}
+
+/// Native reference of the checks of a single header.
+pub fn check_header(header: &Header, tip: &[u8; DIGEST_BYTES], bits: u32) -> bool {
+  prev_hash(header) == *tip
+    && compact_target(header) == bits
+    && BigUint::from_bytes_le(&block_hash(header)) <= target(bits)
+}
+
+pub fn initial_state<Scalar: PrimeField>(tip: &[u8; DIGEST_BYTES], bits: u32) -> Vec<Scalar> {
+  let mut z = digest_to_scalars(tip);
+  z.push(Scalar::from(bits as u64));
+  z
+}
+
+// the bits of `bytes`, most significant first within each byte as the SHA-256 gadget expects
+fn alloc_bytes<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+  mut cs: CS,
+  bytes: &[u8],
+) -> Result<Vec<Boolean>, SynthesisError> {
+  (0..8 * bytes.len())
+    .map(|k| {
+      let bit = (bytes[k / 8] >> (7 - k % 8)) & 1 == 1;
+      AllocatedBit::alloc(cs.namespace(|| format!("bit_{}", k)), Some(bit)).map(Boolean::from)
+    })
+    .collect()
+}
+
+// lc + coeff * byte, where `bits` holds the byte most significant bit first
+fn byte_lc<Scalar: PrimeField>(
+  lc: LinearCombination<Scalar>,
+  bits: &[Boolean],
+  coeff: Scalar,
+  one: Variable,
+) -> LinearCombination<Scalar> {
+  bits
+    .iter()
+    .enumerate()
+    .fold(lc, |lc, (k, bit)| lc + &bit.lc(one, coeff * Scalar::from(1u64 << (7 - k))))
+}
+
+#[derive(Clone, Debug)]
+pub struct HeaderChainCircuit<G: Group> {
+  pub tip: [u8; DIGEST_BYTES],
+  pub bits: u32,
+  pub headers: Vec<Header>,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> HeaderChainCircuit<G> {
+  // extends the chain ending at `tip` with `headers`
+  pub fn new(tip: [u8; DIGEST_BYTES], bits: u32, headers: Vec<Header>) -> Self {
+    Self {
+      tip,
+      bits,
+      headers,
+      _p: PhantomData,
+    }
+  }
+
+  pub fn output_tip(&self) -> [u8; DIGEST_BYTES] {
+    self.headers.last().map_or(self.tip, block_hash)
+  }
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for HeaderChainCircuit<G> {
+  fn arity(&self) -> usize {
+    3
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    // z provides the two halves of the tip hash and the compact target
+    assert_eq!(z.len(), 3);
+    let one = CS::one();
+
+    // the first header links to the tip of the state, the following ones to the hash bits of
+    // their predecessor
+    let mut tip = alloc_bytes(cs.namespace(|| "tip"), &self.tip)?;
+    for (i, half) in tip.chunks(4 * DIGEST_BYTES).enumerate() {
+      enforce_packing(&mut cs.namespace(|| format!("unpack z_{}", i)), half, &z[i]);
+    }
+
+    let mut target_num = None;
+    for (j, header) in self.headers.iter().enumerate() {
+      let mut cs = cs.namespace(|| format!("header_{}", j));
+
+      let mut header_bits = alloc_bytes(cs.namespace(|| "version"), &header[..PREV_HASH])?;
+      header_bits.extend(tip);
+      header_bits.extend(alloc_bytes(cs.namespace(|| "fields"), &header[PREV_HASH + DIGEST_BYTES..])?);
+      let byte = |i: usize| &header_bits[8 * i..8 * (i + 1)];
+
+      // nBits, little-endian, equals the compact target of the state
+      cs.enforce(
+        || "nBits = z_2",
+        |lc| (0..4).fold(lc, |lc, i| byte_lc(lc, byte(BITS + i), G::Scalar::from(1u64 << (8 * i)), one)),
+        |lc| lc + one,
+        |lc| lc + z[2].get_variable(),
+      );
+
+      // the target is expanded once, the following headers share it
+      if target_num.is_none() {
+        let mut cs = cs.namespace(|| "target");
+        let (mantissa, exponent) = (self.bits & 0x00ff_ffff, (self.bits >> 24) as usize);
+
+        // one-hot exponent flags select the scale 256^(exponent - 3)
+        let flags = (MIN_EXPONENT..=MAX_EXPONENT)
+          .map(|e| AllocatedBit::alloc(cs.namespace(|| format!("exponent_{}", e)), Some(e == exponent)))
+          .collect::<Result<Vec<_>, _>>()?;
+        cs.enforce(
+          || "one exponent",
+          |lc| flags.iter().fold(lc, |lc, flag| lc + flag.get_variable()),
+          |lc| lc + one,
+          |lc| lc + one,
+        );
+        cs.enforce(
+          || "exponent",
+          |lc| {
+            flags
+              .iter()
+              .zip(MIN_EXPONENT..)
+              .fold(lc, |lc, (flag, e)| lc + (G::Scalar::from(e as u64), flag.get_variable()))
+          },
+          |lc| lc + one,
+          |lc| byte_lc(lc, byte(BITS + 3), G::Scalar::ONE, one),
+        );
+
+        // the sign bit of the mantissa is clear
+        cs.enforce(
+          || "non-negative target",
+          |_| byte(BITS + 2)[0].lc(one, G::Scalar::ONE),
+          |lc| lc + one,
+          |lc| lc,
+        );
+
+        let scale = |e: usize| G::Scalar::from(2).pow_vartime([(8 * (e - MIN_EXPONENT)) as u64]);
+        let value = AllocatedNum::alloc(cs.namespace(|| "target"), || {
+          Ok(G::Scalar::from(mantissa as u64) * scale(exponent))
+        })?;
+        cs.enforce(
+          || "target = mantissa * scale",
+          |lc| (0..3).fold(lc, |lc, i| byte_lc(lc, byte(BITS + i), G::Scalar::from(1u64 << (8 * i)), one)),
+          |lc| flags.iter().zip(MIN_EXPONENT..).fold(lc, |lc, (flag, e)| lc + (scale(e), flag.get_variable())),
+          |lc| lc + value.get_variable(),
+        );
+        target_num = Some(value);
+      }
+      let target_num = target_num.as_ref().unwrap();
+
+      let hash_bits = sha256(cs.namespace(|| "sha256"), &header_bits)?;
+      let hash_bits = sha256(cs.namespace(|| "sha256d"), &hash_bits)?;
+
+      // the hash is a little-endian integer, its bytes past the target size are zero and the
+      // rest is at most the target, which the decomposition of target - hash shows
+      cs.enforce(
+        || "high hash bytes are zero",
+        |lc| hash_bits[TARGET_BITS..].iter().fold(lc, |lc, bit| lc + &bit.lc(one, G::Scalar::ONE)),
+        |lc| lc + one,
+        |lc| lc,
+      );
+      let hash = BigUint::from_bytes_le(&block_hash(header)[..TARGET_BITS / 8]);
+      let target_value = target(self.bits);
+      let slack = if hash <= target_value { target_value - hash } else { BigUint::from(0u32) };
+      let slack_bits = (0..TARGET_BITS)
+        .map(|k| AllocatedBit::alloc(cs.namespace(|| format!("slack_bit_{}", k)), Some(slack.bit(k as u64))))
+        .collect::<Result<Vec<_>, _>>()?;
+      cs.enforce(
+        || "target - hash = slack",
+        |lc| {
+          let lc = lc + target_num.get_variable();
+          (0..TARGET_BITS / 8).fold(lc, |lc, i| {
+            byte_lc(lc, &hash_bits[8 * i..8 * (i + 1)], -G::Scalar::from(2).pow_vartime([8 * i as u64]), one)
+          })
+        },
+        |lc| lc + one,
+        |lc| {
+          slack_bits
+            .iter()
+            .enumerate()
+            .fold(lc, |lc, (k, bit)| lc + (G::Scalar::from(2).pow_vartime([k as u64]), bit.get_variable()))
+        },
+      );
+
+      tip = hash_bits;
+    }
+
+    let mut z_out = tip
+      .chunks(4 * DIGEST_BYTES)
+      .enumerate()
+      .map(|(i, half_bits)| {
+        let half = AllocatedNum::alloc(cs.namespace(|| format!("z_out_{}", i)), || {
+          half_bits.iter().try_fold(G::Scalar::ZERO, |acc, bit| {
+            let bit = bit.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(acc.double() + if bit { G::Scalar::ONE } else { G::Scalar::ZERO })
+          })
+        })?;
+        enforce_packing(&mut cs.namespace(|| format!("pack z_out_{}", i)), half_bits, &half);
+        Ok(half)
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    z_out.push(z[2].clone());
+
+    Ok(z_out)
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    // sanity check
+    debug_assert_eq!(z, initial_state::<G::Scalar>(&self.tip, self.bits).as_slice());
+
+    initial_state(&self.output_tip(), self.bits)
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, num_headers_per_step: usize,
+    pp: PublicParams<E1, E2, HeaderChainCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // every step extends the chain from the genesis block with the next fixture headers
+    let headers = fixture();
+    assert!(num_steps * num_headers_per_step <= headers.len(), "not enough headers in the fixture");
+    let bits = compact_target(&headers[0]);
+    let mut circuits = Vec::with_capacity(num_steps);
+    let mut tip = [0u8; DIGEST_BYTES];
+    for chunk in headers.chunks(num_headers_per_step).take(num_steps) {
+      let circuit = HeaderChainCircuit::new(tip, bits, chunk.to_vec());
+      tip = circuit.output_tip();
+      circuits.push(circuit);
+    }
+
+    let z0_primary = initial_state(&[0u8; DIGEST_BYTES], bits);
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = HeaderChainCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn header_chain_fixture_test() {
+  let headers = fixture();
+  let mut tip = [0u8; DIGEST_BYTES];
+  for header in &headers {
+    assert!(check_header(header, &tip, 0x1d00ffff));
+    tip = block_hash(header);
+  }
+
+  // hashes are displayed byte-reversed
+  let genesis = block_hash(&headers[0]).iter().rev().map(|b| format!("{:02x}", b)).collect::<String>();
+  assert_eq!(genesis, "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+}
+
+#[test]
+fn header_chain_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  let synthesize = |circuit: &HeaderChainCircuit<G>| {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z = initial_state::<F>(&circuit.tip, circuit.bits)
+      .iter()
+      .enumerate()
+      .map(|(i, z)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", i)), || Ok(*z)).unwrap())
+      .collect::<Vec<_>>();
+    let z_out = circuit.synthesize(&mut cs, &z).unwrap();
+    (cs.is_satisfied(), z_out.iter().map(|z| z.get_value().unwrap()).collect::<Vec<_>>())
+  };
+
+  let headers = fixture();
+  let bits = compact_target(&headers[0]);
+  let circuit = HeaderChainCircuit::<G>::new([0u8; DIGEST_BYTES], bits, headers[..2].to_vec());
+  let (satisfied, z_out) = synthesize(&circuit);
+  assert!(satisfied);
+  assert_eq!(z_out, initial_state::<F>(&block_hash(&headers[1]), bits));
+
+  // a header that doesn't extend the tip
+  let circuit = HeaderChainCircuit::<G>::new([0u8; DIGEST_BYTES], bits, vec![headers[1]]);
+  assert!(!synthesize(&circuit).0);
+
+  // a header whose hash misses the target
+  let mut header = headers[2];
+  header[HEADER_BYTES - 1] ^= 1;
+  let circuit = HeaderChainCircuit::<G>::new(block_hash(&headers[1]), bits, vec![header]);
+  assert!(!synthesize(&circuit).0);
+}
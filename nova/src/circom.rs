@@ -0,0 +1,406 @@
+//! Circom frontend: loads a step circuit compiled by circom, its `.r1cs`, and wraps it as a
+//! `StepCircuit`, so existing Circom circuits can be folded without a bellpepper rewrite.
+//!
+//! The main component takes the state `z_i` as its first input signal (an array for an arity
+//! above one) and outputs `z_{i+1}` as its only output signal, any other input is private to the
+//! step. Circom lays the wires out as the constant one, the outputs, then the inputs, so the
+//! arity is the number of outputs and the input wires of the state directly reuse the variables
+//! of `z_i`.
+//!
+//! The witness of every step either comes from a precomputed `.wtns` file, or from the circuit's
+//! wasm, run by node through the `generate_witness.js` script circom emits next to it.
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError, Variable};
+use ff::{Field, PrimeField};
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use num_bigint::BigUint;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind, Read};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+/// Directory of the bundled multiplier step circuit, see its README.
+pub const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../fixtures/circom");
+/// Number of steps the bundled witnesses cover.
+pub const FIXTURE_STEPS: usize = 10;
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+const HEADER: u32 = 1;
+const CONSTRAINTS: u32 = 2;
+const WITNESS: u32 = 2;
+
+/// Terms of a linear combination, as wire indices and coefficients.
+pub type Terms<F> = Vec<(usize, F)>;
+
+#[derive(Clone, Debug)]
+pub struct R1CS<F: PrimeField> {
+  pub num_wires: usize,
+  pub num_pub_out: usize,
+  pub num_pub_in: usize,
+  pub num_prv_in: usize,
+  pub constraints: Vec<(Terms<F>, Terms<F>, Terms<F>)>,
+}
+
+impl<F: PrimeField> R1CS<F> {
+  pub fn arity(&self) -> usize {
+    self.num_pub_out
+  }
+
+  /// Checks that `witness` is a witness of a step from `z`, the values of the constraints
+  /// themselves are left to the proof.
+  pub fn check_witness(&self, witness: &[F], z: &[F]) -> io::Result<()> {
+    if witness.len() != self.num_wires {
+      return Err(invalid("the witness does not match the number of wires"));
+    }
+    if witness[0] != F::ONE {
+      return Err(invalid("wire 0 of the witness is not one"));
+    }
+    if witness[1 + self.arity()..1 + 2 * self.arity()] != *z {
+      return Err(invalid("the witness does not start from the state"));
+    }
+    Ok(())
+  }
+}
+
+fn invalid(message: &str) -> io::Error {
+  io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn read_u32(reader: &mut &[u8]) -> io::Result<u32> {
+  let mut bytes = [0u8; 4];
+  reader.read_exact(&mut bytes)?;
+  Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut &[u8]) -> io::Result<u64> {
+  let mut bytes = [0u8; 8];
+  reader.read_exact(&mut bytes)?;
+  Ok(u64::from_le_bytes(bytes))
+}
+
+// field elements are stored in little-endian canonical form, as the representation of `F`
+fn read_field<F: PrimeField>(reader: &mut &[u8]) -> io::Result<F> {
+  let mut repr = F::Repr::default();
+  reader.read_exact(repr.as_mut())?;
+  Option::from(F::from_repr(repr)).ok_or_else(|| invalid("field element out of range"))
+}
+
+// the prime of the header, which must be the modulus of `F`
+fn read_prime<F: PrimeField>(reader: &mut &[u8]) -> io::Result<()> {
+  let n8 = read_u32(reader)? as usize;
+  let mut prime = vec![0u8; n8];
+  reader.read_exact(&mut prime)?;
+  if BigUint::from_bytes_le(&prime) != BigUint::from_bytes_le((-F::ONE).to_repr().as_ref()) + 1u32 {
+    return Err(invalid("the file is over another field"));
+  }
+  Ok(())
+}
+
+// sections of an iden3 binary file by type, they may come in any order
+fn read_sections<'a>(bytes: &'a [u8], magic: &[u8; 4]) -> io::Result<HashMap<u32, &'a [u8]>> {
+  let mut reader = bytes;
+  let mut file_magic = [0u8; 4];
+  reader.read_exact(&mut file_magic)?;
+  if file_magic != *magic {
+    return Err(invalid("invalid magic number"));
+  }
+  let _version = read_u32(&mut reader)?;
+  let num_sections = read_u32(&mut reader)?;
+
+  let mut sections = HashMap::new();
+  for _ in 0..num_sections {
+    let section_type = read_u32(&mut reader)?;
+    let size = read_u64(&mut reader)? as usize;
+    if size > reader.len() {
+      return Err(invalid("truncated section"));
+    }
+    sections.insert(section_type, &reader[..size]);
+    reader = &reader[size..];
+  }
+  Ok(sections)
+}
+
+fn section<'a>(sections: &HashMap<u32, &'a [u8]>, section_type: u32) -> io::Result<&'a [u8]> {
+  sections.get(&section_type).copied().ok_or_else(|| invalid("missing section"))
+}
+
+/// Loads a `.r1cs` file compiled by circom over the field `F`.
+pub fn load_r1cs<F: PrimeField>(path: impl AsRef<Path>) -> io::Result<R1CS<F>> {
+  let bytes = fs::read(path)?;
+  let sections = read_sections(&bytes, R1CS_MAGIC)?;
+
+  let mut header = section(&sections, HEADER)?;
+  read_prime::<F>(&mut header)?;
+  let num_wires = read_u32(&mut header)? as usize;
+  let num_pub_out = read_u32(&mut header)? as usize;
+  let num_pub_in = read_u32(&mut header)? as usize;
+  let num_prv_in = read_u32(&mut header)? as usize;
+  let _num_labels = read_u64(&mut header)?;
+  let num_constraints = read_u32(&mut header)? as usize;
+  if num_pub_out == 0 || num_pub_in + num_prv_in < num_pub_out {
+    return Err(invalid("the step circuit takes the state as its first input and outputs the next one"));
+  }
+
+  let mut reader = section(&sections, CONSTRAINTS)?;
+  let mut read_terms = || -> io::Result<Terms<F>> {
+    let num_terms = read_u32(&mut reader)?;
+    (0..num_terms)
+      .map(|_| {
+        let wire = read_u32(&mut reader)? as usize;
+        if wire >= num_wires {
+          return Err(invalid("wire out of range"));
+        }
+        Ok((wire, read_field(&mut reader)?))
+      })
+      .collect()
+  };
+  let constraints = (0..num_constraints)
+    .map(|_| Ok((read_terms()?, read_terms()?, read_terms()?)))
+    .collect::<io::Result<Vec<_>>>()?;
+
+  Ok(R1CS {
+    num_wires,
+    num_pub_out,
+    num_pub_in,
+    num_prv_in,
+    constraints,
+  })
+}
+
+/// Loads a `.wtns` file over the field `F`.
+pub fn load_wtns<F: PrimeField>(path: impl AsRef<Path>) -> io::Result<Vec<F>> {
+  let bytes = fs::read(path)?;
+  let sections = read_sections(&bytes, WTNS_MAGIC)?;
+
+  let mut header = section(&sections, HEADER)?;
+  read_prime::<F>(&mut header)?;
+  let num_witnesses = read_u32(&mut header)?;
+
+  let mut reader = section(&sections, WITNESS)?;
+  (0..num_witnesses).map(|_| read_field(&mut reader)).collect()
+}
+
+#[derive(Clone, Debug)]
+pub enum WitnessGenerator<F: PrimeField> {
+  /// One precomputed `.wtns` file per step.
+  Wtns(Vec<PathBuf>),
+  /// The wasm of the circuit run by node through `script`, the `generate_witness.js` circom
+  /// emits. The state goes to the input signal `step_in`, `inputs` holds the private inputs of
+  /// every step by signal name.
+  Wasm {
+    script: PathBuf,
+    wasm: PathBuf,
+    step_in: String,
+    inputs: Vec<Vec<(String, Vec<F>)>>,
+  },
+}
+
+impl<F: PrimeField> WitnessGenerator<F> {
+  pub fn num_steps(&self) -> usize {
+    match self {
+      WitnessGenerator::Wtns(paths) => paths.len(),
+      WitnessGenerator::Wasm { inputs, .. } => inputs.len(),
+    }
+  }
+
+  /// Witness of step `step` of `r1cs` from the state `z`.
+  pub fn generate(&self, r1cs: &R1CS<F>, step: usize, z: &[F]) -> io::Result<Vec<F>> {
+    let witness = match self {
+      WitnessGenerator::Wtns(paths) => load_wtns(&paths[step])?,
+      WitnessGenerator::Wasm {
+        script,
+        wasm,
+        step_in,
+        inputs,
+      } => {
+        let signals = std::iter::once((step_in, z)).chain(inputs[step].iter().map(|(name, values)| (name, &values[..])));
+        let json = signals
+          .map(|(name, values)| {
+            let values = values
+              .iter()
+              .map(|value| format!("\"{}\"", BigUint::from_bytes_le(value.to_repr().as_ref())))
+              .collect::<Vec<_>>();
+            format!("\"{}\": [{}]", name, values.join(", "))
+          })
+          .collect::<Vec<_>>();
+
+        let file = std::env::temp_dir().join(format!("circom_{}_{}", std::process::id(), step));
+        let (input, output) = (file.with_extension("json"), file.with_extension("wtns"));
+        fs::write(&input, format!("{{{}}}", json.join(", ")))?;
+        let status = Command::new("node").arg(script).arg(wasm).arg(&input).arg(&output).status()?;
+        let witness = if status.success() {
+          load_wtns(&output)
+        } else {
+          Err(io::Error::new(ErrorKind::Other, "witness generation failed"))
+        };
+        let _ = fs::remove_file(input);
+        let _ = fs::remove_file(output);
+        witness?
+      }
+    };
+    r1cs.check_witness(&witness, z)?;
+    Ok(witness)
+  }
+}
+
+/// The bundled multiplier step circuit and the witnesses of its first steps from `z_0 = 3`.
+pub fn fixture<F: PrimeField>() -> io::Result<(R1CS<F>, WitnessGenerator<F>)> {
+  let dir = Path::new(FIXTURE_DIR);
+  let r1cs = load_r1cs(dir.join("multiplier2.r1cs"))?;
+  let paths = (0..FIXTURE_STEPS)
+    .map(|i| dir.join(format!("witness/bn254_{}.wtns", i)))
+    .collect();
+  Ok((r1cs, WitnessGenerator::Wtns(paths)))
+}
+
+#[derive(Clone, Debug)]
+pub struct CircomCircuit<G: Group> {
+  pub r1cs: Arc<R1CS<G::Scalar>>,
+  // witness of the step, all the wires
+  pub witness: Option<Vec<G::Scalar>>,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> CircomCircuit<G> {
+  pub fn new(r1cs: Arc<R1CS<G::Scalar>>, witness: Option<Vec<G::Scalar>>) -> Self {
+    Self {
+      r1cs,
+      witness,
+      _p: PhantomData,
+    }
+  }
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for CircomCircuit<G> {
+  fn arity(&self) -> usize {
+    self.r1cs.arity()
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    let arity = self.r1cs.arity();
+    assert_eq!(z.len(), arity);
+    let value = |wire: usize| {
+      self
+        .witness
+        .as_ref()
+        .map(|witness| witness[wire])
+        .ok_or(SynthesisError::AssignmentMissing)
+    };
+
+    let mut wires = Vec::with_capacity(self.r1cs.num_wires);
+    wires.push(CS::one());
+    let z_out = (0..arity)
+      .map(|k| AllocatedNum::alloc(cs.namespace(|| format!("step_out_{}", k)), || value(1 + k)))
+      .collect::<Result<Vec<_>, _>>()?;
+    wires.extend(z_out.iter().map(|z| z.get_variable()));
+    wires.extend(z.iter().map(|z| z.get_variable()));
+    for wire in wires.len()..self.r1cs.num_wires {
+      let var = AllocatedNum::alloc(cs.namespace(|| format!("wire_{}", wire)), || value(wire))?;
+      wires.push(var.get_variable());
+    }
+
+    let lc = |terms: &Terms<G::Scalar>| -> LinearCombination<G::Scalar> {
+      terms
+        .iter()
+        .fold(LinearCombination::zero(), |lc, (wire, coeff)| lc + (*coeff, wires[*wire]))
+    };
+    for (i, (a, b, c)) in self.r1cs.constraints.iter().enumerate() {
+      cs.enforce(|| format!("constraint_{}", i), |_| lc(a), |_| lc(b), |_| lc(c));
+    }
+
+    Ok(z_out)
+  }
+
+  fn output(&self, _z: &[G::Scalar]) -> Vec<G::Scalar> {
+    let witness = self.witness.as_ref().expect("the witness of the step");
+    witness[1..1 + self.r1cs.arity()].to_vec()
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, r1cs: Arc<R1CS<<E1 as Engine>::Scalar>>, generator: &WitnessGenerator<<E1 as Engine>::Scalar>,
+    z0_primary: Vec<<E1 as Engine>::Scalar>,
+    pp: PublicParams<E1, E2, CircomCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce the witnesses of the steps
+    let mut circuits = Vec::with_capacity(num_steps);
+    let mut z = z0_primary.clone();
+    for step in 0..num_steps {
+      let witness = generator.generate(&r1cs, step, &z).unwrap();
+      let circuit = CircomCircuit::new(r1cs.clone(), Some(witness));
+      z = circuit.output(&z);
+      circuits.push(circuit);
+    }
+
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = CircomCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn circom_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  let (r1cs, generator) = fixture::<F>().unwrap();
+  assert_eq!((r1cs.arity(), r1cs.num_wires, r1cs.constraints.len()), (1, 4, 1));
+  let r1cs = Arc::new(r1cs);
+
+  // c = a * b, with b = i + 2 at step i
+  let mut z_in = vec![F::from(3)];
+  for step in 0..3 {
+    let circuit = CircomCircuit::<G>::new(r1cs.clone(), Some(generator.generate(&r1cs, step, &z_in).unwrap()));
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z = AllocatedNum::alloc(cs.namespace(|| "z"), || Ok(z_in[0])).unwrap();
+    let z_out = circuit.synthesize(&mut cs, &[z]).unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(vec![z_out[0].get_value().unwrap()], circuit.output(&z_in));
+    assert_eq!(z_out[0].get_value().unwrap(), z_in[0] * F::from(step as u64 + 2));
+    z_in = circuit.output(&z_in);
+  }
+
+  // a witness of another step
+  assert!(generator.generate(&r1cs, 3, &[F::from(3)]).is_err());
+  // a witness over another field
+  assert!(load_wtns::<F>(Path::new(FIXTURE_DIR).join("witness/pallas_0.wtns")).is_err());
+}
@@ -0,0 +1,378 @@
+//! Lowering of the `circuit_dsl` step circuits to bellpepper: [`BellpepperBuilder`] synthesizes a
+//! step into a constraint system, [`NativeBuilder`] computes its next state, and [`DslCircuit`]
+//! wraps a step as a Nova `StepCircuit`. Poseidon is the neptune `U4` sponge of the hash chain
+//! workload.
+use bellpepper_core::{
+  boolean::AllocatedBit, num::AllocatedNum, ConstraintSystem, Namespace, SynthesisError,
+};
+use circuit_dsl::{Builder, Step};
+use ff::{Field, PrimeField};
+use generic_array::typenum::U4;
+use neptune::{
+  circuit2::Elt,
+  poseidon::PoseidonConstants,
+  sponge::{
+    api::{IOPattern, SpongeAPI, SpongeOp},
+    circuit::SpongeCircuit,
+    vanilla::{Mode::Simplex, Sponge, SpongeTrait},
+  },
+  Strength,
+};
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{circuit::{StepCircuit, TrivialCircuit}, Engine},
+  PublicParams, RecursiveSNARK,
+};
+use num_bigint::BigUint;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+fn to_biguint<F: PrimeField>(x: &F) -> BigUint {
+  BigUint::from_bytes_le(x.to_repr().as_ref())
+}
+
+fn from_biguint<F: PrimeField>(x: &BigUint) -> F {
+  F::from_str_vartime(&x.to_str_radix(10)).expect("value out of the field")
+}
+
+fn modulus<F: PrimeField>() -> BigUint {
+  to_biguint(&-F::ONE) + 1u32
+}
+
+fn poseidon_io(num_absorbs: usize) -> IOPattern {
+  IOPattern(vec![SpongeOp::Absorb(num_absorbs as u32), SpongeOp::Squeeze(1u32)])
+}
+
+/// Synthesizes the operations of a step into `cs`, one namespace per operation.
+pub struct BellpepperBuilder<'a, F: PrimeField, CS: ConstraintSystem<F>> {
+  cs: &'a mut CS,
+  num_ops: usize,
+  poseidon_constants: Option<PoseidonConstants<F, U4>>,
+}
+
+impl<'a, F: PrimeField, CS: ConstraintSystem<F>> BellpepperBuilder<'a, F, CS> {
+  pub fn new(cs: &'a mut CS) -> Self {
+    Self { cs, num_ops: 0, poseidon_constants: None }
+  }
+
+  fn namespace(&mut self) -> Namespace<'_, F, CS::Root> {
+    self.num_ops += 1;
+    let n = self.num_ops;
+    self.cs.namespace(move || format!("op_{}", n))
+  }
+}
+
+impl<'a, F: PrimeField, CS: ConstraintSystem<F>> Builder for BellpepperBuilder<'a, F, CS> {
+  type Var = AllocatedNum<F>;
+  type Error = SynthesisError;
+
+  fn modulus(&self) -> BigUint {
+    modulus::<F>()
+  }
+
+  fn constant(&mut self, value: &BigUint) -> Result<Self::Var, Self::Error> {
+    let value = from_biguint::<F>(value);
+    let mut cs = self.namespace();
+    let x = AllocatedNum::alloc(cs.namespace(|| "value"), || Ok(value))?;
+    cs.enforce(
+      || "x = value",
+      |lc| lc + x.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + (value, CS::one()),
+    );
+    Ok(x)
+  }
+
+  fn alloc(&mut self, value: impl FnOnce() -> BigUint) -> Result<Self::Var, Self::Error> {
+    AllocatedNum::alloc(self.namespace(), || Ok(from_biguint(&value())))
+  }
+
+  fn hint(
+    &mut self,
+    inputs: &[Self::Var],
+    f: impl FnOnce(&[BigUint]) -> BigUint,
+  ) -> Result<Self::Var, Self::Error> {
+    let values = inputs
+      .iter()
+      .map(|x| x.get_value().map(|value| to_biguint(&value)))
+      .collect::<Option<Vec<_>>>();
+    AllocatedNum::alloc(self.namespace(), || {
+      values
+        .map(|values| from_biguint(&f(&values)))
+        .ok_or(SynthesisError::AssignmentMissing)
+    })
+  }
+
+  fn add(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error> {
+    a.add(self.namespace(), b)
+  }
+
+  fn mul(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error> {
+    a.mul(self.namespace(), b)
+  }
+
+  fn enforce_eq(&mut self, a: &Self::Var, b: &Self::Var) -> Result<(), Self::Error> {
+    self.namespace().enforce(
+      || "a = b",
+      |lc| lc + a.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + b.get_variable(),
+    );
+    Ok(())
+  }
+
+  fn poseidon(&mut self, inputs: &[Self::Var]) -> Result<Self::Var, Self::Error> {
+    self.num_ops += 1;
+    let n = self.num_ops;
+    let pc = self
+      .poseidon_constants
+      .get_or_insert_with(|| Sponge::<F, U4>::api_constants(Strength::Standard));
+    let mut ns = self.cs.namespace(move || format!("op_{}", n));
+
+    let elt = inputs
+      .iter()
+      .map(|x| Elt::Allocated(x.clone()))
+      .collect::<Vec<_>>();
+    let mut sponge = SpongeCircuit::new_with_constants(pc, Simplex);
+    let acc = &mut ns;
+    sponge.start(poseidon_io(inputs.len()), None, acc);
+    SpongeAPI::absorb(&mut sponge, inputs.len() as u32, &elt, acc);
+    let output = SpongeAPI::squeeze(&mut sponge, 1, acc);
+    sponge.finish(acc).unwrap();
+    Elt::ensure_allocated(&output[0], &mut ns.namespace(|| "ensure allocated"), true)
+  }
+
+  // a single linear constraint over the bits, as in the range check workload
+  fn range_check(&mut self, a: &Self::Var, num_bits: usize) -> Result<(), Self::Error> {
+    let value = a.get_value().map(|value| to_biguint(&value));
+    let mut cs = self.namespace();
+    let bits = (0..num_bits)
+      .map(|k| AllocatedBit::alloc(cs.namespace(|| format!("bit_{}", k)), value.as_ref().map(|v| v.bit(k as u64))))
+      .collect::<Result<Vec<_>, _>>()?;
+
+    cs.enforce(
+      || "a = sum_k bit_k * 2^k",
+      |lc| {
+        bits
+          .iter()
+          .fold((lc, F::ONE), |(lc, power), bit| (lc + (power, bit.get_variable()), power.double()))
+          .0
+      },
+      |lc| lc + CS::one(),
+      |lc| lc + a.get_variable(),
+    );
+    Ok(())
+  }
+}
+
+/// Runs the operations of a step on values, failing on an unsatisfied equality.
+pub struct NativeBuilder<F: PrimeField> {
+  poseidon_constants: Option<PoseidonConstants<F, U4>>,
+}
+
+impl<F: PrimeField> Default for NativeBuilder<F> {
+  fn default() -> Self {
+    Self { poseidon_constants: None }
+  }
+}
+
+impl<F: PrimeField> Builder for NativeBuilder<F> {
+  type Var = F;
+  type Error = SynthesisError;
+
+  fn modulus(&self) -> BigUint {
+    modulus::<F>()
+  }
+
+  fn constant(&mut self, value: &BigUint) -> Result<F, SynthesisError> {
+    Ok(from_biguint(value))
+  }
+
+  fn alloc(&mut self, value: impl FnOnce() -> BigUint) -> Result<F, SynthesisError> {
+    Ok(from_biguint(&value()))
+  }
+
+  fn hint(&mut self, inputs: &[F], f: impl FnOnce(&[BigUint]) -> BigUint) -> Result<F, SynthesisError> {
+    Ok(from_biguint(&f(&inputs.iter().map(to_biguint).collect::<Vec<_>>())))
+  }
+
+  fn add(&mut self, a: &F, b: &F) -> Result<F, SynthesisError> {
+    Ok(*a + b)
+  }
+
+  fn mul(&mut self, a: &F, b: &F) -> Result<F, SynthesisError> {
+    Ok(*a * b)
+  }
+
+  fn enforce_eq(&mut self, a: &F, b: &F) -> Result<(), SynthesisError> {
+    if a == b {
+      Ok(())
+    } else {
+      Err(SynthesisError::Unsatisfiable)
+    }
+  }
+
+  fn poseidon(&mut self, inputs: &[F]) -> Result<F, SynthesisError> {
+    let pc = self
+      .poseidon_constants
+      .get_or_insert_with(|| Sponge::<F, U4>::api_constants(Strength::Standard));
+    let mut sponge = Sponge::new_with_constants(pc, Simplex);
+    let acc = &mut ();
+    sponge.start(poseidon_io(inputs.len()), None, acc);
+    SpongeAPI::absorb(&mut sponge, inputs.len() as u32, inputs, acc);
+    let output = SpongeAPI::squeeze(&mut sponge, 1, acc);
+    sponge.finish(acc).unwrap();
+    Ok(output[0])
+  }
+
+  fn range_check(&mut self, a: &F, num_bits: usize) -> Result<(), SynthesisError> {
+    if to_biguint(a).bits() <= num_bits as u64 {
+      Ok(())
+    } else {
+      Err(SynthesisError::Unsatisfiable)
+    }
+  }
+}
+
+/// Step `i` of a DSL step circuit, Nova takes one circuit per step.
+#[derive(Clone, Debug)]
+pub struct DslCircuit<F: PrimeField, S: Step> {
+  pub step: S,
+  pub i: usize,
+  _p: PhantomData<F>,
+}
+
+impl<F: PrimeField, S: Step> DslCircuit<F, S> {
+  pub fn new(step: S, i: usize) -> Self {
+    Self { step, i, _p: PhantomData }
+  }
+}
+
+impl<F: PrimeField, S: Step> StepCircuit<F> for DslCircuit<F, S> {
+  fn arity(&self) -> usize {
+    S::ARITY
+  }
+
+  fn synthesize<CS: ConstraintSystem<F>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<F>],
+  ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+    assert_eq!(z.len(), S::ARITY);
+    self.step.synthesize(&mut BellpepperBuilder::new(cs), self.i, z)
+  }
+
+  fn output(&self, z: &[F]) -> Vec<F> {
+    self
+      .step
+      .synthesize(&mut NativeBuilder::default(), self.i, z)
+      .expect("the step is not satisfied")
+  }
+}
+
+pub fn nova_ivc<S: Step>(num_steps: usize, step: S, z0_primary: Vec<<E1 as Engine>::Scalar>,
+    pp: PublicParams<E1, E2, DslCircuit<<E1 as Engine>::Scalar, S>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    let circuits = (0..num_steps)
+      .map(|i| DslCircuit::new(step.clone(), i))
+      .collect::<Vec<_>>();
+
+    let z0_secondary = vec![<E2 as Engine>::Scalar::ZERO];
+
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, DslCircuit<<E1 as Engine>::Scalar, S>, C2> =
+        RecursiveSNARK::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn dsl_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+  use circuit_dsl::{hashchain::HashChain, minroot::MinRoot, range_check::RangeCheck};
+
+  type F = <E1 as Engine>::Scalar;
+
+  fn run<S: Step>(step: S, z_in: &[F]) -> Vec<F> {
+    let circuit = DslCircuit::<F, S>::new(step, 1);
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z = z_in
+      .iter()
+      .enumerate()
+      .map(|(k, z)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", k)), || Ok(*z)).unwrap())
+      .collect::<Vec<_>>();
+    let z_out = circuit.synthesize(&mut cs, &z).unwrap();
+
+    assert!(cs.is_satisfied());
+    let z_out = z_out.iter().map(|z| z.get_value().unwrap()).collect::<Vec<_>>();
+    assert_eq!(z_out, circuit.output(z_in));
+    z_out
+  }
+
+  // the fifth root is a hint, the constraints check it
+  let z = run(MinRoot { num_iters_per_step: 16 }, &[F::ZERO, F::ZERO, F::ONE]);
+  assert_eq!(z[0], F::from(16));
+
+  run(HashChain { num_elts_per_step: 5, seed: 7 }, &[F::from(3)]);
+  run(RangeCheck { num_adds_per_step: 4 }, &[F::from(u64::MAX)]);
+
+  // an accumulator out of range carries more than one bit
+  let step = RangeCheck { num_adds_per_step: 1 };
+  assert!(step.synthesize(&mut NativeBuilder::default(), 0, &[F::from(u64::MAX).double().double()]).is_err());
+}
+
+#[test]
+fn dsl_baseline_test() {
+  use crate::{hashchain::HashChainCircuit, minroot::minroot_circuits, range_check::RangeCheckCircuit};
+  use bellpepper_core::test_cs::TestConstraintSystem;
+  use circuit_dsl::{hashchain::HashChain, minroot::MinRoot, range_check::RangeCheck};
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  fn num_constraints<C: StepCircuit<F>>(circuit: &C, z_in: &[F]) -> usize {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z = z_in
+      .iter()
+      .enumerate()
+      .map(|(k, z)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", k)), || Ok(*z)).unwrap())
+      .collect::<Vec<_>>();
+    circuit.synthesize(&mut cs, &z).unwrap();
+    assert!(cs.is_satisfied());
+    cs.num_constraints()
+  }
+
+  // the hand-written circuits are the baselines of the DSL lowerings, which spend a constraint on
+  // every addition and on their constants
+  let n = 16;
+  let (z0, circuits) = minroot_circuits(1, n);
+  assert_eq!(num_constraints(&circuits[0], &z0), 5 * n);
+  assert_eq!(num_constraints(&DslCircuit::new(MinRoot { num_iters_per_step: n }, 0), &z0), 7 * n + 1);
+
+  let z0 = [F::ZERO];
+  assert_eq!(
+    num_constraints(&HashChainCircuit::<G>::new(n), &z0),
+    num_constraints(&DslCircuit::new(HashChain { num_elts_per_step: n, seed: 0 }, 0), &z0)
+  );
+
+  assert_eq!(num_constraints(&RangeCheckCircuit::<G>::new(0, 0, n), &z0), 132 * n);
+  assert_eq!(num_constraints(&DslCircuit::new(RangeCheck { num_adds_per_step: n }, 0), &z0), 136 * n + 1);
+}
@@ -0,0 +1,547 @@
+//! ECDSA verification chain over secp256k1, whose base and scalar fields are both foreign to the
+//! BN254 scalar field the primary circuit works in. Each step verifies a configurable number of
+//! signatures with the non-native `BigNat` gadgets of nova-snark and absorbs every verified message
+//! hash into the running accumulator `acc' = H(acc, z)`, with `H` neptune Poseidon over the limbs.
+//!
+//! The verifier recomputes the nonce point `R = u1 * G + u2 * P` with `u1 = z / s` and
+//! `u2 = r / s`, and checks `R.x = r mod n`. The double-and-add starts from a fixed offset point
+//! so that the incomplete addition formulas never see the identity.
+use bellpepper_core::{
+  boolean::AllocatedBit, num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError,
+};
+use ff::{Field, PrimeField};
+use generic_array::typenum::U5;
+use halo2curves::bn256::Fr;
+use halo2curves::group::{prime::PrimeCurveAffine, Curve, Group};
+use halo2curves::secp256k1::{Fp, Fq, Secp256k1, Secp256k1Affine};
+use halo2curves::CurveAffine;
+use neptune::{circuit2::poseidon_hash_allocated, poseidon::PoseidonConstants, Poseidon};
+use nova_snark::{
+  gadgets::nonnative::bignat::{BigNat, BigNatParams},
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use num_bigint::{BigInt, Sign};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+// secp256k1 elements are split into 4 limbs of 64 bits
+pub const LIMB_WIDTH: usize = 64;
+pub const NUM_LIMBS: usize = 4;
+pub const NUM_SCALAR_BITS: usize = LIMB_WIDTH * NUM_LIMBS;
+
+fn to_nat<F: PrimeField>(f: &F) -> BigInt {
+  BigInt::from_bytes_le(Sign::Plus, f.to_repr().as_ref())
+}
+
+fn from_nat<F: PrimeField>(n: &BigInt) -> F {
+  let (_, mut bytes) = (n % modulus::<F>()).to_bytes_le();
+  let mut repr = F::Repr::default();
+  bytes.resize(repr.as_ref().len(), 0);
+  repr.as_mut().copy_from_slice(&bytes);
+  F::from_repr(repr).unwrap()
+}
+
+fn modulus<F: PrimeField>() -> BigInt {
+  to_nat(&-F::ONE) + 1
+}
+
+// the little-endian limbs of `n`
+fn limbs(n: &BigInt) -> Vec<u64> {
+  let mut digits = n.magnitude().iter_u64_digits().collect::<Vec<_>>();
+  digits.resize(NUM_LIMBS, 0);
+  digits
+}
+
+fn coordinates(point: Secp256k1Affine) -> [Fp; 2] {
+  let coordinates = point.coordinates().unwrap();
+  [*coordinates.x(), *coordinates.y()]
+}
+
+// the point the double-and-add starts from, and what it becomes after all the doublings
+fn offset_points() -> (Secp256k1Affine, Secp256k1Affine) {
+  let offset = Secp256k1::generator() * Fq::from(0x5eed);
+  let shifted = (0..NUM_SCALAR_BITS).fold(offset, |point, _| point.double());
+  (offset.to_affine(), shifted.to_affine())
+}
+
+/// A signature `(r, s)` on the message hash `msg_hash` under the public key `pk`.
+#[derive(Clone, Debug)]
+pub struct Signature {
+  pub pk: Secp256k1Affine,
+  pub msg_hash: Fq,
+  pub r: Fq,
+  pub s: Fq,
+}
+
+impl Signature {
+  // signs a random message hash under a fresh random key
+  pub fn random(rng: &mut impl Rng) -> Self {
+    let g = Secp256k1::generator();
+    let sk = Fq::random(&mut *rng);
+    let k = Fq::random(&mut *rng);
+    let msg_hash = Fq::random(&mut *rng);
+
+    let r = from_nat::<Fq>(&to_nat(&coordinates((g * k).to_affine())[0]));
+    let s = k.invert().unwrap() * (msg_hash + r * sk);
+
+    Self { pk: (g * sk).to_affine(), msg_hash, r, s }
+  }
+
+  // u1 = z / s and u2 = r / s
+  fn scalars(&self) -> (Fq, Fq) {
+    let s_inv = self.s.invert().unwrap();
+    (self.msg_hash * s_inv, self.r * s_inv)
+  }
+
+  // R = u1 * G + u2 * P
+  fn nonce_point(&self) -> Secp256k1Affine {
+    let (u1, u2) = self.scalars();
+    (Secp256k1::generator() * u1 + self.pk * u2).to_affine()
+  }
+
+  pub fn verify(&self) -> bool {
+    from_nat::<Fq>(&to_nat(&coordinates(self.nonce_point())[0])) == self.r
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct EcdsaChainCircuit {
+  pub num_sigs_per_step: usize,
+  pub acc: Fr,
+  pub signatures: Vec<Signature>,
+}
+
+impl EcdsaChainCircuit {
+  // produces fresh signatures to be verified on top of the accumulator `acc`
+  pub fn new(acc: Fr, num_sigs_per_step: usize, rng: &mut impl Rng) -> Self {
+    Self {
+      num_sigs_per_step,
+      acc,
+      signatures: (0..num_sigs_per_step).map(|_| Signature::random(&mut *rng)).collect(),
+    }
+  }
+
+  pub fn output_acc(&self) -> Fr {
+    let constants = PoseidonConstants::<Fr, U5>::new();
+    self.signatures.iter().fold(self.acc, |acc, sig| {
+      let mut preimage = vec![acc];
+      preimage.extend(limbs(&to_nat(&sig.msg_hash)).into_iter().map(Fr::from));
+      Poseidon::new_with_preimage(&preimage, &constants).hash()
+    })
+  }
+}
+
+/// A non-native element whose limbs are range checked through their bits.
+struct AllocatedNonNative {
+  nat: BigNat<Fr>,
+  limbs: Vec<AllocatedNum<Fr>>,
+  // little-endian, across the limbs
+  bits: Vec<AllocatedBit>,
+}
+
+fn alloc_nonnative<CS: ConstraintSystem<Fr>>(
+  mut cs: CS,
+  value: &BigInt,
+) -> Result<AllocatedNonNative, SynthesisError> {
+  let digits = limbs(value);
+  let mut limbs = Vec::with_capacity(NUM_LIMBS);
+  let mut bits = Vec::with_capacity(NUM_SCALAR_BITS);
+  for (i, digit) in digits.iter().enumerate() {
+    let limb = AllocatedNum::alloc(cs.namespace(|| format!("limb_{}", i)), || Ok(Fr::from(*digit)))?;
+    let limb_bits = (0..LIMB_WIDTH)
+      .map(|j| AllocatedBit::alloc(cs.namespace(|| format!("bit_{}_{}", i, j)), Some((digit >> j) & 1 == 1)))
+      .collect::<Result<Vec<_>, _>>()?;
+    cs.enforce(
+      || format!("pack_{}", i),
+      |lc| {
+        limb_bits
+          .iter()
+          .enumerate()
+          .fold(lc, |lc, (j, bit)| lc + (Fr::from(1u64 << j), bit.get_variable()))
+      },
+      |lc| lc + CS::one(),
+      |lc| lc + limb.get_variable(),
+    );
+    limbs.push(limb);
+    bits.extend(limb_bits);
+  }
+
+  let nat = BigNat {
+    limb_values: Some(digits.iter().map(|digit| Fr::from(*digit)).collect()),
+    limbs: limbs.iter().map(|limb| LinearCombination::zero() + limb.get_variable()).collect(),
+    value: Some(value.clone()),
+    params: BigNatParams::new(LIMB_WIDTH, NUM_LIMBS),
+  };
+  Ok(AllocatedNonNative { nat, limbs, bits })
+}
+
+fn constant<CS: ConstraintSystem<Fr>>(value: &BigInt) -> BigNat<Fr> {
+  let digits = limbs(value);
+  BigNat {
+    limb_values: Some(digits.iter().map(|digit| Fr::from(*digit)).collect()),
+    limbs: digits.iter().map(|digit| LinearCombination::zero() + (Fr::from(*digit), CS::one())).collect(),
+    value: Some(value.clone()),
+    params: BigNatParams::new(LIMB_WIDTH, NUM_LIMBS),
+  }
+}
+
+// a * b reduced modulo `modulus`
+fn mul<CS: ConstraintSystem<Fr>>(
+  cs: CS,
+  a: &BigNat<Fr>,
+  b: &BigNat<Fr>,
+  modulus: &BigNat<Fr>,
+) -> Result<BigNat<Fr>, SynthesisError> {
+  a.mult_mod(cs, b, modulus).map(|(_, remainder)| remainder)
+}
+
+// lhs = rhs mod `modulus`, both sides are reduced so that no subtraction is needed
+fn enforce_congruent<CS: ConstraintSystem<Fr>>(
+  mut cs: CS,
+  lhs: &BigNat<Fr>,
+  rhs: &BigNat<Fr>,
+  modulus: &BigNat<Fr>,
+) -> Result<(), SynthesisError> {
+  let lhs = lhs.red_mod(cs.namespace(|| "reduce lhs"), modulus)?;
+  let rhs = rhs.red_mod(cs.namespace(|| "reduce rhs"), modulus)?;
+  lhs.equal_when_carried(cs.namespace(|| "equal"), &rhs)
+}
+
+/// An affine secp256k1 point along with its native value, which drives the witness generation.
+#[derive(Clone)]
+struct NonNativePoint {
+  x: BigNat<Fr>,
+  y: BigNat<Fr>,
+  value: Secp256k1Affine,
+}
+
+fn alloc_point<CS: ConstraintSystem<Fr>>(
+  mut cs: CS,
+  value: Secp256k1Affine,
+  p: &BigNat<Fr>,
+) -> Result<NonNativePoint, SynthesisError> {
+  let [x, y] = coordinates(value);
+  let x = alloc_nonnative(cs.namespace(|| "x"), &to_nat(&x))?.nat;
+  let y = alloc_nonnative(cs.namespace(|| "y"), &to_nat(&y))?.nat;
+
+  // y^2 = x^3 + 7
+  let y_y = mul(cs.namespace(|| "y * y"), &y, &y, p)?;
+  let x_x = mul(cs.namespace(|| "x * x"), &x, &x, p)?;
+  let x_x_x = mul(cs.namespace(|| "x * x * x"), &x_x, &x, p)?;
+  let b = constant::<CS>(&BigInt::from(7));
+  enforce_congruent(cs.namespace(|| "on curve"), &y_y, &x_x_x.add(&b)?, p)?;
+
+  Ok(NonNativePoint { x, y, value })
+}
+
+fn constant_point<CS: ConstraintSystem<Fr>>(value: Secp256k1Affine) -> NonNativePoint {
+  let [x, y] = coordinates(value);
+  NonNativePoint {
+    x: constant::<CS>(&to_nat(&x)),
+    y: constant::<CS>(&to_nat(&y)),
+    value,
+  }
+}
+
+// allocates the slope and the coordinates of the result, which the caller constrains
+fn alloc_result<CS: ConstraintSystem<Fr>>(
+  mut cs: CS,
+  lambda: Fp,
+  value: Secp256k1Affine,
+) -> Result<(BigNat<Fr>, NonNativePoint), SynthesisError> {
+  let [x, y] = coordinates(value);
+  let lambda = alloc_nonnative(cs.namespace(|| "lambda"), &to_nat(&lambda))?.nat;
+  let x = alloc_nonnative(cs.namespace(|| "x"), &to_nat(&x))?.nat;
+  let y = alloc_nonnative(cs.namespace(|| "y"), &to_nat(&y))?.nat;
+  Ok((lambda, NonNativePoint { x, y, value }))
+}
+
+// incomplete addition, a and b must have distinct x-coordinates
+fn add_points<CS: ConstraintSystem<Fr>>(
+  mut cs: CS,
+  a: &NonNativePoint,
+  b: &NonNativePoint,
+  p: &BigNat<Fr>,
+) -> Result<NonNativePoint, SynthesisError> {
+  let ([x1, y1], [x2, y2]) = (coordinates(a.value), coordinates(b.value));
+  let lambda = (y2 - y1) * (x2 - x1).invert().unwrap();
+  let (lambda, sum) = alloc_result(cs.namespace(|| "alloc"), lambda, (a.value + b.value).to_affine())?;
+
+  // lambda * (x2 - x1) = y2 - y1
+  let lambda_x1 = mul(cs.namespace(|| "lambda * x1"), &lambda, &a.x, p)?;
+  let lambda_x2 = mul(cs.namespace(|| "lambda * x2"), &lambda, &b.x, p)?;
+  enforce_congruent(cs.namespace(|| "slope"), &lambda_x2.add(&a.y)?, &lambda_x1.add(&b.y)?, p)?;
+
+  // x3 = lambda^2 - x1 - x2
+  let lambda_lambda = mul(cs.namespace(|| "lambda * lambda"), &lambda, &lambda, p)?;
+  enforce_congruent(cs.namespace(|| "x3"), &sum.x.add(&a.x)?.add(&b.x)?, &lambda_lambda, p)?;
+
+  // y3 = lambda * (x1 - x3) - y1
+  let lambda_x3 = mul(cs.namespace(|| "lambda * x3"), &lambda, &sum.x, p)?;
+  enforce_congruent(cs.namespace(|| "y3"), &sum.y.add(&a.y)?.add(&lambda_x3)?, &lambda_x1, p)?;
+
+  Ok(sum)
+}
+
+fn double_point<CS: ConstraintSystem<Fr>>(
+  mut cs: CS,
+  a: &NonNativePoint,
+  p: &BigNat<Fr>,
+) -> Result<NonNativePoint, SynthesisError> {
+  let [x, y] = coordinates(a.value);
+  let lambda = x.square() * Fp::from(3) * (y.double()).invert().unwrap();
+  let (lambda, double) = alloc_result(cs.namespace(|| "alloc"), lambda, a.value.to_curve().double().to_affine())?;
+
+  // lambda * 2y = 3x^2
+  let lambda_y = mul(cs.namespace(|| "lambda * y"), &lambda, &a.y, p)?;
+  let x_x = mul(cs.namespace(|| "x * x"), &a.x, &a.x, p)?;
+  enforce_congruent(cs.namespace(|| "slope"), &lambda_y.add(&lambda_y)?, &x_x.add(&x_x)?.add(&x_x)?, p)?;
+
+  // x3 = lambda^2 - 2x
+  let lambda_lambda = mul(cs.namespace(|| "lambda * lambda"), &lambda, &lambda, p)?;
+  enforce_congruent(cs.namespace(|| "x3"), &double.x.add(&a.x)?.add(&a.x)?, &lambda_lambda, p)?;
+
+  // y3 = lambda * (x - x3) - y
+  let lambda_x = mul(cs.namespace(|| "lambda * x"), &lambda, &a.x, p)?;
+  let lambda_x3 = mul(cs.namespace(|| "lambda * x3"), &lambda, &double.x, p)?;
+  enforce_congruent(cs.namespace(|| "y3"), &double.y.add(&a.y)?.add(&lambda_x3)?, &lambda_x, p)?;
+
+  Ok(double)
+}
+
+// `b` if `bit` is set and `a` otherwise, limb by limb
+fn select_nat<CS: ConstraintSystem<Fr>>(
+  mut cs: CS,
+  bit: &AllocatedBit,
+  a: &BigNat<Fr>,
+  b: &BigNat<Fr>,
+) -> Result<BigNat<Fr>, SynthesisError> {
+  let selected = if bit.get_value().ok_or(SynthesisError::AssignmentMissing)? { b } else { a };
+  let limb_values = selected.limb_values.clone().ok_or(SynthesisError::AssignmentMissing)?;
+  let mut limbs = Vec::with_capacity(NUM_LIMBS);
+  for (i, value) in limb_values.iter().enumerate() {
+    let limb = AllocatedNum::alloc(cs.namespace(|| format!("limb_{}", i)), || Ok(*value))?;
+    // bit * (b - a) = limb - a
+    cs.enforce(
+      || format!("select_{}", i),
+      |lc| lc + bit.get_variable(),
+      |lc| lc + &b.limbs[i] - &a.limbs[i],
+      |lc| lc + limb.get_variable() - &a.limbs[i],
+    );
+    limbs.push(LinearCombination::zero() + limb.get_variable());
+  }
+
+  Ok(BigNat {
+    limb_values: Some(limb_values),
+    limbs,
+    value: selected.value.clone(),
+    params: BigNatParams::new(LIMB_WIDTH, NUM_LIMBS),
+  })
+}
+
+fn select_point<CS: ConstraintSystem<Fr>>(
+  mut cs: CS,
+  bit: &AllocatedBit,
+  a: &NonNativePoint,
+  b: &NonNativePoint,
+) -> Result<NonNativePoint, SynthesisError> {
+  Ok(NonNativePoint {
+    x: select_nat(cs.namespace(|| "x"), bit, &a.x, &b.x)?,
+    y: select_nat(cs.namespace(|| "y"), bit, &a.y, &b.y)?,
+    value: if bit.get_value().ok_or(SynthesisError::AssignmentMissing)? { b.value } else { a.value },
+  })
+}
+
+fn enforce_equal_points<CS: ConstraintSystem<Fr>>(
+  mut cs: CS,
+  a: &NonNativePoint,
+  b: &NonNativePoint,
+) -> Result<(), SynthesisError> {
+  a.x.equal_when_carried(cs.namespace(|| "x"), &b.x)?;
+  a.y.equal_when_carried(cs.namespace(|| "y"), &b.y)
+}
+
+impl StepCircuit<Fr> for EcdsaChainCircuit {
+  fn arity(&self) -> usize {
+    1
+  }
+
+  fn synthesize<CS: ConstraintSystem<Fr>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<Fr>],
+  ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+    // z provides the running accumulator
+    assert_eq!(z.len(), 1);
+
+    let acc_constants = PoseidonConstants::<Fr, U5>::new();
+    let p = constant::<CS>(&modulus::<Fp>());
+    let n = constant::<CS>(&modulus::<Fq>());
+    let generator = constant_point::<CS>(Secp256k1Affine::generator());
+    let (offset, shifted_offset) = offset_points();
+
+    let mut acc = z[0].clone();
+    for (j, sig) in self.signatures.iter().enumerate() {
+      let mut cs = cs.namespace(|| format!("signature_{}", j));
+      let (u1_value, u2_value) = sig.scalars();
+
+      let msg_hash = alloc_nonnative(cs.namespace(|| "z"), &to_nat(&sig.msg_hash))?;
+      let r = alloc_nonnative(cs.namespace(|| "r"), &to_nat(&sig.r))?;
+      let s = alloc_nonnative(cs.namespace(|| "s"), &to_nat(&sig.s))?;
+      let u1 = alloc_nonnative(cs.namespace(|| "u1"), &to_nat(&u1_value))?;
+      let u2 = alloc_nonnative(cs.namespace(|| "u2"), &to_nat(&u2_value))?;
+
+      // u1 * s = z and u2 * s = r mod n
+      let u1_s = mul(cs.namespace(|| "u1 * s"), &u1.nat, &s.nat, &n)?;
+      enforce_congruent(cs.namespace(|| "u1"), &u1_s, &msg_hash.nat, &n)?;
+      let u2_s = mul(cs.namespace(|| "u2 * s"), &u2.nat, &s.nat, &n)?;
+      enforce_congruent(cs.namespace(|| "u2"), &u2_s, &r.nat, &n)?;
+
+      let pk = alloc_point(cs.namespace(|| "pk"), sig.pk, &p)?;
+      let nonce = alloc_point(cs.namespace(|| "nonce"), sig.nonce_point(), &p)?;
+
+      // 2^256 * offset + u1 * G + u2 * P, most significant bits first
+      let mut point = constant_point::<CS>(offset);
+      for i in (0..NUM_SCALAR_BITS).rev() {
+        let mut cs = cs.namespace(|| format!("bit_{}", i));
+        point = double_point(cs.namespace(|| "double"), &point, &p)?;
+        let with_g = add_points(cs.namespace(|| "add G"), &point, &generator, &p)?;
+        point = select_point(cs.namespace(|| "select G"), &u1.bits[i], &point, &with_g)?;
+        let with_pk = add_points(cs.namespace(|| "add P"), &point, &pk, &p)?;
+        point = select_point(cs.namespace(|| "select P"), &u2.bits[i], &point, &with_pk)?;
+      }
+      let expected = add_points(
+        cs.namespace(|| "R + 2^256 * offset"),
+        &nonce,
+        &constant_point::<CS>(shifted_offset),
+        &p,
+      )?;
+      enforce_equal_points(cs.namespace(|| "R = u1 * G + u2 * P"), &point, &expected)?;
+
+      // r = R.x mod n
+      enforce_congruent(cs.namespace(|| "r = R.x"), &nonce.x, &r.nat, &n)?;
+
+      let mut preimage = vec![acc];
+      preimage.extend(msg_hash.limbs);
+      acc = poseidon_hash_allocated(cs.namespace(|| "absorb"), preimage, &acc_constants)?;
+    }
+
+    Ok(vec![acc])
+  }
+
+  fn output(&self, z: &[Fr]) -> Vec<Fr> {
+    // sanity check
+    debug_assert_eq!(z[0], self.acc);
+
+    vec![self.output_acc()]
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, num_sigs_per_step: usize,
+    pp: PublicParams<E1, E2, EcdsaChainCircuit, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice, every step continues from the previous accumulator
+    let mut circuits = Vec::with_capacity(num_steps);
+    let mut acc = Fr::ZERO;
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..num_steps {
+      let circuit = EcdsaChainCircuit::new(acc, num_sigs_per_step, &mut rng);
+      acc = circuit.output_acc();
+      circuits.push(circuit);
+    }
+
+    let z0_primary = vec![<E1 as Engine>::Scalar::zero()];
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = EcdsaChainCircuit;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn ecdsa_point_gadgets_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  // the group law on its own, see below for a full signature
+  let mut rng = StdRng::seed_from_u64(0);
+  let a = Secp256k1::random(&mut rng).to_affine();
+  let b = Secp256k1::random(&mut rng).to_affine();
+
+  let mut cs = TestConstraintSystem::<Fr>::new();
+  let p = constant::<TestConstraintSystem<Fr>>(&modulus::<Fp>());
+  let a_var = alloc_point(cs.namespace(|| "a"), a, &p).unwrap();
+  let b_var = alloc_point(cs.namespace(|| "b"), b, &p).unwrap();
+  let sum = add_points(cs.namespace(|| "a + b"), &a_var, &b_var, &p).unwrap();
+  let double = double_point(cs.namespace(|| "2a"), &a_var, &p).unwrap();
+  assert!(cs.is_satisfied());
+  assert_eq!(sum.value, (a + b).to_affine());
+  assert_eq!(double.x.value, Some(to_nat(&coordinates((a + a).to_affine())[0])));
+
+  // a point off the curve is rejected
+  let mut cs = TestConstraintSystem::<Fr>::new();
+  let [x, y] = coordinates(a);
+  let off_curve = alloc_nonnative(cs.namespace(|| "y + 1"), &to_nat(&(y + Fp::ONE))).unwrap().nat;
+  let x = alloc_nonnative(cs.namespace(|| "x"), &to_nat(&x)).unwrap().nat;
+  let y_y = mul(cs.namespace(|| "y * y"), &off_curve, &off_curve, &p).unwrap();
+  let x_x = mul(cs.namespace(|| "x * x"), &x, &x, &p).unwrap();
+  let x_x_x = mul(cs.namespace(|| "x * x * x"), &x_x, &x, &p).unwrap();
+  let b = constant::<TestConstraintSystem<Fr>>(&BigInt::from(7));
+  enforce_congruent(cs.namespace(|| "on curve"), &y_y, &x_x_x.add(&b).unwrap(), &p).unwrap();
+  assert!(!cs.is_satisfied());
+}
+
+#[test]
+fn ecdsa_signature_test() {
+  let mut sig = Signature::random(&mut StdRng::seed_from_u64(0));
+  assert!(sig.verify());
+
+  sig.s += Fq::ONE;
+  assert!(!sig.verify());
+}
+
+#[test]
+fn ecdsa_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  // a full signature takes millions of constraints, a single one per step keeps this tractable
+  let circuit = EcdsaChainCircuit::new(Fr::from(7), 1, &mut StdRng::seed_from_u64(0));
+  let mut cs = TestConstraintSystem::<Fr>::new();
+  let z = AllocatedNum::alloc(cs.namespace(|| "acc"), || Ok(circuit.acc)).unwrap();
+  let z_out = circuit.synthesize(&mut cs, &[z]).unwrap();
+  assert!(cs.is_satisfied());
+  assert_eq!(z_out[0].get_value().unwrap(), circuit.output_acc());
+
+  // a forged signature doesn't verify
+  let mut circuit = circuit;
+  circuit.signatures[0].s += Fq::ONE;
+  let mut cs = TestConstraintSystem::<Fr>::new();
+  let z = AllocatedNum::alloc(cs.namespace(|| "acc"), || Ok(circuit.acc)).unwrap();
+  circuit.synthesize(&mut cs, &[z]).unwrap();
+  assert!(!cs.is_satisfied());
+}
@@ -0,0 +1,297 @@
+//! Export of step circuits to the iden3 binary formats, to compare against tools outside this
+//! repo: the constraints of a step go to a `.r1cs` file and the witness of a given step to a
+//! `.wtns` file, which `check_files` re-loads to validate the witness against the constraints.
+//!
+//! The wires follow the layout the Circom frontend expects, the constant one, `z_{i+1}`, `z_i`,
+//! then the other variables of the step, so an exported step can be folded back through
+//! `circom::CircomCircuit`. Each output gets its own wire, tied to the variable the step circuit
+//! returns by one extra constraint.
+use crate::circom::{load_r1cs, load_wtns, Terms, R1CS};
+use crate::{hashchain::HashChainCircuit, minroot::minroot_circuits, scalar_mul::ScalarMulChainCircuit};
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use ff::{Field, PrimeField};
+use nova_snark::traits::{circuit::StepCircuit, Engine};
+use nova_snark::provider::Bn256EngineKZG;
+use num_bigint::BigUint;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+type E1 = Bn256EngineKZG;
+
+const HEADER: u32 = 1;
+const CONSTRAINTS: u32 = 2;
+const WIRE_TO_LABEL: u32 = 3;
+const WITNESS: u32 = 2;
+
+/// Records the constraints and the witness of a synthesis, variable 0 is the constant one and
+/// variable `1 + i` the auxiliary variable `i`.
+struct RecordingCS<F: PrimeField> {
+  aux: Vec<F>,
+  constraints: Vec<(Terms<F>, Terms<F>, Terms<F>)>,
+}
+
+impl<F: PrimeField> RecordingCS<F> {
+  fn terms(lc: LinearCombination<F>) -> Terms<F> {
+    lc.iter()
+      .map(|(var, coeff)| match var.get_unchecked() {
+        Index::Input(0) => (0, *coeff),
+        Index::Aux(i) => (1 + i, *coeff),
+        Index::Input(_) => panic!("step circuits have no public inputs"),
+      })
+      .collect()
+  }
+}
+
+impl<F: PrimeField> ConstraintSystem<F> for RecordingCS<F> {
+  type Root = Self;
+
+  fn alloc<V, A, AR>(&mut self, _annotation: A, f: V) -> Result<Variable, SynthesisError>
+  where
+    V: FnOnce() -> Result<F, SynthesisError>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    self.aux.push(f()?);
+    Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+  }
+
+  fn alloc_input<V, A, AR>(&mut self, _annotation: A, _f: V) -> Result<Variable, SynthesisError>
+  where
+    V: FnOnce() -> Result<F, SynthesisError>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    panic!("step circuits have no public inputs")
+  }
+
+  fn enforce<A, AR, LA, LB, LC>(&mut self, _annotation: A, a: LA, b: LB, c: LC)
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+    LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+  {
+    self.constraints.push((
+      Self::terms(a(LinearCombination::zero())),
+      Self::terms(b(LinearCombination::zero())),
+      Self::terms(c(LinearCombination::zero())),
+    ));
+  }
+
+  fn push_namespace<NR, N>(&mut self, _name_fn: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR,
+  {
+  }
+
+  fn pop_namespace(&mut self) {}
+
+  fn get_root(&mut self) -> &mut Self::Root {
+    self
+  }
+}
+
+/// Constraints of `circuit` and its witness on the state `z`, along with the next state.
+pub fn export_step<F: PrimeField, C: StepCircuit<F>>(
+  circuit: &C,
+  z: &[F],
+) -> Result<(R1CS<F>, Vec<F>, Vec<F>), SynthesisError> {
+  let arity = z.len();
+  let mut cs = RecordingCS { aux: Vec::new(), constraints: Vec::new() };
+  let z_in = z
+    .iter()
+    .enumerate()
+    .map(|(k, z)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", k)), || Ok(*z)))
+    .collect::<Result<Vec<_>, _>>()?;
+  let z_out = circuit.synthesize(&mut cs, &z_in)?;
+  assert_eq!(z_out.len(), arity);
+
+  // the variables of the recording shift past the output wires, z_i comes first among them
+  let wire = |var: usize| if var == 0 { 0 } else { arity + var };
+  let shift = |terms: Terms<F>| terms.into_iter().map(|(var, coeff)| (wire(var), coeff)).collect::<Terms<F>>();
+  let mut constraints = cs
+    .constraints
+    .into_iter()
+    .map(|(a, b, c)| (shift(a), shift(b), shift(c)))
+    .collect::<Vec<_>>();
+  let mut outputs = Vec::with_capacity(arity);
+  for (k, z) in z_out.iter().enumerate() {
+    let terms = RecordingCS::<F>::terms(LinearCombination::zero() + z.get_variable());
+    constraints.push((vec![(1 + k, F::ONE)], vec![(0, F::ONE)], shift(terms)));
+    outputs.push(z.get_value().ok_or(SynthesisError::AssignmentMissing)?);
+  }
+
+  let mut witness = vec![F::ONE];
+  witness.extend(&outputs);
+  witness.extend(cs.aux);
+  let r1cs = R1CS {
+    num_wires: witness.len(),
+    num_pub_out: arity,
+    num_pub_in: 0,
+    num_prv_in: arity,
+    constraints,
+  };
+  Ok((r1cs, witness, outputs))
+}
+
+/// Index of the first constraint of `r1cs` that `witness` does not satisfy, if any.
+pub fn first_unsatisfied<F: PrimeField>(r1cs: &R1CS<F>, witness: &[F]) -> Option<usize> {
+  let eval = |terms: &Terms<F>| terms.iter().map(|(wire, coeff)| witness[*wire] * coeff).sum::<F>();
+  r1cs
+    .constraints
+    .iter()
+    .position(|(a, b, c)| eval(a) * eval(b) != eval(c))
+}
+
+fn invalid(message: String) -> io::Error {
+  io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn push_u32(bytes: &mut Vec<u8>, value: usize) {
+  bytes.extend((value as u32).to_le_bytes());
+}
+
+// the size of a field element and the prime, as in the headers of both formats
+fn push_prime<F: PrimeField>(bytes: &mut Vec<u8>) {
+  let n8 = F::Repr::default().as_ref().len();
+  let mut prime = (BigUint::from_bytes_le((-F::ONE).to_repr().as_ref()) + 1u32).to_bytes_le();
+  prime.resize(n8, 0);
+  push_u32(bytes, n8);
+  bytes.extend(prime);
+}
+
+fn write_sections(path: impl AsRef<Path>, magic: &[u8; 4], version: u32, sections: &[(u32, Vec<u8>)]) -> io::Result<()> {
+  let mut bytes = magic.to_vec();
+  bytes.extend(version.to_le_bytes());
+  push_u32(&mut bytes, sections.len());
+  for (section_type, content) in sections {
+    bytes.extend(section_type.to_le_bytes());
+    bytes.extend((content.len() as u64).to_le_bytes());
+    bytes.extend(content);
+  }
+  fs::write(path, bytes)
+}
+
+/// Writes `r1cs` as a `.r1cs` file, every wire labelled by its index.
+pub fn write_r1cs<F: PrimeField>(path: impl AsRef<Path>, r1cs: &R1CS<F>) -> io::Result<()> {
+  let mut header = Vec::new();
+  push_prime::<F>(&mut header);
+  for count in [r1cs.num_wires, r1cs.num_pub_out, r1cs.num_pub_in, r1cs.num_prv_in] {
+    push_u32(&mut header, count);
+  }
+  header.extend((r1cs.num_wires as u64).to_le_bytes());
+  push_u32(&mut header, r1cs.constraints.len());
+
+  let mut constraints = Vec::new();
+  for (a, b, c) in &r1cs.constraints {
+    for terms in [a, b, c] {
+      push_u32(&mut constraints, terms.len());
+      for (wire, coeff) in terms {
+        push_u32(&mut constraints, *wire);
+        constraints.extend(coeff.to_repr().as_ref());
+      }
+    }
+  }
+
+  let labels = (0..r1cs.num_wires as u64).flat_map(u64::to_le_bytes).collect();
+  write_sections(path, b"r1cs", 1, &[(HEADER, header), (CONSTRAINTS, constraints), (WIRE_TO_LABEL, labels)])
+}
+
+/// Writes `witness` as a `.wtns` file.
+pub fn write_wtns<F: PrimeField>(path: impl AsRef<Path>, witness: &[F]) -> io::Result<()> {
+  let mut header = Vec::new();
+  push_prime::<F>(&mut header);
+  push_u32(&mut header, witness.len());
+  let values = witness.iter().flat_map(|value| value.to_repr().as_ref().to_vec()).collect();
+  write_sections(path, b"wtns", 2, &[(HEADER, header), (WITNESS, values)])
+}
+
+/// Re-loads an exported step and checks that the witness satisfies its constraints.
+pub fn check_files<F: PrimeField>(r1cs_path: impl AsRef<Path>, wtns_path: impl AsRef<Path>) -> io::Result<()> {
+  let r1cs = load_r1cs::<F>(r1cs_path)?;
+  let witness = load_wtns::<F>(wtns_path)?;
+  if witness.len() != r1cs.num_wires || witness[0] != F::ONE {
+    return Err(invalid("the witness does not match the wires".to_string()));
+  }
+  match first_unsatisfied(&r1cs, &witness) {
+    Some(i) => Err(invalid(format!("constraint {} is not satisfied", i))),
+    None => Ok(()),
+  }
+}
+
+/// Exports step `step` of the chain of `circuits` from `z0`, as `<name>.r1cs` and
+/// `<name>_<step>.wtns` in `dir`.
+pub fn export_chain<F: PrimeField, C: StepCircuit<F>>(
+  dir: impl AsRef<Path>,
+  name: &str,
+  circuits: &[C],
+  z0: &[F],
+  step: usize,
+) -> io::Result<()> {
+  let to_io = |err: SynthesisError| io::Error::new(ErrorKind::Other, err.to_string());
+  let mut z = z0.to_vec();
+  for circuit in &circuits[..step] {
+    z = export_step(circuit, &z).map_err(to_io)?.2;
+  }
+  let (r1cs, witness, _) = export_step(&circuits[step], &z).map_err(to_io)?;
+
+  fs::create_dir_all(&dir)?;
+  write_r1cs(dir.as_ref().join(format!("{}.r1cs", name)), &r1cs)?;
+  write_wtns(dir.as_ref().join(format!("{}_{}.wtns", name, step)), &witness)
+}
+
+/// Exports step `step` of the MinRoot, hash chain and scalar multiplication workloads, at the
+/// smallest configurations of their benches, to `dir`.
+pub fn export_workloads(dir: impl AsRef<Path>, step: usize) -> io::Result<()> {
+  type F = <E1 as Engine>::Scalar;
+
+  let (z0, circuits) = minroot_circuits(step + 1, 1024);
+  export_chain(&dir, "minroot", &circuits, &z0, step)?;
+
+  let circuits = (0..=step).map(|_| HashChainCircuit::<<E1 as Engine>::GE>::new(32)).collect::<Vec<_>>();
+  export_chain(&dir, "hashchain", &circuits, &[F::ZERO], step)?;
+
+  let circuits = (0..=step).map(|_| ScalarMulChainCircuit::new(1)).collect::<Vec<_>>();
+  export_chain(&dir, "scalar_mul", &circuits, &[F::ZERO, F::ZERO], step)
+}
+
+#[test]
+fn export_workloads_test() {
+  use crate::circom::CircomCircuit;
+  use bellpepper_core::test_cs::TestConstraintSystem;
+  use std::sync::Arc;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  let dir = std::env::temp_dir().join(format!("export_{}", std::process::id()));
+  export_workloads(&dir, 1).unwrap();
+  for name in ["minroot", "hashchain", "scalar_mul"] {
+    let (r1cs_path, wtns_path) = (dir.join(format!("{}.r1cs", name)), dir.join(format!("{}_1.wtns", name)));
+    check_files::<F>(&r1cs_path, &wtns_path).unwrap();
+
+    // the export folds back through the Circom frontend
+    let r1cs = load_r1cs::<F>(&r1cs_path).unwrap();
+    let witness = load_wtns::<F>(&wtns_path).unwrap();
+    let z_in = witness[1 + r1cs.arity()..1 + 2 * r1cs.arity()].to_vec();
+    let circuit = CircomCircuit::<G>::new(Arc::new(r1cs), Some(witness));
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z = z_in
+      .iter()
+      .enumerate()
+      .map(|(k, z)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", k)), || Ok(*z)).unwrap())
+      .collect::<Vec<_>>();
+    circuit.synthesize(&mut cs, &z).unwrap();
+    assert!(cs.is_satisfied());
+
+    // an output off by one
+    let mut witness = load_wtns::<F>(&wtns_path).unwrap();
+    witness[1] += F::ONE;
+    write_wtns(&wtns_path, &witness).unwrap();
+    assert!(check_files::<F>(&r1cs_path, &wtns_path).is_err());
+  }
+  fs::remove_dir_all(dir).unwrap();
+}
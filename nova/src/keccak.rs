@@ -0,0 +1,312 @@
+//! Keccak-f[1600] permutation chain: each step applies the permutation a configurable number of
+//! times to the running state. The 25 lanes are carried across steps as 64-bit field elements
+//! and decomposed into bits, so every XOR and AND of the permutation is an R1CS constraint.
+use bellpepper_core::{
+  boolean::{AllocatedBit, Boolean},
+  num::AllocatedNum,
+  ConstraintSystem, SynthesisError,
+};
+use ff::{Field, PrimeField};
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+pub const NUM_LANES: usize = 25;
+pub const LANE_BITS: usize = 64;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+  0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+  0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+  0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+  0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+  0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+  0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+// rotation offsets of the lane (x, y), at index x + 5y
+const RHO: [usize; NUM_LANES] = [
+  0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+// lane (x, y) moves to (y, 2x + 3y) in the pi step
+fn pi(x: usize, y: usize) -> usize {
+  y + 5 * ((2 * x + 3 * y) % 5)
+}
+
+// native reference, the lane (x, y) is at index x + 5y
+pub fn keccak_f(mut state: [u64; NUM_LANES]) -> [u64; NUM_LANES] {
+  for rc in ROUND_CONSTANTS {
+    let c: [u64; 5] = std::array::from_fn(|x| (0..5).fold(0, |acc, y| acc ^ state[x + 5 * y]));
+    for x in 0..5 {
+      let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+      for y in 0..5 {
+        state[x + 5 * y] ^= d;
+      }
+    }
+
+    let mut b = [0u64; NUM_LANES];
+    for x in 0..5 {
+      for y in 0..5 {
+        b[pi(x, y)] = state[x + 5 * y].rotate_left(RHO[x + 5 * y] as u32);
+      }
+    }
+
+    for x in 0..5 {
+      for y in 0..5 {
+        state[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+      }
+    }
+    state[0] ^= rc;
+  }
+  state
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakChainCircuit<G: Group> {
+  pub num_perms_per_step: usize,
+  pub state: [u64; NUM_LANES],
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> KeccakChainCircuit<G> {
+  // continues the chain from `state`
+  pub fn new(state: [u64; NUM_LANES], num_perms_per_step: usize) -> Self {
+    Self {
+      num_perms_per_step,
+      state,
+      _p: PhantomData,
+    }
+  }
+
+  pub fn output_state(&self) -> [u64; NUM_LANES] {
+    (0..self.num_perms_per_step).fold(self.state, |state, _| keccak_f(state))
+  }
+}
+
+type Lane = Vec<Boolean>;
+
+fn rotate_left(lane: &Lane, n: usize) -> Lane {
+  (0..LANE_BITS).map(|i| lane[(i + LANE_BITS - n) % LANE_BITS].clone()).collect()
+}
+
+fn xor<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+  mut cs: CS,
+  a: &Lane,
+  b: &Lane,
+) -> Result<Lane, SynthesisError> {
+  a.iter()
+    .zip(b)
+    .enumerate()
+    .map(|(i, (a, b))| Boolean::xor(cs.namespace(|| format!("xor_{}", i)), a, b))
+    .collect()
+}
+
+// enforces `num = sum_i bit_i * 2^i`
+fn enforce_packing<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+  cs: &mut CS,
+  bits: &[Boolean],
+  num: &AllocatedNum<Scalar>,
+) {
+  cs.enforce(
+    || "num = sum_i bit_i * 2^i",
+    |lc| {
+      let mut coeff = Scalar::ONE;
+      bits.iter().fold(lc, |lc, bit| {
+        let lc = lc + &bit.lc(CS::one(), coeff);
+        coeff = coeff.double();
+        lc
+      })
+    },
+    |lc| lc + CS::one(),
+    |lc| lc + num.get_variable(),
+  );
+}
+
+fn keccak_round<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+  mut cs: CS,
+  a: Vec<Lane>,
+  rc: u64,
+) -> Result<Vec<Lane>, SynthesisError> {
+  // theta
+  let mut c = Vec::with_capacity(5);
+  for x in 0..5 {
+    let mut column = a[x].clone();
+    for y in 1..5 {
+      column = xor(cs.namespace(|| format!("c_{}_{}", x, y)), &column, &a[x + 5 * y])?;
+    }
+    c.push(column);
+  }
+  let mut a_theta = a;
+  for x in 0..5 {
+    let d = xor(cs.namespace(|| format!("d_{}", x)), &c[(x + 4) % 5], &rotate_left(&c[(x + 1) % 5], 1))?;
+    for y in 0..5 {
+      a_theta[x + 5 * y] = xor(cs.namespace(|| format!("theta_{}_{}", x, y)), &a_theta[x + 5 * y], &d)?;
+    }
+  }
+
+  // rho and pi only move bits around
+  let mut b = vec![Vec::new(); NUM_LANES];
+  for x in 0..5 {
+    for y in 0..5 {
+      b[pi(x, y)] = rotate_left(&a_theta[x + 5 * y], RHO[x + 5 * y]);
+    }
+  }
+
+  // chi
+  let mut a_chi = Vec::with_capacity(NUM_LANES);
+  for y in 0..5 {
+    for x in 0..5 {
+      let mut ns = cs.namespace(|| format!("chi_{}_{}", x, y));
+      let lane = (0..LANE_BITS)
+        .map(|i| {
+          let not_and = Boolean::and(
+            ns.namespace(|| format!("and_{}", i)),
+            &b[(x + 1) % 5 + 5 * y][i].not(),
+            &b[(x + 2) % 5 + 5 * y][i],
+          )?;
+          Boolean::xor(ns.namespace(|| format!("xor_{}", i)), &b[x + 5 * y][i], &not_and)
+        })
+        .collect::<Result<Lane, _>>()?;
+      a_chi.push(lane);
+    }
+  }
+
+  // iota, XOR with constants is free
+  a_chi[0] = a_chi[0]
+    .iter()
+    .enumerate()
+    .map(|(i, bit)| if (rc >> i) & 1 == 1 { bit.not() } else { bit.clone() })
+    .collect();
+
+  Ok(a_chi)
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for KeccakChainCircuit<G> {
+  fn arity(&self) -> usize {
+    NUM_LANES
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    // z provides the lanes of the running state
+    assert_eq!(z.len(), NUM_LANES);
+
+    let mut state = Vec::with_capacity(NUM_LANES);
+    for (l, lane) in z.iter().enumerate() {
+      let bits = (0..LANE_BITS)
+        .map(|i| {
+          AllocatedBit::alloc(cs.namespace(|| format!("z_{}_bit_{}", l, i)), Some((self.state[l] >> i) & 1 == 1))
+            .map(Boolean::from)
+        })
+        .collect::<Result<Lane, _>>()?;
+      enforce_packing(&mut cs.namespace(|| format!("unpack z_{}", l)), &bits, lane);
+      state.push(bits);
+    }
+
+    for p in 0..self.num_perms_per_step {
+      for (r, rc) in ROUND_CONSTANTS.iter().enumerate() {
+        state = keccak_round(cs.namespace(|| format!("perm_{}_round_{}", p, r)), state, *rc)?;
+      }
+    }
+
+    state
+      .iter()
+      .enumerate()
+      .map(|(l, bits)| {
+        let lane = AllocatedNum::alloc(cs.namespace(|| format!("z_out_{}", l)), || {
+          bits.iter().rev().try_fold(G::Scalar::ZERO, |acc, bit| {
+            let bit = bit.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(acc.double() + if bit { G::Scalar::ONE } else { G::Scalar::ZERO })
+          })
+        })?;
+        enforce_packing(&mut cs.namespace(|| format!("pack z_out_{}", l)), bits, &lane);
+        Ok(lane)
+      })
+      .collect()
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    // sanity check
+    debug_assert_eq!(z, self.state.map(G::Scalar::from).as_slice());
+
+    self.output_state().map(G::Scalar::from).to_vec()
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, num_perms_per_step: usize,
+    pp: PublicParams<E1, E2, KeccakChainCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice, every step continues from the previous state
+    let mut circuits = Vec::with_capacity(num_steps);
+    let mut state = [0u64; NUM_LANES];
+    for _ in 0..num_steps {
+      let circuit = KeccakChainCircuit::new(state, num_perms_per_step);
+      state = circuit.output_state();
+      circuits.push(circuit);
+    }
+
+    let z0_primary = vec![<E1 as Engine>::Scalar::zero(); NUM_LANES];
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = KeccakChainCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn keccak_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  // Keccak-f[1600] of the all-zero state
+  let state = keccak_f([0u64; NUM_LANES]);
+  assert_eq!(state[0], 0xF1258F7940E1DDE7);
+  assert_eq!(state[1], 0x84D5CCF933C0478A);
+
+  let circuit = KeccakChainCircuit::<G>::new(state, 1);
+  let z_in = state.map(F::from);
+  let mut cs = TestConstraintSystem::<F>::new();
+  let z = z_in
+    .iter()
+    .enumerate()
+    .map(|(i, z)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", i)), || Ok(*z)))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+  let z_out = circuit.synthesize(&mut cs, &z).unwrap();
+
+  assert!(cs.is_satisfied());
+  assert_eq!(z_out.iter().map(|z| z.get_value().unwrap()).collect::<Vec<_>>(), circuit.output(&z_in));
+}
@@ -1,3 +1,20 @@
 pub mod minroot;
 pub mod hashchain;
-pub mod scalar_mul;
\ No newline at end of file
+pub mod scalar_mul;
+pub mod range_check;
+pub mod supernova;
+pub mod monolithic;
+pub mod sha256;
+pub mod keccak;
+pub mod merkle;
+pub mod schnorr;
+pub mod ecdsa;
+pub mod matvec;
+pub mod zkvm;
+pub mod bitcoin;
+pub mod wide_state;
+pub mod synthetic;
+pub mod random_circuit;
+pub mod circom;
+pub mod export;
+pub mod dsl;
\ No newline at end of file
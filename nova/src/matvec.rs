@@ -0,0 +1,246 @@
+//! Fixed-point dense layer chain, the streaming ML inference workload: each step applies one
+//! layer `y = ReLU((W * x + b) >> FRAC_BITS)` of a configurable width to the state `x`, with the
+//! weights and biases of the layer as private witnesses. Values are signed integers scaled by
+//! `2^FRAC_BITS`, the rescaled pre-activations are range checked to [`VALUE_BITS`] bits by bit
+//! decomposition, whose sign bit then selects the ReLU output.
+use bellpepper_core::{boolean::AllocatedBit, num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+pub const FRAC_BITS: usize = 8;
+// pre-activations live in [-2^(VALUE_BITS - 1), 2^(VALUE_BITS - 1))
+pub const VALUE_BITS: usize = 16;
+
+fn to_scalar<Scalar: PrimeField>(value: i64) -> Scalar {
+  if value < 0 {
+    -Scalar::from(value.unsigned_abs())
+  } else {
+    Scalar::from(value as u64)
+  }
+}
+
+/// A random layer input, the activations of a previous layer are non-negative.
+pub fn random_input(width: usize, rng: &mut impl Rng) -> Vec<i64> {
+  (0..width).map(|_| rng.gen_range(0..1 << (VALUE_BITS - 2))).collect()
+}
+
+/// Rescaled pre-activations and the remainders of the rescaling, `W * x + b = q * 2^FRAC_BITS + r`.
+pub fn pre_activations(weights: &[Vec<i64>], bias: &[i64], input: &[i64]) -> (Vec<i64>, Vec<i64>) {
+  weights
+    .iter()
+    .zip(bias)
+    .map(|(row, b)| {
+      let acc = row.iter().zip(input).map(|(w, x)| w * x).sum::<i64>() + b;
+      (acc.div_euclid(1 << FRAC_BITS), acc.rem_euclid(1 << FRAC_BITS))
+    })
+    .unzip()
+}
+
+#[derive(Clone, Debug)]
+pub struct DenseLayerCircuit<G: Group> {
+  pub width: usize,
+  pub input: Vec<i64>,
+  pub weights: Vec<Vec<i64>>,
+  pub bias: Vec<i64>,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> DenseLayerCircuit<G> {
+  // a layer with random weights applied to `input`
+  pub fn new(input: Vec<i64>, rng: &mut impl Rng) -> Self {
+    let width = input.len();
+    // the weights of a row sum to at most a half in absolute value, so activations stay in range
+    let bound = ((1 << (FRAC_BITS - 1)) / width as i64).max(1);
+    let weights = (0..width)
+      .map(|_| (0..width).map(|_| rng.gen_range(-bound..=bound)).collect())
+      .collect();
+    let bias = (0..width)
+      .map(|_| rng.gen_range(-(1 << (2 * FRAC_BITS))..=1 << (2 * FRAC_BITS)))
+      .collect();
+
+    Self {
+      width,
+      input,
+      weights,
+      bias,
+      _p: PhantomData,
+    }
+  }
+
+  pub fn output_values(&self) -> Vec<i64> {
+    let (q, _) = pre_activations(&self.weights, &self.bias, &self.input);
+    q.into_iter().map(|q| q.max(0)).collect()
+  }
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for DenseLayerCircuit<G> {
+  fn arity(&self) -> usize {
+    self.width
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    // z provides the layer input
+    assert_eq!(z.len(), self.width);
+    let offset = 1i64 << (VALUE_BITS - 1);
+    let (q, r) = pre_activations(&self.weights, &self.bias, &self.input);
+
+    let mut output = Vec::with_capacity(self.width);
+    for i in 0..self.width {
+      let mut cs = cs.namespace(|| format!("neuron_{}", i));
+
+      let mut products = Vec::with_capacity(self.width);
+      for (j, x) in z.iter().enumerate() {
+        let w = AllocatedNum::alloc(cs.namespace(|| format!("w_{}", j)), || Ok(to_scalar(self.weights[i][j])))?;
+        products.push(w.mul(cs.namespace(|| format!("w_{} * x_{}", j, j)), x)?);
+      }
+      let b = AllocatedNum::alloc(cs.namespace(|| "b"), || Ok(to_scalar(self.bias[i])))?;
+
+      // q + 2^(VALUE_BITS - 1) and r are decomposed into VALUE_BITS and FRAC_BITS bits
+      let q_shifted = (q[i] + offset) as u64;
+      let q_bits = (0..VALUE_BITS)
+        .map(|k| AllocatedBit::alloc(cs.namespace(|| format!("q_bit_{}", k)), Some((q_shifted >> k) & 1 == 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+      let r_bits = (0..FRAC_BITS)
+        .map(|k| AllocatedBit::alloc(cs.namespace(|| format!("r_bit_{}", k)), Some((r[i] >> k) & 1 == 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+      // W_i * x + b = q * 2^FRAC_BITS + r
+      let scale = G::Scalar::from(1u64 << FRAC_BITS);
+      cs.enforce(
+        || "rescale",
+        |lc| products.iter().fold(lc + b.get_variable(), |lc, product| lc + product.get_variable()),
+        |lc| lc + CS::one(),
+        |lc| {
+          let lc = q_bits
+            .iter()
+            .enumerate()
+            .fold(lc, |lc, (k, bit)| lc + (scale * G::Scalar::from(1u64 << k), bit.get_variable()));
+          let lc = r_bits
+            .iter()
+            .enumerate()
+            .fold(lc, |lc, (k, bit)| lc + (G::Scalar::from(1u64 << k), bit.get_variable()));
+          lc - (scale * G::Scalar::from(offset as u64), CS::one())
+        },
+      );
+
+      // the top bit of the shifted value is set iff q is non-negative, y = sign * q
+      let sign = &q_bits[VALUE_BITS - 1];
+      let y = AllocatedNum::alloc(cs.namespace(|| "y"), || Ok(to_scalar(q[i].max(0))))?;
+      cs.enforce(
+        || "relu",
+        |lc| lc + sign.get_variable(),
+        |lc| {
+          let lc = q_bits
+            .iter()
+            .enumerate()
+            .fold(lc, |lc, (k, bit)| lc + (G::Scalar::from(1u64 << k), bit.get_variable()));
+          lc - (G::Scalar::from(offset as u64), CS::one())
+        },
+        |lc| lc + y.get_variable(),
+      );
+
+      output.push(y);
+    }
+
+    Ok(output)
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    // sanity check
+    debug_assert_eq!(z, self.input.iter().map(|x| to_scalar(*x)).collect::<Vec<_>>());
+
+    self.output_values().into_iter().map(to_scalar).collect()
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, width: usize,
+    pp: PublicParams<E1, E2, DenseLayerCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice, every layer is applied to the output of the previous one
+    let mut rng = StdRng::seed_from_u64(0);
+    let input = random_input(width, &mut rng);
+    let mut circuits = Vec::with_capacity(num_steps);
+    let mut x = input.clone();
+    for _ in 0..num_steps {
+      let circuit = DenseLayerCircuit::new(x, &mut rng);
+      x = circuit.output_values();
+      circuits.push(circuit);
+    }
+
+    let z0_primary = input.into_iter().map(to_scalar).collect::<Vec<<E1 as Engine>::Scalar>>();
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = DenseLayerCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn dense_layer_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  let synthesize = |circuit: &DenseLayerCircuit<G>| {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z = circuit
+      .input
+      .iter()
+      .enumerate()
+      .map(|(j, x)| AllocatedNum::alloc(cs.namespace(|| format!("x_{}", j)), || Ok(to_scalar(*x))).unwrap())
+      .collect::<Vec<_>>();
+    let z_out = circuit.synthesize(&mut cs, &z).unwrap();
+    (cs.is_satisfied(), z_out.iter().map(|y| y.get_value().unwrap()).collect::<Vec<_>>())
+  };
+
+  let mut rng = StdRng::seed_from_u64(0);
+  let mut circuit = DenseLayerCircuit::<G>::new(random_input(8, &mut rng), &mut rng);
+  let (satisfied, z_out) = synthesize(&circuit);
+  assert!(satisfied);
+  assert_eq!(z_out, circuit.output(&circuit.input.iter().map(|x| to_scalar(*x)).collect::<Vec<_>>()));
+
+  // negative pre-activations are clamped
+  circuit.bias[0] = -(1 << (VALUE_BITS + FRAC_BITS - 2));
+  assert!(synthesize(&circuit).0);
+  assert_eq!(circuit.output_values()[0], 0);
+
+  // an activation out of the fixed-point range has no decomposition
+  circuit.bias[0] = 1 << (VALUE_BITS + FRAC_BITS);
+  assert!(!synthesize(&circuit).0);
+}
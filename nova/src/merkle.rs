@@ -0,0 +1,266 @@
+//! Merkle tree update chain, the state transition of a rollup: each step updates a configurable
+//! number of leaves of a Poseidon Merkle tree of depth `d` and outputs the new root. Every update
+//! opens the old leaf against the current root and recomputes the root from the new leaf along
+//! the same authentication path.
+use bellpepper_core::{
+  boolean::{AllocatedBit, Boolean},
+  num::AllocatedNum,
+  ConstraintSystem, SynthesisError,
+};
+use ff::{Field, PrimeField};
+use generic_array::typenum::U2;
+use neptune::{circuit2::poseidon_hash_allocated, poseidon::PoseidonConstants, Poseidon};
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+/// Sparse Poseidon Merkle tree with zero leaves, only the nodes that differ from an empty subtree
+/// are stored. Level 0 holds the leaves and level `depth` the root.
+#[derive(Clone, Debug)]
+pub struct MerkleTree<F: PrimeField> {
+  pub depth: usize,
+  nodes: HashMap<(usize, u64), F>,
+  // root of an empty subtree at each level
+  empty: Vec<F>,
+  constants: PoseidonConstants<F, U2>,
+}
+
+/// Advice of a single leaf update, the siblings go from the leaf level up to the root.
+#[derive(Clone, Debug)]
+pub struct MerkleUpdate<F: PrimeField> {
+  pub index: u64,
+  pub old_leaf: F,
+  pub new_leaf: F,
+  pub siblings: Vec<F>,
+}
+
+impl<F: PrimeField> MerkleTree<F> {
+  pub fn new(depth: usize) -> Self {
+    assert!(depth < 64, "leaf indices are u64");
+    let constants = PoseidonConstants::<F, U2>::new();
+    let mut empty = vec![F::ZERO];
+    for level in 0..depth {
+      empty.push(Poseidon::new_with_preimage(&[empty[level], empty[level]], &constants).hash());
+    }
+
+    Self {
+      depth,
+      nodes: HashMap::new(),
+      empty,
+      constants,
+    }
+  }
+
+  pub fn hash(&self, left: F, right: F) -> F {
+    Poseidon::new_with_preimage(&[left, right], &self.constants).hash()
+  }
+
+  pub fn node(&self, level: usize, index: u64) -> F {
+    self.nodes.get(&(level, index)).copied().unwrap_or(self.empty[level])
+  }
+
+  pub fn root(&self) -> F {
+    self.node(self.depth, 0)
+  }
+
+  pub fn siblings(&self, index: u64) -> Vec<F> {
+    (0..self.depth).map(|level| self.node(level, (index >> level) ^ 1)).collect()
+  }
+
+  /// Sets the leaf at `index`, returning the advice proving the update.
+  pub fn update(&mut self, index: u64, leaf: F) -> MerkleUpdate<F> {
+    let update = MerkleUpdate {
+      index,
+      old_leaf: self.node(0, index),
+      new_leaf: leaf,
+      siblings: self.siblings(index),
+    };
+
+    let mut node = leaf;
+    for level in 0..self.depth {
+      let position = index >> level;
+      self.nodes.insert((level, position), node);
+      let sibling = update.siblings[level];
+      node = if position & 1 == 0 { self.hash(node, sibling) } else { self.hash(sibling, node) };
+    }
+    self.nodes.insert((self.depth, 0), node);
+
+    update
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct MerkleUpdateCircuit<G: Group> {
+  pub depth: usize,
+  pub num_updates_per_step: usize,
+  pub root: G::Scalar,
+  pub new_root: G::Scalar,
+  pub updates: Vec<MerkleUpdate<G::Scalar>>,
+}
+
+impl<G: Group> MerkleUpdateCircuit<G> {
+  // writes random leaves at random indices of `tree`, recording the advice of every update
+  pub fn new(tree: &mut MerkleTree<G::Scalar>, num_updates_per_step: usize, rng: &mut impl Rng) -> Self {
+    let root = tree.root();
+    let updates = (0..num_updates_per_step)
+      .map(|_| tree.update(rng.gen_range(0..1u64 << tree.depth), G::Scalar::random(&mut *rng)))
+      .collect::<Vec<_>>();
+
+    Self {
+      depth: tree.depth,
+      num_updates_per_step,
+      root,
+      new_root: tree.root(),
+      updates,
+    }
+  }
+}
+
+// hashes `leaf` up to the root, the bits of the leaf index pick the side of every sibling
+pub(crate) fn compute_root<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+  mut cs: CS,
+  leaf: &AllocatedNum<Scalar>,
+  index_bits: &[Boolean],
+  siblings: &[AllocatedNum<Scalar>],
+  constants: &PoseidonConstants<Scalar, U2>,
+) -> Result<AllocatedNum<Scalar>, SynthesisError> {
+  let mut node = leaf.clone();
+  for (level, (bit, sibling)) in index_bits.iter().zip(siblings).enumerate() {
+    let (left, right) = AllocatedNum::conditionally_reverse(
+      cs.namespace(|| format!("swap_{}", level)),
+      &node,
+      sibling,
+      bit,
+    )?;
+    node = poseidon_hash_allocated(cs.namespace(|| format!("hash_{}", level)), vec![left, right], constants)?;
+  }
+  Ok(node)
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for MerkleUpdateCircuit<G> {
+  fn arity(&self) -> usize {
+    1
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    // z provides the current root
+    assert_eq!(z.len(), 1);
+
+    let constants = PoseidonConstants::<G::Scalar, U2>::new();
+    let mut root = z[0].clone();
+    for (j, update) in self.updates.iter().enumerate() {
+      let mut cs = cs.namespace(|| format!("update_{}", j));
+
+      let index_bits = (0..self.depth)
+        .map(|level| {
+          AllocatedBit::alloc(cs.namespace(|| format!("index_bit_{}", level)), Some((update.index >> level) & 1 == 1))
+            .map(Boolean::from)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+      let siblings = update
+        .siblings
+        .iter()
+        .enumerate()
+        .map(|(level, sibling)| AllocatedNum::alloc(cs.namespace(|| format!("sibling_{}", level)), || Ok(*sibling)))
+        .collect::<Result<Vec<_>, _>>()?;
+      let old_leaf = AllocatedNum::alloc(cs.namespace(|| "old_leaf"), || Ok(update.old_leaf))?;
+      let new_leaf = AllocatedNum::alloc(cs.namespace(|| "new_leaf"), || Ok(update.new_leaf))?;
+
+      let old_root = compute_root(cs.namespace(|| "old path"), &old_leaf, &index_bits, &siblings, &constants)?;
+      cs.enforce(
+        || "old_root = root",
+        |lc| lc + old_root.get_variable(),
+        |lc| lc + CS::one(),
+        |lc| lc + root.get_variable(),
+      );
+      root = compute_root(cs.namespace(|| "new path"), &new_leaf, &index_bits, &siblings, &constants)?;
+    }
+
+    Ok(vec![root])
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    // sanity check
+    debug_assert_eq!(z[0], self.root);
+
+    vec![self.new_root]
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, depth: usize, num_updates_per_step: usize,
+    pp: PublicParams<E1, E2, MerkleUpdateCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice from a native tree updated along the chain
+    let mut tree = MerkleTree::new(depth);
+    let mut rng = StdRng::seed_from_u64(0);
+    let z0_primary = vec![tree.root()];
+    let circuits = (0..num_steps)
+      .map(|_| MerkleUpdateCircuit::new(&mut tree, num_updates_per_step, &mut rng))
+      .collect::<Vec<_>>();
+
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = MerkleUpdateCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn merkle_update_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  let mut tree = MerkleTree::<F>::new(8);
+  let mut rng = StdRng::seed_from_u64(0);
+  let circuit = MerkleUpdateCircuit::<G>::new(&mut tree, 4, &mut rng);
+  assert_eq!(circuit.new_root, tree.root());
+
+  let mut cs = TestConstraintSystem::<F>::new();
+  let z = AllocatedNum::alloc(cs.namespace(|| "root"), || Ok(circuit.root)).unwrap();
+  let z_out = circuit.synthesize(&mut cs, &[z]).unwrap();
+
+  assert!(cs.is_satisfied());
+  assert_eq!(z_out[0].get_value().unwrap(), tree.root());
+
+  // a stale root doesn't open the updated leaves
+  let circuit = MerkleUpdateCircuit::<G>::new(&mut tree, 1, &mut rng);
+  let mut cs = TestConstraintSystem::<F>::new();
+  let z = AllocatedNum::alloc(cs.namespace(|| "root"), || Ok(circuit.root + F::ONE)).unwrap();
+  circuit.synthesize(&mut cs, &[z]).unwrap();
+  assert!(!cs.is_satisfied());
+}
@@ -178,13 +178,9 @@ impl<G: Group> StepCircuit<G::Scalar> for MinRootCircuit<G> {
   }
 }
 
-pub fn nova_ivc(num_steps: usize, num_iters_per_step: usize, 
-  pp: PublicParams<E1, E2, MinRootCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>, 
-  circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
-) -> Duration {
-
-    let start = Instant::now();
-    // produce non-deterministic advice
+// splits a MinRoot chain starting from (0, 0, 1) into `num_steps` step circuits
+pub fn minroot_circuits(num_steps: usize, num_iters_per_step: usize
+  ) -> (Vec<<E1 as Engine>::Scalar>, Vec<MinRootCircuit<<E1 as Engine>::GE>>) {
     let (z0_primary, minroot_iterations) = MinRootIteration::<<E1 as Engine>::GE>::new(
       num_iters_per_step * num_steps,
       &<E1 as Engine>::Scalar::zero(),
@@ -193,19 +189,22 @@ pub fn nova_ivc(num_steps: usize, num_iters_per_step: usize,
     );
     let minroot_circuits = (0..num_steps)
       .map(|i| MinRootCircuit {
-        seq: (0..num_iters_per_step)
-          .map(|j| MinRootIteration {
-            i: minroot_iterations[i * num_iters_per_step + j].i,
-            x_i: minroot_iterations[i * num_iters_per_step + j].x_i,
-            y_i: minroot_iterations[i * num_iters_per_step + j].y_i,
-            i_plus_1: minroot_iterations[i * num_iters_per_step + j].i_plus_1,
-            x_i_plus_1: minroot_iterations[i * num_iters_per_step + j].x_i_plus_1,
-            y_i_plus_1: minroot_iterations[i * num_iters_per_step + j].y_i_plus_1,
-          })
-          .collect::<Vec<_>>(),
+        seq: minroot_iterations[i * num_iters_per_step..(i + 1) * num_iters_per_step].to_vec(),
       })
       .collect::<Vec<_>>();
 
+    (z0_primary, minroot_circuits)
+}
+
+pub fn nova_ivc(num_steps: usize, num_iters_per_step: usize, 
+  pp: PublicParams<E1, E2, MinRootCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>, 
+  circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice
+    let (z0_primary, minroot_circuits) = minroot_circuits(num_steps, num_iters_per_step);
+
     let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
 
     type C1 = MinRootCircuit<<E1 as Engine>::GE>;
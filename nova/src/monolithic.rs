@@ -0,0 +1,120 @@
+//! Non-folding baseline: the step circuits of a whole IVC run are synthesized into a single R1CS
+//! and proven at once with Spartan (nova-snark's `RelaxedR1CSSNARK`), the SNARK Nova compresses with.
+use bellpepper_core::{num::AllocatedNum, Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use nova_snark::{
+  bellpepper::{
+    r1cs::{NovaShape, NovaWitness},
+    shape_cs::ShapeCS,
+    solver::SatisfyingAssignment,
+  },
+  provider::Bn256EngineKZG,
+  r1cs::{R1CSShape, RelaxedR1CSInstance, RelaxedR1CSWitness},
+  traits::{
+    circuit::StepCircuit, commitment::CommitmentEngineTrait, snark::RelaxedR1CSSNARKTrait, Engine,
+  },
+};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type EE1 = nova_snark::provider::hyperkzg::EvaluationEngine<E1>;
+type S1 = nova_snark::spartan::snark::RelaxedR1CSSNARK<E1, EE1>; // non-preprocessing SNARK
+type CommitmentKey = <<E1 as Engine>::CE as CommitmentEngineTrait<E1>>::CommitmentKey;
+
+/// Chains `steps` from `z0`, which is the only public input next to the final state.
+#[derive(Clone, Debug)]
+pub struct MonolithicCircuit<F: PrimeField, SC: StepCircuit<F>> {
+  pub z0: Vec<F>,
+  pub steps: Vec<SC>,
+  _p: PhantomData<F>,
+}
+
+impl<F: PrimeField, SC: StepCircuit<F>> MonolithicCircuit<F, SC> {
+  pub fn new(z0: Vec<F>, steps: Vec<SC>) -> Self {
+    Self {
+      z0,
+      steps,
+      _p: PhantomData,
+    }
+  }
+}
+
+impl<F: PrimeField, SC: StepCircuit<F>> Circuit<F> for MonolithicCircuit<F, SC> {
+  fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+    let mut z = self
+      .z0
+      .iter()
+      .enumerate()
+      .map(|(i, z)| AllocatedNum::alloc_input(cs.namespace(|| format!("z0_{}", i)), || Ok(*z)))
+      .collect::<Result<Vec<_>, _>>()?;
+
+    for (i, step) in self.steps.iter().enumerate() {
+      z = step.synthesize(&mut cs.namespace(|| format!("step_{}", i)), &z)?;
+    }
+
+    for (i, z) in z.iter().enumerate() {
+      z.inputize(cs.namespace(|| format!("z_n_{}", i)))?;
+    }
+    Ok(())
+  }
+}
+
+pub struct SpartanParams {
+  pub shape: R1CSShape<E1>,
+  ck: CommitmentKey,
+  pk: <S1 as RelaxedR1CSSNARKTrait<E1>>::ProverKey,
+  vk: <S1 as RelaxedR1CSSNARKTrait<E1>>::VerifierKey,
+}
+
+impl SpartanParams {
+  pub fn num_constraints(&self) -> usize {
+    self.shape.num_cons
+  }
+}
+
+pub fn spartan_setup<SC: StepCircuit<<E1 as Engine>::Scalar>>(
+  circuit: MonolithicCircuit<<E1 as Engine>::Scalar, SC>,
+) -> SpartanParams {
+  let mut cs = ShapeCS::<E1>::new();
+  circuit.synthesize(&mut cs).unwrap();
+  let (shape, ck) = cs.r1cs_shape(&*S1::ck_floor());
+  let (pk, vk) = S1::setup(&ck, &shape).unwrap();
+
+  SpartanParams { shape, ck, pk, vk }
+}
+
+// proves the monolithic circuit, witness generation included as for the IVC provers
+pub fn spartan_prove<SC: StepCircuit<<E1 as Engine>::Scalar>>(
+  params: &SpartanParams,
+  circuit: MonolithicCircuit<<E1 as Engine>::Scalar, SC>,
+) -> Duration {
+  let start = Instant::now();
+  let mut cs = SatisfyingAssignment::<E1>::new();
+  circuit.synthesize(&mut cs).unwrap();
+  let (u, w) = cs.r1cs_instance_and_witness(&params.shape, &params.ck).unwrap();
+
+  let relaxed_u = RelaxedR1CSInstance::from_r1cs_instance(&params.ck, &params.shape, &u);
+  let relaxed_w = RelaxedR1CSWitness::from_r1cs_witness(&params.shape, &w);
+  let res = S1::prove(&params.ck, &params.pk, &params.shape, &relaxed_u, &relaxed_w);
+  assert!(res.is_ok());
+  start.elapsed()
+}
+
+#[test]
+fn spartan_round_trip_test() {
+  use crate::minroot::minroot_circuits;
+
+  let (z0, steps) = minroot_circuits(2, 4);
+  let circuit = MonolithicCircuit::new(z0, steps);
+  let params = spartan_setup(circuit.clone());
+
+  let mut cs = SatisfyingAssignment::<E1>::new();
+  circuit.synthesize(&mut cs).unwrap();
+  let (u, w) = cs.r1cs_instance_and_witness(&params.shape, &params.ck).unwrap();
+  let relaxed_u = RelaxedR1CSInstance::from_r1cs_instance(&params.ck, &params.shape, &u);
+  let relaxed_w = RelaxedR1CSWitness::from_r1cs_witness(&params.shape, &w);
+
+  let snark = S1::prove(&params.ck, &params.pk, &params.shape, &relaxed_u, &relaxed_w).unwrap();
+  assert!(snark.verify(&params.vk, &relaxed_u).is_ok());
+}
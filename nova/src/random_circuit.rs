@@ -0,0 +1,241 @@
+//! Random satisfiable R1CS, for scaling studies that skip hand-written circuits: given a number of
+//! constraints, a number of witness variables, a density and a seed, a step has exactly
+//! `num_constraints` constraints over exactly `num_variables` fresh witness variables, each row of
+//! `A`, `B` and `C` having `density` non-zero entries.
+//!
+//! Variable 0 is `z_i`, then come `num_variables - num_constraints` free advice variables, then
+//! one variable defined by each constraint. The rows of constraint `k` pick random variables among
+//! the ones before the one it defines, which enters `C` with coefficient 1, so every constraint can
+//! be solved in order for any `z_i` and advice. The state is the last defined variable.
+//!
+//! The rows and the advice of every step are not stored but derived on the fly from the seed, with
+//! the same generator as the arkworks version of the crate `sonobe`, so both frameworks are swept
+//! on identical matrices and witnesses up to `2^MAX_LOG_CONSTRAINTS` constraints.
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError, Variable};
+use circuit_dsl::mix;
+use ff::{Field, PrimeField};
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+pub const MAX_LOG_CONSTRAINTS: usize = 22;
+
+const A: usize = 0;
+const B: usize = 1;
+const C: usize = 2;
+
+/// `n`-th random word of the stream of `seed`.
+pub fn word(seed: u64, n: u64) -> u64 {
+  mix(mix(seed) ^ n)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RandomR1CS {
+  pub num_constraints: usize,
+  pub num_variables: usize,
+  pub density: usize,
+  pub seed: u64,
+}
+
+impl RandomR1CS {
+  pub fn new(num_constraints: usize, num_variables: usize, density: usize, seed: u64) -> Self {
+    assert!(
+      (1..=1 << MAX_LOG_CONSTRAINTS).contains(&num_constraints),
+      "the number of constraints ranges from 1 to 2^{}",
+      MAX_LOG_CONSTRAINTS
+    );
+    assert!(num_variables >= num_constraints, "every constraint defines a variable");
+    assert!(density > 0);
+    Self {
+      num_constraints,
+      num_variables,
+      density,
+      seed,
+    }
+  }
+
+  pub fn num_advice(&self) -> usize {
+    self.num_variables - self.num_constraints
+  }
+
+  /// The advice of step `i`.
+  pub fn advice<Scalar: PrimeField>(&self, i: usize) -> Vec<Scalar> {
+    (0..self.num_advice())
+      .map(|k| Scalar::from(word(self.seed, (i * self.num_advice() + k) as u64)))
+      .collect()
+  }
+
+  /// Terms of the row of `matrix` of constraint `k`, as variable indices and coefficients.
+  pub fn row(&self, k: usize, matrix: usize) -> Vec<(usize, u64)> {
+    let num_vars = (1 + self.num_advice() + k) as u64;
+    let random_term = |t: usize| {
+      let n = (((k * 3 + matrix) * self.density + t) * 2) as u64;
+      ((word(self.seed, n) % num_vars) as usize, 1 + word(self.seed, n + 1) % 0xffff)
+    };
+    if matrix == C {
+      // the defined variable, then terms over the previous ones
+      let mut row = vec![(num_vars as usize, 1)];
+      row.extend((1..self.density).map(random_term));
+      row
+    } else {
+      (0..self.density).map(random_term).collect()
+    }
+  }
+
+  /// Values of all the variables of a step from `z_i` and the advice.
+  pub fn evaluate<Scalar: PrimeField>(&self, z: Scalar, advice: &[Scalar]) -> Vec<Scalar> {
+    assert_eq!(advice.len(), self.num_advice());
+    let eval = |row: &[(usize, u64)], values: &[Scalar]| -> Scalar {
+      row.iter().map(|(var, coeff)| values[*var] * Scalar::from(*coeff)).sum()
+    };
+
+    let mut values = Vec::with_capacity(1 + self.num_variables);
+    values.push(z);
+    values.extend(advice);
+    for k in 0..self.num_constraints {
+      let value = eval(&self.row(k, A), &values) * eval(&self.row(k, B), &values)
+        - eval(&self.row(k, C)[1..], &values);
+      values.push(value);
+    }
+    values
+  }
+}
+
+fn lc<Scalar: PrimeField>(row: &[(usize, u64)], vars: &[Variable]) -> LinearCombination<Scalar> {
+  row
+    .iter()
+    .fold(LinearCombination::zero(), |lc, (var, coeff)| lc + (Scalar::from(*coeff), vars[*var]))
+}
+
+#[derive(Clone, Debug)]
+pub struct RandomCircuit<G: Group> {
+  pub r1cs: RandomR1CS,
+  pub advice: Vec<G::Scalar>,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> RandomCircuit<G> {
+  // step `i` of `r1cs`, whose advice follows from the seed as in the arkworks version
+  pub fn new(r1cs: RandomR1CS, i: usize) -> Self {
+    Self {
+      r1cs,
+      advice: r1cs.advice(i),
+      _p: PhantomData,
+    }
+  }
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for RandomCircuit<G> {
+  fn arity(&self) -> usize {
+    1
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    assert_eq!(z.len(), 1);
+    let values = z[0].get_value().map(|z| self.r1cs.evaluate(z, &self.advice));
+    let value = |var: usize| values.as_ref().map(|values| values[var]).ok_or(SynthesisError::AssignmentMissing);
+
+    let mut vars = Vec::with_capacity(1 + self.r1cs.num_variables);
+    vars.push(z[0].get_variable());
+    for k in 0..self.r1cs.num_advice() {
+      let advice = AllocatedNum::alloc(cs.namespace(|| format!("advice_{}", k)), || value(1 + k))?;
+      vars.push(advice.get_variable());
+    }
+
+    let mut out = z[0].clone();
+    for k in 0..self.r1cs.num_constraints {
+      out = AllocatedNum::alloc(cs.namespace(|| format!("var_{}", k)), || value(vars.len()))?;
+      vars.push(out.get_variable());
+      cs.enforce(
+        || format!("constraint_{}", k),
+        |_| lc(&self.r1cs.row(k, A), &vars),
+        |_| lc(&self.r1cs.row(k, B), &vars),
+        |_| lc(&self.r1cs.row(k, C), &vars),
+      );
+    }
+
+    Ok(vec![out])
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    vec![*self.r1cs.evaluate(z[0], &self.advice).last().unwrap()]
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, r1cs: RandomR1CS,
+    pp: PublicParams<E1, E2, RandomCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice
+    let circuits = (0..num_steps)
+      .map(|i| RandomCircuit::new(r1cs, i))
+      .collect::<Vec<_>>();
+
+    let z0_primary = vec![<E1 as Engine>::Scalar::ONE];
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = RandomCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn random_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  for (num_constraints, num_variables, density) in [(64, 64, 1), (64, 256, 3), (128, 128, 16)] {
+    let r1cs = RandomR1CS::new(num_constraints, num_variables, density, 7);
+    let circuit = RandomCircuit::<G>::new(r1cs, 2);
+    let z_in = vec![F::from(3)];
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z = AllocatedNum::alloc(cs.namespace(|| "z"), || Ok(z_in[0])).unwrap();
+    let z_out = circuit.synthesize(&mut cs, &[z]).unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(cs.num_constraints(), num_constraints);
+    assert_eq!(vec![z_out[0].get_value().unwrap()], circuit.output(&z_in));
+    for k in 0..num_constraints {
+      assert!([A, B, C].iter().all(|matrix| r1cs.row(k, *matrix).len() == density));
+    }
+  }
+
+  // the matrices only depend on the seed, the row is the one of the arkworks test
+  let [r1cs, same, other] = [7, 7, 8].map(|seed| RandomR1CS::new(32, 32, 4, seed));
+  assert_eq!(r1cs.row(31, A), vec![(6, 57388), (19, 13810), (25, 2184), (1, 26751)]);
+  assert_eq!(r1cs.row(31, A), same.row(31, A));
+  assert_ne!(r1cs.row(31, A), other.row(31, A));
+}
@@ -0,0 +1,199 @@
+//! Range-check chain: each step performs a configurable number of u64 additions with carry,
+//! `acc + a_j = acc_{j+1} + carry_j * 2^64`, range-checking every `a_j` and `acc_{j+1}` to 64 bits
+//! by bit decomposition. The output of each step is the running accumulator.
+//!
+//! The addends of step `i` are the ones of the DSL version, so every framework adds the same
+//! numbers.
+use bellpepper_core::{boolean::AllocatedBit, num::AllocatedNum, ConstraintSystem, SynthesisError};
+use circuit_dsl::range_check::RangeCheck;
+use ff::{Field, PrimeField};
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+pub const NUM_BITS: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct RangeCheckCircuit<G: Group> {
+  pub num_adds_per_step: usize,
+  pub acc: u64,
+  pub a: Vec<u64>,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> RangeCheckCircuit<G> {
+  // produces the addends of step `i`, continuing the chain from `acc`
+  pub fn new(acc: u64, i: usize, num_adds_per_step: usize) -> Self {
+    let step = RangeCheck { num_adds_per_step };
+    let a = (0..num_adds_per_step).map(|j| step.addend(i, j)).collect::<Vec<u64>>();
+
+    Self {
+      num_adds_per_step,
+      acc,
+      a,
+      _p: PhantomData,
+    }
+  }
+
+  // running accumulator after each addition
+  fn accs(&self) -> Vec<u64> {
+    self
+      .a
+      .iter()
+      .scan(self.acc, |acc, a| {
+        *acc = acc.wrapping_add(*a);
+        Some(*acc)
+      })
+      .collect()
+  }
+
+  pub fn output_acc(&self) -> u64 {
+    self.a.iter().fold(self.acc, |acc, a| acc.wrapping_add(*a))
+  }
+}
+
+// allocates `value` together with its 64-bit little-endian decomposition
+fn alloc_range_checked<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+  mut cs: CS,
+  value: u64,
+) -> Result<AllocatedNum<Scalar>, SynthesisError> {
+  let num = AllocatedNum::alloc(cs.namespace(|| "value"), || Ok(Scalar::from(value)))?;
+  let bits = (0..NUM_BITS)
+    .map(|i| AllocatedBit::alloc(cs.namespace(|| format!("bit_{}", i)), Some((value >> i) & 1 == 1)))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  cs.enforce(
+    || "value = sum_i bit_i * 2^i",
+    |lc| {
+      bits
+        .iter()
+        .enumerate()
+        .fold(lc, |lc, (i, bit)| lc + (Scalar::from(1u64 << i), bit.get_variable()))
+    },
+    |lc| lc + CS::one(),
+    |lc| lc + num.get_variable(),
+  );
+
+  Ok(num)
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for RangeCheckCircuit<G> {
+  fn arity(&self) -> usize {
+    1
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    // z provides the running accumulator
+    assert_eq!(z.len(), 1);
+    let two_pow_64 = G::Scalar::from(u64::MAX) + G::Scalar::ONE;
+
+    let mut acc_prev = self.acc;
+    let mut acc = z[0].clone();
+    for (j, acc_next) in self.accs().into_iter().enumerate() {
+      let a = alloc_range_checked(cs.namespace(|| format!("a_{}", j)), self.a[j])?;
+      let acc_next_num = alloc_range_checked(cs.namespace(|| format!("acc_{}", j + 1)), acc_next)?;
+      let carry = AllocatedBit::alloc(
+        cs.namespace(|| format!("carry_{}", j)),
+        Some(acc_prev.checked_add(self.a[j]).is_none()),
+      )?;
+
+      cs.enforce(
+        || format!("acc + a = acc_next + carry * 2^64 iter_{}", j),
+        |lc| lc + acc.get_variable() + a.get_variable(),
+        |lc| lc + CS::one(),
+        |lc| lc + acc_next_num.get_variable() + (two_pow_64, carry.get_variable()),
+      );
+
+      acc_prev = acc_next;
+      acc = acc_next_num;
+    }
+
+    Ok(vec![acc])
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    // sanity check
+    debug_assert_eq!(z[0], G::Scalar::from(self.acc));
+
+    vec![G::Scalar::from(self.output_acc())]
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, num_adds_per_step: usize,
+    pp: PublicParams<E1, E2, RangeCheckCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice, every step continues from the previous accumulator
+    let mut circuits = Vec::with_capacity(num_steps);
+    let mut acc = 0u64;
+    for i in 0..num_steps {
+      let circuit = RangeCheckCircuit::new(acc, i, num_adds_per_step);
+      acc = circuit.output_acc();
+      circuits.push(circuit);
+    }
+
+    let z0_primary = vec![<E1 as Engine>::Scalar::zero()];
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = RangeCheckCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn range_check_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  // starting from u64::MAX, the first addition carries
+  let circuit = RangeCheckCircuit::<G>::new(u64::MAX, 3, 4);
+  let step = RangeCheck { num_adds_per_step: 4 };
+  assert_eq!(circuit.a, (0..4).map(|j| step.addend(3, j)).collect::<Vec<_>>());
+
+  let z_in = vec![F::from(u64::MAX)];
+  let mut cs = TestConstraintSystem::<F>::new();
+  let z = AllocatedNum::alloc(cs.namespace(|| "acc"), || Ok(z_in[0])).unwrap();
+  let z_out = circuit.synthesize(&mut cs, &[z]).unwrap();
+
+  assert!(cs.is_satisfied());
+  assert_eq!(vec![z_out[0].get_value().unwrap()], circuit.output(&z_in));
+
+  // an accumulator out of the 64-bit range doesn't fit a single carry
+  let mut cs = TestConstraintSystem::<F>::new();
+  let z = AllocatedNum::alloc(cs.namespace(|| "acc"), || Ok(z_in[0] + F::from(4) * (z_in[0] + F::ONE))).unwrap();
+  circuit.synthesize(&mut cs, &[z]).unwrap();
+  assert!(!cs.is_satisfied());
+}
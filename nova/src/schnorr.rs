@@ -0,0 +1,267 @@
+//! Schnorr signature verification chain over Grumpkin, whose base field is the BN254 scalar field
+//! the primary circuit works in. Each step verifies a configurable number of signatures,
+//! `s * G = R + e * P` with the challenge `e = H(R, P, m)`, and absorbs every verified public key
+//! and message into the running accumulator `acc' = H(acc, P, m)`. Hashes are neptune Poseidon.
+use bellpepper_core::{
+  boolean::{AllocatedBit, Boolean},
+  num::AllocatedNum,
+  ConstraintSystem, SynthesisError,
+};
+use ff::{Field, PrimeField};
+use generic_array::typenum::{U4, U5};
+use halo2curves::group::prime::PrimeCurveAffine;
+use halo2curves::{bn256::Fr, grumpkin, CurveAffine};
+use neptune::{circuit2::poseidon_hash_allocated, poseidon::PoseidonConstants, Poseidon};
+use nova_snark::{
+  gadgets::ecc::AllocatedPoint,
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+// the Grumpkin scalar field is the BN254 base field, which fits in 254 bits
+pub const NUM_SCALAR_BITS: usize = 254;
+
+fn coordinates(point: grumpkin::G1Affine) -> [Fr; 2] {
+  let coordinates = point.coordinates().unwrap();
+  [*coordinates.x(), *coordinates.y()]
+}
+
+fn challenge(r: &[Fr; 2], pk: &[Fr; 2], m: Fr) -> Fr {
+  let constants = PoseidonConstants::<Fr, U5>::new();
+  Poseidon::new_with_preimage(&[r[0], r[1], pk[0], pk[1], m], &constants).hash()
+}
+
+/// A signature on `m` under the public key `pk`, both points in affine coordinates.
+#[derive(Clone, Debug)]
+pub struct Signature {
+  pub pk: [Fr; 2],
+  pub r: [Fr; 2],
+  pub s: grumpkin::Fr,
+  pub m: Fr,
+}
+
+impl Signature {
+  // signs a random message under a fresh random key
+  pub fn random(rng: &mut impl Rng) -> Self {
+    let g = grumpkin::G1Affine::generator();
+    let sk = grumpkin::Fr::random(&mut *rng);
+    let k = grumpkin::Fr::random(&mut *rng);
+    let m = Fr::random(&mut *rng);
+
+    let pk = coordinates((g * sk).into());
+    let r = coordinates((g * k).into());
+    // the challenge is smaller than the Grumpkin scalar field modulus
+    let e = grumpkin::Fr::from_repr(challenge(&r, &pk, m).to_repr()).unwrap();
+
+    Self { pk, r, s: k + e * sk, m }
+  }
+
+  pub fn verify(&self) -> bool {
+    let g = grumpkin::G1Affine::generator();
+    let to_point = |[x, y]: [Fr; 2]| grumpkin::G1Affine::from_xy(x, y).unwrap();
+    let e = grumpkin::Fr::from_repr(challenge(&self.r, &self.pk, self.m).to_repr()).unwrap();
+
+    g * self.s == to_point(self.r) + to_point(self.pk) * e
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct SchnorrChainCircuit {
+  pub num_sigs_per_step: usize,
+  pub acc: Fr,
+  pub signatures: Vec<Signature>,
+}
+
+impl SchnorrChainCircuit {
+  // produces fresh signatures to be verified on top of the accumulator `acc`
+  pub fn new(acc: Fr, num_sigs_per_step: usize, rng: &mut impl Rng) -> Self {
+    Self {
+      num_sigs_per_step,
+      acc,
+      signatures: (0..num_sigs_per_step).map(|_| Signature::random(&mut *rng)).collect(),
+    }
+  }
+
+  pub fn output_acc(&self) -> Fr {
+    let constants = PoseidonConstants::<Fr, U4>::new();
+    self.signatures.iter().fold(self.acc, |acc, sig| {
+      Poseidon::new_with_preimage(&[acc, sig.pk[0], sig.pk[1], sig.m], &constants).hash()
+    })
+  }
+}
+
+fn alloc_point<CS: ConstraintSystem<Fr>>(
+  mut cs: CS,
+  [x, y]: [Fr; 2],
+) -> Result<AllocatedPoint<E2>, SynthesisError> {
+  let point = AllocatedPoint::<E2>::alloc(cs.namespace(|| "alloc"), Some((x, y, false)))?;
+  point.check_on_curve(cs.namespace(|| "on curve"))?;
+  Ok(point)
+}
+
+fn enforce_equal_points<CS: ConstraintSystem<Fr>>(cs: &mut CS, a: &AllocatedPoint<E2>, b: &AllocatedPoint<E2>) {
+  let (a, b) = (a.get_coordinates(), b.get_coordinates());
+  for (i, (a, b)) in [(a.0, b.0), (a.1, b.1), (a.2, b.2)].into_iter().enumerate() {
+    cs.enforce(
+      || format!("coordinate_{}", i),
+      |lc| lc + a.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + b.get_variable(),
+    );
+  }
+}
+
+impl StepCircuit<Fr> for SchnorrChainCircuit {
+  fn arity(&self) -> usize {
+    1
+  }
+
+  fn synthesize<CS: ConstraintSystem<Fr>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<Fr>],
+  ) -> Result<Vec<AllocatedNum<Fr>>, SynthesisError> {
+    // z provides the running accumulator
+    assert_eq!(z.len(), 1);
+
+    let challenge_constants = PoseidonConstants::<Fr, U5>::new();
+    let acc_constants = PoseidonConstants::<Fr, U4>::new();
+
+    // the generator is fixed by the verifier
+    let g = coordinates(grumpkin::G1Affine::generator());
+    let generator = AllocatedPoint::<E2>::alloc(cs.namespace(|| "generator"), Some((g[0], g[1], false)))?;
+    let (gx, gy, g_inf) = generator.get_coordinates();
+    for (i, (coordinate, value)) in [(gx, g[0]), (gy, g[1]), (g_inf, Fr::ZERO)].into_iter().enumerate() {
+      cs.enforce(
+        || format!("generator_{}", i),
+        |lc| lc + coordinate.get_variable(),
+        |lc| lc + CS::one(),
+        |lc| lc + (value, CS::one()),
+      );
+    }
+
+    let mut acc = z[0].clone();
+    for (j, sig) in self.signatures.iter().enumerate() {
+      let mut cs = cs.namespace(|| format!("signature_{}", j));
+
+      let pk = alloc_point(cs.namespace(|| "pk"), sig.pk)?;
+      let r = alloc_point(cs.namespace(|| "r"), sig.r)?;
+      let m = AllocatedNum::alloc(cs.namespace(|| "m"), || Ok(sig.m))?;
+      let (pk_x, pk_y, _) = pk.get_coordinates();
+      let (r_x, r_y, _) = r.get_coordinates();
+
+      // e = H(R, P, m), decomposed into its canonical bits
+      let e = poseidon_hash_allocated(
+        cs.namespace(|| "challenge"),
+        vec![r_x.clone(), r_y.clone(), pk_x.clone(), pk_y.clone(), m.clone()],
+        &challenge_constants,
+      )?;
+      let e_bits = e
+        .to_bits_le_strict(cs.namespace(|| "challenge bits"))?
+        .into_iter()
+        .map(|bit| match bit {
+          Boolean::Is(bit) => bit,
+          _ => unreachable!("the decomposition is allocated"),
+        })
+        .collect::<Vec<_>>();
+
+      let s_repr = sig.s.to_repr();
+      let s_bits = (0..NUM_SCALAR_BITS)
+        .map(|i| AllocatedBit::alloc(cs.namespace(|| format!("s_bit_{}", i)), Some((s_repr[i / 8] >> (i % 8)) & 1 == 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+      // s * G = R + e * P
+      let lhs = generator.scalar_mul(cs.namespace(|| "s * G"), &s_bits)?;
+      let e_pk = pk.scalar_mul(cs.namespace(|| "e * P"), &e_bits)?;
+      let rhs = e_pk.add(cs.namespace(|| "R + e * P"), &r)?;
+      enforce_equal_points(&mut cs.namespace(|| "s * G = R + e * P"), &lhs, &rhs);
+
+      acc = poseidon_hash_allocated(
+        cs.namespace(|| "absorb"),
+        vec![acc, pk_x.clone(), pk_y.clone(), m],
+        &acc_constants,
+      )?;
+    }
+
+    Ok(vec![acc])
+  }
+
+  fn output(&self, z: &[Fr]) -> Vec<Fr> {
+    // sanity check
+    debug_assert_eq!(z[0], self.acc);
+
+    vec![self.output_acc()]
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, num_sigs_per_step: usize,
+    pp: PublicParams<E1, E2, SchnorrChainCircuit, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice, every step continues from the previous accumulator
+    let mut circuits = Vec::with_capacity(num_steps);
+    let mut acc = Fr::ZERO;
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..num_steps {
+      let circuit = SchnorrChainCircuit::new(acc, num_sigs_per_step, &mut rng);
+      acc = circuit.output_acc();
+      circuits.push(circuit);
+    }
+
+    let z0_primary = vec![<E1 as Engine>::Scalar::zero()];
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = SchnorrChainCircuit;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn schnorr_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  let mut rng = StdRng::seed_from_u64(0);
+  let mut circuit = SchnorrChainCircuit::new(Fr::ZERO, 2, &mut rng);
+  assert!(circuit.signatures.iter().all(Signature::verify));
+
+  let mut cs = TestConstraintSystem::<Fr>::new();
+  let z = AllocatedNum::alloc(cs.namespace(|| "acc"), || Ok(circuit.acc)).unwrap();
+  let z_out = circuit.synthesize(&mut cs, &[z]).unwrap();
+
+  assert!(cs.is_satisfied());
+  assert_eq!(z_out[0].get_value().unwrap(), circuit.output_acc());
+
+  // a forged signature doesn't verify
+  circuit.signatures[1].s += grumpkin::Fr::ONE;
+  assert!(!circuit.signatures[1].verify());
+  let mut cs = TestConstraintSystem::<Fr>::new();
+  let z = AllocatedNum::alloc(cs.namespace(|| "acc"), || Ok(circuit.acc)).unwrap();
+  circuit.synthesize(&mut cs, &[z]).unwrap();
+  assert!(!cs.is_satisfied());
+}
@@ -0,0 +1,203 @@
+//! SHA-256 hash chain: each step hashes the running digest a configurable number of times,
+//! `h_{j+1} = SHA256(h_j)`, with bellpepper's SHA-256 gadget. A 32-byte message fits in a single
+//! block, so every hash is one compression. The digest is carried across steps as two 128-bit
+//! big-endian halves, since it does not fit in a single field element.
+use bellpepper::gadgets::sha256::sha256;
+use bellpepper_core::{
+  boolean::{AllocatedBit, Boolean},
+  num::AllocatedNum,
+  ConstraintSystem, SynthesisError,
+};
+use ff::{Field, PrimeField};
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use sha2::{Digest, Sha256};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+pub const DIGEST_BYTES: usize = 32;
+const HALF_BYTES: usize = DIGEST_BYTES / 2;
+
+// native reference, hashes `digest` `num_hashes` times
+pub fn sha256_chain(digest: [u8; DIGEST_BYTES], num_hashes: usize) -> [u8; DIGEST_BYTES] {
+  (0..num_hashes).fold(digest, |digest, _| Sha256::digest(digest).into())
+}
+
+pub fn digest_to_scalars<Scalar: PrimeField>(digest: &[u8; DIGEST_BYTES]) -> Vec<Scalar> {
+  digest
+    .chunks(HALF_BYTES)
+    .map(|half| Scalar::from_u128(u128::from_be_bytes(half.try_into().unwrap())))
+    .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct Sha256ChainCircuit<G: Group> {
+  pub num_hashes_per_step: usize,
+  pub digest: [u8; DIGEST_BYTES],
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> Sha256ChainCircuit<G> {
+  // continues the chain from `digest`
+  pub fn new(digest: [u8; DIGEST_BYTES], num_hashes_per_step: usize) -> Self {
+    Self {
+      num_hashes_per_step,
+      digest,
+      _p: PhantomData,
+    }
+  }
+
+  pub fn output_digest(&self) -> [u8; DIGEST_BYTES] {
+    sha256_chain(self.digest, self.num_hashes_per_step)
+  }
+}
+
+// enforces `num = sum_k bit_k * 2^(n - 1 - k)`, with the bits in big-endian order
+pub(crate) fn enforce_packing<Scalar: PrimeField, CS: ConstraintSystem<Scalar>>(
+  cs: &mut CS,
+  bits: &[Boolean],
+  num: &AllocatedNum<Scalar>,
+) {
+  cs.enforce(
+    || "num = sum_k bit_k * 2^(n - 1 - k)",
+    |lc| {
+      let mut coeff = Scalar::ONE;
+      bits.iter().rev().fold(lc, |lc, bit| {
+        let lc = lc + &bit.lc(CS::one(), coeff);
+        coeff = coeff.double();
+        lc
+      })
+    },
+    |lc| lc + CS::one(),
+    |lc| lc + num.get_variable(),
+  );
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for Sha256ChainCircuit<G> {
+  fn arity(&self) -> usize {
+    2
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    // z provides the two halves of the running digest
+    assert_eq!(z.len(), 2);
+
+    let mut bits = Vec::with_capacity(8 * DIGEST_BYTES);
+    for (i, half) in z.iter().enumerate() {
+      let half_bits = (0..8 * HALF_BYTES)
+        .map(|k| {
+          let byte = self.digest[i * HALF_BYTES + k / 8];
+          AllocatedBit::alloc(cs.namespace(|| format!("z_{}_bit_{}", i, k)), Some((byte >> (7 - k % 8)) & 1 == 1))
+            .map(Boolean::from)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+      enforce_packing(&mut cs.namespace(|| format!("unpack z_{}", i)), &half_bits, half);
+      bits.extend(half_bits);
+    }
+
+    for j in 0..self.num_hashes_per_step {
+      bits = sha256(cs.namespace(|| format!("sha256_{}", j)), &bits)?;
+    }
+
+    bits
+      .chunks(8 * HALF_BYTES)
+      .enumerate()
+      .map(|(i, half_bits)| {
+        let half = AllocatedNum::alloc(cs.namespace(|| format!("z_out_{}", i)), || {
+          half_bits.iter().try_fold(G::Scalar::ZERO, |acc, bit| {
+            let bit = bit.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(acc.double() + if bit { G::Scalar::ONE } else { G::Scalar::ZERO })
+          })
+        })?;
+        enforce_packing(&mut cs.namespace(|| format!("pack z_out_{}", i)), half_bits, &half);
+        Ok(half)
+      })
+      .collect()
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    // sanity check
+    debug_assert_eq!(z, digest_to_scalars::<G::Scalar>(&self.digest).as_slice());
+
+    digest_to_scalars(&self.output_digest())
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, num_hashes_per_step: usize,
+    pp: PublicParams<E1, E2, Sha256ChainCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice, every step continues from the previous digest
+    let mut circuits = Vec::with_capacity(num_steps);
+    let mut digest = [0u8; DIGEST_BYTES];
+    for _ in 0..num_steps {
+      let circuit = Sha256ChainCircuit::new(digest, num_hashes_per_step);
+      digest = circuit.output_digest();
+      circuits.push(circuit);
+    }
+
+    let z0_primary = digest_to_scalars(&[0u8; DIGEST_BYTES]);
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = Sha256ChainCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn sha256_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  // SHA256 of 32 zero bytes
+  let expected = "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925";
+  let circuit = Sha256ChainCircuit::<G>::new([0u8; DIGEST_BYTES], 1);
+  let hex = circuit.output_digest().iter().map(|b| format!("{:02x}", b)).collect::<String>();
+  assert_eq!(hex, expected);
+
+  let circuit = Sha256ChainCircuit::<G>::new(circuit.output_digest(), 2);
+  let z_in = digest_to_scalars::<F>(&circuit.digest);
+  let mut cs = TestConstraintSystem::<F>::new();
+  let z = z_in
+    .iter()
+    .enumerate()
+    .map(|(i, z)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", i)), || Ok(*z)))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+  let z_out = circuit.synthesize(&mut cs, &z).unwrap();
+
+  assert!(cs.is_satisfied());
+  assert_eq!(z_out.iter().map(|z| z.get_value().unwrap()).collect::<Vec<_>>(), circuit.output(&z_in));
+}
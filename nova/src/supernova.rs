@@ -0,0 +1,530 @@
+//! Non-uniform IVC over a small instruction set acting on a register pair `(x, y)`: `Add`, `Mul`,
+//! `Hash` (Poseidon) and `MinRoot`, each applied a configurable number of times per step.
+//! SuperNova folds every step into the running instance of its opcode's circuit only, whereas the
+//! universal step circuit used with plain Nova executes every opcode and selects the result.
+//!
+//! The state is `(trace, x, y)`, where `trace` fingerprints the executed opcodes
+//! (`trace_next = 5 * trace + op + 1`), so a verifier holding the public program can check that
+//! the program counters chosen by the prover followed it.
+use bellpepper_core::{
+  boolean::AllocatedBit, num::AllocatedNum, test_cs::TestConstraintSystem, ConstraintSystem,
+  LinearCombination, SynthesisError,
+};
+use ff::{Field, PrimeField};
+use generic_array::typenum::U2;
+use neptune::{circuit2::poseidon_hash_allocated, poseidon::PoseidonConstants, Poseidon};
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  supernova::{
+    NonUniformCircuit, PublicParams as SuperNovaPublicParams,
+    RecursiveSNARK as SuperNovaRecursiveSNARK, StepCircuit as SuperNovaStepCircuit,
+    TrivialSecondaryCircuit,
+  },
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use num_bigint::BigUint;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+pub const NUM_OPCODES: usize = 4;
+// opcodes are encoded as 1..=NUM_OPCODES in the trace
+const TRACE_BASE: u64 = NUM_OPCODES as u64 + 1;
+/// Longest program whose trace does not wrap around the BN254 scalar field (5^109 < 2^254).
+pub const MAX_PROGRAM_LEN: usize = 109;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opcode {
+  Add,
+  Mul,
+  Hash,
+  MinRoot,
+}
+
+impl Opcode {
+  pub const ALL: [Opcode; NUM_OPCODES] = [Opcode::Add, Opcode::Mul, Opcode::Hash, Opcode::MinRoot];
+}
+
+// 5^{-1} mod (p - 1), so that x^{exp} is the fifth root of x. The representation is assumed to be
+// little-endian, as for the BN254 scalar field.
+fn fifth_root_exp<F: PrimeField>() -> Vec<u64> {
+  let p_minus_one = BigUint::from_bytes_le((-F::ONE).to_repr().as_ref());
+  let exp = (1u32..5)
+    .map(|t| &p_minus_one * t + 1u32)
+    .find(|n| (n % 5u32) == BigUint::from(0u32))
+    .expect("5 must not divide p - 1")
+    / 5u32;
+  exp.to_u64_digits()
+}
+
+/// Applies `op` `num_iters` times to `(x, y)`, every opcode moves the previous `x` into `y`.
+pub fn execute<F: PrimeField>(op: Opcode, num_iters: usize, mut x: F, mut y: F) -> (F, F) {
+  let constants = PoseidonConstants::<F, U2>::new();
+  let exp = fifth_root_exp::<F>();
+  for _ in 0..num_iters {
+    let x_next = match op {
+      Opcode::Add => x + y,
+      Opcode::Mul => x * y,
+      Opcode::Hash => Poseidon::<F, U2>::new_with_preimage(&[x, y], &constants).hash(),
+      Opcode::MinRoot => (x + y).pow_vartime(&exp),
+    };
+    y = x;
+    x = x_next;
+  }
+  (x, y)
+}
+
+/// Native counterpart of a step executing `op` on the state `(trace, x, y)`.
+pub fn step_native<F: PrimeField>(op: Opcode, num_iters: usize, z: &[F]) -> Vec<F> {
+  let (x, y) = execute(op, num_iters, z[1], z[2]);
+  vec![z[0] * F::from(TRACE_BASE) + F::from(op as u64 + 1), x, y]
+}
+
+fn synthesize_op<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  op: Opcode,
+  num_iters: usize,
+  x: &AllocatedNum<F>,
+  y: &AllocatedNum<F>,
+) -> Result<(AllocatedNum<F>, AllocatedNum<F>), SynthesisError> {
+  let constants = (op == Opcode::Hash).then(PoseidonConstants::<F, U2>::new);
+  let exp = fifth_root_exp::<F>();
+
+  let (mut x, mut y) = (x.clone(), y.clone());
+  for i in 0..num_iters {
+    let mut cs = cs.namespace(|| format!("iter_{}", i));
+    let x_next = match op {
+      Opcode::Add => x.add(cs.namespace(|| "x + y"), &y)?,
+      Opcode::Mul => x.mul(cs.namespace(|| "x * y"), &y)?,
+      Opcode::Hash => poseidon_hash_allocated(
+        cs.namespace(|| "H(x, y)"),
+        vec![x.clone(), y.clone()],
+        constants.as_ref().unwrap(),
+      )?,
+      Opcode::MinRoot => {
+        // x_next = (x + y)^{1/5}, which can be more easily checked with x_next^5 = x + y
+        let x_next = AllocatedNum::alloc(cs.namespace(|| "x_next"), || {
+          let sum = x.get_value().zip(y.get_value()).map(|(x, y)| x + y);
+          Ok(sum.ok_or(SynthesisError::AssignmentMissing)?.pow_vartime(&exp))
+        })?;
+        let x_next_sq = x_next.square(cs.namespace(|| "x_next_sq"))?;
+        let x_next_quad = x_next_sq.square(cs.namespace(|| "x_next_quad"))?;
+        cs.enforce(
+          || "x_next_quad * x_next = x + y",
+          |lc| lc + x_next_quad.get_variable(),
+          |lc| lc + x_next.get_variable(),
+          |lc| lc + x.get_variable() + y.get_variable(),
+        );
+        x_next
+      }
+    };
+    y = x;
+    x = x_next;
+  }
+
+  Ok((x, y))
+}
+
+// trace_next = 5 * trace + code
+fn append_trace<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  trace: &AllocatedNum<F>,
+  code: LinearCombination<F>,
+  code_value: Option<F>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+  let trace_next = AllocatedNum::alloc(cs.namespace(|| "trace_next"), || {
+    let (trace, code) = trace.get_value().zip(code_value).ok_or(SynthesisError::AssignmentMissing)?;
+    Ok(trace * F::from(TRACE_BASE) + code)
+  })?;
+  cs.enforce(
+    || "trace_next = 5 * trace + code",
+    |lc| lc + (F::from(TRACE_BASE), trace.get_variable()) + &code,
+    |lc| lc + CS::one(),
+    |lc| lc + trace_next.get_variable(),
+  );
+  Ok(trace_next)
+}
+
+// returns `bit ? b : a`
+fn select<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  a: &AllocatedNum<F>,
+  b: &AllocatedNum<F>,
+  bit: &AllocatedBit,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+  let r = AllocatedNum::alloc(cs.namespace(|| "r"), || {
+    let chosen = if bit.get_value().ok_or(SynthesisError::AssignmentMissing)? { b } else { a };
+    chosen.get_value().ok_or(SynthesisError::AssignmentMissing)
+  })?;
+  cs.enforce(
+    || "(b - a) * bit = r - a",
+    |lc| lc + b.get_variable() - a.get_variable(),
+    |lc| lc + bit.get_variable(),
+    |lc| lc + r.get_variable() - a.get_variable(),
+  );
+  Ok(r)
+}
+
+// returns `values[bits[0] + 2 * bits[1]]`
+fn mux4<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  values: [&AllocatedNum<F>; NUM_OPCODES],
+  bits: &[AllocatedBit],
+) -> Result<AllocatedNum<F>, SynthesisError> {
+  let lo = select(cs.namespace(|| "lo"), values[0], values[1], &bits[0])?;
+  let hi = select(cs.namespace(|| "hi"), values[2], values[3], &bits[0])?;
+  select(cs.namespace(|| "lo/hi"), &lo, &hi, &bits[1])
+}
+
+/// Step circuit of a single opcode, folded by SuperNova into the running instance of `op`.
+#[derive(Clone, Debug)]
+pub struct OpcodeCircuit<G: Group> {
+  pub op: Opcode,
+  // advice for the program counter of the following step
+  pub next_op: Opcode,
+  pub num_iters_per_step: usize,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> OpcodeCircuit<G> {
+  pub fn new(op: Opcode, next_op: Opcode, num_iters_per_step: usize) -> Self {
+    Self {
+      op,
+      next_op,
+      num_iters_per_step,
+      _p: PhantomData,
+    }
+  }
+}
+
+impl<G: Group> SuperNovaStepCircuit<G::Scalar> for OpcodeCircuit<G> {
+  fn arity(&self) -> usize {
+    3
+  }
+
+  fn circuit_index(&self) -> usize {
+    self.op as usize
+  }
+
+  #[allow(clippy::type_complexity)]
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    pc: Option<&AllocatedNum<G::Scalar>>,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<(Option<AllocatedNum<G::Scalar>>, Vec<AllocatedNum<G::Scalar>>), SynthesisError> {
+    // z provides (trace, x, y)
+    assert_eq!(z.len(), 3);
+    let code = G::Scalar::from(self.op as u64 + 1);
+
+    // the program counter selected this circuit, so it must point to this opcode
+    if let Some(pc) = pc {
+      cs.enforce(
+        || "pc = op",
+        |lc| lc + pc.get_variable(),
+        |lc| lc + CS::one(),
+        |lc| lc + (G::Scalar::from(self.op as u64), CS::one()),
+      );
+    }
+
+    let trace = append_trace(
+      cs.namespace(|| "trace"),
+      &z[0],
+      LinearCombination::zero() + (code, CS::one()),
+      Some(code),
+    )?;
+    let (x, y) = synthesize_op(cs.namespace(|| "op"), self.op, self.num_iters_per_step, &z[1], &z[2])?;
+
+    // the trace binds the program counters to the program, so they are pure advice
+    let pc_next = AllocatedNum::alloc(cs.namespace(|| "pc_next"), || {
+      Ok(G::Scalar::from(self.next_op as u64))
+    })?;
+
+    Ok((Some(pc_next), vec![trace, x, y]))
+  }
+}
+
+/// Step circuit executing every opcode and selecting the result of `op`, as uniform IVC requires.
+#[derive(Clone, Debug)]
+pub struct UniversalCircuit<G: Group> {
+  pub op: Opcode,
+  pub num_iters_per_step: usize,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> UniversalCircuit<G> {
+  pub fn new(op: Opcode, num_iters_per_step: usize) -> Self {
+    Self {
+      op,
+      num_iters_per_step,
+      _p: PhantomData,
+    }
+  }
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for UniversalCircuit<G> {
+  fn arity(&self) -> usize {
+    3
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    // z provides (trace, x, y)
+    assert_eq!(z.len(), 3);
+
+    // op = bits[0] + 2 * bits[1]
+    let op = self.op as u64;
+    let bits = (0..2)
+      .map(|i| AllocatedBit::alloc(cs.namespace(|| format!("op_bit_{}", i)), Some((op >> i) & 1 == 1)))
+      .collect::<Result<Vec<_>, _>>()?;
+    let code = LinearCombination::zero()
+      + CS::one()
+      + bits[0].get_variable()
+      + (G::Scalar::from(2), bits[1].get_variable());
+    let trace = append_trace(
+      cs.namespace(|| "trace"),
+      &z[0],
+      code,
+      Some(G::Scalar::from(op + 1)),
+    )?;
+
+    let results = Opcode::ALL
+      .iter()
+      .map(|op| synthesize_op(cs.namespace(|| format!("{:?}", op)), *op, self.num_iters_per_step, &z[1], &z[2]))
+      .collect::<Result<Vec<_>, _>>()?;
+    let x = mux4(cs.namespace(|| "select x"), [0, 1, 2, 3].map(|i| &results[i].0), &bits)?;
+    let y = mux4(cs.namespace(|| "select y"), [0, 1, 2, 3].map(|i| &results[i].1), &bits)?;
+
+    Ok(vec![trace, x, y])
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    step_native(self.op, self.num_iters_per_step, z)
+  }
+}
+
+/// Program executed one opcode per step.
+#[derive(Clone, Debug)]
+pub struct Program {
+  pub ops: Vec<Opcode>,
+  pub num_iters_per_step: usize,
+}
+
+impl Program {
+  // produces a program of uniformly random opcodes, the same for the same seed
+  pub fn random(len: usize, num_iters_per_step: usize, seed: u64) -> Self {
+    assert!(len <= MAX_PROGRAM_LEN);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let ops = (0..len).map(|_| Opcode::ALL[rng.gen_range(0..NUM_OPCODES)]).collect();
+
+    Self {
+      ops,
+      num_iters_per_step,
+    }
+  }
+
+  pub fn z0<F: PrimeField>() -> Vec<F> {
+    vec![F::ZERO, F::ONE, F::from(2)]
+  }
+
+  /// Trace a verifier expects after running the whole program.
+  pub fn trace<F: PrimeField>(&self) -> F {
+    self
+      .ops
+      .iter()
+      .fold(F::ZERO, |trace, op| trace * F::from(TRACE_BASE) + F::from(*op as u64 + 1))
+  }
+
+  pub fn opcode_circuits<G: Group>(&self) -> Vec<OpcodeCircuit<G>> {
+    // the pc after the last step is unused, it points back to the first opcode
+    (0..self.ops.len())
+      .map(|i| {
+        let next_op = self.ops[(i + 1) % self.ops.len()];
+        OpcodeCircuit::new(self.ops[i], next_op, self.num_iters_per_step)
+      })
+      .collect()
+  }
+
+  pub fn universal_circuits<G: Group>(&self) -> Vec<UniversalCircuit<G>> {
+    self
+      .ops
+      .iter()
+      .map(|op| UniversalCircuit::new(*op, self.num_iters_per_step))
+      .collect()
+  }
+}
+
+type C1 = OpcodeCircuit<<E1 as Engine>::GE>;
+type C2 = TrivialSecondaryCircuit<<E2 as Engine>::Scalar>;
+
+impl NonUniformCircuit<E1, E2, C1, C2> for Program {
+  fn num_circuits(&self) -> usize {
+    NUM_OPCODES
+  }
+
+  fn primary_circuit(&self, circuit_index: usize) -> C1 {
+    let op = Opcode::ALL[circuit_index];
+    OpcodeCircuit::new(op, op, self.num_iters_per_step)
+  }
+
+  fn secondary_circuit(&self) -> C2 {
+    Default::default()
+  }
+
+  fn initial_circuit_index(&self) -> usize {
+    self.ops[0] as usize
+  }
+}
+
+/// Number of constraints of a step circuit alone, without the augmented verifier circuit.
+fn num_step_constraints<F: PrimeField>(
+  synthesize: impl FnOnce(&mut TestConstraintSystem<F>, &[AllocatedNum<F>]) -> Result<(), SynthesisError>,
+) -> usize {
+  let mut cs = TestConstraintSystem::<F>::new();
+  let z = Program::z0::<F>()
+    .into_iter()
+    .enumerate()
+    .map(|(i, value)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", i)), || Ok(value)))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+  synthesize(&mut cs, &z).unwrap();
+  assert!(cs.is_satisfied());
+  cs.num_constraints()
+}
+
+pub fn opcode_num_constraints(op: Opcode, num_iters_per_step: usize) -> usize {
+  let circuit = OpcodeCircuit::<<E1 as Engine>::GE>::new(op, op, num_iters_per_step);
+  num_step_constraints(|cs, z| circuit.synthesize(cs, None, z).map(|_| ()))
+}
+
+pub fn universal_num_constraints(num_iters_per_step: usize) -> usize {
+  let circuit = UniversalCircuit::<<E1 as Engine>::GE>::new(Opcode::Add, num_iters_per_step);
+  num_step_constraints(|cs, z| StepCircuit::synthesize(&circuit, cs, z).map(|_| ()))
+}
+
+pub fn supernova_ivc(program: &Program, pp: &SuperNovaPublicParams<E1, E2, C1, C2>) -> Duration {
+  let start = Instant::now();
+  let circuits = program.opcode_circuits::<<E1 as Engine>::GE>();
+  let circuit_secondary = C2::default();
+
+  let z0_primary = Program::z0::<<E1 as Engine>::Scalar>();
+  let z0_secondary = vec![<E2 as Engine>::Scalar::ZERO];
+
+  // produce a recursive SNARK
+  let mut recursive_snark = SuperNovaRecursiveSNARK::<E1, E2>::new(
+    pp,
+    program,
+    &circuits[0],
+    &circuit_secondary,
+    &z0_primary,
+    &z0_secondary,
+  )
+  .unwrap();
+
+  for circuit_primary in circuits.iter() {
+    let res = recursive_snark.prove_step(pp, circuit_primary, &circuit_secondary);
+    assert!(res.is_ok());
+  }
+  start.elapsed()
+}
+
+pub fn universal_nova_ivc(
+  program: &Program,
+  pp: PublicParams<
+    E1,
+    E2,
+    UniversalCircuit<<E1 as Engine>::GE>,
+    TrivialCircuit<<E2 as Engine>::Scalar>,
+  >,
+  circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>,
+) -> Duration {
+  let start = Instant::now();
+  let circuits = program.universal_circuits::<<E1 as Engine>::GE>();
+
+  let z0_primary = Program::z0::<<E1 as Engine>::Scalar>();
+  let z0_secondary = vec![<E2 as Engine>::Scalar::ZERO];
+
+  // produce a recursive SNARK
+  let mut recursive_snark = RecursiveSNARK::<
+    E1,
+    E2,
+    UniversalCircuit<<E1 as Engine>::GE>,
+    TrivialCircuit<<E2 as Engine>::Scalar>,
+  >::new(
+    &pp,
+    &circuits[0],
+    &circuit_secondary,
+    &z0_primary,
+    &z0_secondary,
+  )
+  .unwrap();
+
+  for circuit_primary in circuits.iter() {
+    let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+    assert!(res.is_ok());
+  }
+  start.elapsed()
+}
+
+#[test]
+fn supernova_step_circuits_test() {
+  type F = <E1 as Engine>::Scalar;
+  let num_iters_per_step = 2;
+  let program = Program {
+    ops: Opcode::ALL.to_vec(),
+    num_iters_per_step,
+  };
+
+  // both step circuits agree with the native execution
+  let mut z = Program::z0::<F>();
+  for (opcode_circuit, universal_circuit) in program
+    .opcode_circuits::<<E1 as Engine>::GE>()
+    .iter()
+    .zip(program.universal_circuits::<<E1 as Engine>::GE>())
+  {
+    let z_next = step_native(opcode_circuit.op, num_iters_per_step, &z);
+    for universal in [false, true] {
+      let mut cs = TestConstraintSystem::<F>::new();
+      let z_in = z
+        .iter()
+        .enumerate()
+        .map(|(i, value)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", i)), || Ok(*value)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+      let z_out = if universal {
+        StepCircuit::synthesize(&universal_circuit, &mut cs, &z_in).unwrap()
+      } else {
+        opcode_circuit.synthesize(&mut cs, None, &z_in).unwrap().1
+      };
+      assert!(cs.is_satisfied());
+      assert_eq!(
+        z_out
+          .iter()
+          .map(|z| z.get_value().unwrap())
+          .collect::<Vec<_>>(),
+        z_next
+      );
+    }
+    z = z_next;
+  }
+  assert_eq!(z[0], program.trace::<F>());
+
+  // the universal circuit pays for every opcode
+  let universal = universal_num_constraints(num_iters_per_step);
+  for op in Opcode::ALL {
+    assert!(opcode_num_constraints(op, num_iters_per_step) < universal);
+  }
+
+  // the benches prove the same program with both schemes
+  assert_eq!(Program::random(20, 1, 0).ops, Program::random(20, 1, 0).ops);
+}
@@ -0,0 +1,224 @@
+//! Synthetic R1CS workload, to separate the costs driven by the witness size from the ones driven
+//! by the constraints: a step has exactly `num_constraints` constraints over exactly
+//! `num_witnesses` fresh witness variables, each row of `A` and `B` having `density` non-zero
+//! entries.
+//!
+//! The shape is generated once from a seed. The first `min(num_witnesses, num_constraints)`
+//! constraints are products `out_j = A_j * B_j`, whose rows pick random variables among `z_i`, the
+//! advice and the previous products. The remaining witnesses are free advice, the remaining
+//! constraints re-check earlier products scaled by a random factor, `(s * A_k) * B_k = s * out_k`.
+//! The state is the last product.
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError, Variable};
+use ff::{Field, PrimeField};
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+/// Terms of a row, as variable indices and coefficients. Variable 0 is `z_i`, the advice comes
+/// next, then the products.
+pub type Row = Vec<(usize, u64)>;
+
+#[derive(Clone, Debug)]
+pub struct SyntheticShape {
+  pub num_constraints: usize,
+  pub num_witnesses: usize,
+  pub density: usize,
+  pub num_advice: usize,
+  // rows of A and B of the products
+  pub products: Vec<(Row, Row)>,
+  // product re-checked by each of the remaining constraints, with its scale
+  pub rechecks: Vec<(usize, u64)>,
+}
+
+impl SyntheticShape {
+  pub fn new(num_constraints: usize, num_witnesses: usize, density: usize, seed: u64) -> Self {
+    assert!(num_constraints > 0 && num_witnesses > 0 && density > 0);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let num_products = num_constraints.min(num_witnesses);
+    let num_advice = num_witnesses - num_products;
+
+    let products = (0..num_products)
+      .map(|j| {
+        // every variable allocated so far is available
+        let num_vars = 1 + num_advice + j;
+        let mut row = || (0..density).map(|_| (rng.gen_range(0..num_vars), rng.gen_range(1..1 << 16))).collect::<Row>();
+        (row(), row())
+      })
+      .collect();
+    let rechecks = (num_products..num_constraints)
+      .map(|_| (rng.gen_range(0..num_products), rng.gen_range(2..1 << 16)))
+      .collect();
+
+    Self {
+      num_constraints,
+      num_witnesses,
+      density,
+      num_advice,
+      products,
+      rechecks,
+    }
+  }
+
+  /// Values of all the variables of a step from `z_i` and the advice.
+  pub fn evaluate<Scalar: PrimeField>(&self, z: Scalar, advice: &[Scalar]) -> Vec<Scalar> {
+    let mut values = vec![z];
+    values.extend(advice);
+    for (a, b) in &self.products {
+      let product = eval(a, &values) * eval(b, &values);
+      values.push(product);
+    }
+    values
+  }
+}
+
+fn eval<Scalar: PrimeField>(row: &Row, values: &[Scalar]) -> Scalar {
+  row.iter().map(|(var, coeff)| values[*var] * Scalar::from(*coeff)).sum()
+}
+
+fn lc<Scalar: PrimeField>(row: &Row, vars: &[Variable], scale: Scalar) -> LinearCombination<Scalar> {
+  row
+    .iter()
+    .fold(LinearCombination::zero(), |lc, (var, coeff)| lc + (scale * Scalar::from(*coeff), vars[*var]))
+}
+
+#[derive(Clone, Debug)]
+pub struct SyntheticCircuit<G: Group> {
+  pub shape: Arc<SyntheticShape>,
+  pub advice: Vec<G::Scalar>,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> SyntheticCircuit<G> {
+  // random advice for a step of `shape`
+  pub fn new(shape: Arc<SyntheticShape>, rng: &mut impl Rng) -> Self {
+    let advice = (0..shape.num_advice).map(|_| G::Scalar::random(&mut *rng)).collect();
+
+    Self {
+      shape,
+      advice,
+      _p: PhantomData,
+    }
+  }
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for SyntheticCircuit<G> {
+  fn arity(&self) -> usize {
+    1
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    assert_eq!(z.len(), 1);
+    let values = z[0].get_value().map(|z| self.shape.evaluate(z, &self.advice));
+    let value = |var: usize| values.as_ref().map(|values| values[var]).ok_or(SynthesisError::AssignmentMissing);
+
+    let mut vars = vec![z[0].get_variable()];
+    for k in 0..self.shape.num_advice {
+      let advice = AllocatedNum::alloc(cs.namespace(|| format!("advice_{}", k)), || value(1 + k))?;
+      vars.push(advice.get_variable());
+    }
+
+    let mut out = z[0].clone();
+    for (j, (a, b)) in self.shape.products.iter().enumerate() {
+      out = AllocatedNum::alloc(cs.namespace(|| format!("out_{}", j)), || value(vars.len()))?;
+      cs.enforce(
+        || format!("out_{} = A_{} * B_{}", j, j, j),
+        |_| lc(a, &vars, G::Scalar::ONE),
+        |_| lc(b, &vars, G::Scalar::ONE),
+        |lc| lc + out.get_variable(),
+      );
+      vars.push(out.get_variable());
+    }
+
+    let first_product = 1 + self.shape.num_advice;
+    for (j, (k, scale)) in self.shape.rechecks.iter().enumerate() {
+      let (a, b) = &self.shape.products[*k];
+      let scale = G::Scalar::from(*scale);
+      cs.enforce(
+        || format!("recheck_{}", j),
+        |_| lc(a, &vars, scale),
+        |_| lc(b, &vars, G::Scalar::ONE),
+        |lc| lc + (scale, vars[first_product + k]),
+      );
+    }
+
+    Ok(vec![out])
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    vec![*self.shape.evaluate(z[0], &self.advice).last().unwrap()]
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, shape: Arc<SyntheticShape>,
+    pp: PublicParams<E1, E2, SyntheticCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice
+    let mut rng = StdRng::seed_from_u64(0);
+    let circuits = (0..num_steps)
+      .map(|_| SyntheticCircuit::new(shape.clone(), &mut rng))
+      .collect::<Vec<_>>();
+
+    let z0_primary = vec![<E1 as Engine>::Scalar::ONE];
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = SyntheticCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn synthetic_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  // witness-heavy, balanced and constraint-heavy shapes
+  let mut rng = StdRng::seed_from_u64(0);
+  for (num_constraints, num_witnesses, density) in [(16, 64, 1), (32, 32, 4), (64, 16, 8)] {
+    let shape = Arc::new(SyntheticShape::new(num_constraints, num_witnesses, density, 0));
+    let circuit = SyntheticCircuit::<G>::new(shape, &mut rng);
+    let z_in = vec![F::from(3)];
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z = AllocatedNum::alloc(cs.namespace(|| "z"), || Ok(z_in[0])).unwrap();
+    let z_out = circuit.synthesize(&mut cs, &[z]).unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(cs.num_constraints(), num_constraints);
+    assert_eq!(vec![z_out[0].get_value().unwrap()], circuit.output(&z_in));
+  }
+}
@@ -0,0 +1,127 @@
+//! Wide state counter, the IO hashing workload: each step increments every element of a state of
+//! configurable arity, `z_{i+1}[k] = z_i[k] + 1`. The step costs a single constraint per element,
+//! so the growth of the per-step cost with the arity is dominated by the hashing of `z_0` and
+//! `z_i` into the public IO of the augmented circuit.
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::Field;
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+pub const MAX_ARITY: usize = 1024;
+
+#[derive(Clone, Debug)]
+pub struct WideStateCircuit<G: Group> {
+  pub arity: usize,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> WideStateCircuit<G> {
+  pub fn new(arity: usize) -> Self {
+    assert!((1..=MAX_ARITY).contains(&arity), "the arity ranges from 1 to {}", MAX_ARITY);
+    Self {
+      arity,
+      _p: PhantomData,
+    }
+  }
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for WideStateCircuit<G> {
+  fn arity(&self) -> usize {
+    self.arity
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    assert_eq!(z.len(), self.arity);
+
+    z.iter()
+      .enumerate()
+      .map(|(k, z)| {
+        let z_next = AllocatedNum::alloc(cs.namespace(|| format!("z_next_{}", k)), || {
+          z.get_value().map(|z| z + G::Scalar::ONE).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+          || format!("z_next_{} = z_{} + 1", k, k),
+          |lc| lc + z.get_variable() + CS::one(),
+          |lc| lc + CS::one(),
+          |lc| lc + z_next.get_variable(),
+        );
+        Ok(z_next)
+      })
+      .collect()
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    z.iter().map(|z| *z + G::Scalar::ONE).collect()
+  }
+}
+
+pub fn nova_ivc(num_steps: usize, arity: usize,
+    pp: PublicParams<E1, E2, WideStateCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    let circuit_primary = WideStateCircuit::new(arity);
+
+    let z0_primary = vec![<E1 as Engine>::Scalar::zero(); arity];
+    let z0_secondary = vec![<E2 as Engine>::Scalar::zero()];
+
+      type C1 = WideStateCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuit_primary,
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for _ in 0..num_steps {
+        let res = recursive_snark.prove_step(&pp, &circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn wide_state_step_circuit_test() {
+  use bellpepper_core::test_cs::TestConstraintSystem;
+
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  for arity in [1, 7, 64] {
+    let circuit = WideStateCircuit::<G>::new(arity);
+    let z_in = (0..arity).map(|k| F::from(k as u64)).collect::<Vec<_>>();
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z = z_in
+      .iter()
+      .enumerate()
+      .map(|(k, z)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", k)), || Ok(*z)))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    let z_out = circuit.synthesize(&mut cs, &z).unwrap();
+
+    assert!(cs.is_satisfied());
+    assert_eq!(cs.num_constraints(), arity);
+    assert_eq!(z_out.iter().map(|z| z.get_value().unwrap()).collect::<Vec<_>>(), circuit.output(&z_in));
+  }
+}
@@ -0,0 +1,1147 @@
+//! Tiny register machine, the zkVM workload: every step fetches the instruction at `pc` from a
+//! program committed as a Poseidon Merkle tree, decodes it and executes it on eight 32-bit
+//! registers and a word-addressed memory, which is also committed as a Merkle tree and opened at
+//! every access.
+//!
+//! The state is `(program_root, pc, r0, ..., r7, memory_root)`. The step is implemented once as
+//! a uniform Nova step circuit, which decodes every opcode and selects the result of the fetched
+//! one, and once as one SuperNova circuit per opcode, which only pays for its own instruction.
+//! A native interpreter runs the same programs for cross-checking.
+use crate::merkle::{compute_root, MerkleTree, MerkleUpdate};
+use bellpepper_core::{
+  boolean::{AllocatedBit, Boolean},
+  num::AllocatedNum,
+  test_cs::TestConstraintSystem,
+  ConstraintSystem, LinearCombination, SynthesisError,
+};
+use ff::{Field, PrimeField};
+use generic_array::typenum::U2;
+use neptune::poseidon::PoseidonConstants;
+use nova_snark::{
+  provider::{Bn256EngineKZG, GrumpkinEngine},
+  supernova::{
+    NonUniformCircuit, PublicParams as SuperNovaPublicParams,
+    RecursiveSNARK as SuperNovaRecursiveSNARK, StepCircuit as SuperNovaStepCircuit,
+    TrivialSecondaryCircuit,
+  },
+  traits::{
+    circuit::{StepCircuit, TrivialCircuit},
+    Engine, Group,
+  },
+  PublicParams, RecursiveSNARK,
+};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+pub const NUM_REGS: usize = 8;
+const REG_BITS: usize = 3;
+pub const WORD_BITS: usize = 32;
+/// Programs hold up to `2^PROGRAM_DEPTH` instructions.
+pub const PROGRAM_DEPTH: usize = 6;
+/// Memory holds `2^MEMORY_DEPTH` words, addresses wrap around.
+pub const MEMORY_DEPTH: usize = 10;
+pub const NUM_OPCODES: usize = 10;
+/// `(program_root, pc, registers, memory_root)`
+pub const STATE_LEN: usize = NUM_REGS + 3;
+
+// an instruction is encoded as `op | rd << 4 | rs1 << 7 | rs2 << 10 | imm << 13`
+const OPCODE_BITS: usize = 4;
+const RD_SHIFT: usize = OPCODE_BITS;
+const RS1_SHIFT: usize = RD_SHIFT + REG_BITS;
+const RS2_SHIFT: usize = RS1_SHIFT + REG_BITS;
+const IMM_SHIFT: usize = RS2_SHIFT + REG_BITS;
+
+/// `Halt` is encoded as zero, so the empty leaves past the end of a program halt the machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opcode {
+  Halt,
+  Add,
+  Sub,
+  Mul,
+  Addi,
+  Slt,
+  Load,
+  Store,
+  Bnz,
+  Jmp,
+}
+
+impl Opcode {
+  pub const ALL: [Opcode; NUM_OPCODES] = [
+    Opcode::Halt,
+    Opcode::Add,
+    Opcode::Sub,
+    Opcode::Mul,
+    Opcode::Addi,
+    Opcode::Slt,
+    Opcode::Load,
+    Opcode::Store,
+    Opcode::Bnz,
+    Opcode::Jmp,
+  ];
+
+  pub fn reads_rs1(self) -> bool {
+    !matches!(self, Opcode::Halt | Opcode::Jmp)
+  }
+
+  pub fn reads_rs2(self) -> bool {
+    matches!(self, Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Slt | Opcode::Store)
+  }
+
+  pub fn writes_rd(self) -> bool {
+    matches!(
+      self,
+      Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Addi | Opcode::Slt | Opcode::Load
+    )
+  }
+
+  pub fn accesses_memory(self) -> bool {
+    matches!(self, Opcode::Load | Opcode::Store)
+  }
+}
+
+/// `imm` is an immediate for `Addi`, an address offset for `Load` and `Store` and a jump target
+/// for `Bnz` and `Jmp`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instruction {
+  pub op: Opcode,
+  pub rd: usize,
+  pub rs1: usize,
+  pub rs2: usize,
+  pub imm: u32,
+}
+
+impl Instruction {
+  fn new(op: Opcode, rd: usize, rs1: usize, rs2: usize, imm: u32) -> Self {
+    assert!(rd < NUM_REGS && rs1 < NUM_REGS && rs2 < NUM_REGS);
+    Self { op, rd, rs1, rs2, imm }
+  }
+
+  pub fn halt() -> Self {
+    Self::new(Opcode::Halt, 0, 0, 0, 0)
+  }
+
+  pub fn add(rd: usize, rs1: usize, rs2: usize) -> Self {
+    Self::new(Opcode::Add, rd, rs1, rs2, 0)
+  }
+
+  pub fn sub(rd: usize, rs1: usize, rs2: usize) -> Self {
+    Self::new(Opcode::Sub, rd, rs1, rs2, 0)
+  }
+
+  pub fn mul(rd: usize, rs1: usize, rs2: usize) -> Self {
+    Self::new(Opcode::Mul, rd, rs1, rs2, 0)
+  }
+
+  pub fn addi(rd: usize, rs1: usize, imm: i32) -> Self {
+    Self::new(Opcode::Addi, rd, rs1, 0, imm as u32)
+  }
+
+  // rd = rs1 < rs2, as unsigned words
+  pub fn slt(rd: usize, rs1: usize, rs2: usize) -> Self {
+    Self::new(Opcode::Slt, rd, rs1, rs2, 0)
+  }
+
+  // rd = mem[rs1 + offset]
+  pub fn load(rd: usize, rs1: usize, offset: u32) -> Self {
+    Self::new(Opcode::Load, rd, rs1, 0, offset)
+  }
+
+  // mem[rs1 + offset] = rs2
+  pub fn store(rs2: usize, rs1: usize, offset: u32) -> Self {
+    Self::new(Opcode::Store, 0, rs1, rs2, offset)
+  }
+
+  // jumps to `target` if rs1 != 0
+  pub fn bnz(rs1: usize, target: u32) -> Self {
+    Self::new(Opcode::Bnz, 0, rs1, 0, target)
+  }
+
+  pub fn jmp(target: u32) -> Self {
+    Self::new(Opcode::Jmp, 0, 0, 0, target)
+  }
+
+  pub fn encode(&self) -> u64 {
+    (self.op as u64)
+      | ((self.rd as u64) << RD_SHIFT)
+      | ((self.rs1 as u64) << RS1_SHIFT)
+      | ((self.rs2 as u64) << RS2_SHIFT)
+      | ((self.imm as u64) << IMM_SHIFT)
+  }
+
+  pub fn next_pc(&self, pc: u32, regs: &[u32]) -> u32 {
+    match self.op {
+      Opcode::Halt => pc,
+      Opcode::Jmp => self.imm,
+      Opcode::Bnz if regs[self.rs1] != 0 => self.imm,
+      _ => pc + 1,
+    }
+  }
+}
+
+fn address(base: u32, offset: u32) -> usize {
+  base.wrapping_add(offset) as usize & ((1 << MEMORY_DEPTH) - 1)
+}
+
+/// A program together with its inputs, the initial registers and memory.
+#[derive(Clone, Debug)]
+pub struct Program {
+  pub instructions: Vec<Instruction>,
+  pub registers: [u32; NUM_REGS],
+  pub memory: Vec<u32>,
+}
+
+impl Program {
+  /// Stores the `n`-th Fibonacci number at address 0.
+  pub fn fibonacci(n: u32) -> Self {
+    let instructions = vec![
+      Instruction::addi(2, 0, 0),
+      Instruction::addi(3, 0, 1),
+      Instruction::bnz(1, 5),
+      Instruction::store(2, 0, 0),
+      Instruction::halt(),
+      // loop body, (r2, r3) = (r3, r2 + r3)
+      Instruction::add(4, 2, 3),
+      Instruction::addi(2, 3, 0),
+      Instruction::addi(3, 4, 0),
+      Instruction::addi(1, 1, -1),
+      Instruction::jmp(2),
+    ];
+    let mut registers = [0; NUM_REGS];
+    registers[1] = n;
+
+    Self {
+      instructions,
+      registers,
+      memory: Vec::new(),
+    }
+  }
+
+  /// Sorts `values`, which are laid out from address 0, in place.
+  pub fn bubble_sort(values: &[u32]) -> Self {
+    assert!(!values.is_empty());
+    let instructions = vec![
+      // r2 = i, the end of the unsorted prefix
+      Instruction::addi(2, 1, -1),
+      Instruction::bnz(2, 3),
+      Instruction::halt(),
+      // r3 = j, the inner loop runs while j < i
+      Instruction::addi(3, 0, 0),
+      Instruction::slt(4, 3, 2),
+      Instruction::bnz(4, 8),
+      Instruction::addi(2, 2, -1),
+      Instruction::jmp(1),
+      Instruction::load(5, 3, 0),
+      Instruction::load(6, 3, 1),
+      Instruction::slt(7, 6, 5),
+      Instruction::bnz(7, 14),
+      Instruction::addi(3, 3, 1),
+      Instruction::jmp(4),
+      // swap mem[j] and mem[j + 1]
+      Instruction::store(6, 3, 0),
+      Instruction::store(5, 3, 1),
+      Instruction::jmp(12),
+    ];
+    let mut registers = [0; NUM_REGS];
+    registers[1] = values.len() as u32;
+
+    Self {
+      instructions,
+      registers,
+      memory: values.to_vec(),
+    }
+  }
+}
+
+/// Native interpreter, the reference the circuits are checked against.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Machine {
+  pub pc: u32,
+  pub regs: [u32; NUM_REGS],
+  pub memory: Vec<u32>,
+}
+
+impl Machine {
+  pub fn new(program: &Program) -> Self {
+    assert!(program.instructions.len() <= 1 << PROGRAM_DEPTH);
+    assert!(program.memory.len() <= 1 << MEMORY_DEPTH);
+    let mut memory = program.memory.clone();
+    memory.resize(1 << MEMORY_DEPTH, 0);
+
+    Self {
+      pc: 0,
+      regs: program.registers,
+      memory,
+    }
+  }
+
+  pub fn fetch(&self, program: &Program) -> Instruction {
+    program
+      .instructions
+      .get(self.pc as usize)
+      .copied()
+      .unwrap_or_else(Instruction::halt)
+  }
+
+  /// Executes one instruction and returns it, a halted machine stays in place.
+  pub fn step(&mut self, program: &Program) -> Instruction {
+    let ins = self.fetch(program);
+    let (a, b) = (self.regs[ins.rs1], self.regs[ins.rs2]);
+    let result = match ins.op {
+      Opcode::Add => Some(a.wrapping_add(b)),
+      Opcode::Sub => Some(a.wrapping_sub(b)),
+      Opcode::Mul => Some(a.wrapping_mul(b)),
+      Opcode::Addi => Some(a.wrapping_add(ins.imm)),
+      Opcode::Slt => Some((a < b) as u32),
+      Opcode::Load => Some(self.memory[address(a, ins.imm)]),
+      Opcode::Store => {
+        self.memory[address(a, ins.imm)] = b;
+        None
+      }
+      Opcode::Halt | Opcode::Bnz | Opcode::Jmp => None,
+    };
+    self.pc = ins.next_pc(self.pc, &self.regs);
+    if let Some(result) = result {
+      self.regs[ins.rd] = result;
+    }
+    ins
+  }
+
+  pub fn halted(&self, program: &Program) -> bool {
+    self.fetch(program).op == Opcode::Halt
+  }
+
+  /// Runs `program` to completion, returning the final machine and the number of steps taken,
+  /// including the final `Halt`.
+  pub fn run(program: &Program, max_steps: usize) -> (Self, usize) {
+    let mut machine = Self::new(program);
+    for num_steps in 1..=max_steps {
+      if machine.step(program).op == Opcode::Halt {
+        return (machine, num_steps);
+      }
+    }
+    panic!("program did not halt within {} steps", max_steps);
+  }
+}
+
+/// Non-deterministic advice of a single step: the machine before the step, the fetched
+/// instruction with its authentication path, and the memory access. Steps that don't write to
+/// memory rewrite the accessed word with itself.
+#[derive(Clone, Debug)]
+pub struct StepAdvice<F: PrimeField> {
+  pub pc: u32,
+  pub regs: [u32; NUM_REGS],
+  pub instruction: Instruction,
+  pub program_siblings: Vec<F>,
+  pub memory: MerkleUpdate<F>,
+}
+
+impl<F: PrimeField> StepAdvice<F> {
+  // advice with the right shape, used to set up the circuit of `op`
+  fn dummy(op: Opcode) -> Self {
+    Self {
+      pc: 0,
+      regs: [0; NUM_REGS],
+      instruction: Instruction::new(op, 0, 0, 0, 0),
+      program_siblings: vec![F::ZERO; PROGRAM_DEPTH],
+      memory: MerkleUpdate {
+        index: 0,
+        old_leaf: F::ZERO,
+        new_leaf: F::ZERO,
+        siblings: vec![F::ZERO; MEMORY_DEPTH],
+      },
+    }
+  }
+}
+
+/// States and advice of `num_steps` steps of a program.
+#[derive(Clone, Debug)]
+pub struct Execution<F: PrimeField> {
+  pub states: Vec<Vec<F>>,
+  pub steps: Vec<StepAdvice<F>>,
+}
+
+fn state<F: PrimeField>(program_root: F, machine: &Machine, memory_root: F) -> Vec<F> {
+  let mut z = vec![program_root, F::from(machine.pc as u64)];
+  z.extend(machine.regs.iter().map(|r| F::from(*r as u64)));
+  z.push(memory_root);
+  z
+}
+
+pub fn execute<F: PrimeField>(program: &Program, num_steps: usize) -> Execution<F> {
+  let mut program_tree = MerkleTree::<F>::new(PROGRAM_DEPTH);
+  for (i, ins) in program.instructions.iter().enumerate() {
+    program_tree.update(i as u64, F::from(ins.encode()));
+  }
+  let mut machine = Machine::new(program);
+  let mut memory_tree = MerkleTree::<F>::new(MEMORY_DEPTH);
+  for (i, word) in machine.memory.iter().enumerate().filter(|(_, word)| **word != 0) {
+    memory_tree.update(i as u64, F::from(*word as u64));
+  }
+
+  let mut states = vec![state(program_tree.root(), &machine, memory_tree.root())];
+  let mut steps = Vec::with_capacity(num_steps);
+  for _ in 0..num_steps {
+    let ins = machine.fetch(program);
+    let index = address(machine.regs[ins.rs1], ins.imm);
+    let pc = machine.pc;
+    let regs = machine.regs;
+    machine.step(program);
+
+    steps.push(StepAdvice {
+      pc,
+      regs,
+      instruction: ins,
+      program_siblings: program_tree.siblings(pc as u64),
+      memory: memory_tree.update(index as u64, F::from(machine.memory[index] as u64)),
+    });
+    states.push(state(program_tree.root(), &machine, memory_tree.root()));
+  }
+
+  Execution { states, steps }
+}
+
+fn alloc_bits<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  value: u64,
+  num_bits: usize,
+) -> Result<Vec<AllocatedBit>, SynthesisError> {
+  (0..num_bits)
+    .map(|k| AllocatedBit::alloc(cs.namespace(|| format!("bit_{}", k)), Some((value >> k) & 1 == 1)))
+    .collect()
+}
+
+// lc + sum_k bits[k] * 2^(shift + k)
+fn pack<F: PrimeField>(lc: LinearCombination<F>, bits: &[AllocatedBit], shift: usize) -> LinearCombination<F> {
+  bits
+    .iter()
+    .enumerate()
+    .fold(lc, |lc, (k, bit)| lc + (F::from(1u64 << (shift + k)), bit.get_variable()))
+}
+
+// the little-endian bits of `value`, which `lc` must equal
+fn decompose<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  lc: LinearCombination<F>,
+  value: u64,
+  num_bits: usize,
+) -> Result<Vec<AllocatedBit>, SynthesisError> {
+  let bits = alloc_bits(cs.namespace(|| "bits"), value, num_bits)?;
+  cs.enforce(
+    || "decomposition",
+    |_| lc,
+    |lc| lc + CS::one(),
+    |lc| pack(lc, &bits, 0),
+  );
+  Ok(bits)
+}
+
+// the word made of the lowest WORD_BITS bits
+fn pack_word<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  bits: &[AllocatedBit],
+  value: u32,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+  let word = AllocatedNum::alloc(cs.namespace(|| "word"), || Ok(F::from(value as u64)))?;
+  cs.enforce(
+    || "word = bits",
+    |lc| pack(lc, &bits[..WORD_BITS], 0),
+    |lc| lc + CS::one(),
+    |lc| lc + word.get_variable(),
+  );
+  Ok(word)
+}
+
+// returns `bit ? b : a`
+fn select<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  a: &AllocatedNum<F>,
+  b: &AllocatedNum<F>,
+  bit: &AllocatedBit,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+  let r = AllocatedNum::alloc(cs.namespace(|| "r"), || {
+    let chosen = if bit.get_value().ok_or(SynthesisError::AssignmentMissing)? { b } else { a };
+    chosen.get_value().ok_or(SynthesisError::AssignmentMissing)
+  })?;
+  cs.enforce(
+    || "(b - a) * bit = r - a",
+    |lc| lc + b.get_variable() - a.get_variable(),
+    |lc| lc + bit.get_variable(),
+    |lc| lc + r.get_variable() - a.get_variable(),
+  );
+  Ok(r)
+}
+
+// returns `regs[index]`, with a tree of selections over the little-endian index bits
+fn read_register<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  regs: &[AllocatedNum<F>],
+  index_bits: &[AllocatedBit],
+) -> Result<AllocatedNum<F>, SynthesisError> {
+  let mut values = regs.to_vec();
+  for (level, bit) in index_bits.iter().enumerate() {
+    values = values
+      .chunks(2)
+      .enumerate()
+      .map(|(i, pair)| select(cs.namespace(|| format!("select_{}_{}", level, i)), &pair[0], &pair[1], bit))
+      .collect::<Result<Vec<_>, _>>()?;
+  }
+  Ok(values.remove(0))
+}
+
+// sets `regs[index] = value` if `enable` holds
+fn write_register<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  regs: &[AllocatedNum<F>],
+  index_bits: &[AllocatedBit],
+  enable: &Boolean,
+  value: &AllocatedNum<F>,
+) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+  let one = CS::one();
+  let bits = index_bits.iter().cloned().map(Boolean::from).collect::<Vec<_>>();
+  // the register is selected by one of the four products of the two low bits, and by the high
+  // bit multiplied by the enable flag
+  let both = Boolean::and(cs.namespace(|| "b0 & b1"), &bits[0], &bits[1])?;
+  let high = Boolean::and(cs.namespace(|| "enable & b2"), enable, &bits[2])?;
+  let low_lc = |i: usize| match i & 3 {
+    0 => LinearCombination::zero() + one - &bits[0].lc(one, F::ONE) - &bits[1].lc(one, F::ONE) + &both.lc(one, F::ONE),
+    1 => bits[0].lc(one, F::ONE) - &both.lc(one, F::ONE),
+    2 => bits[1].lc(one, F::ONE) - &both.lc(one, F::ONE),
+    _ => both.lc(one, F::ONE),
+  };
+  let high_lc = |i: usize| {
+    if i >> 2 == 1 {
+      high.lc(one, F::ONE)
+    } else {
+      enable.lc(one, F::ONE) - &high.lc(one, F::ONE)
+    }
+  };
+  let index = bits
+    .iter()
+    .rev()
+    .try_fold(0, |acc, bit| bit.get_value().map(|bit| 2 * acc + bit as usize));
+
+  let mut new_regs = Vec::with_capacity(regs.len());
+  for (i, reg) in regs.iter().enumerate() {
+    let mut cs = cs.namespace(|| format!("r{}", i));
+    let selected = index.zip(enable.get_value()).map(|(index, enable)| enable && index == i);
+    let is_selected = AllocatedNum::alloc(cs.namespace(|| "selected"), || {
+      Ok(if selected.ok_or(SynthesisError::AssignmentMissing)? { F::ONE } else { F::ZERO })
+    })?;
+    cs.enforce(
+      || "selected = low * high",
+      |_| low_lc(i),
+      |_| high_lc(i),
+      |lc| lc + is_selected.get_variable(),
+    );
+    let new_reg = AllocatedNum::alloc(cs.namespace(|| "new"), || {
+      let chosen = if selected.ok_or(SynthesisError::AssignmentMissing)? { value } else { reg };
+      chosen.get_value().ok_or(SynthesisError::AssignmentMissing)
+    })?;
+    cs.enforce(
+      || "selected * (value - reg) = new - reg",
+      |lc| lc + is_selected.get_variable(),
+      |lc| lc + value.get_variable() - reg.get_variable(),
+      |lc| lc + new_reg.get_variable() - reg.get_variable(),
+    );
+    new_regs.push(new_reg);
+  }
+  Ok(new_regs)
+}
+
+// returns `flag * x`
+fn gate<F: PrimeField, CS: ConstraintSystem<F>>(
+  mut cs: CS,
+  flag: &Boolean,
+  x: &AllocatedNum<F>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+  let r = AllocatedNum::alloc(cs.namespace(|| "r"), || {
+    let flag = flag.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+    Ok(if flag { x.get_value().ok_or(SynthesisError::AssignmentMissing)? } else { F::ZERO })
+  })?;
+  cs.enforce(
+    || "flag * x = r",
+    |_| flag.lc(CS::one(), F::ONE),
+    |lc| lc + x.get_variable(),
+    |lc| lc + r.get_variable(),
+  );
+  Ok(r)
+}
+
+/// Synthesizes a step of the machine. With `op` set, the step only supports that opcode and its
+/// flags are constants, otherwise it decodes the fetched instruction and executes every opcode.
+fn synthesize_step<F: PrimeField, CS: ConstraintSystem<F>>(
+  cs: &mut CS,
+  z: &[AllocatedNum<F>],
+  advice: &StepAdvice<F>,
+  op: Option<Opcode>,
+) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+  // z provides (program_root, pc, registers, memory_root)
+  assert_eq!(z.len(), STATE_LEN);
+  let (program_root, pc, regs, memory_root) = (&z[0], &z[1], &z[2..2 + NUM_REGS], &z[STATE_LEN - 1]);
+  let one = CS::one();
+  let ins = advice.instruction;
+  let has = |other: Opcode| op.map_or(true, |op| op == other);
+  let any = |f: fn(Opcode) -> bool| op.map_or(true, f);
+  let constants = PoseidonConstants::<F, U2>::new();
+
+  // one-hot opcode flags
+  let flags = match op {
+    Some(op) => Opcode::ALL.iter().map(|other| Boolean::Constant(*other == op)).collect::<Vec<_>>(),
+    None => {
+      let flags = Opcode::ALL
+        .iter()
+        .map(|other| {
+          AllocatedBit::alloc(cs.namespace(|| format!("is_{:?}", other)), Some(*other == ins.op)).map(Boolean::from)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+      cs.enforce(
+        || "one opcode",
+        |lc| flags.iter().fold(lc, |lc, flag| lc + &flag.lc(one, F::ONE)),
+        |lc| lc + one,
+        |lc| lc + one,
+      );
+      flags
+    }
+  };
+  let flag = |other: Opcode| &flags[other as usize];
+
+  // fetch the instruction at pc, its decomposition also range checks the fields
+  let pc_bits = decompose(
+    cs.namespace(|| "pc"),
+    LinearCombination::zero() + pc.get_variable(),
+    advice.pc as u64,
+    PROGRAM_DEPTH,
+  )?;
+  let rd_bits = alloc_bits(cs.namespace(|| "rd"), ins.rd as u64, REG_BITS)?;
+  let rs1_bits = alloc_bits(cs.namespace(|| "rs1"), ins.rs1 as u64, REG_BITS)?;
+  let rs2_bits = alloc_bits(cs.namespace(|| "rs2"), ins.rs2 as u64, REG_BITS)?;
+  let imm_bits = alloc_bits(cs.namespace(|| "imm"), ins.imm as u64, WORD_BITS)?;
+  let instruction = AllocatedNum::alloc(cs.namespace(|| "instruction"), || Ok(F::from(ins.encode())))?;
+  cs.enforce(
+    || "instruction encoding",
+    |lc| {
+      let lc = Opcode::ALL
+        .iter()
+        .fold(lc, |lc, other| lc + &flag(*other).lc(one, F::from(*other as u64)));
+      let lc = pack(lc, &rd_bits, RD_SHIFT);
+      let lc = pack(lc, &rs1_bits, RS1_SHIFT);
+      let lc = pack(lc, &rs2_bits, RS2_SHIFT);
+      pack(lc, &imm_bits, IMM_SHIFT)
+    },
+    |lc| lc + one,
+    |lc| lc + instruction.get_variable(),
+  );
+  let program_siblings = advice
+    .program_siblings
+    .iter()
+    .enumerate()
+    .map(|(level, sibling)| AllocatedNum::alloc(cs.namespace(|| format!("program_sibling_{}", level)), || Ok(*sibling)))
+    .collect::<Result<Vec<_>, _>>()?;
+  let fetched_root = compute_root(
+    cs.namespace(|| "fetch"),
+    &instruction,
+    &pc_bits.iter().cloned().map(Boolean::from).collect::<Vec<_>>(),
+    &program_siblings,
+    &constants,
+  )?;
+  cs.enforce(
+    || "fetched_root = program_root",
+    |lc| lc + fetched_root.get_variable(),
+    |lc| lc + one,
+    |lc| lc + program_root.get_variable(),
+  );
+
+  let (a_value, b_value) = (advice.regs[ins.rs1] as u64, advice.regs[ins.rs2] as u64);
+  let a = any(Opcode::reads_rs1)
+    .then(|| read_register(cs.namespace(|| "rs1 value"), regs, &rs1_bits))
+    .transpose()?;
+  let b = any(Opcode::reads_rs2)
+    .then(|| read_register(cs.namespace(|| "rs2 value"), regs, &rs2_bits))
+    .transpose()?;
+
+  // words are reduced by decomposing the full result and keeping its low bits
+  let mut results = Vec::new();
+  if has(Opcode::Add) {
+    let (a, b) = (a.as_ref().unwrap(), b.as_ref().unwrap());
+    let bits = decompose(
+      cs.namespace(|| "a + b"),
+      LinearCombination::zero() + a.get_variable() + b.get_variable(),
+      a_value + b_value,
+      WORD_BITS + 1,
+    )?;
+    let sum = pack_word(cs.namespace(|| "add"), &bits, (a_value + b_value) as u32)?;
+    results.push((Opcode::Add, sum));
+  }
+  if has(Opcode::Sub) || has(Opcode::Slt) {
+    // the top bit of a - b + 2^32 is set iff a >= b
+    let (a, b) = (a.as_ref().unwrap(), b.as_ref().unwrap());
+    let shifted = a_value + (1 << WORD_BITS) - b_value;
+    let bits = decompose(
+      cs.namespace(|| "a - b"),
+      LinearCombination::zero() + a.get_variable() - b.get_variable() + (F::from(1u64 << WORD_BITS), one),
+      shifted,
+      WORD_BITS + 1,
+    )?;
+    if has(Opcode::Sub) {
+      results.push((Opcode::Sub, pack_word(cs.namespace(|| "sub"), &bits, shifted as u32)?));
+    }
+    if has(Opcode::Slt) {
+      let lt = AllocatedNum::alloc(cs.namespace(|| "slt"), || Ok(F::from((a_value < b_value) as u64)))?;
+      cs.enforce(
+        || "lt = 1 - top bit",
+        |lc| lc + one - bits[WORD_BITS].get_variable(),
+        |lc| lc + one,
+        |lc| lc + lt.get_variable(),
+      );
+      results.push((Opcode::Slt, lt));
+    }
+  }
+  if has(Opcode::Mul) {
+    let (a, b) = (a.as_ref().unwrap(), b.as_ref().unwrap());
+    let product = a.mul(cs.namespace(|| "a * b"), b)?;
+    let bits = decompose(
+      cs.namespace(|| "product"),
+      LinearCombination::zero() + product.get_variable(),
+      a_value * b_value,
+      2 * WORD_BITS,
+    )?;
+    results.push((Opcode::Mul, pack_word(cs.namespace(|| "mul"), &bits, (a_value * b_value) as u32)?));
+  }
+
+  // a + imm is both the result of Addi and the address of Load and Store
+  let mut memory_root = memory_root.clone();
+  if has(Opcode::Addi) || any(Opcode::accesses_memory) {
+    let a = a.as_ref().unwrap();
+    let sum = a_value + ins.imm as u64;
+    let bits = decompose(
+      cs.namespace(|| "a + imm"),
+      pack(LinearCombination::zero() + a.get_variable(), &imm_bits, 0),
+      sum,
+      WORD_BITS + 1,
+    )?;
+    if has(Opcode::Addi) {
+      results.push((Opcode::Addi, pack_word(cs.namespace(|| "addi"), &bits, sum as u32)?));
+    }
+
+    if any(Opcode::accesses_memory) {
+      let mut cs = cs.namespace(|| "memory");
+      let address_bits = bits[..MEMORY_DEPTH].iter().cloned().map(Boolean::from).collect::<Vec<_>>();
+      let siblings = advice
+        .memory
+        .siblings
+        .iter()
+        .enumerate()
+        .map(|(level, sibling)| AllocatedNum::alloc(cs.namespace(|| format!("sibling_{}", level)), || Ok(*sibling)))
+        .collect::<Result<Vec<_>, _>>()?;
+      let old_leaf = AllocatedNum::alloc(cs.namespace(|| "old_leaf"), || Ok(advice.memory.old_leaf))?;
+      let old_root = compute_root(cs.namespace(|| "old path"), &old_leaf, &address_bits, &siblings, &constants)?;
+      cs.enforce(
+        || "old_root = memory_root",
+        |lc| lc + old_root.get_variable(),
+        |lc| lc + one,
+        |lc| lc + memory_root.get_variable(),
+      );
+
+      if has(Opcode::Store) {
+        let b = b.as_ref().unwrap();
+        let new_leaf = AllocatedNum::alloc(cs.namespace(|| "new_leaf"), || Ok(advice.memory.new_leaf))?;
+        cs.enforce(
+          || "is_store * (b - old_leaf) = new_leaf - old_leaf",
+          |_| flag(Opcode::Store).lc(one, F::ONE),
+          |lc| lc + b.get_variable() - old_leaf.get_variable(),
+          |lc| lc + new_leaf.get_variable() - old_leaf.get_variable(),
+        );
+        memory_root = compute_root(cs.namespace(|| "new path"), &new_leaf, &address_bits, &siblings, &constants)?;
+      }
+      if has(Opcode::Load) {
+        results.push((Opcode::Load, old_leaf));
+      }
+    }
+  }
+
+  // write back the result of the executed opcode
+  let new_regs = if results.is_empty() {
+    regs.to_vec()
+  } else {
+    let enable = match op {
+      Some(op) => Boolean::Constant(op.writes_rd()),
+      None => {
+        let enable = AllocatedBit::alloc(cs.namespace(|| "write enable"), Some(ins.op.writes_rd()))?;
+        cs.enforce(
+          || "write enable = sum of writing flags",
+          |lc| results.iter().fold(lc, |lc, (other, _)| lc + &flag(*other).lc(one, F::ONE)),
+          |lc| lc + one,
+          |lc| lc + enable.get_variable(),
+        );
+        Boolean::from(enable)
+      }
+    };
+    let gated = results
+      .iter()
+      .map(|(other, result)| gate(cs.namespace(|| format!("gate {:?}", other)), flag(*other), result))
+      .collect::<Result<Vec<_>, _>>()?;
+    let value = AllocatedNum::alloc(cs.namespace(|| "rd value"), || {
+      gated.iter().try_fold(F::ZERO, |acc, x| x.get_value().map(|x| acc + x)).ok_or(SynthesisError::AssignmentMissing)
+    })?;
+    cs.enforce(
+      || "value = sum of gated results",
+      |lc| gated.iter().fold(lc, |lc, x| lc + x.get_variable()),
+      |lc| lc + one,
+      |lc| lc + value.get_variable(),
+    );
+    write_register(cs.namespace(|| "write"), regs, &rd_bits, &enable, &value)?
+  };
+
+  // next_pc - pc - 1 + is_halt = take * (imm - pc - 1), where a jump is taken by Jmp and by Bnz
+  // on a non-zero register
+  let mut take = flag(Opcode::Jmp).lc(one, F::ONE);
+  if has(Opcode::Bnz) {
+    let a = a.as_ref().unwrap();
+    let a_value = F::from(a_value);
+    let inv = AllocatedNum::alloc(cs.namespace(|| "a_inv"), || Ok(a_value.invert().unwrap_or(F::ZERO)))?;
+    let non_zero = AllocatedNum::alloc(cs.namespace(|| "a != 0"), || Ok(F::from(!bool::from(a_value.is_zero()) as u64)))?;
+    cs.enforce(
+      || "a * a_inv = non_zero",
+      |lc| lc + a.get_variable(),
+      |lc| lc + inv.get_variable(),
+      |lc| lc + non_zero.get_variable(),
+    );
+    cs.enforce(
+      || "a * (1 - non_zero) = 0",
+      |lc| lc + a.get_variable(),
+      |lc| lc + one - non_zero.get_variable(),
+      |lc| lc,
+    );
+    let taken = gate(cs.namespace(|| "branch taken"), flag(Opcode::Bnz), &non_zero)?;
+    take = take + taken.get_variable();
+  }
+  let next_pc = AllocatedNum::alloc(cs.namespace(|| "next_pc"), || {
+    Ok(F::from(ins.next_pc(advice.pc, &advice.regs) as u64))
+  })?;
+  cs.enforce(
+    || "next_pc",
+    |_| take,
+    |lc| pack(lc, &imm_bits, 0) - pc.get_variable() - one,
+    |lc| lc + next_pc.get_variable() - pc.get_variable() - one + &flag(Opcode::Halt).lc(one, F::ONE),
+  );
+
+  let mut z_next = vec![program_root.clone(), next_pc];
+  z_next.extend(new_regs);
+  z_next.push(memory_root);
+  Ok(z_next)
+}
+
+/// Uniform step circuit, decoding and executing every opcode.
+#[derive(Clone, Debug)]
+pub struct ZkvmCircuit<G: Group> {
+  pub advice: StepAdvice<G::Scalar>,
+  pub z: Vec<G::Scalar>,
+  pub z_next: Vec<G::Scalar>,
+}
+
+impl<G: Group> StepCircuit<G::Scalar> for ZkvmCircuit<G> {
+  fn arity(&self) -> usize {
+    STATE_LEN
+  }
+
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<Vec<AllocatedNum<G::Scalar>>, SynthesisError> {
+    synthesize_step(cs, z, &self.advice, None)
+  }
+
+  fn output(&self, z: &[G::Scalar]) -> Vec<G::Scalar> {
+    // sanity check
+    debug_assert_eq!(z, self.z);
+
+    self.z_next.clone()
+  }
+}
+
+/// Step circuit of a single opcode, folded by SuperNova into the running instance of `op`.
+#[derive(Clone, Debug)]
+pub struct OpcodeCircuit<G: Group> {
+  pub op: Opcode,
+  // advice for the program counter of the following step
+  pub next_op: Opcode,
+  pub advice: StepAdvice<G::Scalar>,
+  _p: PhantomData<G>,
+}
+
+impl<G: Group> OpcodeCircuit<G> {
+  pub fn new(next_op: Opcode, advice: StepAdvice<G::Scalar>) -> Self {
+    Self {
+      op: advice.instruction.op,
+      next_op,
+      advice,
+      _p: PhantomData,
+    }
+  }
+}
+
+impl<G: Group> SuperNovaStepCircuit<G::Scalar> for OpcodeCircuit<G> {
+  fn arity(&self) -> usize {
+    STATE_LEN
+  }
+
+  fn circuit_index(&self) -> usize {
+    self.op as usize
+  }
+
+  #[allow(clippy::type_complexity)]
+  fn synthesize<CS: ConstraintSystem<G::Scalar>>(
+    &self,
+    cs: &mut CS,
+    pc: Option<&AllocatedNum<G::Scalar>>,
+    z: &[AllocatedNum<G::Scalar>],
+  ) -> Result<(Option<AllocatedNum<G::Scalar>>, Vec<AllocatedNum<G::Scalar>>), SynthesisError> {
+    // the program counter selected this circuit, so it must point to this opcode
+    if let Some(pc) = pc {
+      cs.enforce(
+        || "pc = op",
+        |lc| lc + pc.get_variable(),
+        |lc| lc + CS::one(),
+        |lc| lc + (G::Scalar::from(self.op as u64), CS::one()),
+      );
+    }
+
+    let z_next = synthesize_step(cs, z, &self.advice, Some(self.op))?;
+
+    // the next step fetches its instruction with a constant opcode, so this is pure advice
+    let pc_next = AllocatedNum::alloc(cs.namespace(|| "pc_next"), || {
+      Ok(G::Scalar::from(self.next_op as u64))
+    })?;
+
+    Ok((Some(pc_next), z_next))
+  }
+}
+
+impl<F: PrimeField> Execution<F> {
+  pub fn uniform_circuits<G: Group<Scalar = F>>(&self) -> Vec<ZkvmCircuit<G>> {
+    self
+      .steps
+      .iter()
+      .enumerate()
+      .map(|(i, advice)| ZkvmCircuit {
+        advice: advice.clone(),
+        z: self.states[i].clone(),
+        z_next: self.states[i + 1].clone(),
+      })
+      .collect()
+  }
+
+  pub fn opcode_circuits<G: Group<Scalar = F>>(&self) -> Vec<OpcodeCircuit<G>> {
+    // the pc after the last step is unused, it stays on the last opcode
+    (0..self.steps.len())
+      .map(|i| {
+        let next_op = self.steps.get(i + 1).unwrap_or(&self.steps[i]).instruction.op;
+        OpcodeCircuit::new(next_op, self.steps[i].clone())
+      })
+      .collect()
+  }
+}
+
+type C1 = OpcodeCircuit<<E1 as Engine>::GE>;
+type C2 = TrivialSecondaryCircuit<<E2 as Engine>::Scalar>;
+
+impl NonUniformCircuit<E1, E2, C1, C2> for Execution<<E1 as Engine>::Scalar> {
+  fn num_circuits(&self) -> usize {
+    NUM_OPCODES
+  }
+
+  fn primary_circuit(&self, circuit_index: usize) -> C1 {
+    let op = Opcode::ALL[circuit_index];
+    OpcodeCircuit::new(op, StepAdvice::dummy(op))
+  }
+
+  fn secondary_circuit(&self) -> C2 {
+    Default::default()
+  }
+
+  fn initial_circuit_index(&self) -> usize {
+    self.steps[0].instruction.op as usize
+  }
+}
+
+/// Number of constraints of a step circuit alone, without the augmented verifier circuit.
+fn num_step_constraints<F: PrimeField>(
+  synthesize: impl FnOnce(&mut TestConstraintSystem<F>, &[AllocatedNum<F>]) -> Result<(), SynthesisError>,
+) -> usize {
+  let mut cs = TestConstraintSystem::<F>::new();
+  let z = (0..STATE_LEN)
+    .map(|i| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", i)), || Ok(F::ZERO)))
+    .collect::<Result<Vec<_>, _>>()
+    .unwrap();
+  synthesize(&mut cs, &z).unwrap();
+  cs.num_constraints()
+}
+
+pub fn opcode_num_constraints(op: Opcode) -> usize {
+  let circuit = OpcodeCircuit::<<E1 as Engine>::GE>::new(op, StepAdvice::dummy(op));
+  num_step_constraints(|cs, z| circuit.synthesize(cs, None, z).map(|_| ()))
+}
+
+pub fn uniform_num_constraints() -> usize {
+  let advice = StepAdvice::dummy(Opcode::Halt);
+  num_step_constraints(|cs, z| synthesize_step(cs, z, &advice, None).map(|_| ()))
+}
+
+pub fn supernova_ivc(program: &Program, num_steps: usize, pp: &SuperNovaPublicParams<E1, E2, C1, C2>) -> Duration {
+  let start = Instant::now();
+  // produce non-deterministic advice by running the interpreter along the committed trees
+  let execution = execute::<<E1 as Engine>::Scalar>(program, num_steps);
+  let circuits = execution.opcode_circuits::<<E1 as Engine>::GE>();
+  let circuit_secondary = C2::default();
+
+  let z0_primary = execution.states[0].clone();
+  let z0_secondary = vec![<E2 as Engine>::Scalar::ZERO];
+
+  // produce a recursive SNARK
+  let mut recursive_snark = SuperNovaRecursiveSNARK::<E1, E2>::new(
+    pp,
+    &execution,
+    &circuits[0],
+    &circuit_secondary,
+    &z0_primary,
+    &z0_secondary,
+  )
+  .unwrap();
+
+  for circuit_primary in circuits.iter() {
+    let res = recursive_snark.prove_step(pp, circuit_primary, &circuit_secondary);
+    assert!(res.is_ok());
+  }
+  start.elapsed()
+}
+
+pub fn nova_ivc(program: &Program, num_steps: usize,
+    pp: PublicParams<E1, E2, ZkvmCircuit<<E1 as Engine>::GE>, TrivialCircuit<<E2 as Engine>::Scalar>>,
+    circuit_secondary: TrivialCircuit<<E2 as Engine>::Scalar>
+  ) -> Duration {
+
+    let start = Instant::now();
+    // produce non-deterministic advice by running the interpreter along the committed trees
+    let execution = execute::<<E1 as Engine>::Scalar>(program, num_steps);
+    let circuits = execution.uniform_circuits::<<E1 as Engine>::GE>();
+
+    let z0_primary = execution.states[0].clone();
+    let z0_secondary = vec![<E2 as Engine>::Scalar::ZERO];
+
+      type C1 = ZkvmCircuit<<E1 as Engine>::GE>;
+      type C2 = TrivialCircuit<<E2 as Engine>::Scalar>;
+      // produce a recursive SNARK
+      let mut recursive_snark: RecursiveSNARK<E1, E2, C1, C2> =
+        RecursiveSNARK::<E1, E2, C1, C2>::new(
+          &pp,
+          &circuits[0],
+          &circuit_secondary,
+          &z0_primary,
+          &z0_secondary,
+        )
+        .unwrap();
+
+      for circuit_primary in circuits.iter() {
+        let res = recursive_snark.prove_step(&pp, circuit_primary, &circuit_secondary);
+        assert!(res.is_ok());
+      }
+      start.elapsed()
+  }
+
+#[test]
+fn zkvm_interpreter_test() {
+  let (machine, num_steps) = Machine::run(&Program::fibonacci(10), 1000);
+  assert_eq!(machine.memory[0], 55);
+  // two setup instructions, six per iteration, then the final check, store and halt
+  assert_eq!(num_steps, 2 + 10 * 6 + 3);
+
+  let values = [9, 3, 7, 1, 8, 2, 2, 0];
+  let (machine, _) = Machine::run(&Program::bubble_sort(&values), 10_000);
+  let mut sorted = values.to_vec();
+  sorted.sort();
+  assert_eq!(&machine.memory[..values.len()], sorted.as_slice());
+}
+
+#[test]
+fn zkvm_step_circuits_test() {
+  type G = <E1 as Engine>::GE;
+  type F = <E1 as Engine>::Scalar;
+
+  fn synthesize(
+    z: &[F],
+    synthesize_fn: impl FnOnce(&mut TestConstraintSystem<F>, &[AllocatedNum<F>]) -> Vec<AllocatedNum<F>>,
+  ) -> (bool, Vec<F>) {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z_in = z
+      .iter()
+      .enumerate()
+      .map(|(i, value)| AllocatedNum::alloc(cs.namespace(|| format!("z_{}", i)), || Ok(*value)).unwrap())
+      .collect::<Vec<_>>();
+    let z_out = synthesize_fn(&mut cs, &z_in);
+    (cs.is_satisfied(), z_out.iter().map(|z| z.get_value().unwrap()).collect())
+  }
+
+  // the bubble sort exercises every opcode but Sub and Mul, the short program covers them
+  let programs = [
+    Program::bubble_sort(&[3, 1, 2]),
+    Program {
+      instructions: vec![
+        Instruction::sub(3, 1, 2),
+        Instruction::mul(4, 3, 3),
+        Instruction::store(4, 2, 7),
+        Instruction::load(5, 2, 7),
+      ],
+      registers: [0, 5, 9, 0, 0, 0, 0, 0],
+      memory: Vec::new(),
+    },
+  ];
+  for program in programs {
+    let (machine, num_steps) = Machine::run(&program, 1000);
+    let execution = execute::<F>(&program, num_steps);
+
+    // both step circuits agree with the native execution
+    for (i, (uniform, opcode)) in execution
+      .uniform_circuits::<G>()
+      .iter()
+      .zip(execution.opcode_circuits::<G>())
+      .enumerate()
+    {
+      let (satisfied, z_next) = synthesize(&execution.states[i], |cs, z| uniform.synthesize(cs, z).unwrap());
+      assert!(satisfied);
+      assert_eq!(z_next, execution.states[i + 1]);
+
+      let (satisfied, z_next) = synthesize(&execution.states[i], |cs, z| opcode.synthesize(cs, None, z).unwrap().1);
+      assert!(satisfied);
+      assert_eq!(z_next, execution.states[i + 1]);
+    }
+
+    let z = execution.states.last().unwrap();
+    assert_eq!(z[1], F::from(machine.pc as u64));
+    assert_eq!(z[2..2 + NUM_REGS], machine.regs.map(|r| F::from(r as u64)));
+  }
+
+  // an instruction that is not in the committed program can't be fetched
+  let execution = execute::<F>(&Program::fibonacci(3), 4);
+  let mut circuit = execution.uniform_circuits::<G>().remove(2);
+  circuit.advice.instruction.imm = 3;
+  assert!(!synthesize(&execution.states[2], |cs, z| circuit.synthesize(cs, z).unwrap()).0);
+
+  // the circuit of an opcode can't execute another one
+  let advice = StepAdvice {
+    instruction: Instruction::jmp(2),
+    ..execution.steps[2].clone()
+  };
+  let circuit = OpcodeCircuit::<G>::new(Opcode::Bnz, advice);
+  assert!(!synthesize(&execution.states[2], |cs, z| circuit.synthesize(cs, None, z).unwrap().1).0);
+
+  // the uniform circuit pays for every opcode
+  let uniform = uniform_num_constraints();
+  for op in Opcode::ALL {
+    assert!(opcode_num_constraints(op) < uniform);
+  }
+}
@@ -0,0 +1,97 @@
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+use std::time::Duration;
+use criterion::black_box;
+use criterion::BenchmarkId;
+use criterion::measurement::WallTime;
+use criterion::BenchmarkGroup;
+use std::collections::HashMap;
+use plonkish_backend::util::test::seeded_std_rng;
+use halo2_proofs::halo2curves::{bn256::Bn256, grumpkin};
+use custom_cyclefold::matvec::run_protostar_hyperplonk_ivc_matvec_preprocess;
+use custom_cyclefold::ivc::run_protostar_hyperplonk_ivc_prove;
+use criterion::{criterion_group, criterion_main, Criterion};
+use plonkish_backend::pcs::multilinear::MultilinearIpa;
+use plonkish_backend::pcs::univariate::UnivariateKzg;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::backend::PlonkishCircuit;
+use custom_cyclefold::params::{min_num_vars, pcs_setup_size, probe_params, MAX_NUM_VARS};
+
+// the arity is part of the step circuit type, so every width is benchmarked on its own
+fn bench_width<const WIDTH: usize>(group: &mut BenchmarkGroup<'_, WallTime>, num_steps: usize) -> (usize, usize, u128, usize, usize) {
+    let (probe_primary_params, probe_cyclefold_params) = probe_params();
+    let (probe_primary_circuit, probe_secondary_circuit, _, _)
+        = run_protostar_hyperplonk_ivc_matvec_preprocess::<WIDTH>(MAX_NUM_VARS, probe_primary_params, MAX_NUM_VARS, probe_cyclefold_params);
+    let primary_num_vars = min_num_vars(probe_primary_circuit.circuit(), probe_primary_circuit.instances().to_vec())
+        .unwrap_or_else(|err| panic!("primary circuit with width {}: {}", WIDTH, err));
+    let cyclefold_num_vars = min_num_vars(probe_secondary_circuit.circuit(), probe_secondary_circuit.instances().to_vec())
+        .unwrap_or_else(|err| panic!("cyclefold circuit with width {}: {}", WIDTH, err));
+
+    let primary_params = UnivariateKzg::<Bn256>::setup(pcs_setup_size(primary_num_vars), 0, &mut seeded_std_rng()).unwrap();
+    let cyclefold_params = MultilinearIpa::<grumpkin::G1Affine>::setup(pcs_setup_size(cyclefold_num_vars), 0, &mut seeded_std_rng()).unwrap();
+    let (mut primary_circuit, mut secondary_circuit, ivc_pp, _)
+        = run_protostar_hyperplonk_ivc_matvec_preprocess::<WIDTH>(primary_num_vars, primary_params, cyclefold_num_vars, cyclefold_params);
+
+    let mut time = HashMap::new();
+    let test_name = format!("entire_process_{}", WIDTH);
+    let benchmark_id = BenchmarkId::new(test_name, WIDTH);
+    group.bench_function(benchmark_id, |b| {
+      b.iter_custom(|iters| {
+          let start = Instant::now();
+          for _ in 0..iters {
+              black_box(run_protostar_hyperplonk_ivc_prove(&mut primary_circuit, &mut secondary_circuit, &ivc_pp, num_steps));
+          }
+          let elapsed = start.elapsed();
+          let _ = *time.entry(WIDTH)
+              .and_modify(|e| *e += elapsed)
+              .or_insert(elapsed);
+          elapsed
+      })
+    });
+
+    let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+    let total_duration = time.entry(WIDTH).or_insert(Duration::ZERO).as_millis();
+    let average_execution_time = total_duration / iterations;
+    (
+        primary_num_vars,
+        cyclefold_num_vars,
+        average_execution_time,
+        ivc_pp.primary_pp.witness_count - ivc_pp.primary_pp.copy_count,
+        ivc_pp.cyclefold_pp.witness_count - ivc_pp.cyclefold_pp.copy_count,
+    )
+}
+
+fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
+    let num_steps = 10;
+    let mut group = c.benchmark_group("Halo2lib Protostar Cyclefold IVC");
+    group.sample_size(10);
+
+    // layer widths, same as the Nova and Sonobe runs
+    let results = vec![
+        (16, bench_width::<16>(&mut group, num_steps)),
+        (32, bench_width::<32>(&mut group, num_steps)),
+        (64, bench_width::<64>(&mut group, num_steps)),
+        (128, bench_width::<128>(&mut group, num_steps)),
+    ];
+
+    group.finish();
+
+    let mut file = File::create("../../benchmark_results/halo2_matvec_custom_cyclefold.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Num Vars  | Cyclefold Num Vars | Width | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-----------|--------------------|-------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (width, (num_vars, cyclefold_num_vars, duration, primary_size, secondary_size)) in results.iter() {
+        writeln!(
+            file,
+            "| {}         | {}         | {}                  | {}    | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, num_vars, cyclefold_num_vars, width, duration, primary_size, secondary_size
+        ).expect("Failed to write to file");
+    }
+}
+
+fn matvec_protostar_cyclefold(c: &mut Criterion) {
+    bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c);
+}
+
+criterion_group!(benches, matvec_protostar_cyclefold);
+criterion_main!(benches);
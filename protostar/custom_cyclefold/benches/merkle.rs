@@ -0,0 +1,95 @@
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+use std::time::Duration;
+use criterion::black_box;
+use criterion::BenchmarkId;
+use std::collections::HashMap;
+use plonkish_backend::util::test::seeded_std_rng;
+use halo2_proofs::halo2curves::{bn256::Bn256, grumpkin};
+use custom_cyclefold::merkle::run_protostar_hyperplonk_ivc_merkle_preprocess;
+use custom_cyclefold::ivc::run_protostar_hyperplonk_ivc_prove;
+use criterion::{criterion_group, criterion_main, Criterion};
+use plonkish_backend::pcs::multilinear::MultilinearIpa;
+use plonkish_backend::pcs::univariate::UnivariateKzg;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::backend::PlonkishCircuit;
+use custom_cyclefold::params::{min_num_vars, pcs_setup_size, probe_params, MAX_NUM_VARS};
+
+fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
+    let num_steps = 10;
+    // (tree depth, updates per step), same as the Nova and Sonobe runs
+    let configs = vec![(16, 1), (16, 4), (32, 1), (32, 4), (32, 16)];
+    let (mut primary_circuits, mut secondary_circuits, mut pp_vec, mut num_vars)
+        = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+
+    let (probe_primary_params, probe_cyclefold_params) = probe_params();
+
+    for &(depth, num_updates) in configs.iter() {
+        let (probe_primary_circuit, probe_secondary_circuit, _, _)
+            = run_protostar_hyperplonk_ivc_merkle_preprocess(depth, num_updates, MAX_NUM_VARS, probe_primary_params.clone(), MAX_NUM_VARS, probe_cyclefold_params.clone());
+        let primary_num_vars = min_num_vars(probe_primary_circuit.circuit(), probe_primary_circuit.instances().to_vec())
+            .unwrap_or_else(|err| panic!("primary circuit with depth {} and {} updates per step: {}", depth, num_updates, err));
+        let cyclefold_num_vars = min_num_vars(probe_secondary_circuit.circuit(), probe_secondary_circuit.instances().to_vec())
+            .unwrap_or_else(|err| panic!("cyclefold circuit with depth {} and {} updates per step: {}", depth, num_updates, err));
+
+        let primary_params = UnivariateKzg::<Bn256>::setup(pcs_setup_size(primary_num_vars), 0, &mut seeded_std_rng()).unwrap();
+        let cyclefold_params = MultilinearIpa::<grumpkin::G1Affine>::setup(pcs_setup_size(cyclefold_num_vars), 0, &mut seeded_std_rng()).unwrap();
+
+        let (primary_circuit, secondary_circuit, ivc_pp, _)
+            = run_protostar_hyperplonk_ivc_merkle_preprocess(depth, num_updates, primary_num_vars, primary_params, cyclefold_num_vars, cyclefold_params);
+        
+        primary_circuits.push(primary_circuit);
+        secondary_circuits.push(secondary_circuit);
+        pp_vec.push(ivc_pp);
+        num_vars.push((primary_num_vars, cyclefold_num_vars));
+    }
+
+    let mut group = c.benchmark_group("Halo2lib Protostar Cyclefold IVC");
+    group.sample_size(10);
+
+    let mut results = Vec::new();
+    for (i, (depth, num_updates)) in configs.iter().enumerate() {
+      let mut time = HashMap::new();
+        let test_name = format!("entire_process_{}_{}", depth, num_updates);
+        let benchmark_id = BenchmarkId::new(test_name, num_updates);
+        group.bench_function(benchmark_id, |b| {
+          b.iter_custom(|iters| {
+              let start = Instant::now();
+              for _ in 0..iters {
+                  black_box(run_protostar_hyperplonk_ivc_prove(&mut primary_circuits[i], &mut secondary_circuits[i], &pp_vec[i], num_steps));
+              }
+              let elapsed = start.elapsed();
+              let _ = *time.entry(i)
+                  .and_modify(|e| *e += elapsed)
+                  .or_insert(elapsed);
+              elapsed
+          })
+      });
+
+      let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+      let total_duration = time.entry(i).or_insert(Duration::ZERO).as_millis();
+      let average_execution_time = total_duration / iterations;
+      results.push((depth, num_updates, average_execution_time));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../../benchmark_results/halo2_merkle_custom_cyclefold.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Num Vars  | Cyclefold Num Vars | Depth | Num Updates per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-----------|--------------------|-------|----------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (i, (depth, num_updates, duration)) in results.iter().enumerate() {
+        writeln!(
+            file,
+            "| {}         | {}         | {}                  | {}    | {}                   | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, num_vars[i].0, num_vars[i].1, depth, num_updates, duration, (pp_vec[i].primary_pp.witness_count - pp_vec[i].primary_pp.copy_count), (pp_vec[i].cyclefold_pp.witness_count - pp_vec[i].cyclefold_pp.copy_count)
+        ).expect("Failed to write to file");
+    }
+}
+
+fn merkle_protostar_cyclefold(c: &mut Criterion) {
+    bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c);
+}
+
+criterion_group!(benches, merkle_protostar_cyclefold);
+criterion_main!(benches);
@@ -6,40 +6,65 @@ use criterion::black_box;
 use criterion::BenchmarkId;
 use std::collections::HashMap;
 use plonkish_backend::util::test::seeded_std_rng;
-use halo2_proofs::halo2curves::{bn256::{self, Bn256}, grumpkin};
-use plonkish_backend::accumulation::protostar::ivc::halo2::test::{run_protostar_hyperplonk_ivc_minroot_preprocess, run_protostar_hyperplonk_ivc_prove};
+use halo2_proofs::halo2curves::{bn256::Bn256, grumpkin};
+use circuit_dsl::minroot::MinRoot;
+use custom_cyclefold::dsl::run_protostar_hyperplonk_ivc_dsl_preprocess;
+use custom_cyclefold::minroot::{run_protostar_hyperplonk_ivc_minroot_preprocess, Degree5, MinRootGate};
+use custom_cyclefold::ivc::{
+    run_protostar_hyperplonk_ivc_prove, Fq, Fr, IvcProverParam, IvcVerifierParam, PrimaryCircuit, SecondaryCircuit, C1, P1, P2,
+};
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
 use criterion::{criterion_group, criterion_main, Criterion};
-use plonkish_backend::pcs::multilinear::{Gemini, MultilinearIpa};
+use plonkish_backend::pcs::multilinear::MultilinearIpa;
 use plonkish_backend::pcs::univariate::UnivariateKzg;
 use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::backend::PlonkishCircuit;
+use custom_cyclefold::params::{min_num_vars, pcs_setup_size, probe_params, MAX_NUM_VARS};
 
-fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
-    let num_steps = 10;
+const NUM_STEPS: usize = 10;
+
+// (gate degree, num vars, cyclefold num vars, num iters per step, execution time, primary size, secondary size)
+type ResultRow = (usize, usize, usize, usize, u128, usize, usize);
+
+fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc<Sc: StepCircuit<C1>>(
+    c: &mut Criterion,
+    degree: usize,
+    preprocess: impl Fn(
+        usize,
+        usize,
+        <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+        usize,
+        <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+    ) -> (PrimaryCircuit<Sc>, SecondaryCircuit, IvcProverParam, IvcVerifierParam),
+) -> Vec<ResultRow> {
+    let num_steps = NUM_STEPS;
     let num_iters_steps = vec![1000, 9000, 25000, 58000, 100000];
-    let num_vars = vec![12, 13, 14, 15, 16];
-    let (mut primary_circuits, mut secondary_circuits, mut pp_vec, mut vp_vec) 
+    let (mut primary_circuits, mut secondary_circuits, mut pp_vec, mut num_vars)
         = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
-    
-    for (i, &num_iters) in num_iters_steps.iter().enumerate() {
-        let primary_num_vars = num_vars[i];
-        let cyclefold_num_vars = 10;
-        let primary_params = UnivariateKzg::setup(1 << (primary_num_vars + 4), 0, &mut seeded_std_rng()).unwrap();
-        let cyclefold_params = MultilinearIpa::setup(1 << (cyclefold_num_vars + 4), 0, &mut seeded_std_rng()).unwrap();
 
-        let (primary_circuit, secondary_circuit, ivc_pp, ivc_vp)
-            = run_protostar_hyperplonk_ivc_minroot_preprocess::<
-                bn256::G1Affine,
-                Gemini<UnivariateKzg<Bn256>>,
-                MultilinearIpa<grumpkin::G1Affine>,
-            >(num_iters,primary_num_vars, primary_params, cyclefold_num_vars, cyclefold_params);
+    let (probe_primary_params, probe_cyclefold_params) = probe_params();
+
+    for &num_iters in num_iters_steps.iter() {
+        let (probe_primary_circuit, probe_secondary_circuit, _, _)
+            = preprocess(num_iters, MAX_NUM_VARS, probe_primary_params.clone(), MAX_NUM_VARS, probe_cyclefold_params.clone());
+        let primary_num_vars = min_num_vars(probe_primary_circuit.circuit(), probe_primary_circuit.instances().to_vec())
+            .unwrap_or_else(|err| panic!("primary circuit with {} iters per step: {}", num_iters, err));
+        let cyclefold_num_vars = min_num_vars(probe_secondary_circuit.circuit(), probe_secondary_circuit.instances().to_vec())
+            .unwrap_or_else(|err| panic!("cyclefold circuit with {} iters per step: {}", num_iters, err));
+
+        let primary_params = UnivariateKzg::<Bn256>::setup(pcs_setup_size(primary_num_vars), 0, &mut seeded_std_rng()).unwrap();
+        let cyclefold_params = MultilinearIpa::<grumpkin::G1Affine>::setup(pcs_setup_size(cyclefold_num_vars), 0, &mut seeded_std_rng()).unwrap();
+
+        let (primary_circuit, secondary_circuit, ivc_pp, _)
+            = preprocess(num_iters, primary_num_vars, primary_params, cyclefold_num_vars, cyclefold_params);
         
         primary_circuits.push(primary_circuit);
         secondary_circuits.push(secondary_circuit);
         pp_vec.push(ivc_pp);
-        vp_vec.push(ivc_vp);
+        num_vars.push((primary_num_vars, cyclefold_num_vars));
     }
 
-    let mut group = c.benchmark_group("Halo2lib Protostar Cyclefold IVC");
+    let mut group = c.benchmark_group(format!("Halo2lib Protostar Cyclefold IVC degree {}", degree));
     group.sample_size(10);
 
     let mut results = Vec::new();
@@ -51,7 +76,7 @@ fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
           b.iter_custom(|iters| {
               let start = Instant::now();
               for _ in 0..iters {
-                  black_box(run_protostar_hyperplonk_ivc_prove(&mut primary_circuits[i], &mut secondary_circuits[i], &pp_vec[i], &vp_vec[i], *num_iters, num_steps));
+                  black_box(run_protostar_hyperplonk_ivc_prove(&mut primary_circuits[i], &mut secondary_circuits[i], &pp_vec[i], num_steps));
               }
               let elapsed = start.elapsed();
               let _ = *time.entry(num_iters)
@@ -64,26 +89,41 @@ fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
       let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
       let total_duration = time.entry(num_iters).or_insert(Duration::ZERO).as_millis();
       let average_execution_time = total_duration / iterations;
-      results.push((num_iters, average_execution_time));
+      results.push((
+          degree,
+          num_vars[i].0,
+          num_vars[i].1,
+          *num_iters,
+          average_execution_time,
+          pp_vec[i].primary_pp.witness_count - pp_vec[i].primary_pp.copy_count,
+          pp_vec[i].cyclefold_pp.witness_count - pp_vec[i].cyclefold_pp.copy_count,
+      ));
     }
 
     group.finish();
+    results
+}
+
+fn minroot_protostar_cyclefold(c: &mut Criterion) {
+    // the DSL lowering splits x^5 into degree-2 multiplications, the DSL has no custom gates so
+    // the single degree-5 gate stays hand-written
+    let mut results = bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c, 2, |num_iters, primary_num_vars, primary_params, cyclefold_num_vars, cyclefold_params| {
+        let initial_input = vec![Fr::from(0), Fr::from(0), Fr::from(1)];
+        run_protostar_hyperplonk_ivc_dsl_preprocess(MinRoot { num_iters_per_step: num_iters }, initial_input, primary_num_vars, primary_params, cyclefold_num_vars, cyclefold_params)
+    });
+    results.extend(bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c, Degree5::DEGREE, run_protostar_hyperplonk_ivc_minroot_preprocess::<Degree5>));
 
     let mut file = File::create("../../benchmark_results/halo2_minroot_custom_cyclefold_best2.md").expect("Failed to create file");
-    writeln!(file, "| Num Steps  | Num Vars  | Num Iters per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
-    writeln!(file, "|------------|-----------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
-    for (i, (num_iters, duration)) in results.iter().enumerate() {
+    writeln!(file, "| Num Steps  | Gate Degree | Num Vars  | Cyclefold Num Vars | Num Iters per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-------------|-----------|--------------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (degree, num_vars, cyclefold_num_vars, num_iters, duration, primary_size, secondary_size) in results {
         writeln!(
             file,
-            "| {}         | {}         | {}               | {:?} ms             | {:?}                | {:?}                  |",
-            num_steps, num_vars[i], num_iters, duration, (pp_vec[i].primary_pp.witness_count - pp_vec[i].primary_pp.copy_count), (pp_vec[i].cyclefold_pp.witness_count - pp_vec[i].cyclefold_pp.copy_count)
+            "| {}         | {}           | {}         | {}                  | {}               | {:?} ms             | {:?}                | {:?}                  |",
+            NUM_STEPS, degree, num_vars, cyclefold_num_vars, num_iters, duration, primary_size, secondary_size
         ).expect("Failed to write to file");
     }
 }
 
-fn minroot_protostar_cyclefold(c: &mut Criterion) {
-    bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c);
-}
-
 criterion_group!(benches, minroot_protostar_cyclefold);
 criterion_main!(benches);
@@ -0,0 +1,102 @@
+use std::fs::File;
+use std::io::Write;
+use std::time::Duration;
+use criterion::black_box;
+use criterion::BenchmarkId;
+use std::collections::HashMap;
+use halo2_proofs::halo2curves::ff::Field;
+use custom_cyclefold::hashchain::HashChainCircuit;
+use custom_cyclefold::ivc::{Fr, C1};
+use custom_cyclefold::minroot::{Degree2, MinRootCircuit};
+use custom_cyclefold::monolithic::{hyperplonk_prove, hyperplonk_setup, MonolithicCircuit};
+use criterion::{criterion_group, criterion_main, Criterion};
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use custom_cyclefold::params::min_num_vars_up_to;
+
+const NUM_STEPS: usize = 10;
+
+// a single circuit holds all the steps, so it is allowed to grow past the IVC MAX_NUM_VARS
+const MONOLITHIC_MAX_NUM_VARS: usize = 22;
+
+// (workload, num vars, num iters per step, execution time, circuit size)
+type ResultRow = (&'static str, usize, usize, u128, usize);
+
+// proves `NUM_STEPS` steps of a workload as a single HyperPlonk proof
+fn bench_gemini_kzg_hyperplonk<Sc: StepCircuit<C1>>(
+    c: &mut Criterion,
+    workload: &'static str,
+    num_iters_steps: &[usize],
+    step_circuit: impl Fn(usize) -> Sc,
+) -> Vec<ResultRow> {
+    let (mut circuits, mut pp_vec, mut num_vars) = (Vec::new(), Vec::new(), Vec::new());
+    for &num_iters in num_iters_steps.iter() {
+        let circuit = MonolithicCircuit::new(step_circuit(num_iters), NUM_STEPS);
+        let circuit_num_vars = min_num_vars_up_to(&circuit, Vec::new(), MONOLITHIC_MAX_NUM_VARS)
+            .unwrap_or_else(|err| panic!("{} circuit with {} iters per step: {}", workload, num_iters, err));
+
+        pp_vec.push(hyperplonk_setup(circuit.clone(), circuit_num_vars));
+        circuits.push(circuit);
+        num_vars.push(circuit_num_vars);
+    }
+
+    let mut group = c.benchmark_group(format!("HyperPlonk {}", workload));
+    group.sample_size(10);
+
+    let mut results = Vec::new();
+    for (i, num_iters) in num_iters_steps.iter().enumerate() {
+      let mut time = HashMap::new();
+        let test_name = format!("entire_process_{}", num_iters);
+        let benchmark_id = BenchmarkId::new(test_name, num_iters);
+        group.bench_function(benchmark_id, |b| {
+          b.iter_custom(|iters| {
+              let mut elapsed = Duration::ZERO;
+              for _ in 0..iters {
+                  elapsed += black_box(hyperplonk_prove(&pp_vec[i], circuits[i].clone()));
+              }
+              let _ = *time.entry(num_iters)
+                  .and_modify(|e| *e += elapsed)
+                  .or_insert(elapsed);
+              elapsed
+          })
+      });
+
+      let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+      let total_duration = time.entry(num_iters).or_insert(Duration::ZERO).as_millis();
+      let average_execution_time = total_duration / iterations;
+      results.push((
+          workload,
+          num_vars[i],
+          *num_iters,
+          average_execution_time,
+          pp_vec[i].witness_count - pp_vec[i].copy_count,
+      ));
+    }
+
+    group.finish();
+    results
+}
+
+fn monolithic_hyperplonk(c: &mut Criterion) {
+    // the largest IVC configurations are left out, they do not fit in a single circuit
+    let mut results = bench_gemini_kzg_hyperplonk(c, "MinRoot", &[1000, 9000, 25000], |num_iters| {
+        MinRootCircuit::<Degree2>::new(vec![Fr::ZERO, Fr::ZERO, Fr::ONE], num_iters)
+    });
+    results.extend(bench_gemini_kzg_hyperplonk(c, "HashChain", &[1400, 3200, 6600], |num_elts| {
+        HashChainCircuit::new(vec![Fr::ZERO], num_elts)
+    }));
+
+    // same schema as the IVC tables, a monolithic proof has no secondary circuit
+    let mut file = File::create("../../benchmark_results/halo2_monolithic_hyperplonk.md").expect("Failed to create file");
+    writeln!(file, "| Workload   | Num Steps  | Num Vars  | Num Iters per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|------------|-----------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (workload, num_vars, num_iters, duration, size) in results {
+        writeln!(
+            file,
+            "| {}    | {}         | {}         | {}               | {:?} ms             | {:?}                | -                      |",
+            workload, NUM_STEPS, num_vars, num_iters, duration, size
+        ).expect("Failed to write to file");
+    }
+}
+
+criterion_group!(benches, monolithic_hyperplonk);
+criterion_main!(benches);
@@ -0,0 +1,114 @@
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+use std::time::Duration;
+use criterion::black_box;
+use criterion::BenchmarkId;
+use criterion::measurement::WallTime;
+use criterion::BenchmarkGroup;
+use std::collections::HashMap;
+use plonkish_backend::util::test::seeded_std_rng;
+use halo2_proofs::halo2curves::{bn256::Bn256, grumpkin};
+use halo2_proofs::halo2curves::ff::Field;
+use custom_cyclefold::random_circuit::{run_protostar_hyperplonk_ivc_random_circuit_preprocess, RandomPlonkishCircuit, MAX_LOG_GATES};
+use custom_cyclefold::ivc::{run_protostar_hyperplonk_ivc_prove, Fr};
+use criterion::{criterion_group, criterion_main, Criterion};
+use plonkish_backend::pcs::multilinear::MultilinearIpa;
+use plonkish_backend::pcs::univariate::UnivariateKzg;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::backend::PlonkishCircuit;
+use custom_cyclefold::params::{min_num_vars, pcs_setup_size, probe_params, ParamsError, MAX_NUM_VARS};
+
+// None for the shapes whose step circuit alone doesn't fit in 2^MAX_NUM_VARS rows
+fn bench_shape(group: &mut BenchmarkGroup<'_, WallTime>, num_gates: usize, num_variables: usize, num_steps: usize) -> Option<(usize, usize, u128, usize, usize)> {
+    match min_num_vars(&RandomPlonkishCircuit::new(num_gates, num_variables, 0, Fr::ONE), Vec::new()) {
+        Ok(_) => {}
+        Err(ParamsError::DoesNotFit { .. }) => return None,
+        Err(err) => panic!("step circuit with {} gates: {}", num_gates, err),
+    }
+
+    let (probe_primary_params, probe_cyclefold_params) = probe_params();
+    let (probe_primary_circuit, probe_secondary_circuit, _, _)
+        = run_protostar_hyperplonk_ivc_random_circuit_preprocess(num_gates, num_variables, 0, MAX_NUM_VARS, probe_primary_params, MAX_NUM_VARS, probe_cyclefold_params);
+    let primary_num_vars = min_num_vars(probe_primary_circuit.circuit(), probe_primary_circuit.instances().to_vec())
+        .unwrap_or_else(|err| panic!("primary circuit with {} gates: {}", num_gates, err));
+    let cyclefold_num_vars = min_num_vars(probe_secondary_circuit.circuit(), probe_secondary_circuit.instances().to_vec())
+        .unwrap_or_else(|err| panic!("cyclefold circuit with {} gates: {}", num_gates, err));
+
+    let primary_params = UnivariateKzg::<Bn256>::setup(pcs_setup_size(primary_num_vars), 0, &mut seeded_std_rng()).unwrap();
+    let cyclefold_params = MultilinearIpa::<grumpkin::G1Affine>::setup(pcs_setup_size(cyclefold_num_vars), 0, &mut seeded_std_rng()).unwrap();
+    let (mut primary_circuit, mut secondary_circuit, ivc_pp, _)
+        = run_protostar_hyperplonk_ivc_random_circuit_preprocess(num_gates, num_variables, 0, primary_num_vars, primary_params, cyclefold_num_vars, cyclefold_params);
+
+    let mut time = HashMap::new();
+    let test_name = format!("entire_process_{}_{}", num_gates, num_variables);
+    let benchmark_id = BenchmarkId::new(test_name, num_gates);
+    group.bench_function(benchmark_id, |b| {
+      b.iter_custom(|iters| {
+          let start = Instant::now();
+          for _ in 0..iters {
+              black_box(run_protostar_hyperplonk_ivc_prove(&mut primary_circuit, &mut secondary_circuit, &ivc_pp, num_steps));
+          }
+          let elapsed = start.elapsed();
+          let _ = *time.entry(num_gates)
+              .and_modify(|e| *e += elapsed)
+              .or_insert(elapsed);
+          elapsed
+      })
+    });
+
+    let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+    let total_duration = time.entry(num_gates).or_insert(Duration::ZERO).as_millis();
+    let average_execution_time = total_duration / iterations;
+    Some((
+        primary_num_vars,
+        cyclefold_num_vars,
+        average_execution_time,
+        ivc_pp.primary_pp.witness_count - ivc_pp.primary_pp.copy_count,
+        ivc_pp.cyclefold_pp.witness_count - ivc_pp.cyclefold_pp.copy_count,
+    ))
+}
+
+fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
+    let num_steps = 10;
+    let mut group = c.benchmark_group("Halo2lib Protostar Cyclefold IVC");
+    group.sample_size(10);
+
+    // (num_gates, num_variables), the sizes of the Nova and Sonobe runs up to the largest of the
+    // generator, the ones past MAX_NUM_VARS are skipped and reported as such
+    let results = (10..=MAX_LOG_GATES)
+        .step_by(2)
+        .map(|log_gates| {
+            let (num_gates, num_variables) = (1 << log_gates, 2 << log_gates);
+            ((num_gates, num_variables), bench_shape(&mut group, num_gates, num_variables, num_steps))
+        })
+        .collect::<Vec<_>>();
+
+    group.finish();
+
+    let mut file = File::create("../../benchmark_results/halo2_random_circuit_custom_cyclefold.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Num Vars  | Cyclefold Num Vars | Num Gates | Num Variables | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-----------|--------------------|-----------|---------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for ((num_gates, num_variables), result) in results.iter() {
+        match result {
+            Some((num_vars, cyclefold_num_vars, duration, primary_size, secondary_size)) => writeln!(
+                file,
+                "| {}         | {}         | {}                  | {}         | {}             | {:?} ms             | {:?}                | {:?}                  |",
+                num_steps, num_vars, cyclefold_num_vars, num_gates, num_variables, duration, primary_size, secondary_size
+            ),
+            None => writeln!(
+                file,
+                "| {}         | -         | -                  | {}         | {}             | skipped, over 2^{} rows | -                | -                  |",
+                num_steps, num_gates, num_variables, MAX_NUM_VARS
+            ),
+        }
+        .expect("Failed to write to file");
+    }
+}
+
+fn random_circuit_protostar_cyclefold(c: &mut Criterion) {
+    bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c);
+}
+
+criterion_group!(benches, random_circuit_protostar_cyclefold);
+criterion_main!(benches);
@@ -6,37 +6,42 @@ use criterion::black_box;
 use criterion::BenchmarkId;
 use std::collections::HashMap;
 use plonkish_backend::util::test::seeded_std_rng;
-use halo2_proofs::halo2curves::{bn256::{self, Bn256}, grumpkin};
-use plonkish_backend::accumulation::protostar::ivc::halo2::test::{run_protostar_hyperplonk_ivc_smchain_preprocess, run_protostar_hyperplonk_ivc_prove};
+use halo2_proofs::halo2curves::{bn256::Bn256, grumpkin};
+use custom_cyclefold::scalar_mul::run_protostar_hyperplonk_ivc_smchain_preprocess;
+use custom_cyclefold::ivc::run_protostar_hyperplonk_ivc_prove;
 use criterion::{criterion_group, criterion_main, Criterion};
-use plonkish_backend::pcs::multilinear::{Gemini, MultilinearIpa};
+use plonkish_backend::pcs::multilinear::MultilinearIpa;
 use plonkish_backend::pcs::univariate::UnivariateKzg;
 use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::backend::PlonkishCircuit;
+use custom_cyclefold::params::{min_num_vars, pcs_setup_size, probe_params, MAX_NUM_VARS};
 
 fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
     let num_steps = 10;
     let num_iters_steps = vec![95, 225, 475, 975, 1975];
-    let num_vars = vec![14, 15, 16, 17, 18];
-    let (mut primary_circuits, mut secondary_circuits, mut pp_vec, mut vp_vec) 
+    let (mut primary_circuits, mut secondary_circuits, mut pp_vec, mut num_vars)
         = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
-    
-    for (i, &num_iters) in num_iters_steps.iter().enumerate() {
-        let primary_num_vars = num_vars[i];
-        let cyclefold_num_vars = 10;
-        let primary_params = UnivariateKzg::setup(1 << (primary_num_vars + 4), 0, &mut seeded_std_rng()).unwrap();
-        let cyclefold_params = MultilinearIpa::setup(1 << (cyclefold_num_vars + 4), 0, &mut seeded_std_rng()).unwrap();
 
-        let (primary_circuit, secondary_circuit, ivc_pp, ivc_vp)
-            = run_protostar_hyperplonk_ivc_smchain_preprocess::<
-                bn256::G1Affine,
-                Gemini<UnivariateKzg<Bn256>>,
-                MultilinearIpa<grumpkin::G1Affine>,
-            >(num_iters,primary_num_vars, primary_params, cyclefold_num_vars, cyclefold_params);
+    let (probe_primary_params, probe_cyclefold_params) = probe_params();
+
+    for &num_iters in num_iters_steps.iter() {
+        let (probe_primary_circuit, probe_secondary_circuit, _, _)
+            = run_protostar_hyperplonk_ivc_smchain_preprocess(num_iters, MAX_NUM_VARS, probe_primary_params.clone(), MAX_NUM_VARS, probe_cyclefold_params.clone());
+        let primary_num_vars = min_num_vars(probe_primary_circuit.circuit(), probe_primary_circuit.instances().to_vec())
+            .unwrap_or_else(|err| panic!("primary circuit with {} iters per step: {}", num_iters, err));
+        let cyclefold_num_vars = min_num_vars(probe_secondary_circuit.circuit(), probe_secondary_circuit.instances().to_vec())
+            .unwrap_or_else(|err| panic!("cyclefold circuit with {} iters per step: {}", num_iters, err));
+
+        let primary_params = UnivariateKzg::<Bn256>::setup(pcs_setup_size(primary_num_vars), 0, &mut seeded_std_rng()).unwrap();
+        let cyclefold_params = MultilinearIpa::<grumpkin::G1Affine>::setup(pcs_setup_size(cyclefold_num_vars), 0, &mut seeded_std_rng()).unwrap();
+
+        let (primary_circuit, secondary_circuit, ivc_pp, _)
+            = run_protostar_hyperplonk_ivc_smchain_preprocess(num_iters, primary_num_vars, primary_params, cyclefold_num_vars, cyclefold_params);
         
         primary_circuits.push(primary_circuit);
         secondary_circuits.push(secondary_circuit);
         pp_vec.push(ivc_pp);
-        vp_vec.push(ivc_vp);
+        num_vars.push((primary_num_vars, cyclefold_num_vars));
     }
 
     let mut group = c.benchmark_group("Halo2lib Protostar Cyclefold IVC");
@@ -51,7 +56,7 @@ fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
           b.iter_custom(|iters| {
               let start = Instant::now();
               for _ in 0..iters {
-                  black_box(run_protostar_hyperplonk_ivc_prove(&mut primary_circuits[i], &mut secondary_circuits[i], &pp_vec[i], &vp_vec[i], *num_iters, num_steps));
+                  black_box(run_protostar_hyperplonk_ivc_prove(&mut primary_circuits[i], &mut secondary_circuits[i], &pp_vec[i], num_steps));
               }
               let elapsed = start.elapsed();
               let _ = *time.entry(num_iters)
@@ -70,13 +75,13 @@ fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
     group.finish();
 
     let mut file = File::create("../../benchmark_results/halo2_smchain_custom_cyclefold2.md").expect("Failed to create file");
-    writeln!(file, "| Num Steps  | Num Vars  | Num Iters per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
-    writeln!(file, "|------------|-----------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    writeln!(file, "| Num Steps  | Num Vars  | Cyclefold Num Vars | Num Iters per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-----------|--------------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
     for (i, (num_iters, duration)) in results.iter().enumerate() {
         writeln!(
             file,
-            "| {}         | {}         | {}               | {:?} ms             | {:?}                | {:?}                  |",
-            num_steps, num_vars[i], num_iters, duration, (pp_vec[i].primary_pp.witness_count - pp_vec[i].primary_pp.copy_count), (pp_vec[i].cyclefold_pp.witness_count - pp_vec[i].cyclefold_pp.copy_count)
+            "| {}         | {}         | {}                  | {}               | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, num_vars[i].0, num_vars[i].1, num_iters, duration, (pp_vec[i].primary_pp.witness_count - pp_vec[i].primary_pp.copy_count), (pp_vec[i].cyclefold_pp.witness_count - pp_vec[i].cyclefold_pp.copy_count)
         ).expect("Failed to write to file");
     }
 }
@@ -0,0 +1,101 @@
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+use std::time::Duration;
+use criterion::black_box;
+use criterion::measurement::WallTime;
+use criterion::{BenchmarkGroup, BenchmarkId};
+use plonkish_backend::util::test::seeded_std_rng;
+use halo2_proofs::halo2curves::{bn256::Bn256, grumpkin};
+use custom_cyclefold::wide_state::run_protostar_hyperplonk_ivc_wide_state_preprocess;
+use custom_cyclefold::ivc::{run_protostar_hyperplonk_ivc_prove, Fq, Fr, P1, P2};
+use criterion::{criterion_group, criterion_main, Criterion};
+use plonkish_backend::pcs::multilinear::MultilinearIpa;
+use plonkish_backend::pcs::univariate::UnivariateKzg;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::backend::PlonkishCircuit;
+use custom_cyclefold::params::{min_num_vars, pcs_setup_size, probe_params, MAX_NUM_VARS};
+
+// the arity is part of the step circuit type, so every arity is preprocessed and proven on its own
+fn bench_arity<const ARITY: usize>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    probe_primary_params: &<P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    probe_cyclefold_params: &<P2 as PolynomialCommitmentScheme<Fq>>::Param,
+    num_steps: usize,
+) -> (usize, usize, u128, usize, usize) {
+    let (probe_primary_circuit, probe_secondary_circuit, _, _)
+        = run_protostar_hyperplonk_ivc_wide_state_preprocess::<ARITY>(MAX_NUM_VARS, probe_primary_params.clone(), MAX_NUM_VARS, probe_cyclefold_params.clone());
+    let primary_num_vars = min_num_vars(probe_primary_circuit.circuit(), probe_primary_circuit.instances().to_vec())
+        .unwrap_or_else(|err| panic!("primary circuit with arity {}: {}", ARITY, err));
+    let cyclefold_num_vars = min_num_vars(probe_secondary_circuit.circuit(), probe_secondary_circuit.instances().to_vec())
+        .unwrap_or_else(|err| panic!("cyclefold circuit with arity {}: {}", ARITY, err));
+
+    let primary_params = UnivariateKzg::<Bn256>::setup(pcs_setup_size(primary_num_vars), 0, &mut seeded_std_rng()).unwrap();
+    let cyclefold_params = MultilinearIpa::<grumpkin::G1Affine>::setup(pcs_setup_size(cyclefold_num_vars), 0, &mut seeded_std_rng()).unwrap();
+
+    let (mut primary_circuit, mut secondary_circuit, ivc_pp, _)
+        = run_protostar_hyperplonk_ivc_wide_state_preprocess::<ARITY>(primary_num_vars, primary_params, cyclefold_num_vars, cyclefold_params);
+
+    let mut time = Duration::ZERO;
+    let test_name = format!("entire_process_{}", ARITY);
+    let benchmark_id = BenchmarkId::new(test_name, ARITY);
+    group.bench_function(benchmark_id, |b| {
+      b.iter_custom(|iters| {
+          let start = Instant::now();
+          for _ in 0..iters {
+              black_box(run_protostar_hyperplonk_ivc_prove(&mut primary_circuit, &mut secondary_circuit, &ivc_pp, num_steps));
+          }
+          let elapsed = start.elapsed();
+          time += elapsed;
+          elapsed
+      })
+    });
+
+    let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+    (
+        primary_num_vars,
+        cyclefold_num_vars,
+        time.as_millis() / iterations,
+        ivc_pp.primary_pp.witness_count - ivc_pp.primary_pp.copy_count,
+        ivc_pp.cyclefold_pp.witness_count - ivc_pp.cyclefold_pp.copy_count,
+    )
+}
+
+fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
+    let num_steps = 10;
+
+    let (probe_primary_params, probe_cyclefold_params) = probe_params();
+
+    let mut group = c.benchmark_group("Halo2lib Protostar Cyclefold IVC");
+    group.sample_size(10);
+
+    // same arities as the Nova and Sonobe runs
+    let results = vec![
+        (1, bench_arity::<1>(&mut group, &probe_primary_params, &probe_cyclefold_params, num_steps)),
+        (4, bench_arity::<4>(&mut group, &probe_primary_params, &probe_cyclefold_params, num_steps)),
+        (16, bench_arity::<16>(&mut group, &probe_primary_params, &probe_cyclefold_params, num_steps)),
+        (64, bench_arity::<64>(&mut group, &probe_primary_params, &probe_cyclefold_params, num_steps)),
+        (256, bench_arity::<256>(&mut group, &probe_primary_params, &probe_cyclefold_params, num_steps)),
+        (1024, bench_arity::<1024>(&mut group, &probe_primary_params, &probe_cyclefold_params, num_steps)),
+    ];
+
+    group.finish();
+
+    let mut file = File::create("../../benchmark_results/halo2_wide_state_custom_cyclefold.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Num Vars  | Cyclefold Num Vars | Arity              | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-----------|--------------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (arity, (primary_num_vars, cyclefold_num_vars, duration, primary_size, secondary_size)) in results.iter() {
+        writeln!(
+            file,
+            "| {}         | {}         | {}                  | {}               | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, primary_num_vars, cyclefold_num_vars, arity, duration, primary_size, secondary_size
+        ).expect("Failed to write to file");
+    }
+}
+
+fn wide_state_protostar_cyclefold(c: &mut Criterion) {
+    bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c);
+}
+
+criterion_group!(benches, wide_state_protostar_cyclefold);
+criterion_main!(benches);
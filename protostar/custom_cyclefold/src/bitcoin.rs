@@ -0,0 +1,414 @@
+//! Bitcoin header chain for Protostar, the light-client workload: each step takes a configurable
+//! number of 80-byte block headers, checks that every header commits to the previous block hash,
+//! hashes it with double SHA-256 and checks that the hash, read as a little-endian integer, is at
+//! most the target encoded by the header's compact `nBits` field. The state is the tip hash, as
+//! two 128-bit big-endian halves of the raw digest, and the compact target, which is carried
+//! unchanged, so difficulty retargeting is out of scope.
+//!
+//! A header takes three compressions of the SHA-256 chip: two for the 80-byte header, the second
+//! one continuing from the digest of the first, and one for the 32-byte intermediate hash. The
+//! target comparison uses the lookups of the chip too: the `nBits` word and the hash words are
+//! byte-swapped by their dense conversions, the scale `256^(exponent - 3)` of the target is an
+//! extra row of the table, and `target - hash` is range checked to the bits of the largest target.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use crate::sha256::{
+    compress, compress_from, digest_to_scalars, pow, scalars_to_digest, to_digits, Map, Sha256Config, Tag, Word,
+    DIGEST_WORDS, WORD_BITS,
+};
+use halo2_proofs::circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner};
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error, Selector};
+use halo2_proofs::poly::Rotation;
+use num_bigint::BigUint;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+
+pub const HEADER_BYTES: usize = 80;
+pub const MIN_EXPONENT: usize = 3;
+pub const MAX_EXPONENT: usize = 29;
+// targets are below 2^TARGET_BITS, so are the hashes meeting them
+const TARGET_BITS: usize = 8 * MAX_EXPONENT;
+// the mantissa of nBits without its sign bit
+const MANTISSA_BITS: usize = 23;
+
+// position of nBits among the words of the second block
+const BITS_WORD: usize = 2;
+
+pub type Header = [u8; HEADER_BYTES];
+
+/// Bitcoin mainnet headers from the genesis block on, bundled so the workload runs offline.
+pub fn fixture() -> Vec<Header> {
+    include_str!("../../../fixtures/bitcoin_headers.txt")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let bytes = (0..line.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&line[i..i + 2], 16).expect("invalid hex"))
+                .collect::<Vec<_>>();
+            bytes.try_into().expect("a header is 80 bytes")
+        })
+        .collect()
+}
+
+fn words(bytes: &[u8]) -> Vec<u32> {
+    bytes.chunks(4).map(|word| u32::from_be_bytes(word.try_into().unwrap())).collect()
+}
+
+// the scale of the mantissa of a target of exponent `exponent`
+fn scale(exponent: usize) -> Fr {
+    pow(2, 8 * exponent.saturating_sub(MIN_EXPONENT))
+}
+
+/// Witness of a single header: the blocks of its three compressions and its hash.
+#[derive(Clone, Debug)]
+pub struct HeaderWitness {
+    pub blocks: [[u32; 16]; 3],
+    pub hash: [u32; DIGEST_WORDS],
+}
+
+impl HeaderWitness {
+    pub fn new(header: &Header) -> Self {
+        // the header spans a full block and a padded one of 16 bytes, the intermediate hash a
+        // padded block of 32 bytes
+        let first = words(&header[..64]).try_into().unwrap();
+        let mut second = [0u32; 16];
+        second[..4].copy_from_slice(&words(&header[64..]));
+        second[4] = 0x8000_0000;
+        second[15] = 8 * HEADER_BYTES as u32;
+        let digest = compress_from(compress(first), second);
+        let mut third = [0u32; 16];
+        third[..DIGEST_WORDS].copy_from_slice(&digest);
+        third[DIGEST_WORDS] = 0x8000_0000;
+        third[15] = 256;
+
+        Self {
+            blocks: [first, second, third],
+            hash: compress(third),
+        }
+    }
+
+    /// The compact target, as the little-endian integer of the header bytes.
+    pub fn bits(&self) -> u32 {
+        self.blocks[1][BITS_WORD].swap_bytes()
+    }
+
+    fn target(&self) -> BigUint {
+        let exponent = (self.bits() >> 24) as usize;
+        BigUint::from(self.bits() & 0x00ff_ffff) << (8 * exponent.saturating_sub(MIN_EXPONENT))
+    }
+
+    // the hash as a little-endian integer
+    fn hash_value(&self) -> BigUint {
+        BigUint::from_bytes_le(&self.hash.iter().flat_map(|word| word.to_be_bytes()).collect::<Vec<_>>())
+    }
+
+    /// Native reference of the target comparison.
+    pub fn meets_target(&self) -> bool {
+        let exponent = (self.bits() >> 24) as usize;
+        (MIN_EXPONENT..=MAX_EXPONENT).contains(&exponent)
+            && self.bits() & 0x0080_0000 == 0
+            && self.hash_value() <= self.target()
+    }
+
+    // the bits of target - hash, zero for a header that misses its target
+    fn slack(&self) -> Vec<u8> {
+        let slack = if self.meets_target() { self.target() - self.hash_value() } else { BigUint::from(0u32) };
+        (0..TARGET_BITS).map(|k| slack.bit(k as u64) as u8).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HeaderChainConfig {
+    sha256: Sha256Config,
+    target: Selector,
+}
+
+impl HeaderChainConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let sha256 = Sha256Config::configure(meta);
+        let target = meta.selector();
+
+        // the target is the mantissa times its scale, on the columns of the linear rows
+        meta.create_gate("target", |meta| {
+            let s = meta.query_selector(target);
+            let [mantissa, scale] = [0, 1].map(|j| meta.query_advice(sha256.terms[j], Rotation::cur()));
+            vec![s * (mantissa * scale - meta.query_advice(sha256.sum, Rotation::cur()))]
+        });
+
+        Self { sha256, target }
+    }
+
+    // checks the hash of `header` against the target of its nBits word, returning the cell of the
+    // compact target
+    fn assign_pow(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        header: &HeaderWitness,
+        hash: &[Word],
+        bits_word: &Word,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let sha256 = &self.sha256;
+        let (input, [bits, _]) = sha256.normalize(region, offset, Tag::Dense, &bits_word.digits, [&[Map::SwapBytes], &[]])?;
+        region.constrain_equal(input.cell(), bits_word.cell.cell())?;
+
+        // nBits is a mantissa with a clear sign bit below the exponent byte, whose scale is in the
+        // table for the supported exponents only
+        let (mantissa, exponent) = (header.bits() & 0x00ff_ffff, (header.bits() >> 24) as usize);
+        let (mantissa, _) = sha256.normalize(region, offset, Tag::Dense, &to_digits(mantissa as u64, MANTISSA_BITS), [&[], &[]])?;
+        let (exponent, scale) = sha256.lookup(region, offset, (Fr::from(exponent as u64), scale(exponent)))?;
+        let terms = [(Fr::ONE, &mantissa), (pow(2, 24), &exponent), (-Fr::ONE, &bits.cell)];
+        let difference = sha256.linear(region, offset, &terms, Fr::ZERO)?;
+        region.constrain_constant(difference.cell(), Fr::ZERO)?;
+
+        let row = *offset;
+        self.target.enable(region, row)?;
+        mantissa.copy_advice(|| "mantissa", region, sha256.terms[0], row)?;
+        scale.copy_advice(|| "scale", region, sha256.terms[1], row)?;
+        let target = region.assign_advice(|| "target", sha256.sum, row, || mantissa.value().copied() * scale.value().copied())?;
+        *offset += 1;
+
+        // the hash is a little-endian integer of the big-endian digest bytes, its bytes past the
+        // target size are zero and the rest is at most the target
+        let mut hash_le = Vec::with_capacity(DIGEST_WORDS);
+        for word in hash {
+            let (input, [word_le, _]) = sha256.normalize(region, offset, Tag::Dense, &word.digits, [&[Map::SwapBytes], &[]])?;
+            region.constrain_equal(input.cell(), word.cell.cell())?;
+            hash_le.push(word_le);
+        }
+        let last = &hash_le[DIGEST_WORDS - 1];
+        let last_bits = TARGET_BITS - (DIGEST_WORDS - 1) * WORD_BITS;
+        let (input, _) = sha256.normalize(region, offset, Tag::Dense, &last.digits[..last_bits], [&[], &[]])?;
+        region.constrain_equal(input.cell(), last.cell.cell())?;
+
+        let terms = hash_le.iter().enumerate().map(|(j, word)| (pow(2, WORD_BITS * j), &word.cell)).collect::<Vec<_>>();
+        let low = sha256.linear(region, offset, &terms[..5], Fr::ZERO)?;
+        let high = [&[(Fr::ONE, &low)][..], &terms[5..]].concat();
+        let hash_value = sha256.linear(region, offset, &high, Fr::ZERO)?;
+        let slack = sha256.linear(region, offset, &[(Fr::ONE, &target), (-Fr::ONE, &hash_value)], Fr::ZERO)?;
+        let (input, _) = sha256.normalize(region, offset, Tag::Dense, &header.slack(), [&[], &[]])?;
+        region.constrain_equal(input.cell(), slack.cell())?;
+
+        Ok(bits.cell)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HeaderChainCircuit {
+    pub num_headers_per_step: usize,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+    pub headers: Vec<HeaderWitness>,
+}
+
+impl HeaderChainCircuit {
+    // extends the chain ending at the input tip with `headers`
+    pub fn new(initial_input: Vec<Fr>, headers: &[Header]) -> Self {
+        let headers = headers.iter().map(HeaderWitness::new).collect::<Vec<_>>();
+        let mut output = digest_to_scalars(&headers.last().map_or(scalars_to_digest(&initial_input), |header| header.hash));
+        output.push(initial_input[2]);
+
+        Self {
+            num_headers_per_step: headers.len(),
+            step_idx: 0,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output,
+            headers,
+        }
+    }
+
+    /// The state before the genesis block, at the target of `header`.
+    pub fn genesis_input(header: &Header) -> Vec<Fr> {
+        let mut z = digest_to_scalars(&[0; DIGEST_WORDS]);
+        z.push(Fr::from(HeaderWitness::new(header).bits() as u64));
+        z
+    }
+}
+
+impl Circuit<Fr> for HeaderChainCircuit {
+    type Config = HeaderChainConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        HeaderChainConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fr> for HeaderChainCircuit {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C1> for HeaderChainCircuit {
+    fn arity() -> usize {
+        3
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        // the next headers of the fixture, which is finite: past its end the chain restarts from
+        // the genesis block, such a step doesn't extend the previous one and only keeps the
+        // circuit shape for runs longer than the fixture
+        let fixture = fixture();
+        let start = (self.step_idx + 1) * self.num_headers_per_step;
+        let (input, headers) = match fixture.get(start..start + self.num_headers_per_step) {
+            Some(headers) => (self.output.clone(), headers),
+            None => (Self::genesis_input(&fixture[0]), &fixture[..self.num_headers_per_step]),
+        };
+        *self = Self {
+            step_idx: self.step_idx + 1,
+            initial_input: self.initial_input.clone(),
+            ..Self::new(input, headers)
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        let sha256 = &config.sha256;
+        let scales = (MIN_EXPONENT..=MAX_EXPONENT).map(|e| (Fr::from(e as u64), scale(e))).collect::<Vec<_>>();
+        sha256.assign_table(&mut layouter, &scales)?;
+
+        layouter.assign_region(
+            || "header chain",
+            |mut region| {
+                let mut offset = 0;
+                let mut z_in = Vec::new();
+                let mut tip: Option<Vec<Word>> = None;
+                let mut bits: Option<AssignedCell<Fr, Fr>> = None;
+                for header in &self.headers {
+                    let (message, state) = sha256.assign_block(&mut region, &mut offset, None, &header.blocks[0], 16)?;
+
+                    // words 1..9 are the previous block hash, the tip of the state for the first header
+                    let prev_hash = &message[1..1 + DIGEST_WORDS];
+                    match &tip {
+                        Some(tip) => {
+                            for (word, tip_word) in prev_hash.iter().zip(tip) {
+                                region.constrain_equal(word.cell.cell(), tip_word.cell.cell())?;
+                            }
+                        }
+                        None => {
+                            z_in = prev_hash
+                                .chunks(4)
+                                .zip(&self.input)
+                                .map(|(words, half)| sha256.pack(&mut region, &mut offset, words, *half))
+                                .collect::<Result<Vec<_>, _>>()?;
+                        }
+                    }
+
+                    let (message, digest) = sha256.assign_block(&mut region, &mut offset, Some(&state), &header.blocks[1], 4)?;
+                    let (hash_message, hash) = sha256.assign_block(&mut region, &mut offset, None, &header.blocks[2], DIGEST_WORDS)?;
+                    for (word, digest_word) in hash_message.iter().zip(&digest) {
+                        region.constrain_equal(word.cell.cell(), digest_word.cell.cell())?;
+                    }
+
+                    // every header carries the compact target of the state
+                    let header_bits = config.assign_pow(&mut region, &mut offset, header, &hash, &message[BITS_WORD])?;
+                    match &bits {
+                        Some(bits) => region.constrain_equal(header_bits.cell(), bits.cell())?,
+                        None => bits = Some(header_bits),
+                    }
+                    tip = Some(hash);
+                }
+
+                let mut z_out = tip
+                    .expect("a step has at least one header")
+                    .chunks(4)
+                    .zip(&self.output)
+                    .map(|(words, half)| sha256.pack(&mut region, &mut offset, words, *half))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let bits = bits.unwrap();
+                z_in.push(bits.clone());
+                z_out.push(bits);
+                Ok((z_in, z_out))
+            },
+        )
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_bitcoin_preprocess(
+    num_headers_per_step: usize,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<HeaderChainCircuit>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let fixture = fixture();
+    let step_circuit = HeaderChainCircuit::new(HeaderChainCircuit::genesis_input(&fixture[0]), &fixture[..num_headers_per_step]);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn header_chain_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+
+    let headers = fixture();
+    assert!(headers.iter().map(HeaderWitness::new).all(|header| header.meets_target()));
+
+    // hashes are displayed byte-reversed
+    let genesis = HeaderWitness::new(&headers[0]).hash.iter().flat_map(|word| word.to_be_bytes()).rev();
+    let genesis = genesis.map(|byte| format!("{:02x}", byte)).collect::<String>();
+    assert_eq!(genesis, "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+
+    let mut circuit = HeaderChainCircuit::new(HeaderChainCircuit::genesis_input(&headers[0]), &headers[..2]);
+    MockProver::run(16, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // the chain continues from the previous tip
+    let output = circuit.output().to_vec();
+    circuit.next();
+    assert_eq!(circuit.input(), output.as_slice());
+    MockProver::run(16, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // a header that doesn't extend the tip
+    circuit.input = circuit.initial_input.clone();
+    assert!(MockProver::run(16, &circuit, Vec::new()).unwrap().verify().is_err());
+
+    // a header whose hash misses the target
+    let mut header = headers[0];
+    header[HEADER_BYTES - 1] ^= 1;
+    let circuit = HeaderChainCircuit::new(HeaderChainCircuit::genesis_input(&headers[0]), &[header]);
+    assert!(!circuit.headers[0].meets_target());
+    assert!(MockProver::run(16, &circuit, Vec::new()).unwrap().verify().is_err());
+}
@@ -0,0 +1,405 @@
+//! Lowering of the `circuit_dsl` step circuits to halo2: [`Halo2Builder`] assigns every operation
+//! to a row of the vanilla gate `q_l * a + q_r * b + q_m * a * b + q_c = q_o * c`, with its inputs
+//! copied from earlier cells, [`NativeBuilder`] computes the next state, and [`DslCircuit`] wraps
+//! a step as a Protostar step circuit. Poseidon is the `Pow5Chip` sponge of the hash chain
+//! workload, range checks use the bit decomposition of the DSL.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use crate::poseidon::{self, PoseidonConfig};
+use circuit_dsl::{Builder, Step};
+use halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::{Field, PrimeField};
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed};
+use halo2_proofs::poly::Rotation;
+use num_bigint::BigUint;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+
+fn to_biguint(x: &Fr) -> BigUint {
+    BigUint::from_bytes_le(x.to_repr().as_ref())
+}
+
+fn from_biguint(x: &BigUint) -> Fr {
+    Fr::from_str_vartime(&x.to_str_radix(10)).expect("value out of the field")
+}
+
+#[derive(Clone, Debug)]
+pub struct DslConfig {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    q_l: Column<Fixed>,
+    q_r: Column<Fixed>,
+    q_m: Column<Fixed>,
+    q_c: Column<Fixed>,
+    q_o: Column<Fixed>,
+    poseidon: PoseidonConfig,
+}
+
+impl DslConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let [a, b, c] = [(); 3].map(|_| meta.advice_column());
+        for column in [a, b, c] {
+            meta.enable_equality(column);
+        }
+        let [q_l, q_r, q_m, q_c, q_o] = [(); 5].map(|_| meta.fixed_column());
+
+        // free witnesses have all their selectors zero
+        meta.create_gate("vanilla", |meta| {
+            let [a, b, c] = [a, b, c].map(|column| meta.query_advice(column, Rotation::cur()));
+            let [q_l, q_r, q_m, q_c, q_o] = [q_l, q_r, q_m, q_c, q_o].map(|column| meta.query_fixed(column, Rotation::cur()));
+            vec![q_l * a.clone() + q_r * b.clone() + q_m * a * b + q_c - q_o * c]
+        });
+
+        Self { a, b, c, q_l, q_r, q_m, q_c, q_o, poseidon: poseidon::configure(meta) }
+    }
+}
+
+/// Assigns the operations of a step, one region per operation.
+pub struct Halo2Builder<'a, L: Layouter<Fr>> {
+    config: &'a DslConfig,
+    layouter: L,
+}
+
+impl<'a, L: Layouter<Fr>> Halo2Builder<'a, L> {
+    pub fn new(config: &'a DslConfig, layouter: L) -> Self {
+        Self { config, layouter }
+    }
+
+    // a row of the vanilla gate with selectors `[q_l, q_r, q_m, q_c, q_o]`, returns the cell of c
+    fn gate(
+        &mut self,
+        inputs: [Option<&AssignedCell<Fr, Fr>>; 2],
+        selectors: [Fr; 5],
+        c: Value<Fr>,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let config = self.config;
+        self.layouter.assign_region(
+            || "gate",
+            |mut region| {
+                let columns = [config.q_l, config.q_r, config.q_m, config.q_c, config.q_o];
+                for (column, value) in columns.into_iter().zip(selectors) {
+                    region.assign_fixed(|| "selector", column, 0, || Value::known(value))?;
+                }
+                for (column, input) in [(config.a, inputs[0]), (config.b, inputs[1])] {
+                    match input {
+                        Some(input) => input.copy_advice(|| "input", &mut region, column, 0)?,
+                        None => region.assign_advice(|| "unused", column, 0, || Value::known(Fr::ZERO))?,
+                    };
+                }
+                region.assign_advice(|| "c", config.c, 0, || c)
+            },
+        )
+    }
+
+    /// Allocates a free witness.
+    pub fn witness(&mut self, value: Value<Fr>) -> Result<AssignedCell<Fr, Fr>, Error> {
+        self.gate([None, None], [Fr::ZERO; 5], value)
+    }
+}
+
+impl<'a, L: Layouter<Fr>> Builder for Halo2Builder<'a, L> {
+    type Var = AssignedCell<Fr, Fr>;
+    type Error = Error;
+
+    fn modulus(&self) -> BigUint {
+        to_biguint(&-Fr::ONE) + 1u32
+    }
+
+    fn constant(&mut self, value: &BigUint) -> Result<Self::Var, Self::Error> {
+        let value = from_biguint(value);
+        self.gate([None, None], [Fr::ZERO, Fr::ZERO, Fr::ZERO, value, Fr::ONE], Value::known(value))
+    }
+
+    fn alloc(&mut self, value: impl FnOnce() -> BigUint) -> Result<Self::Var, Self::Error> {
+        self.witness(Value::known(from_biguint(&value())))
+    }
+
+    fn hint(
+        &mut self,
+        inputs: &[Self::Var],
+        f: impl FnOnce(&[BigUint]) -> BigUint,
+    ) -> Result<Self::Var, Self::Error> {
+        let values: Value<Vec<Fr>> = inputs.iter().map(|x| x.value().copied()).collect();
+        let value = values.map(|values| from_biguint(&f(&values.iter().map(to_biguint).collect::<Vec<_>>())));
+        self.witness(value)
+    }
+
+    fn add(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error> {
+        let value = a.value().copied() + b.value().copied();
+        self.gate([Some(a), Some(b)], [Fr::ONE, Fr::ONE, Fr::ZERO, Fr::ZERO, Fr::ONE], value)
+    }
+
+    fn mul(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error> {
+        let value = a.value().copied() * b.value().copied();
+        self.gate([Some(a), Some(b)], [Fr::ZERO, Fr::ZERO, Fr::ONE, Fr::ZERO, Fr::ONE], value)
+    }
+
+    fn enforce_eq(&mut self, a: &Self::Var, b: &Self::Var) -> Result<(), Self::Error> {
+        self.layouter.assign_region(|| "eq", |mut region| region.constrain_equal(a.cell(), b.cell()))
+    }
+
+    fn poseidon(&mut self, inputs: &[Self::Var]) -> Result<Self::Var, Self::Error> {
+        poseidon::hash_circuit(&self.config.poseidon, self.layouter.namespace(|| "poseidon"), inputs)
+    }
+}
+
+/// Runs the operations of a step on values, failing on an unsatisfied equality.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NativeBuilder;
+
+impl Builder for NativeBuilder {
+    type Var = Fr;
+    type Error = Error;
+
+    fn modulus(&self) -> BigUint {
+        to_biguint(&-Fr::ONE) + 1u32
+    }
+
+    fn constant(&mut self, value: &BigUint) -> Result<Fr, Error> {
+        Ok(from_biguint(value))
+    }
+
+    fn alloc(&mut self, value: impl FnOnce() -> BigUint) -> Result<Fr, Error> {
+        Ok(from_biguint(&value()))
+    }
+
+    fn hint(&mut self, inputs: &[Fr], f: impl FnOnce(&[BigUint]) -> BigUint) -> Result<Fr, Error> {
+        Ok(from_biguint(&f(&inputs.iter().map(to_biguint).collect::<Vec<_>>())))
+    }
+
+    fn add(&mut self, a: &Fr, b: &Fr) -> Result<Fr, Error> {
+        Ok(*a + b)
+    }
+
+    fn mul(&mut self, a: &Fr, b: &Fr) -> Result<Fr, Error> {
+        Ok(*a * b)
+    }
+
+    fn enforce_eq(&mut self, a: &Fr, b: &Fr) -> Result<(), Error> {
+        if a == b {
+            Ok(())
+        } else {
+            Err(Error::Synthesis)
+        }
+    }
+
+    fn poseidon(&mut self, inputs: &[Fr]) -> Result<Fr, Error> {
+        Ok(poseidon::hash(inputs))
+    }
+
+    fn range_check(&mut self, a: &Fr, num_bits: usize) -> Result<(), Error> {
+        if to_biguint(a).bits() <= num_bits as u64 {
+            Ok(())
+        } else {
+            Err(Error::Synthesis)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DslCircuit<S: Step> {
+    pub step: S,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+}
+
+impl<S: Step> DslCircuit<S> {
+    pub fn new(step: S, initial_input: Vec<Fr>) -> Self {
+        assert_eq!(initial_input.len(), S::ARITY);
+        let output = Self::run(&step, 0, &initial_input);
+
+        Self {
+            step,
+            step_idx: 0,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output,
+        }
+    }
+
+    fn run(step: &S, step_idx: usize, input: &[Fr]) -> Vec<Fr> {
+        step.synthesize(&mut NativeBuilder, step_idx, input)
+            .unwrap_or_else(|err| panic!("step {} is not satisfied: {:?}", step_idx, err))
+    }
+}
+
+impl<S: Step> Circuit<Fr> for DslCircuit<S> {
+    type Config = DslConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        DslConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl<S: Step> CircuitExt<Fr> for DslCircuit<S> {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl<S: Step> StepCircuit<C1> for DslCircuit<S> {
+    fn arity() -> usize {
+        S::ARITY
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        self.step_idx += 1;
+        self.input = self.output.clone();
+        self.output = Self::run(&self.step, self.step_idx, &self.input);
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        let mut builder = Halo2Builder::new(&config, layouter);
+        let z_in = self
+            .input
+            .iter()
+            .map(|z| builder.witness(Value::known(*z)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let z_out = self.step.synthesize(&mut builder, self.step_idx, &z_in)?;
+
+        Ok((z_in, z_out))
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_dsl_preprocess<S: Step>(
+    step: S,
+    initial_input: Vec<Fr>,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<DslCircuit<S>>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    run_protostar_hyperplonk_ivc_preprocess(
+        DslCircuit::new(step, initial_input),
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn dsl_step_circuit_test() {
+    use crate::minroot::{Degree2, MinRootCircuit};
+    use circuit_dsl::{hashchain::HashChain, minroot::MinRoot, range_check::RangeCheck};
+    use halo2_proofs::dev::MockProver;
+
+    // checks the output cells of the step against the native next state
+    #[derive(Clone)]
+    struct Checked<S: Step>(DslCircuit<S>);
+
+    impl<S: Step> Circuit<Fr> for Checked<S> {
+        type Config = DslConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            DslConfig::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+            let (_, z_out) = StepCircuit::<C1>::synthesize(&self.0, config, layouter)?;
+            assert_eq!(z_out.len(), self.0.output().len());
+            for (cell, z) in z_out.iter().zip(self.0.output()) {
+                cell.value().assert_if_known(|value| *value == z);
+            }
+            Ok(())
+        }
+    }
+
+    fn run<S: Step>(k: u32, step: S, initial_input: Vec<Fr>) -> DslCircuit<S> {
+        let mut circuit = DslCircuit::new(step, initial_input);
+        MockProver::run(k, &Checked(circuit.clone()), Vec::new()).unwrap().assert_satisfied();
+
+        // the chain continues from the previous output
+        let output = circuit.output().to_vec();
+        circuit.next();
+        assert_eq!(circuit.input(), output.as_slice());
+        MockProver::run(k, &Checked(circuit.clone()), Vec::new()).unwrap().assert_satisfied();
+        circuit
+    }
+
+    // the same chain as the hand-written MinRoot
+    let z0 = vec![Fr::ZERO, Fr::ZERO, Fr::ONE];
+    let minroot = run(9, MinRoot { num_iters_per_step: 16 }, z0.clone());
+    let mut expected = MinRootCircuit::<Degree2>::new(z0, 16);
+    expected.next();
+    assert_eq!(minroot.output(), expected.output());
+
+    let step = HashChain { num_elts_per_step: 5, seed: 7 };
+    let hashchain = run(10, step, vec![Fr::from(3)]);
+    let preimage = (0..5).map(|k| Fr::from(step.preimage(1, k)));
+    assert_eq!(hashchain.output()[0], poseidon::hash(&[hashchain.input().to_vec(), preimage.collect()].concat()));
+
+    run(12, RangeCheck { num_adds_per_step: 2 }, vec![Fr::from(u64::MAX)]);
+}
+
+#[test]
+fn dsl_baseline_test() {
+    use crate::hashchain::HashChainCircuit;
+    use crate::minroot::{Degree2, MinRootCircuit};
+    use crate::params::min_num_vars;
+    use crate::range_check::RangeCheckCircuit;
+    use circuit_dsl::{hashchain::HashChain, minroot::MinRoot, range_check::RangeCheck};
+
+    // the hand-written circuits stay as row-count baselines of the lowerings
+    let n = 512;
+    let z0 = vec![Fr::ZERO, Fr::ZERO, Fr::ONE];
+    let baseline = min_num_vars(&MinRootCircuit::<Degree2>::new(z0.clone(), n), Vec::new()).unwrap();
+    let dsl = min_num_vars(&DslCircuit::new(MinRoot { num_iters_per_step: n }, z0), Vec::new()).unwrap();
+    // one row per iteration against a row per operation
+    assert_eq!(baseline, 10);
+    assert_eq!(dsl, 12);
+
+    // both spend their rows in the same sponge
+    let n = 64;
+    let baseline = min_num_vars(&HashChainCircuit::new(vec![Fr::ZERO], n), Vec::new()).unwrap();
+    let step = HashChain { num_elts_per_step: n, seed: 0 };
+    let dsl = min_num_vars(&DslCircuit::new(step, vec![Fr::ZERO]), Vec::new()).unwrap();
+    assert!(baseline <= dsl && dsl <= baseline + 1);
+
+    // byte lookups against a bit decomposition
+    let n = 16;
+    let baseline = min_num_vars(&RangeCheckCircuit::new(vec![Fr::ZERO], 0, n), Vec::new()).unwrap();
+    let dsl = min_num_vars(&DslCircuit::new(RangeCheck { num_adds_per_step: n }, vec![Fr::ZERO]), Vec::new()).unwrap();
+    assert!(baseline < dsl);
+}
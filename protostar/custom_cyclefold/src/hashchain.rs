@@ -0,0 +1,197 @@
+//! Proves the knowledge of preimage to a hash chain tail, with a configurable number of elements
+//! per hash chain node. The output of each step tracks the current tail of the hash chain.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use crate::poseidon::{self, PoseidonConfig};
+use halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error};
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::util::test::seeded_std_rng;
+use std::iter;
+
+#[derive(Clone, Debug)]
+pub struct HashChainConfig {
+    preimage: Column<Advice>,
+    poseidon: PoseidonConfig,
+}
+
+#[derive(Clone, Debug)]
+pub struct HashChainCircuit {
+    pub num_elts_per_step: usize,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+    pub x_i: Vec<Fr>,
+}
+
+impl HashChainCircuit {
+    // produces a preimage to be hashed
+    pub fn new(initial_input: Vec<Fr>, num_elts_per_step: usize) -> Self {
+        let mut rng = seeded_std_rng();
+        let x_i = (0..num_elts_per_step)
+            .map(|_| Fr::random(&mut rng))
+            .collect::<Vec<_>>();
+        let output = vec![poseidon::hash(&[initial_input.as_slice(), &x_i].concat())];
+
+        Self {
+            num_elts_per_step,
+            step_idx: 0,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output,
+            x_i,
+        }
+    }
+}
+
+impl Circuit<Fr> for HashChainCircuit {
+    type Config = HashChainConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let preimage = meta.advice_column();
+        meta.enable_equality(preimage);
+
+        HashChainConfig {
+            preimage,
+            poseidon: poseidon::configure(meta),
+        }
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fr> for HashChainCircuit {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C1> for HashChainCircuit {
+    fn arity() -> usize {
+        1
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        // fresh preimage chained onto the current tail
+        *self = Self {
+            step_idx: self.step_idx + 1,
+            initial_input: self.initial_input.clone(),
+            ..Self::new(self.output.clone(), self.num_elts_per_step)
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        // z_in provides the running digest, x_i the preimage of this step
+        let preimage = layouter.assign_region(
+            || "preimage",
+            |mut region| {
+                iter::once(&self.input[0])
+                    .chain(self.x_i.iter())
+                    .enumerate()
+                    .map(|(offset, value)| {
+                        region.assign_advice(|| format!("m_{}", offset), config.preimage, offset, || Value::known(*value))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        let z_out = poseidon::hash_circuit(&config.poseidon, layouter.namespace(|| "hash"), &preimage)?;
+
+        Ok((vec![preimage[0].clone()], vec![z_out]))
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_hashchain_preprocess(
+    num_elts_per_step: usize,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<HashChainCircuit>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = HashChainCircuit::new(vec![Fr::ZERO], num_elts_per_step);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn hashchain_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::plonk::Instance;
+
+    // exposes the tail computed by the circuit
+    #[derive(Clone)]
+    struct Tail(HashChainCircuit);
+
+    impl Circuit<Fr> for Tail {
+        type Config = (HashChainConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (HashChainCircuit::configure(meta), instance)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+            let (_, z_out) = StepCircuit::<C1>::synthesize(&self.0, config.0, layouter.namespace(|| "step"))?;
+            layouter.constrain_instance(z_out[0].cell(), config.1, 0)
+        }
+    }
+
+    let mut circuit = HashChainCircuit::new(vec![Fr::from(3)], 5);
+    let tail = poseidon::hash(&[vec![Fr::from(3)], circuit.x_i.clone()].concat());
+    assert_eq!(circuit.output(), &[tail]);
+    MockProver::run(10, &Tail(circuit.clone()), vec![vec![tail]]).unwrap().assert_satisfied();
+
+    // the chain continues from the previous tail
+    circuit.next();
+    assert_eq!(circuit.input(), &[tail]);
+    let tail = circuit.output()[0];
+    MockProver::run(10, &Tail(circuit.clone()), vec![vec![tail]]).unwrap().assert_satisfied();
+
+    // a tail that doesn't hash the preimage
+    assert!(MockProver::run(10, &Tail(circuit), vec![vec![tail + Fr::ONE]]).unwrap().verify().is_err());
+}
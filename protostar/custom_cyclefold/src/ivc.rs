@@ -0,0 +1,74 @@
+//! Runs a local [`StepCircuit`] through plonkish's Protostar IVC with a CycleFold secondary
+//! circuit, using HyperPlonk over Gemini-KZG for the primary curve and IPA for the secondary.
+use halo2_proofs::halo2curves::{bn256::{self, Bn256}, grumpkin, CurveAffine};
+use plonkish_backend::accumulation::protostar::ivc::halo2::{
+    preprocess, prove_steps, CycleFoldCircuit, ProtostarIvcProverParam, ProtostarIvcVerifierParam,
+    RecursiveCircuit, StepCircuit,
+};
+use plonkish_backend::backend::hyperplonk::HyperPlonk;
+use plonkish_backend::frontend::halo2::Halo2Circuit;
+use plonkish_backend::pcs::multilinear::{Gemini, MultilinearIpa};
+use plonkish_backend::pcs::univariate::UnivariateKzg;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::util::test::seeded_std_rng;
+use plonkish_backend::util::transcript::PoseidonTranscript;
+use std::time::{Duration, Instant};
+
+pub type C1 = bn256::G1Affine;
+pub type C2 = grumpkin::G1Affine;
+pub type Fr = <C1 as CurveAffine>::ScalarExt;
+pub type Fq = <C1 as CurveAffine>::Base;
+pub type P1 = Gemini<UnivariateKzg<Bn256>>;
+pub type P2 = MultilinearIpa<C2>;
+
+pub type PrimaryCircuit<Sc> = Halo2Circuit<Fr, RecursiveCircuit<C1, Sc>>;
+pub type SecondaryCircuit = Halo2Circuit<Fq, CycleFoldCircuit<C2>>;
+pub type IvcProverParam = ProtostarIvcProverParam<C1, HyperPlonk<P1>, HyperPlonk<P2>>;
+pub type IvcVerifierParam = ProtostarIvcVerifierParam<C1, HyperPlonk<P1>, HyperPlonk<P2>>;
+
+/// Wraps `step_circuit` into the primary recursive circuit and preprocesses it together with
+/// the CycleFold circuit.
+pub fn run_protostar_hyperplonk_ivc_preprocess<Sc: StepCircuit<C1>>(
+    step_circuit: Sc,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<Sc>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    preprocess::<
+        C1,
+        HyperPlonk<P1>,
+        HyperPlonk<P2>,
+        Sc,
+        PoseidonTranscript<Fr, _>,
+        PoseidonTranscript<Fq, _>,
+    >(
+        primary_num_vars,
+        primary_param,
+        step_circuit,
+        cyclefold_num_vars,
+        cyclefold_param,
+        seeded_std_rng(),
+    )
+    .unwrap()
+}
+
+/// Proves `num_steps` IVC steps and returns the proving time.
+pub fn run_protostar_hyperplonk_ivc_prove<Sc: StepCircuit<C1>>(
+    primary_circuit: &mut PrimaryCircuit<Sc>,
+    secondary_circuit: &mut SecondaryCircuit,
+    ivc_pp: &IvcProverParam,
+    num_steps: usize,
+) -> Duration {
+    let start = Instant::now();
+    prove_steps::<
+        C1,
+        HyperPlonk<P1>,
+        HyperPlonk<P2>,
+        Sc,
+        PoseidonTranscript<Fr, _>,
+        PoseidonTranscript<Fq, _>,
+    >(ivc_pp, primary_circuit, secondary_circuit, num_steps, seeded_std_rng())
+    .unwrap();
+    start.elapsed()
+}
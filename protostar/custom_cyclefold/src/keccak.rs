@@ -0,0 +1,580 @@
+//! Keccak-f[1600] permutation chain for Protostar, the lookup-based counterpart of the bit
+//! decomposed Nova and Sonobe circuits. Each step applies the permutation a configurable number of
+//! times to the running state, carried across steps as 25 dense 64-bit lanes.
+//!
+//! Inside a step the lanes are kept in a sparse base-8 representation with one digit per bit, so
+//! the XORs of theta and the chi expression `3 - 2a + b - c` are linear combinations without
+//! carries. Lanes are normalized back to bits by looking up chunks of [`CHUNK_DIGITS`] digits in a
+//! single table, which maps every digit to its parity or to the chi output. Chunks never cross the
+//! rotation offset of the lane, so the rotated lane of rho is recomposed from the same chunks.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use halo2_proofs::circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::{Field, PrimeField};
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Selector, TableColumn};
+use halo2_proofs::poly::Rotation;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use std::ops::RangeInclusive;
+
+pub const NUM_LANES: usize = 25;
+pub const LANE_BITS: usize = 64;
+pub const CHUNK_DIGITS: usize = 4;
+
+const SPARSE_BASE: u64 = 8;
+const NUM_TERMS: usize = 5;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+    0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+    0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+// rotation offsets of the lane (x, y), at index x + 5y
+const RHO: [usize; NUM_LANES] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+// lane (x, y) moves to (y, 2x + 3y) in the pi step
+fn pi(x: usize, y: usize) -> usize {
+    y + 5 * ((2 * x + 3 * y) % 5)
+}
+
+// native reference, the lane (x, y) is at index x + 5y
+pub fn keccak_f(mut state: [u64; NUM_LANES]) -> [u64; NUM_LANES] {
+    for rc in ROUND_CONSTANTS {
+        let c: [u64; 5] = std::array::from_fn(|x| (0..5).fold(0, |acc, y| acc ^ state[x + 5 * y]));
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+
+        let mut b = [0u64; NUM_LANES];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[pi(x, y)] = state[x + 5 * y].rotate_left(RHO[x + 5 * y] as u32);
+            }
+        }
+
+        for x in 0..5 {
+            for y in 0..5 {
+                state[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+        state[0] ^= rc;
+    }
+    state
+}
+
+// every lane always fits in the lowest 8 bytes of its representation
+fn to_u64(value: Fr) -> u64 {
+    u64::from_le_bytes(value.to_repr()[..8].try_into().unwrap())
+}
+
+type Digits = [u8; LANE_BITS];
+
+fn to_digits(value: u64) -> Digits {
+    std::array::from_fn(|i| ((value >> i) & 1) as u8)
+}
+
+fn compose(digits: &[u8], base: u64) -> Fr {
+    digits.iter().rev().fold(Fr::ZERO, |acc, digit| acc * Fr::from(base) + Fr::from(*digit as u64))
+}
+
+fn pow(base: u64, exp: usize) -> Fr {
+    Fr::from(base).pow_vartime([exp as u64])
+}
+
+/// Kind of normalization performed by a looked up chunk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tag {
+    FromDense = 1,
+    Parity,
+    Chi,
+    ToDense,
+}
+
+const TAGS: [Tag; 4] = [Tag::FromDense, Tag::Parity, Tag::Chi, Tag::ToDense];
+
+impl Tag {
+    // base of the input digits, largest input digit and base of the output digits
+    fn bases(self) -> (u64, u8, u64) {
+        match self {
+            Tag::FromDense => (2, 1, SPARSE_BASE),
+            Tag::Parity => (SPARSE_BASE, 7, SPARSE_BASE),
+            Tag::Chi => (SPARSE_BASE, 4, SPARSE_BASE),
+            Tag::ToDense => (SPARSE_BASE, 3, 2),
+        }
+    }
+
+    // chi maps the digit 3 - 2a + b - c to a ^ (!b & c), every other tag keeps its parity
+    fn apply(self, digit: u8) -> u8 {
+        match self {
+            Tag::Chi => [0, 1, 1, 0, 0][digit as usize],
+            _ => digit & 1,
+        }
+    }
+
+    // only the parity is normalized at rotation offsets, which leave shorter chunks
+    fn chunk_sizes(self) -> RangeInclusive<usize> {
+        match self {
+            Tag::Parity => 1..=CHUNK_DIGITS,
+            _ => CHUNK_DIGITS..=CHUNK_DIGITS,
+        }
+    }
+}
+
+// (start, size) of the chunks of a lane, split where the digits wrap around in a left rotation
+fn chunks(rotation: usize) -> Vec<(usize, usize)> {
+    let boundary = (LANE_BITS - rotation) % LANE_BITS;
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < LANE_BITS {
+        let mut end = (start + CHUNK_DIGITS).min(LANE_BITS);
+        if start < boundary && boundary < end {
+            end = boundary;
+        }
+        chunks.push((start, end - start));
+        start = end;
+    }
+    chunks
+}
+
+/// An assigned lane together with its digits, in the base of the context it is used in.
+#[derive(Clone, Debug)]
+struct Lane {
+    cell: AssignedCell<Fr, Fr>,
+    digits: Digits,
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakConfig {
+    chunk_in: Column<Advice>,
+    chunk_out: Column<Advice>,
+    acc_in: Column<Advice>,
+    acc_out: Column<Advice>,
+    acc_rot: Column<Advice>,
+    tag: Column<Fixed>,
+    size: Column<Fixed>,
+    coef_in: Column<Fixed>,
+    coef_out: Column<Fixed>,
+    coef_rot: Column<Fixed>,
+    terms: [Column<Advice>; NUM_TERMS],
+    coefs: [Column<Fixed>; NUM_TERMS],
+    constant: Column<Fixed>,
+    sum: Column<Advice>,
+    table: [TableColumn; 4],
+    first: Selector,
+    next: Selector,
+    lookup: Selector,
+    linear: Selector,
+}
+
+impl KeccakConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let [chunk_in, chunk_out, acc_in, acc_out, acc_rot, sum] = [(); 6].map(|_| meta.advice_column());
+        let terms = [(); NUM_TERMS].map(|_| meta.advice_column());
+        for column in [acc_in, acc_out, acc_rot, sum].into_iter().chain(terms) {
+            meta.enable_equality(column);
+        }
+        let [tag, size, coef_in, coef_out, coef_rot, constant] = [(); 6].map(|_| meta.fixed_column());
+        let coefs = [(); NUM_TERMS].map(|_| meta.fixed_column());
+        let table = [(); 4].map(|_| meta.lookup_table_column());
+        let [first, next, linear] = [(); 3].map(|_| meta.selector());
+        // lookup inputs can't use simple selectors, disabled rows look up zero
+        let lookup = meta.complex_selector();
+
+        meta.lookup("normalize chunk", |meta| {
+            let s = meta.query_selector(lookup);
+            let [tag, size] = [tag, size].map(|column| meta.query_fixed(column, Rotation::cur()));
+            let [chunk_in, chunk_out] = [chunk_in, chunk_out].map(|column| meta.query_advice(column, Rotation::cur()));
+            [tag, size, chunk_in, chunk_out]
+                .into_iter()
+                .zip(table)
+                .map(|(input, column)| (s.clone() * input, column))
+                .collect()
+        });
+
+        // the accumulators recompose the chunks from the least significant one
+        meta.create_gate("normalize", |meta| {
+            let [first, next] = [first, next].map(|selector| meta.query_selector(selector));
+            let mut constraints = Vec::new();
+            for (acc, chunk, coef) in [(acc_in, chunk_in, coef_in), (acc_out, chunk_out, coef_out), (acc_rot, chunk_out, coef_rot)] {
+                let [acc_cur, acc_prev] = [0, -1].map(|rotation| meta.query_advice(acc, Rotation(rotation)));
+                let term = meta.query_fixed(coef, Rotation::cur()) * meta.query_advice(chunk, Rotation::cur());
+                constraints.push(first.clone() * (term.clone() - acc_cur.clone()));
+                constraints.push(next.clone() * (acc_prev + term - acc_cur));
+            }
+            constraints
+        });
+
+        meta.create_gate("linear", |meta| {
+            let s = meta.query_selector(linear);
+            let constant = meta.query_fixed(constant, Rotation::cur());
+            let combination = terms.iter().zip(coefs).fold(constant, |acc, (term, coef)| {
+                acc + meta.query_fixed(coef, Rotation::cur()) * meta.query_advice(*term, Rotation::cur())
+            });
+            vec![s * (combination - meta.query_advice(sum, Rotation::cur()))]
+        });
+
+        Self {
+            chunk_in,
+            chunk_out,
+            acc_in,
+            acc_out,
+            acc_rot,
+            tag,
+            size,
+            coef_in,
+            coef_out,
+            coef_rot,
+            terms,
+            coefs,
+            constant,
+            sum,
+            table,
+            first,
+            next,
+            lookup,
+            linear,
+        }
+    }
+
+    fn assign_table(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
+        // disabled rows look up the all-zero row, which also pads the rest of the table
+        let mut rows = vec![[Fr::ZERO; 4]];
+        for tag in TAGS {
+            let (base_in, max_digit, base_out) = tag.bases();
+            let radix = max_digit as usize + 1;
+            for size in tag.chunk_sizes() {
+                for index in 0..radix.pow(size as u32) {
+                    let digits = (0..size).map(|k| (index / radix.pow(k as u32) % radix) as u8).collect::<Vec<_>>();
+                    let out = digits.iter().map(|digit| tag.apply(*digit)).collect::<Vec<_>>();
+                    rows.push([Fr::from(tag as u64), Fr::from(size as u64), compose(&digits, base_in), compose(&out, base_out)]);
+                }
+            }
+        }
+
+        layouter.assign_table(
+            || "keccak table",
+            |mut table| {
+                for (offset, row) in rows.iter().enumerate() {
+                    for (column, value) in self.table.iter().zip(row) {
+                        table.assign_cell(|| "keccak table", *column, offset, || Value::known(*value))?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // looks up the chunks of `digits` from `offset` on, returning the recomposed input, the
+    // normalized lane and the normalized lane rotated left by `rotation`
+    fn normalize(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        tag: Tag,
+        digits: &Digits,
+        rotation: usize,
+    ) -> Result<(AssignedCell<Fr, Fr>, Lane, Lane), Error> {
+        let (base_in, _, base_out) = tag.bases();
+        let out = digits.map(|digit| tag.apply(digit));
+        let rotated = std::array::from_fn(|i| out[(i + LANE_BITS - rotation) % LANE_BITS]);
+
+        let (mut acc_in, mut acc_out, mut acc_rot) = (Fr::ZERO, Fr::ZERO, Fr::ZERO);
+        let mut accs = Vec::new();
+        for (start, size) in chunks(rotation) {
+            let row = *offset;
+            self.lookup.enable(region, row)?;
+            if start == 0 {
+                self.first.enable(region, row)?;
+            } else {
+                self.next.enable(region, row)?;
+            }
+
+            let coef_in = pow(base_in, start);
+            let coef_out = pow(base_out, start);
+            let coef_rot = pow(base_out, (start + rotation) % LANE_BITS);
+            let chunk_in = compose(&digits[start..start + size], base_in);
+            let chunk_out = compose(&out[start..start + size], base_out);
+            acc_in += coef_in * chunk_in;
+            acc_out += coef_out * chunk_out;
+            acc_rot += coef_rot * chunk_out;
+
+            let fixed = [
+                (self.tag, Fr::from(tag as u64)),
+                (self.size, Fr::from(size as u64)),
+                (self.coef_in, coef_in),
+                (self.coef_out, coef_out),
+                (self.coef_rot, coef_rot),
+            ];
+            for (column, value) in fixed {
+                region.assign_fixed(|| "normalize", column, row, || Value::known(value))?;
+            }
+            region.assign_advice(|| "chunk_in", self.chunk_in, row, || Value::known(chunk_in))?;
+            region.assign_advice(|| "chunk_out", self.chunk_out, row, || Value::known(chunk_out))?;
+            accs = [(self.acc_in, acc_in), (self.acc_out, acc_out), (self.acc_rot, acc_rot)]
+                .into_iter()
+                .map(|(column, value)| region.assign_advice(|| "acc", column, row, || Value::known(value)))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            *offset += 1;
+        }
+
+        let [input, output, rot]: [AssignedCell<Fr, Fr>; 3] = accs.try_into().unwrap();
+        Ok((input, Lane { cell: output, digits: out }, Lane { cell: rot, digits: rotated }))
+    }
+
+    fn normalize_lane(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        tag: Tag,
+        lane: &Lane,
+        rotation: usize,
+    ) -> Result<(Lane, Lane), Error> {
+        let (input, output, rotated) = self.normalize(region, offset, tag, &lane.digits, rotation)?;
+        region.constrain_equal(input.cell(), lane.cell.cell())?;
+        Ok((output, rotated))
+    }
+
+    // a single row with `sum_i coef_i * lane_i + constant` of sparse lanes, the digits of the
+    // combination must stay below the sparse base so that no carries occur
+    fn linear(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        terms: &[(i64, &Lane)],
+        constant: &Digits,
+    ) -> Result<Lane, Error> {
+        let row = *offset;
+        self.linear.enable(region, row)?;
+
+        let mut digits = constant.map(|digit| digit as i64);
+        for j in 0..NUM_TERMS {
+            let coef = match terms.get(j) {
+                Some((coef, lane)) => {
+                    lane.cell.copy_advice(|| "term", region, self.terms[j], row)?;
+                    for (digit, term) in digits.iter_mut().zip(lane.digits) {
+                        *digit += coef * term as i64;
+                    }
+                    *coef
+                }
+                None => {
+                    region.assign_advice(|| "term", self.terms[j], row, || Value::known(Fr::ZERO))?;
+                    0
+                }
+            };
+            let coef = if coef < 0 { -Fr::from(coef.unsigned_abs()) } else { Fr::from(coef as u64) };
+            region.assign_fixed(|| "coef", self.coefs[j], row, || Value::known(coef))?;
+        }
+        region.assign_fixed(|| "constant", self.constant, row, || Value::known(compose(constant, SPARSE_BASE)))?;
+
+        let digits = digits.map(|digit| {
+            debug_assert!((0..SPARSE_BASE as i64).contains(&digit));
+            digit as u8
+        });
+        let cell = region.assign_advice(|| "sum", self.sum, row, || Value::known(compose(&digits, SPARSE_BASE)))?;
+        *offset += 1;
+
+        Ok(Lane { cell, digits })
+    }
+
+    // a permutation of the sparse state, lane (x, y) at index x + 5y
+    fn assign_permutation(&self, region: &mut Region<'_, Fr>, offset: &mut usize, mut a: Vec<Lane>) -> Result<Vec<Lane>, Error> {
+        for rc in ROUND_CONSTANTS {
+            // theta, the column parities are normalized together with their rotation by one
+            let mut c = Vec::with_capacity(5);
+            for x in 0..5 {
+                let column = (0..5).map(|y| (1, &a[x + 5 * y])).collect::<Vec<_>>();
+                let sum = self.linear(region, offset, &column, &[0; LANE_BITS])?;
+                c.push(self.normalize_lane(region, offset, Tag::Parity, &sum, 1)?);
+            }
+
+            // rho and pi come with the normalization of the theta output
+            let mut b = vec![None; NUM_LANES];
+            for x in 0..5 {
+                for y in 0..5 {
+                    let terms = [(1, &a[x + 5 * y]), (1, &c[(x + 4) % 5].0), (1, &c[(x + 1) % 5].1)];
+                    let theta = self.linear(region, offset, &terms, &[0; LANE_BITS])?;
+                    let (_, rotated) = self.normalize_lane(region, offset, Tag::Parity, &theta, RHO[x + 5 * y])?;
+                    b[pi(x, y)] = Some(rotated);
+                }
+            }
+            let b = b.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+
+            // chi
+            a = (0..NUM_LANES)
+                .map(|i| {
+                    let (x, y) = (i % 5, i / 5);
+                    let terms = [(-2, &b[i]), (1, &b[(x + 1) % 5 + 5 * y]), (-1, &b[(x + 2) % 5 + 5 * y])];
+                    let chi = self.linear(region, offset, &terms, &[3; LANE_BITS])?;
+                    Ok(self.normalize_lane(region, offset, Tag::Chi, &chi, 0)?.0)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            // iota leaves digits up to 2 in the first lane, the next normalization takes care of them
+            a[0] = self.linear(region, offset, &[(1, &a[0])], &to_digits(rc))?;
+        }
+
+        Ok(a)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KeccakChainCircuit {
+    pub num_perms_per_step: usize,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+}
+
+impl KeccakChainCircuit {
+    // permutes the input state `num_perms_per_step` times
+    pub fn new(initial_input: Vec<Fr>, num_perms_per_step: usize) -> Self {
+        let state = std::array::from_fn(|l| to_u64(initial_input[l]));
+        let state = (0..num_perms_per_step).fold(state, |state, _| keccak_f(state));
+
+        Self {
+            num_perms_per_step,
+            step_idx: 0,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output: state.map(Fr::from).to_vec(),
+        }
+    }
+}
+
+impl Circuit<Fr> for KeccakChainCircuit {
+    type Config = KeccakConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        KeccakConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fr> for KeccakChainCircuit {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C1> for KeccakChainCircuit {
+    fn arity() -> usize {
+        NUM_LANES
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        // the chain continues from the current state
+        *self = Self {
+            step_idx: self.step_idx + 1,
+            initial_input: self.initial_input.clone(),
+            ..Self::new(self.output.clone(), self.num_perms_per_step)
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        config.assign_table(&mut layouter)?;
+
+        layouter.assign_region(
+            || "keccak",
+            |mut region| {
+                let mut offset = 0;
+
+                // the dense input lanes are the recomposed inputs of their sparse conversion
+                let (mut z_in, mut state) = (Vec::new(), Vec::new());
+                for lane in self.input.iter() {
+                    let (input, sparse, _) = config.normalize(&mut region, &mut offset, Tag::FromDense, &to_digits(to_u64(*lane)), 0)?;
+                    z_in.push(input);
+                    state.push(sparse);
+                }
+
+                for _ in 0..self.num_perms_per_step {
+                    state = config.assign_permutation(&mut region, &mut offset, state)?;
+                }
+
+                let z_out = state
+                    .iter()
+                    .map(|lane| Ok(config.normalize_lane(&mut region, &mut offset, Tag::ToDense, lane, 0)?.0.cell))
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                Ok((z_in, z_out))
+            },
+        )
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_keccak_preprocess(
+    num_perms_per_step: usize,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<KeccakChainCircuit>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = KeccakChainCircuit::new(vec![Fr::ZERO; NUM_LANES], num_perms_per_step);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn keccak_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+
+    let mut circuit = KeccakChainCircuit::new(vec![Fr::ZERO; NUM_LANES], 1);
+    MockProver::run(15, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // Keccak-f[1600] of the all-zero state
+    assert_eq!(circuit.output()[0], Fr::from(0xF1258F7940E1DDE7));
+    assert_eq!(circuit.output()[1], Fr::from(0x84D5CCF933C0478A));
+
+    // the chain continues from the previous output
+    let output = circuit.output().to_vec();
+    circuit.next();
+    assert_eq!(circuit.input(), output.as_slice());
+    MockProver::run(15, &circuit, Vec::new()).unwrap().assert_satisfied();
+}
@@ -0,0 +1,16 @@
+pub mod bitcoin;
+pub mod dsl;
+pub mod hashchain;
+pub mod ivc;
+pub mod keccak;
+pub mod matvec;
+pub mod merkle;
+pub mod minroot;
+pub mod monolithic;
+pub mod params;
+pub mod poseidon;
+pub mod random_circuit;
+pub mod range_check;
+pub mod scalar_mul;
+pub mod sha256;
+pub mod wide_state;
@@ -0,0 +1,333 @@
+//! Fixed-point dense layer chain for Protostar, the lookup-based counterpart of the Nova and
+//! Sonobe bit decompositions: each step applies one layer `y = ReLU((W * x + b) >> FRAC_BITS)`
+//! of width `WIDTH` to the state `x`, with the weights and biases of the layer as witnesses.
+//! Every neuron accumulates its dot product over `WIDTH` multiply-accumulate rows, then a single
+//! row splits the result as `q * 2^FRAC_BITS + r` and looks `(q, ReLU(q))` and `r` up in a table.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::{Field, PrimeField};
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector, TableColumn};
+use halo2_proofs::poly::Rotation;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::util::test::seeded_std_rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+pub const FRAC_BITS: usize = 8;
+// pre-activations live in [-2^(VALUE_BITS - 1), 2^(VALUE_BITS - 1))
+pub const VALUE_BITS: usize = 16;
+
+// table rows are tagged with the function they tabulate, the all-zero row is tagged 0
+const TAG_RELU: u64 = 1;
+const TAG_RANGE: u64 = 2;
+
+fn to_field(value: i64) -> Fr {
+    if value < 0 {
+        -Fr::from(value.unsigned_abs())
+    } else {
+        Fr::from(value as u64)
+    }
+}
+
+// activations are non-negative and fit in the lowest 8 bytes of their representation
+fn to_i64(value: Fr) -> i64 {
+    u64::from_le_bytes(value.to_repr()[..8].try_into().unwrap()) as i64
+}
+
+/// A random layer input, the activations of a previous layer are non-negative.
+pub fn random_input(width: usize, rng: &mut impl Rng) -> Vec<Fr> {
+    (0..width).map(|_| Fr::from(rng.gen_range(0..1 << (VALUE_BITS - 2)))).collect()
+}
+
+/// Rescaled pre-activations and the remainders of the rescaling, `W * x + b = q * 2^FRAC_BITS + r`.
+pub fn pre_activations(weights: &[Vec<i64>], bias: &[i64], input: &[i64]) -> (Vec<i64>, Vec<i64>) {
+    weights
+        .iter()
+        .zip(bias)
+        .map(|(row, b)| {
+            let acc = row.iter().zip(input).map(|(w, x)| w * x).sum::<i64>() + b;
+            (acc.div_euclid(1 << FRAC_BITS), acc.rem_euclid(1 << FRAC_BITS))
+        })
+        .unzip()
+}
+
+#[derive(Clone, Debug)]
+pub struct DenseLayerConfig {
+    x: Column<Advice>,
+    w: Column<Advice>,
+    acc: Column<Advice>,
+    q: Column<Advice>,
+    r: Column<Advice>,
+    y: Column<Advice>,
+    table: [TableColumn; 3],
+    mac: Selector,
+    activation: Selector,
+}
+
+impl DenseLayerConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let [x, w, acc, q, r, y] = [(); 6].map(|_| meta.advice_column());
+        for column in [x, y] {
+            meta.enable_equality(column);
+        }
+        let table = [(); 3].map(|_| meta.lookup_table_column());
+        let mac = meta.selector();
+        // lookup inputs can't use simple selectors, disabled rows look up zero
+        let activation = meta.complex_selector();
+
+        meta.create_gate("multiply-accumulate", |meta| {
+            let s = meta.query_selector(mac);
+            let [x, w, acc_cur] = [x, w, acc].map(|column| meta.query_advice(column, Rotation::cur()));
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            vec![s * (acc_cur + w * x - acc_next)]
+        });
+
+        meta.create_gate("rescale", |meta| {
+            let s = meta.query_selector(activation);
+            let [acc, q, r] = [acc, q, r].map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![s * (q * Expression::Constant(Fr::from(1 << FRAC_BITS)) + r - acc)]
+        });
+
+        meta.lookup("relu", |meta| {
+            let s = meta.query_selector(activation);
+            let [q, y] = [q, y].map(|column| meta.query_advice(column, Rotation::cur()));
+            [Expression::Constant(Fr::from(TAG_RELU)), q, y]
+                .into_iter()
+                .zip(table)
+                .map(|(input, column)| (s.clone() * input, column))
+                .collect()
+        });
+
+        meta.lookup("remainder range", |meta| {
+            let s = meta.query_selector(activation);
+            let r = meta.query_advice(r, Rotation::cur());
+            [Expression::Constant(Fr::from(TAG_RANGE)), r.clone(), r]
+                .into_iter()
+                .zip(table)
+                .map(|(input, column)| (s.clone() * input, column))
+                .collect()
+        });
+
+        Self {
+            x,
+            w,
+            acc,
+            q,
+            r,
+            y,
+            table,
+            mac,
+            activation,
+        }
+    }
+
+    fn assign_table(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
+        // disabled rows look up the all-zero row, which also pads the rest of the table
+        let mut rows = vec![[Fr::ZERO; 3]];
+        let half = 1i64 << (VALUE_BITS - 1);
+        rows.extend((-half..half).map(|q| [Fr::from(TAG_RELU), to_field(q), to_field(q.max(0))]));
+        rows.extend((0..1 << FRAC_BITS).map(|r| [Fr::from(TAG_RANGE), Fr::from(r), Fr::from(r)]));
+
+        layouter.assign_table(
+            || "activation table",
+            |mut table| {
+                for (offset, row) in rows.iter().enumerate() {
+                    for (column, value) in self.table.iter().zip(row) {
+                        table.assign_cell(|| "activation table", *column, offset, || Value::known(*value))?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DenseLayerCircuit<const WIDTH: usize> {
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+    pub weights: Vec<Vec<i64>>,
+    pub bias: Vec<i64>,
+}
+
+impl<const WIDTH: usize> DenseLayerCircuit<WIDTH> {
+    // the layer of step `step_idx`, with weights drawn from a rng seeded with it, applied to
+    // `initial_input`
+    pub fn new(initial_input: Vec<Fr>, step_idx: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(step_idx as u64);
+        // the weights of a row sum to at most a half in absolute value, so activations stay in range
+        let bound = ((1 << (FRAC_BITS - 1)) / WIDTH as i64).max(1);
+        let weights = (0..WIDTH)
+            .map(|_| (0..WIDTH).map(|_| rng.gen_range(-bound..=bound)).collect())
+            .collect();
+        let bias = (0..WIDTH)
+            .map(|_| rng.gen_range(-(1 << (2 * FRAC_BITS))..=1 << (2 * FRAC_BITS)))
+            .collect();
+
+        let mut circuit = Self {
+            step_idx,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output: Vec::new(),
+            weights,
+            bias,
+        };
+        circuit.output = circuit.activations().0.into_iter().map(|q| to_field(q.max(0))).collect();
+        circuit
+    }
+
+    fn activations(&self) -> (Vec<i64>, Vec<i64>) {
+        let input = self.input.iter().map(|x| to_i64(*x)).collect::<Vec<_>>();
+        pre_activations(&self.weights, &self.bias, &input)
+    }
+}
+
+impl<const WIDTH: usize> Circuit<Fr> for DenseLayerCircuit<WIDTH> {
+    type Config = DenseLayerConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        DenseLayerConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl<const WIDTH: usize> CircuitExt<Fr> for DenseLayerCircuit<WIDTH> {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl<const WIDTH: usize> StepCircuit<C1> for DenseLayerCircuit<WIDTH> {
+    fn arity() -> usize {
+        WIDTH
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        // the next layer applied to the current activations
+        *self = Self {
+            initial_input: self.initial_input.clone(),
+            ..Self::new(self.output.clone(), self.step_idx + 1)
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        config.assign_table(&mut layouter)?;
+        let (q, r) = self.activations();
+
+        layouter.assign_region(
+            || "dense layer",
+            |mut region| {
+                // z_in provides the layer input
+                let z_in = self
+                    .input
+                    .iter()
+                    .enumerate()
+                    .map(|(j, x)| region.assign_advice(|| "x", config.x, j, || Value::known(*x)))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let mut z_out = Vec::with_capacity(WIDTH);
+                for i in 0..WIDTH {
+                    // the accumulator starts from the bias and takes one row per input
+                    let start = WIDTH + i * (WIDTH + 1);
+                    let mut acc = to_field(self.bias[i]);
+                    region.assign_advice(|| "acc", config.acc, start, || Value::known(acc))?;
+                    for (j, x) in z_in.iter().enumerate() {
+                        let offset = start + j;
+                        config.mac.enable(&mut region, offset)?;
+                        let w = to_field(self.weights[i][j]);
+                        x.copy_advice(|| "x", &mut region, config.x, offset)?;
+                        region.assign_advice(|| "w", config.w, offset, || Value::known(w))?;
+
+                        acc += w * self.input[j];
+                        region.assign_advice(|| "acc", config.acc, offset + 1, || Value::known(acc))?;
+                    }
+
+                    let offset = start + WIDTH;
+                    config.activation.enable(&mut region, offset)?;
+                    region.assign_advice(|| "q", config.q, offset, || Value::known(to_field(q[i])))?;
+                    region.assign_advice(|| "r", config.r, offset, || Value::known(to_field(r[i])))?;
+                    z_out.push(region.assign_advice(|| "y", config.y, offset, || Value::known(to_field(q[i].max(0))))?);
+                }
+
+                Ok((z_in, z_out))
+            },
+        )
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_matvec_preprocess<const WIDTH: usize>(
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<DenseLayerCircuit<WIDTH>>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = DenseLayerCircuit::<WIDTH>::new(random_input(WIDTH, &mut seeded_std_rng()), 0);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn dense_layer_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+
+    // the activation table takes 2^VALUE_BITS rows
+    let k = VALUE_BITS as u32 + 1;
+    let mut circuit = DenseLayerCircuit::<4>::new(random_input(4, &mut seeded_std_rng()), 0);
+    MockProver::run(k, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // the chain continues from the previous activations
+    let output = circuit.output().to_vec();
+    circuit.next();
+    assert_eq!(circuit.input(), output.as_slice());
+    MockProver::run(k, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // negative pre-activations are clamped
+    circuit.bias[0] = -(1 << (VALUE_BITS + FRAC_BITS - 2));
+    assert_eq!(circuit.activations().0[0].max(0), 0);
+    MockProver::run(k, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // an activation out of the fixed-point range is not in the table
+    circuit.bias[0] = 1 << (VALUE_BITS + FRAC_BITS);
+    assert!(MockProver::run(k, &circuit, Vec::new()).unwrap().verify().is_err());
+}
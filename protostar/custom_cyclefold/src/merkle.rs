@@ -0,0 +1,323 @@
+//! Merkle tree update chain for Protostar, the state transition of a rollup: each step updates a
+//! configurable number of leaves of a Poseidon Merkle tree of depth `d` and outputs the new root.
+//! Every update opens the old leaf against the current root and recomputes the root from the new
+//! leaf along the same authentication path. A level of both paths takes a single swap row, which
+//! orders the two children by the bit of the leaf index, followed by the two node hashes.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use crate::poseidon::{self, PoseidonConfig};
+use circuit_dsl::mix;
+use halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector};
+use halo2_proofs::poly::Rotation;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use std::collections::HashMap;
+
+/// Sparse Poseidon Merkle tree with zero leaves, only the nodes that differ from an empty subtree
+/// are stored. Level 0 holds the leaves and level `depth` the root.
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    pub depth: usize,
+    nodes: HashMap<(usize, u64), Fr>,
+    // root of an empty subtree at each level
+    empty: Vec<Fr>,
+}
+
+/// Advice of a single leaf update, the siblings go from the leaf level up to the root.
+#[derive(Clone, Debug)]
+pub struct MerkleUpdate {
+    pub index: u64,
+    pub old_leaf: Fr,
+    pub new_leaf: Fr,
+    pub siblings: Vec<Fr>,
+}
+
+impl MerkleTree {
+    pub fn new(depth: usize) -> Self {
+        assert!(depth < 64, "leaf indices are u64");
+        let mut empty = vec![Fr::ZERO];
+        for level in 0..depth {
+            empty.push(poseidon::hash(&[empty[level], empty[level]]));
+        }
+
+        Self {
+            depth,
+            nodes: HashMap::new(),
+            empty,
+        }
+    }
+
+    pub fn node(&self, level: usize, index: u64) -> Fr {
+        self.nodes.get(&(level, index)).copied().unwrap_or(self.empty[level])
+    }
+
+    pub fn root(&self) -> Fr {
+        self.node(self.depth, 0)
+    }
+
+    pub fn siblings(&self, index: u64) -> Vec<Fr> {
+        (0..self.depth).map(|level| self.node(level, (index >> level) ^ 1)).collect()
+    }
+
+    /// Sets the leaf at `index`, returning the advice proving the update.
+    pub fn update(&mut self, index: u64, leaf: Fr) -> MerkleUpdate {
+        let update = MerkleUpdate {
+            index,
+            old_leaf: self.node(0, index),
+            new_leaf: leaf,
+            siblings: self.siblings(index),
+        };
+
+        for (level, node) in path(leaf, index, &update.siblings).into_iter().enumerate() {
+            self.nodes.insert((level, index >> level), node);
+        }
+
+        update
+    }
+}
+
+// the nodes from `leaf` up to the root
+fn path(leaf: Fr, index: u64, siblings: &[Fr]) -> Vec<Fr> {
+    let mut nodes = vec![leaf];
+    for (level, sibling) in siblings.iter().enumerate() {
+        let node = nodes[level];
+        nodes.push(if (index >> level) & 1 == 0 { poseidon::hash(&[node, *sibling]) } else { poseidon::hash(&[*sibling, node]) });
+    }
+    nodes
+}
+
+#[derive(Clone, Debug)]
+pub struct MerkleConfig {
+    // old and new node, sibling, index bit, then the ordered children of the old and new node
+    swap_columns: [Column<Advice>; 8],
+    swap: Selector,
+    poseidon: PoseidonConfig,
+}
+
+impl MerkleConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let swap_columns = [(); 8].map(|_| meta.advice_column());
+        for column in swap_columns {
+            meta.enable_equality(column);
+        }
+        let swap = meta.selector();
+
+        meta.create_gate("swap", |meta| {
+            let s = meta.query_selector(swap);
+            let [old, new, sibling, bit, left_old, right_old, left_new, right_new] =
+                swap_columns.map(|column| meta.query_advice(column, Rotation::cur()));
+            let one = Expression::Constant(Fr::ONE);
+
+            let mut constraints = vec![s.clone() * bit.clone() * (one - bit.clone())];
+            for (node, left, right) in [(old, left_old, right_old), (new, left_new, right_new)] {
+                // the node is the left child unless the bit is set
+                constraints.push(s.clone() * (node.clone() + bit.clone() * (sibling.clone() - node.clone()) - left));
+                constraints.push(s.clone() * (sibling.clone() + bit.clone() * (node - sibling.clone()) - right));
+            }
+            constraints
+        });
+
+        Self {
+            swap_columns,
+            swap,
+            poseidon: poseidon::configure(meta),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MerkleUpdateCircuit {
+    pub depth: usize,
+    pub num_updates_per_step: usize,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+    pub tree: MerkleTree,
+    pub updates: Vec<MerkleUpdate>,
+}
+
+impl MerkleUpdateCircuit {
+    // applies the updates of step `step_idx` to `tree`, the leaves and indices of the Sonobe
+    // version, recording the advice of every update
+    pub fn new(mut tree: MerkleTree, step_idx: usize, num_updates_per_step: usize) -> Self {
+        let input = vec![tree.root()];
+        let updates = (0..num_updates_per_step)
+            .map(|j| {
+                let seed = mix((step_idx * num_updates_per_step + j) as u64);
+                tree.update(seed & ((1 << tree.depth) - 1), Fr::from(mix(seed)))
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            depth: tree.depth,
+            num_updates_per_step,
+            step_idx,
+            initial_input: input.clone(),
+            input,
+            output: vec![tree.root()],
+            tree,
+            updates,
+        }
+    }
+}
+
+impl Circuit<Fr> for MerkleUpdateCircuit {
+    type Config = MerkleConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        MerkleConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fr> for MerkleUpdateCircuit {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C1> for MerkleUpdateCircuit {
+    fn arity() -> usize {
+        1
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        // the updates of the next step applied onto the current tree
+        *self = Self {
+            initial_input: self.initial_input.clone(),
+            ..Self::new(self.tree.clone(), self.step_idx + 1, self.num_updates_per_step)
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        let [old_column, new_column, sibling_column, bit_column, ..] = config.swap_columns;
+
+        // z_in provides the current root
+        let z_in = layouter.assign_region(
+            || "root",
+            |mut region| region.assign_advice(|| "root", old_column, 0, || Value::known(self.input[0])),
+        )?;
+
+        let mut root = z_in.clone();
+        for (j, update) in self.updates.iter().enumerate() {
+            let old_path = path(update.old_leaf, update.index, &update.siblings);
+            let new_path = path(update.new_leaf, update.index, &update.siblings);
+
+            let mut nodes: Option<(AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>)> = None;
+            for (level, sibling) in update.siblings.iter().enumerate() {
+                let bit = (update.index >> level) & 1;
+                let children = layouter.assign_region(
+                    || format!("swap_{}_{}", j, level),
+                    |mut region| {
+                        config.swap.enable(&mut region, 0)?;
+
+                        // the leaves are fresh witnesses, the inner nodes the outputs of the previous hashes
+                        match &nodes {
+                            Some((old, new)) => {
+                                old.copy_advice(|| "old", &mut region, old_column, 0)?;
+                                new.copy_advice(|| "new", &mut region, new_column, 0)?;
+                            }
+                            None => {
+                                region.assign_advice(|| "old", old_column, 0, || Value::known(update.old_leaf))?;
+                                region.assign_advice(|| "new", new_column, 0, || Value::known(update.new_leaf))?;
+                            }
+                        }
+                        region.assign_advice(|| "sibling", sibling_column, 0, || Value::known(*sibling))?;
+                        region.assign_advice(|| "bit", bit_column, 0, || Value::known(Fr::from(bit)))?;
+
+                        let children = [old_path[level], new_path[level]]
+                            .into_iter()
+                            .flat_map(|node| if bit == 0 { [node, *sibling] } else { [*sibling, node] })
+                            .collect::<Vec<_>>();
+                        config.swap_columns[4..]
+                            .iter()
+                            .zip(children)
+                            .map(|(column, value)| region.assign_advice(|| "child", *column, 0, || Value::known(value)))
+                            .collect::<Result<Vec<_>, _>>()
+                    },
+                )?;
+
+                let old = poseidon::hash_circuit(&config.poseidon, layouter.namespace(|| format!("old_{}_{}", j, level)), &children[..2])?;
+                let new = poseidon::hash_circuit(&config.poseidon, layouter.namespace(|| format!("new_{}_{}", j, level)), &children[2..])?;
+                nodes = Some((old, new));
+            }
+
+            let (old_root, new_root) = nodes.expect("the tree has at least one level");
+            layouter.assign_region(|| format!("open_{}", j), |mut region| region.constrain_equal(old_root.cell(), root.cell()))?;
+            root = new_root;
+        }
+
+        Ok((vec![z_in], vec![root]))
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_merkle_preprocess(
+    depth: usize,
+    num_updates_per_step: usize,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<MerkleUpdateCircuit>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = MerkleUpdateCircuit::new(MerkleTree::new(depth), 0, num_updates_per_step);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn merkle_update_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+
+    let mut circuit = MerkleUpdateCircuit::new(MerkleTree::new(4), 0, 2);
+    MockProver::run(12, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // the chain continues from the updated tree
+    let output = circuit.output().to_vec();
+    circuit.next();
+    assert_eq!(circuit.input(), output.as_slice());
+    MockProver::run(12, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // a stale root doesn't open the updated leaves
+    circuit.input = circuit.initial_input.clone();
+    assert!(MockProver::run(12, &circuit, Vec::new()).unwrap().verify().is_err());
+}
@@ -0,0 +1,352 @@
+//! MinRoot step circuit for Protostar, executing a configurable number of iterations of the
+//! `MinRoot` function per step of the IVC.
+//! Each iteration takes a single row and checks `x_{i+1}^5 = x_i + y_i`, either through degree-2
+//! squarings matching the R1CS decomposition of the Nova and Sonobe MinRoot circuits ([`Degree2`]),
+//! or in a single degree-5 gate that only high-degree folding schemes can use ([`Degree5`]).
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use halo2_proofs::circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::{Field, PrimeField};
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector};
+use halo2_proofs::poly::Rotation;
+use num_bigint::BigUint;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(Clone, Debug)]
+pub struct MinRootIteration<F: PrimeField> {
+    pub i: F,
+    pub x_i: F,
+    pub y_i: F,
+    pub i_plus_1: F,
+    pub x_i_plus_1: F,
+    pub y_i_plus_1: F,
+}
+
+impl<F: PrimeField> MinRootIteration<F> {
+    // produces a sample non-deterministic advice, executing `num_iters` invocations of MinRoot
+    pub fn new(num_iters: usize, i_0: &F, x_0: &F, y_0: &F) -> (Vec<F>, Vec<Self>) {
+        // exp = 5^{-1} mod (p - 1), so that x^{exp} is the fifth root of x
+        let exp = {
+            let p_minus_one = BigUint::from_bytes_le((-F::ONE).to_repr().as_ref());
+            (1u32..5)
+                .map(|t| &p_minus_one * t + 1u32)
+                .find(|n| (n % 5u32) == BigUint::from(0u32))
+                .expect("5 must not divide p - 1")
+                / 5u32
+        };
+
+        let mut res = Vec::new();
+        let mut i = *i_0;
+        let mut x_i = *x_0;
+        let mut y_i = *y_0;
+        for _ in 0..num_iters {
+            let x_i_plus_1 = (x_i + y_i).pow_vartime(exp.to_u64_digits()); // computes the fifth root of x_i + y_i
+
+            // sanity check
+            debug_assert_eq!(x_i_plus_1.square().square() * x_i_plus_1, x_i + y_i);
+
+            let y_i_plus_1 = x_i + i;
+            let i_plus_1 = i + F::ONE;
+
+            res.push(Self {
+                i,
+                x_i,
+                y_i,
+                i_plus_1,
+                x_i_plus_1,
+                y_i_plus_1,
+            });
+
+            i = i_plus_1;
+            x_i = x_i_plus_1;
+            y_i = y_i_plus_1;
+        }
+
+        let z0 = vec![*i_0, *x_0, *y_0];
+
+        (z0, res)
+    }
+}
+
+/// How a MinRoot iteration `x_{i+1}^5 = x_i + y_i` is arithmetized.
+pub trait MinRootGate: Clone + Debug {
+    /// Degree of the gate, used to label benchmark results.
+    const DEGREE: usize;
+
+    fn configure<F: PrimeField>(meta: &mut ConstraintSystem<F>) -> MinRootConfig;
+}
+
+/// Checks `x_{i+1}^5` through two squarings and a multiplication, one witness per product,
+/// the same decomposition as Nova's R1CS squaring chain.
+#[derive(Clone, Debug)]
+pub struct Degree2;
+
+/// Checks `x_{i+1}^5 = x_i + y_i` in a single degree-5 gate, without intermediate witnesses.
+#[derive(Clone, Debug)]
+pub struct Degree5;
+
+#[derive(Clone, Debug)]
+pub struct MinRootConfig {
+    i: Column<Advice>,
+    x: Column<Advice>,
+    y: Column<Advice>,
+    // only allocated by the degree-2 gate
+    x_next_powers: Option<[Column<Advice>; 2]>,
+    selector: Selector,
+}
+
+impl MinRootGate for Degree2 {
+    const DEGREE: usize = 2;
+
+    fn configure<F: PrimeField>(meta: &mut ConstraintSystem<F>) -> MinRootConfig {
+        let [x_next_sq, x_next_quad] = [(); 2].map(|_| meta.advice_column());
+        let config = MinRootConfig::configure_state(meta, [x_next_sq, x_next_quad]);
+
+        meta.create_gate("minroot iteration", |meta| {
+            let s = meta.query_selector(config.selector);
+            let [i_cur, x_cur, y_cur] = [config.i, config.x, config.y].map(|column| meta.query_advice(column, Rotation::cur()));
+            let [i_next, x_next, y_next] = [config.i, config.x, config.y].map(|column| meta.query_advice(column, Rotation::next()));
+            let sq = meta.query_advice(x_next_sq, Rotation::cur());
+            let quad = meta.query_advice(x_next_quad, Rotation::cur());
+
+            // (i) x_next = (x + y)^{1/5}, which can be more easily checked with x_next^5 = x + y
+            // (ii) y_next = x + i
+            // (iii) i_next = i + 1
+            vec![
+                s.clone() * (x_next.clone() * x_next.clone() - sq.clone()),
+                s.clone() * (sq.clone() * sq - quad.clone()),
+                s.clone() * (quad * x_next - x_cur.clone() - y_cur),
+                s.clone() * (y_next - x_cur - i_cur.clone()),
+                s * (i_next - i_cur - Expression::Constant(F::ONE)),
+            ]
+        });
+
+        config
+    }
+}
+
+impl MinRootGate for Degree5 {
+    const DEGREE: usize = 5;
+
+    fn configure<F: PrimeField>(meta: &mut ConstraintSystem<F>) -> MinRootConfig {
+        let config = MinRootConfig::configure_state(meta, None);
+
+        meta.create_gate("minroot iteration", |meta| {
+            let s = meta.query_selector(config.selector);
+            let [i_cur, x_cur, y_cur] = [config.i, config.x, config.y].map(|column| meta.query_advice(column, Rotation::cur()));
+            let [i_next, x_next, y_next] = [config.i, config.x, config.y].map(|column| meta.query_advice(column, Rotation::next()));
+            let x_next_fifth = (0..4).fold(x_next.clone(), |acc, _| acc * x_next.clone());
+
+            // (i) x_next^5 = x + y
+            // (ii) y_next = x + i
+            // (iii) i_next = i + 1
+            vec![
+                s.clone() * (x_next_fifth - x_cur.clone() - y_cur),
+                s.clone() * (y_next - x_cur - i_cur.clone()),
+                s * (i_next - i_cur - Expression::Constant(F::ONE)),
+            ]
+        });
+
+        config
+    }
+}
+
+impl MinRootConfig {
+    fn configure_state<F: PrimeField>(
+        meta: &mut ConstraintSystem<F>,
+        x_next_powers: impl Into<Option<[Column<Advice>; 2]>>,
+    ) -> Self {
+        let [i, x, y] = [(); 3].map(|_| meta.advice_column());
+        for column in [i, x, y] {
+            meta.enable_equality(column);
+        }
+
+        Self {
+            i,
+            x,
+            y,
+            x_next_powers: x_next_powers.into(),
+            selector: meta.selector(),
+        }
+    }
+
+    fn assign_state<F: PrimeField>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        [i, x, y]: [F; 3],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        Ok(vec![
+            region.assign_advice(|| "i", self.i, offset, || Value::known(i))?,
+            region.assign_advice(|| "x", self.x, offset, || Value::known(x))?,
+            region.assign_advice(|| "y", self.y, offset, || Value::known(y))?,
+        ])
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MinRootCircuit<G: MinRootGate = Degree2> {
+    pub num_iters_per_step: usize,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+    pub seq: Vec<MinRootIteration<Fr>>,
+    _marker: PhantomData<G>,
+}
+
+impl<G: MinRootGate> MinRootCircuit<G> {
+    pub fn new(initial_input: Vec<Fr>, num_iters_per_step: usize) -> Self {
+        let (_, seq) = MinRootIteration::new(
+            num_iters_per_step,
+            &initial_input[0],
+            &initial_input[1],
+            &initial_input[2],
+        );
+        let last = &seq[seq.len() - 1];
+        let output = vec![last.i_plus_1, last.x_i_plus_1, last.y_i_plus_1];
+
+        Self {
+            num_iters_per_step,
+            step_idx: 0,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output,
+            seq,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<G: MinRootGate> Circuit<Fr> for MinRootCircuit<G> {
+    type Config = MinRootConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        G::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl<G: MinRootGate> CircuitExt<Fr> for MinRootCircuit<G> {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl<G: MinRootGate> StepCircuit<C1> for MinRootCircuit<G> {
+    fn arity() -> usize {
+        3
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        // the next step continues the MinRoot chain from the current output
+        *self = Self {
+            step_idx: self.step_idx + 1,
+            initial_input: self.initial_input.clone(),
+            ..Self::new(self.output.clone(), self.num_iters_per_step)
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        layouter.assign_region(
+            || "minroot",
+            |mut region| {
+                let mut input = Vec::new();
+                for (offset, iter) in self.seq.iter().enumerate() {
+                    config.selector.enable(&mut region, offset)?;
+                    let state = config.assign_state(&mut region, offset, [iter.i, iter.x_i, iter.y_i])?;
+                    if offset == 0 {
+                        input = state;
+                    }
+
+                    if let Some([x_next_sq, x_next_quad]) = config.x_next_powers {
+                        let sq = iter.x_i_plus_1.square();
+                        region.assign_advice(|| "x_next_sq", x_next_sq, offset, || Value::known(sq))?;
+                        region.assign_advice(|| "x_next_quad", x_next_quad, offset, || Value::known(sq.square()))?;
+                    }
+                }
+
+                // the row after the last iteration holds (i_plus_1, x_i_plus_1, y_i_plus_1)
+                let last = &self.seq[self.seq.len() - 1];
+                let output = config.assign_state(
+                    &mut region,
+                    self.seq.len(),
+                    [last.i_plus_1, last.x_i_plus_1, last.y_i_plus_1],
+                )?;
+
+                Ok((input, output))
+            },
+        )
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_minroot_preprocess<G: MinRootGate>(
+    num_iters_per_step: usize,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<MinRootCircuit<G>>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = MinRootCircuit::<G>::new(vec![Fr::ZERO, Fr::ZERO, Fr::ONE], num_iters_per_step);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn minroot_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+
+    fn run<G: MinRootGate>() {
+        let mut circuit = MinRootCircuit::<G>::new(vec![Fr::ZERO, Fr::ZERO, Fr::ONE], 16);
+        MockProver::run(8, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+        // the chain continues from the previous output
+        let output = circuit.output().to_vec();
+        circuit.next();
+        assert_eq!(circuit.input(), output.as_slice());
+        MockProver::run(8, &circuit, Vec::new()).unwrap().assert_satisfied();
+    }
+
+    run::<Degree2>();
+    run::<Degree5>();
+}
@@ -0,0 +1,115 @@
+//! Non-folding baseline: the steps of a whole IVC run are laid out in a single halo2 circuit and
+//! proven at once with HyperPlonk over Gemini-KZG, the backend of the Protostar primary circuit.
+//! Consecutive steps are linked by copy constraints from the output of a step to the input of the
+//! next one.
+use crate::ivc::{Fr, C1, P1};
+use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner};
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::backend::hyperplonk::{HyperPlonk, HyperPlonkProverParam};
+use plonkish_backend::backend::{PlonkishBackend, PlonkishCircuit};
+use plonkish_backend::frontend::halo2::{CircuitExt, Halo2Circuit};
+use plonkish_backend::util::test::seeded_std_rng;
+use plonkish_backend::util::transcript::Keccak256Transcript;
+use std::time::{Duration, Instant};
+
+pub type MonolithicProverParam = HyperPlonkProverParam<Fr, P1>;
+
+#[derive(Clone, Debug)]
+pub struct MonolithicCircuit<Sc: StepCircuit<C1>> {
+    pub steps: Vec<Sc>,
+}
+
+impl<Sc: StepCircuit<C1>> MonolithicCircuit<Sc> {
+    // collects `num_steps` consecutive steps starting from `step_circuit`
+    pub fn new(mut step_circuit: Sc, num_steps: usize) -> Self {
+        let mut steps = Vec::with_capacity(num_steps);
+        for _ in 0..num_steps {
+            steps.push(step_circuit.clone());
+            step_circuit.next();
+        }
+
+        Self { steps }
+    }
+}
+
+impl<Sc: StepCircuit<C1>> Circuit<Fr> for MonolithicCircuit<Sc> {
+    type Config = Sc::Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Sc::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        let mut prev_output = None;
+        for (i, step) in self.steps.iter().enumerate() {
+            let (input, output) = StepCircuit::synthesize(
+                step,
+                config.clone(),
+                layouter.namespace(|| format!("step_{}", i)),
+            )?;
+
+            if let Some(prev_output) = prev_output {
+                layouter.assign_region(
+                    || format!("link_{}", i),
+                    |mut region| {
+                        for (a, b) in input.iter().zip(&prev_output) {
+                            region.constrain_equal(a.cell(), b.cell())?;
+                        }
+                        Ok(())
+                    },
+                )?;
+            }
+            prev_output = Some(output);
+        }
+
+        Ok(())
+    }
+}
+
+impl<Sc: StepCircuit<C1>> CircuitExt<Fr> for MonolithicCircuit<Sc> {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+pub fn hyperplonk_setup<Sc: StepCircuit<C1>>(
+    circuit: MonolithicCircuit<Sc>,
+    num_vars: usize,
+) -> MonolithicProverParam {
+    let circuit = Halo2Circuit::new::<HyperPlonk<P1>>(num_vars, circuit);
+    let circuit_info = circuit.circuit_info().unwrap();
+    let param = HyperPlonk::<P1>::setup(&circuit_info, seeded_std_rng()).unwrap();
+    let (pp, _vp) = HyperPlonk::<P1>::preprocess(&param, &circuit_info).unwrap();
+    pp
+}
+
+// proves the monolithic circuit, witness generation included as for the IVC provers
+pub fn hyperplonk_prove<Sc: StepCircuit<C1>>(
+    pp: &MonolithicProverParam,
+    circuit: MonolithicCircuit<Sc>,
+) -> Duration {
+    let start = Instant::now();
+    let circuit = Halo2Circuit::new::<HyperPlonk<P1>>(pp.num_vars, circuit);
+    let mut transcript = Keccak256Transcript::default();
+    HyperPlonk::<P1>::prove(pp, &circuit, &mut transcript, seeded_std_rng()).unwrap();
+    start.elapsed()
+}
+
+#[test]
+fn monolithic_circuit_test() {
+    use crate::minroot::{Degree2, MinRootCircuit};
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::halo2curves::ff::Field;
+
+    let circuit = MonolithicCircuit::new(
+        MinRootCircuit::<Degree2>::new(vec![Fr::ZERO, Fr::ZERO, Fr::ONE], 16),
+        4,
+    );
+    MockProver::run(8, &circuit, Vec::new()).unwrap().assert_satisfied();
+}
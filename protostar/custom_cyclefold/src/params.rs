@@ -0,0 +1,94 @@
+//! Picks the smallest `num_vars` (log2 of the number of rows) the primary and CycleFold
+//! circuits fit in, and the matching PCS setup size.
+use crate::ivc::{Fq, Fr, C2};
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::bn256::Bn256;
+use halo2_proofs::halo2curves::ff::FromUniformBytes;
+use halo2_proofs::plonk::{Circuit, Error as Halo2Error};
+use plonkish_backend::pcs::multilinear::MultilinearIpa;
+use plonkish_backend::pcs::univariate::UnivariateKzg;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::util::test::seeded_std_rng;
+use std::fmt;
+
+/// Smallest `num_vars` considered when fitting a circuit.
+pub const MIN_NUM_VARS: usize = 10;
+
+/// Largest `num_vars` the benches are allowed to use. Circuits are first preprocessed at this
+/// size to measure their row usage, so raise it if a configuration does not fit.
+pub const MAX_NUM_VARS: usize = 18;
+
+/// The PCS is set up for `2^(num_vars + PCS_EXTRA_VARS)` coefficients, the headroom the
+/// high-degree Protostar cross-term polynomials need.
+pub const PCS_EXTRA_VARS: usize = 4;
+
+#[derive(Debug)]
+pub enum ParamsError {
+    /// The circuit needs more rows than `2^max_num_vars` can provide.
+    DoesNotFit { max_num_vars: usize },
+    /// Synthesis failed for a reason other than running out of rows.
+    Synthesis(Halo2Error),
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamsError::DoesNotFit { max_num_vars } => write!(
+                f,
+                "circuit does not fit in 2^{} rows, raise the maximum num_vars",
+                max_num_vars
+            ),
+            ParamsError::Synthesis(err) => write!(f, "circuit synthesis failed: {:?}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+/// Size of the PCS setup needed for a circuit with `num_vars` variables.
+pub fn pcs_setup_size(num_vars: usize) -> usize {
+    1 << (num_vars + PCS_EXTRA_VARS)
+}
+
+/// PCS parameters of the primary and CycleFold circuits at [`MAX_NUM_VARS`]. Every configuration
+/// of a bench is first preprocessed with them to measure the rows its circuits use, then set up
+/// again at the `num_vars` it fits in.
+#[allow(clippy::type_complexity)]
+pub fn probe_params() -> (
+    <UnivariateKzg<Bn256> as PolynomialCommitmentScheme<Fr>>::Param,
+    <MultilinearIpa<C2> as PolynomialCommitmentScheme<Fq>>::Param,
+) {
+    let primary_params = UnivariateKzg::<Bn256>::setup(pcs_setup_size(MAX_NUM_VARS), 0, &mut seeded_std_rng()).unwrap();
+    let cyclefold_params = MultilinearIpa::<C2>::setup(pcs_setup_size(MAX_NUM_VARS), 0, &mut seeded_std_rng()).unwrap();
+    (primary_params, cyclefold_params)
+}
+
+/// Returns the minimal `num_vars` in which `circuit` can be synthesized without running out of
+/// usable rows (i.e. rows left after the blinding factors).
+pub fn min_num_vars<F, C>(circuit: &C, instances: Vec<Vec<F>>) -> Result<usize, ParamsError>
+where
+    F: FromUniformBytes<64> + Ord,
+    C: Circuit<F>,
+{
+    min_num_vars_up_to(circuit, instances, MAX_NUM_VARS)
+}
+
+/// Same as [`min_num_vars`], for circuits allowed to grow past [`MAX_NUM_VARS`].
+pub fn min_num_vars_up_to<F, C>(
+    circuit: &C,
+    instances: Vec<Vec<F>>,
+    max_num_vars: usize,
+) -> Result<usize, ParamsError>
+where
+    F: FromUniformBytes<64> + Ord,
+    C: Circuit<F>,
+{
+    for num_vars in MIN_NUM_VARS..=max_num_vars {
+        match MockProver::run(num_vars as u32, circuit, instances.clone()) {
+            Ok(_) => return Ok(num_vars),
+            Err(Halo2Error::NotEnoughRowsAvailable { .. }) => continue,
+            Err(err) => return Err(ParamsError::Synthesis(err)),
+        }
+    }
+    Err(ParamsError::DoesNotFit { max_num_vars })
+}
@@ -0,0 +1,137 @@
+//! Poseidon sponge shared by the Protostar workloads, with the same width as the neptune
+//! `U4` sponge used by the Nova hash chain (width 5, rate 4, `x^5` S-box).
+//! The in-circuit sponge is halo2_gadgets' `Pow5Chip`, the native one is used for advice.
+use halo2_gadgets::poseidon::primitives::{generate_constants, Domain, Mds, Spec};
+use halo2_gadgets::poseidon::{PaddedWord, Pow5Chip, Pow5Config, Sponge};
+use halo2_proofs::circuit::{AssignedCell, Layouter};
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::plonk::{ConstraintSystem, Error};
+use std::iter;
+
+pub const WIDTH: usize = 5;
+pub const RATE: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct PoseidonSpec;
+
+impl Spec<Fr, WIDTH, RATE> for PoseidonSpec {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        60
+    }
+
+    fn sbox(val: Fr) -> Fr {
+        val.pow_vartime([5])
+    }
+
+    fn secure_mds() -> usize {
+        0
+    }
+
+    fn constants() -> (Vec<[Fr; WIDTH]>, Mds<Fr, WIDTH>, Mds<Fr, WIDTH>) {
+        generate_constants::<_, Self, WIDTH, RATE>()
+    }
+}
+
+/// Sponge domain absorbing any number of elements, zero-padded to a multiple of `RATE`.
+/// Like the neptune sponge in Simplex mode the capacity starts at zero.
+#[derive(Clone, Copy, Debug)]
+pub struct VariableLength;
+
+impl Domain<Fr, RATE> for VariableLength {
+    type Padding = Vec<Fr>;
+
+    fn name() -> String {
+        "VariableLength".to_string()
+    }
+
+    fn initial_capacity_element() -> Fr {
+        Fr::ZERO
+    }
+
+    fn padding(input_len: usize) -> Self::Padding {
+        vec![Fr::ZERO; input_len.next_multiple_of(RATE) - input_len]
+    }
+}
+
+pub type PoseidonConfig = Pow5Config<Fr, WIDTH, RATE>;
+
+pub fn configure(meta: &mut ConstraintSystem<Fr>) -> PoseidonConfig {
+    let state = [(); WIDTH].map(|_| meta.advice_column());
+    let partial_sbox = meta.advice_column();
+    let rc_a = [(); WIDTH].map(|_| meta.fixed_column());
+    let rc_b = [(); WIDTH].map(|_| meta.fixed_column());
+    meta.enable_constant(rc_b[0]);
+
+    Pow5Chip::configure::<PoseidonSpec>(meta, state, partial_sbox, rc_a, rc_b)
+}
+
+/// Absorbs `inputs` and squeezes a single element.
+pub fn hash_circuit(
+    config: &PoseidonConfig,
+    mut layouter: impl Layouter<Fr>,
+    inputs: &[AssignedCell<Fr, Fr>],
+) -> Result<AssignedCell<Fr, Fr>, Error> {
+    let chip = Pow5Chip::construct(config.clone());
+    let mut sponge = Sponge::<_, _, PoseidonSpec, _, VariableLength, WIDTH, RATE>::new(
+        chip,
+        layouter.namespace(|| "init sponge"),
+    )?;
+
+    let padding = <VariableLength as Domain<Fr, RATE>>::padding(inputs.len());
+    let words = inputs
+        .iter()
+        .cloned()
+        .map(PaddedWord::Message)
+        .chain(padding.into_iter().map(PaddedWord::Padding));
+    for (i, word) in words.enumerate() {
+        sponge.absorb(layouter.namespace(|| format!("absorb_{}", i)), word)?;
+    }
+
+    sponge
+        .finish_absorbing(layouter.namespace(|| "finish absorbing"))?
+        .squeeze(layouter.namespace(|| "squeeze"))
+}
+
+/// Native counterpart of [`hash_circuit`].
+pub fn hash(inputs: &[Fr]) -> Fr {
+    let (round_constants, mds, _) = PoseidonSpec::constants();
+    let mut state = [Fr::ZERO; WIDTH];
+
+    let padding = vec![Fr::ZERO; inputs.len().next_multiple_of(RATE) - inputs.len()];
+    let padded = inputs.iter().copied().chain(padding).collect::<Vec<_>>();
+    for chunk in padded.chunks(RATE) {
+        for (word, input) in state.iter_mut().zip(chunk) {
+            *word += input;
+        }
+        permute(&mut state, &mds, &round_constants);
+    }
+
+    state[0]
+}
+
+fn permute(state: &mut [Fr; WIDTH], mds: &Mds<Fr, WIDTH>, round_constants: &[[Fr; WIDTH]]) {
+    let r_f = PoseidonSpec::full_rounds() / 2;
+    let r_p = PoseidonSpec::partial_rounds();
+    let is_full_round = |round: usize| round < r_f || round >= r_f + r_p;
+
+    for (round, rc) in round_constants.iter().enumerate() {
+        for (word, rc) in state.iter_mut().zip(rc) {
+            *word += rc;
+        }
+        if is_full_round(round) {
+            state.iter_mut().for_each(|word| *word = PoseidonSpec::sbox(*word));
+        } else {
+            state[0] = PoseidonSpec::sbox(state[0]);
+        }
+
+        let prev = *state;
+        for (word, row) in state.iter_mut().zip(mds) {
+            *word = iter::zip(row, &prev).map(|(m, s)| *m * s).sum();
+        }
+    }
+}
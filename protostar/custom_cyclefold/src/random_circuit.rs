@@ -0,0 +1,290 @@
+//! Random satisfiable Plonkish circuit for Protostar, the counterpart of the random R1CS of the
+//! Nova and Sonobe crates: given a number of gates, a number of witness variables and a seed, a
+//! step has `num_gates` rows of the vanilla gate `q_l * a + q_r * b + q_m * a * b + q_c = q_o * c`
+//! with random selectors, over `num_variables` fresh witness cells.
+//!
+//! Variable 0 is `z_i`, then come `num_variables - num_gates` free advice cells, then the output
+//! `c` of each gate. The inputs `a` and `b` of a gate are copies of random variables before its
+//! output, so the gates can be evaluated in order for any `z_i` and advice. The state is the
+//! output of the last gate. The selectors are derived from the seed with the generator of the
+//! R1CS version.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use circuit_dsl::mix;
+use halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed};
+use halo2_proofs::poly::Rotation;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+
+pub const MAX_LOG_GATES: usize = 22;
+
+/// `n`-th random word of the stream of `seed`.
+pub fn word(seed: u64, n: u64) -> u64 {
+    mix(mix(seed) ^ n)
+}
+
+/// Wiring and selectors of a gate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gate {
+    pub a: usize,
+    pub b: usize,
+    pub q_l: u64,
+    pub q_r: u64,
+    pub q_m: u64,
+    pub q_c: u64,
+}
+
+impl Gate {
+    pub fn evaluate(&self, values: &[Fr]) -> Fr {
+        let (a, b) = (values[self.a], values[self.b]);
+        Fr::from(self.q_l) * a + Fr::from(self.q_r) * b + Fr::from(self.q_m) * a * b + Fr::from(self.q_c)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RandomPlonkishConfig {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    w: Column<Advice>,
+    q_l: Column<Fixed>,
+    q_r: Column<Fixed>,
+    q_m: Column<Fixed>,
+    q_c: Column<Fixed>,
+    q_o: Column<Fixed>,
+}
+
+impl RandomPlonkishConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let [a, b, c, w] = [(); 4].map(|_| meta.advice_column());
+        for column in [a, b, c, w] {
+            meta.enable_equality(column);
+        }
+        let [q_l, q_r, q_m, q_c, q_o] = [(); 5].map(|_| meta.fixed_column());
+
+        // unused rows have all their selectors zero, q_o doubles as the selector
+        meta.create_gate("vanilla", |meta| {
+            let [a, b, c] = [a, b, c].map(|column| meta.query_advice(column, Rotation::cur()));
+            let [q_l, q_r, q_m, q_c, q_o] = [q_l, q_r, q_m, q_c, q_o].map(|column| meta.query_fixed(column, Rotation::cur()));
+            vec![q_l * a.clone() + q_r * b.clone() + q_m * a * b + q_c - q_o * c]
+        });
+
+        Self { a, b, c, w, q_l, q_r, q_m, q_c, q_o }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RandomPlonkishCircuit {
+    pub num_gates: usize,
+    pub num_variables: usize,
+    pub seed: u64,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+    advice: Vec<Fr>,
+}
+
+impl RandomPlonkishCircuit {
+    pub fn new(num_gates: usize, num_variables: usize, seed: u64, input: Fr) -> Self {
+        assert!(
+            (1..=1 << MAX_LOG_GATES).contains(&num_gates),
+            "the number of gates ranges from 1 to 2^{}",
+            MAX_LOG_GATES
+        );
+        assert!(num_variables >= num_gates, "every gate defines a variable");
+        let mut circuit = Self {
+            num_gates,
+            num_variables,
+            seed,
+            step_idx: 0,
+            initial_input: vec![input],
+            input: vec![input],
+            output: Vec::new(),
+            advice: Vec::new(),
+        };
+        circuit.draw_advice();
+        circuit
+    }
+
+    pub fn num_advice(&self) -> usize {
+        self.num_variables - self.num_gates
+    }
+
+    pub fn gate(&self, j: usize) -> Gate {
+        let num_vars = (1 + self.num_advice() + j) as u64;
+        let n = (6 * j) as u64;
+        let random = |k: u64| word(self.seed, n + k);
+        Gate {
+            a: (random(0) % num_vars) as usize,
+            b: (random(1) % num_vars) as usize,
+            q_l: random(2) & 0xffff,
+            q_r: random(3) & 0xffff,
+            q_m: random(4) & 0xffff,
+            q_c: random(5) & 0xffff,
+        }
+    }
+
+    /// Values of all the variables of the step.
+    pub fn evaluate(&self) -> Vec<Fr> {
+        let mut values = Vec::with_capacity(1 + self.num_variables);
+        values.push(self.input[0]);
+        values.extend(&self.advice);
+        for j in 0..self.num_gates {
+            let value = self.gate(j).evaluate(&values);
+            values.push(value);
+        }
+        values
+    }
+
+    // advice of the current step, and the output it leads to
+    fn draw_advice(&mut self) {
+        let num_advice = self.num_advice();
+        // a stream apart from the one of the gates
+        self.advice = (0..num_advice)
+            .map(|k| Fr::from(word(!self.seed, (self.step_idx * num_advice + k) as u64)))
+            .collect();
+        self.output = vec![*self.evaluate().last().unwrap()];
+    }
+}
+
+impl Circuit<Fr> for RandomPlonkishCircuit {
+    type Config = RandomPlonkishConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        RandomPlonkishConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fr> for RandomPlonkishCircuit {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C1> for RandomPlonkishCircuit {
+    fn arity() -> usize {
+        1
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        self.step_idx += 1;
+        self.input = self.output.clone();
+        self.draw_advice();
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        let values = self.evaluate();
+        layouter.assign_region(
+            || "random gates",
+            |mut region| {
+                // z_i and the advice in w, the gates in a, b and c
+                let mut cells = Vec::with_capacity(1 + self.num_variables);
+                for (offset, value) in values[..1 + self.num_advice()].iter().enumerate() {
+                    cells.push(region.assign_advice(|| "w", config.w, offset, || Value::known(*value))?);
+                }
+
+                for j in 0..self.num_gates {
+                    let gate = self.gate(j);
+                    for (column, value) in [
+                        (config.q_l, gate.q_l),
+                        (config.q_r, gate.q_r),
+                        (config.q_m, gate.q_m),
+                        (config.q_c, gate.q_c),
+                        (config.q_o, 1),
+                    ] {
+                        region.assign_fixed(|| "selector", column, j, || Value::known(Fr::from(value)))?;
+                    }
+                    for (column, var) in [(config.a, gate.a), (config.b, gate.b)] {
+                        let cell = region.assign_advice(|| "input", column, j, || Value::known(values[var]))?;
+                        region.constrain_equal(cell.cell(), cells[var].cell())?;
+                    }
+                    // the last gate outputs the claimed step output
+                    let c = if j + 1 == self.num_gates { self.output[0] } else { values[cells.len()] };
+                    cells.push(region.assign_advice(|| "c", config.c, j, || Value::known(c))?);
+                }
+
+                Ok((vec![cells[0].clone()], vec![cells.last().unwrap().clone()]))
+            },
+        )
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_random_circuit_preprocess(
+    num_gates: usize,
+    num_variables: usize,
+    seed: u64,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<RandomPlonkishCircuit>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = RandomPlonkishCircuit::new(num_gates, num_variables, seed, Fr::ONE);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn random_plonkish_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+
+    for (num_gates, num_variables) in [(64, 64), (64, 256), (200, 200)] {
+        let mut circuit = RandomPlonkishCircuit::new(num_gates, num_variables, 7, Fr::from(3));
+        MockProver::run(9, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+        // the chain continues from the previous output, with fresh advice
+        let output = circuit.output().to_vec();
+        circuit.next();
+        assert_eq!(circuit.input(), output.as_slice());
+        MockProver::run(9, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+        // an output off by one
+        circuit.output[0] += Fr::ONE;
+        assert!(MockProver::run(9, &circuit, Vec::new()).unwrap().verify().is_err());
+    }
+
+    // the gates only depend on the seed
+    let [circuit, same, other] = [7, 7, 8].map(|seed| RandomPlonkishCircuit::new(32, 32, seed, Fr::ONE));
+    assert_eq!(circuit.gate(31), same.gate(31));
+    assert_ne!(circuit.gate(31), other.gate(31));
+}
@@ -0,0 +1,250 @@
+//! Range-check chain for Protostar, the lookup-heavy counterpart of the Nova and Sonobe bit
+//! decompositions. Each step performs a configurable number of u64 additions with carry,
+//! `acc + a_j = acc_{j+1} + carry_j * 2^64`, where `a_j` and `acc_{j+1}` are split into
+//! [`NUM_LIMBS`] limbs of [`LIMB_BITS`] bits, each looked up in a byte table.
+//! Every addition takes a single row, the row after the last one holds the output accumulator.
+//! The addends of step `i` are the ones of the DSL version, as in the Nova and Sonobe circuits.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::{Field, PrimeField};
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector, TableColumn, VirtualCells};
+use halo2_proofs::poly::Rotation;
+use circuit_dsl::range_check::RangeCheck;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+
+pub const LIMB_BITS: usize = 8;
+pub const NUM_LIMBS: usize = 64 / LIMB_BITS;
+
+fn limbs(value: u64) -> [Fr; NUM_LIMBS] {
+    let mask = (1 << LIMB_BITS) - 1;
+    std::array::from_fn(|k| Fr::from((value >> (k * LIMB_BITS)) & mask))
+}
+
+// the accumulator always fits in the lowest 8 bytes of its representation
+fn to_u64(value: Fr) -> u64 {
+    u64::from_le_bytes(value.to_repr()[..8].try_into().unwrap())
+}
+
+#[derive(Clone, Debug)]
+pub struct RangeCheckConfig {
+    acc: Column<Advice>,
+    a: Column<Advice>,
+    carry: Column<Advice>,
+    a_limbs: [Column<Advice>; NUM_LIMBS],
+    acc_next_limbs: [Column<Advice>; NUM_LIMBS],
+    table: TableColumn,
+    selector: Selector,
+}
+
+impl RangeCheckConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let [acc, a, carry] = [(); 3].map(|_| meta.advice_column());
+        meta.enable_equality(acc);
+        let a_limbs = [(); NUM_LIMBS].map(|_| meta.advice_column());
+        let acc_next_limbs = [(); NUM_LIMBS].map(|_| meta.advice_column());
+        let table = meta.lookup_table_column();
+        // lookup inputs can't use simple selectors, disabled rows look up zero
+        let selector = meta.complex_selector();
+
+        for column in a_limbs.into_iter().chain(acc_next_limbs) {
+            meta.lookup("limb range", |meta| {
+                let s = meta.query_selector(selector);
+                let limb = meta.query_advice(column, Rotation::cur());
+                vec![(s * limb, table)]
+            });
+        }
+
+        meta.create_gate("add with carry", |meta| {
+            let s = meta.query_selector(selector);
+            let [acc_cur, a_cur, carry_cur] = [acc, a, carry].map(|column| meta.query_advice(column, Rotation::cur()));
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            let recompose = |meta: &mut VirtualCells<'_, Fr>, columns: [Column<Advice>; NUM_LIMBS]| {
+                columns.iter().rev().fold(Expression::Constant(Fr::ZERO), |sum, column| {
+                    sum * Expression::Constant(Fr::from(1 << LIMB_BITS)) + meta.query_advice(*column, Rotation::cur())
+                })
+            };
+            let a_recomposed = recompose(meta, a_limbs);
+            let acc_next_recomposed = recompose(meta, acc_next_limbs);
+            let two_pow_64 = Expression::Constant(Fr::from(u64::MAX) + Fr::ONE);
+
+            vec![
+                s.clone() * carry_cur.clone() * (Expression::Constant(Fr::ONE) - carry_cur.clone()),
+                s.clone() * (a_recomposed - a_cur.clone()),
+                s.clone() * (acc_next_recomposed - acc_next.clone()),
+                s * (acc_cur + a_cur - acc_next - carry_cur * two_pow_64),
+            ]
+        });
+
+        Self {
+            acc,
+            a,
+            carry,
+            a_limbs,
+            acc_next_limbs,
+            table,
+            selector,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RangeCheckCircuit {
+    pub num_adds_per_step: usize,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+    pub a: Vec<u64>,
+}
+
+impl RangeCheckCircuit {
+    // produces the addends of step `step_idx`, continuing the chain from the input accumulator
+    pub fn new(initial_input: Vec<Fr>, step_idx: usize, num_adds_per_step: usize) -> Self {
+        let step = RangeCheck { num_adds_per_step };
+        let a = (0..num_adds_per_step).map(|j| step.addend(step_idx, j)).collect::<Vec<u64>>();
+        let acc = a.iter().fold(to_u64(initial_input[0]), |acc, a| acc.wrapping_add(*a));
+
+        Self {
+            num_adds_per_step,
+            step_idx,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output: vec![Fr::from(acc)],
+            a,
+        }
+    }
+}
+
+impl Circuit<Fr> for RangeCheckCircuit {
+    type Config = RangeCheckConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        RangeCheckConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fr> for RangeCheckCircuit {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C1> for RangeCheckCircuit {
+    fn arity() -> usize {
+        1
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        // the addends of the next step added onto the current accumulator
+        *self = Self {
+            initial_input: self.initial_input.clone(),
+            ..Self::new(self.output.clone(), self.step_idx + 1, self.num_adds_per_step)
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        layouter.assign_table(
+            || "byte table",
+            |mut table| {
+                for value in 0..1 << LIMB_BITS {
+                    table.assign_cell(|| "byte", config.table, value, || Value::known(Fr::from(value as u64)))?;
+                }
+                Ok(())
+            },
+        )?;
+
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                let mut acc = to_u64(self.input[0]);
+                let z_in = region.assign_advice(|| "acc", config.acc, 0, || Value::known(self.input[0]))?;
+                let mut z_out = z_in.clone();
+                for (offset, a) in self.a.iter().enumerate() {
+                    config.selector.enable(&mut region, offset)?;
+                    let (acc_next, carry) = acc.overflowing_add(*a);
+
+                    region.assign_advice(|| "a", config.a, offset, || Value::known(Fr::from(*a)))?;
+                    region.assign_advice(|| "carry", config.carry, offset, || Value::known(Fr::from(carry as u64)))?;
+                    let (a_limbs, acc_next_limbs) = (limbs(*a), limbs(acc_next));
+                    for k in 0..NUM_LIMBS {
+                        region.assign_advice(|| "a_limb", config.a_limbs[k], offset, || Value::known(a_limbs[k]))?;
+                        region.assign_advice(|| "acc_next_limb", config.acc_next_limbs[k], offset, || Value::known(acc_next_limbs[k]))?;
+                    }
+                    z_out = region.assign_advice(|| "acc", config.acc, offset + 1, || Value::known(Fr::from(acc_next)))?;
+
+                    acc = acc_next;
+                }
+
+                Ok((vec![z_in], vec![z_out]))
+            },
+        )
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_rangecheck_preprocess(
+    num_adds_per_step: usize,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<RangeCheckCircuit>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = RangeCheckCircuit::new(vec![Fr::ZERO], 0, num_adds_per_step);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn range_check_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+
+    let mut circuit = RangeCheckCircuit::new(vec![Fr::ZERO], 0, 16);
+    let step = RangeCheck { num_adds_per_step: 16 };
+    assert_eq!(circuit.a, (0..16).map(|j| step.addend(0, j)).collect::<Vec<_>>());
+    MockProver::run(9, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // the chain continues from the previous output
+    let output = circuit.output().to_vec();
+    circuit.next();
+    assert_eq!(circuit.input(), output.as_slice());
+    MockProver::run(9, &circuit, Vec::new()).unwrap().assert_satisfied();
+}
@@ -0,0 +1,349 @@
+//! Scalar multiplication chain over Grumpkin, whose base field is the BN254 scalar field, so the
+//! curve arithmetic is native to the primary circuit. Every scalar multiplication folds a pair of
+//! commitments `comm2 + r * comm1` for a 128-bit challenge `r`, as a CycleFold verifier would.
+//! The output of each step is the last folded commitment.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::{Field, PrimeField};
+use halo2_proofs::halo2curves::group::{Curve, Group};
+use halo2_proofs::halo2curves::{grumpkin, CurveAffine};
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector};
+use halo2_proofs::poly::Rotation;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::util::test::seeded_std_rng;
+use rand::Rng;
+
+pub const NUM_CHALLENGE_BITS: usize = 128;
+
+// one row of the double-and-add ladder: acc_next = acc + b * p, p_next = 2 * p
+#[derive(Clone, Copy, Debug)]
+struct LadderRow {
+    b: Fr,
+    p: [Fr; 2],
+    acc: [Fr; 2],
+    lambda_add: Fr,
+    lambda_dbl: Fr,
+}
+
+fn add(p: [Fr; 2], q: [Fr; 2]) -> ([Fr; 2], Fr) {
+    let lambda = (p[1] - q[1]) * (p[0] - q[0]).invert().unwrap();
+    let x = lambda.square() - p[0] - q[0];
+    let y = lambda * (q[0] - x) - q[1];
+    ([x, y], lambda)
+}
+
+fn double(p: [Fr; 2]) -> ([Fr; 2], Fr) {
+    let lambda = Fr::from(3) * p[0].square() * p[1].double().invert().unwrap();
+    let x = lambda.square() - p[0].double();
+    let y = lambda * (p[0] - x) - p[1];
+    ([x, y], lambda)
+}
+
+// computes comm2 + r * comm1 with r given by its little-endian bits, returning the ladder rows
+// and the result
+fn ladder(comm1: [Fr; 2], comm2: [Fr; 2], rbits: &[bool]) -> (Vec<LadderRow>, [Fr; 2]) {
+    let mut rows = Vec::with_capacity(rbits.len());
+    let (mut p, mut acc) = (comm1, comm2);
+    for bit in rbits {
+        let (sum, lambda_add) = add(p, acc);
+        let (p_next, lambda_dbl) = double(p);
+        rows.push(LadderRow {
+            b: if *bit { Fr::ONE } else { Fr::ZERO },
+            p,
+            acc,
+            lambda_add,
+            lambda_dbl,
+        });
+        if *bit {
+            acc = sum;
+        }
+        p = p_next;
+    }
+    (rows, acc)
+}
+
+fn coordinates(point: grumpkin::G1Affine) -> [Fr; 2] {
+    let coords = point.coordinates().unwrap();
+    [*coords.x(), *coords.y()]
+}
+
+#[derive(Clone, Debug)]
+pub struct ScalarMulConfig {
+    b: Column<Advice>,
+    p: [Column<Advice>; 2],
+    acc: [Column<Advice>; 2],
+    lambda_add: Column<Advice>,
+    lambda_dbl: Column<Advice>,
+    selector: Selector,
+}
+
+impl ScalarMulConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let [b, px, py, ax, ay, lambda_add, lambda_dbl] = [(); 7].map(|_| meta.advice_column());
+        for column in [ax, ay] {
+            meta.enable_equality(column);
+        }
+        let selector = meta.selector();
+
+        // incomplete addition formulas, exceptional cases happen with negligible probability
+        // for random commitments
+        meta.create_gate("double and add", |meta| {
+            let s = meta.query_selector(selector);
+            let b = meta.query_advice(b, Rotation::cur());
+            let [px_cur, py_cur, ax_cur, ay_cur, l_add, l_dbl] = [px, py, ax, ay, lambda_add, lambda_dbl]
+                .map(|column| meta.query_advice(column, Rotation::cur()));
+            let [px_next, py_next, ax_next, ay_next] = [px, py, ax, ay]
+                .map(|column| meta.query_advice(column, Rotation::next()));
+            let one = Expression::Constant(Fr::ONE);
+
+            let sum_x = l_add.clone() * l_add.clone() - ax_cur.clone() - px_cur.clone();
+            let sum_y = l_add.clone() * (ax_cur.clone() - sum_x.clone()) - ay_cur.clone();
+            vec![
+                s.clone() * b.clone() * (one - b.clone()),
+                // acc_next = acc + b * p
+                s.clone() * (l_add * (px_cur.clone() - ax_cur.clone()) - (py_cur.clone() - ay_cur.clone())),
+                s.clone() * (ax_next - ax_cur.clone() - b.clone() * (sum_x - ax_cur)),
+                s.clone() * (ay_next - ay_cur.clone() - b * (sum_y - ay_cur)),
+                // p_next = 2 * p
+                s.clone() * (l_dbl.clone() * Expression::Constant(Fr::from(2)) * py_cur.clone()
+                    - Expression::Constant(Fr::from(3)) * px_cur.clone() * px_cur.clone()),
+                s.clone() * (px_next.clone() - l_dbl.clone() * l_dbl.clone() + Expression::Constant(Fr::from(2)) * px_cur.clone()),
+                s * (py_next - l_dbl * (px_cur - px_next) + py_cur),
+            ]
+        });
+
+        Self {
+            b,
+            p: [px, py],
+            acc: [ax, ay],
+            lambda_add,
+            lambda_dbl,
+            selector,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ScalarMulChainCircuit {
+    pub num_sm_per_step: usize,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+    pub comm1: Vec<[Fr; 2]>,
+    pub comm2: Vec<[Fr; 2]>,
+    pub rbits: Vec<[bool; NUM_CHALLENGE_BITS]>,
+}
+
+impl ScalarMulChainCircuit {
+    pub fn new(initial_input: Vec<Fr>, num_sm_per_step: usize) -> Self {
+        let mut rng = seeded_std_rng();
+        let rbits_i: [bool; NUM_CHALLENGE_BITS] = (0..NUM_CHALLENGE_BITS)
+            .map(|_| rng.gen_bool(1.0 / 3.0))
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        let grumpkin_random = grumpkin::G1Affine::from_xy(
+            Fr::from_str_vartime("19834382608297447889961323302677467055070110053155139740545148874538063289754").unwrap(),
+            Fr::from_str_vartime("20084669131162155340423162249467328031170931348295785825029782732565818853520").unwrap(),
+        )
+        .unwrap();
+        // comm2 is independent of comm1 so the ladder never hits the doubling case of addition
+        let comm2_random = grumpkin::G1::random(&mut rng).to_affine();
+        let comm1 = vec![coordinates(grumpkin_random); num_sm_per_step];
+        let comm2 = vec![coordinates(comm2_random); num_sm_per_step];
+        let rbits = vec![rbits_i; num_sm_per_step];
+        let (_, folded) = ladder(comm1[num_sm_per_step - 1], comm2[num_sm_per_step - 1], &rbits_i);
+
+        Self {
+            num_sm_per_step,
+            step_idx: 0,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output: folded.to_vec(),
+            comm1,
+            comm2,
+            rbits,
+        }
+    }
+}
+
+impl Circuit<Fr> for ScalarMulChainCircuit {
+    type Config = ScalarMulConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        ScalarMulConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fr> for ScalarMulChainCircuit {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C1> for ScalarMulChainCircuit {
+    fn arity() -> usize {
+        2
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        *self = Self {
+            step_idx: self.step_idx + 1,
+            initial_input: self.initial_input.clone(),
+            ..Self::new(self.output.clone(), self.num_sm_per_step)
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        // z_in provides the running accumulator, which the chain overwrites like the Nova circuit
+        let z_in = layouter.assign_region(
+            || "z_in",
+            |mut region| {
+                (0..2)
+                    .map(|i| region.assign_advice(|| format!("z_in_{}", i), config.acc[i], 0, || Value::known(self.input[i])))
+                    .collect::<Result<Vec<_>, _>>()
+            },
+        )?;
+
+        let mut z_out = Vec::new();
+        for i in 0..self.num_sm_per_step {
+            let (rows, folded) = ladder(self.comm1[i], self.comm2[i], &self.rbits[i]);
+            z_out = layouter.assign_region(
+                || format!("scalar_mul_{}", i),
+                |mut region| {
+                    for (offset, row) in rows.iter().enumerate() {
+                        config.selector.enable(&mut region, offset)?;
+                        region.assign_advice(|| "b", config.b, offset, || Value::known(row.b))?;
+                        for j in 0..2 {
+                            region.assign_advice(|| "p", config.p[j], offset, || Value::known(row.p[j]))?;
+                            region.assign_advice(|| "acc", config.acc[j], offset, || Value::known(row.acc[j]))?;
+                        }
+                        region.assign_advice(|| "lambda_add", config.lambda_add, offset, || Value::known(row.lambda_add))?;
+                        region.assign_advice(|| "lambda_dbl", config.lambda_dbl, offset, || Value::known(row.lambda_dbl))?;
+                    }
+
+                    // the last row holds the folded commitment, p is doubled once more but unused
+                    let (p_last, _) = double(rows[rows.len() - 1].p);
+                    let offset = rows.len();
+                    for j in 0..2 {
+                        region.assign_advice(|| "p", config.p[j], offset, || Value::known(p_last[j]))?;
+                    }
+                    (0..2)
+                        .map(|j| region.assign_advice(|| "folded", config.acc[j], offset, || Value::known(folded[j])))
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+        }
+
+        Ok((z_in, z_out))
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_smchain_preprocess(
+    num_sm_per_step: usize,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<ScalarMulChainCircuit>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = ScalarMulChainCircuit::new(vec![Fr::ZERO, Fr::ZERO], num_sm_per_step);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn scalar_mul_chain_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::plonk::Instance;
+
+    // exposes the folded commitment computed by the circuit
+    #[derive(Clone)]
+    struct Folded(ScalarMulChainCircuit);
+
+    impl Circuit<Fr> for Folded {
+        type Config = (ScalarMulConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (ScalarMulConfig::configure(meta), instance)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+            let (_, z_out) = StepCircuit::<C1>::synthesize(&self.0, config.0, layouter.namespace(|| "step"))?;
+            for (row, cell) in z_out.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), config.1, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    // comm2 + r * comm1 with the group law of Grumpkin
+    let folded = |circuit: &ScalarMulChainCircuit| {
+        let [comm1, comm2] = [circuit.comm1[0], circuit.comm2[0]]
+            .map(|[x, y]| grumpkin::G1Affine::from_xy(x, y).unwrap());
+        let r = circuit.rbits[0].iter().rev().fold(Fq::ZERO, |acc, bit| acc.double() + Fq::from(*bit as u64));
+        coordinates((comm1 * r + comm2).to_affine()).to_vec()
+    };
+
+    let mut circuit = ScalarMulChainCircuit::new(vec![Fr::ZERO, Fr::ZERO], 2);
+    assert_eq!(circuit.output(), folded(&circuit).as_slice());
+    MockProver::run(9, &Folded(circuit.clone()), vec![circuit.output().to_vec()]).unwrap().assert_satisfied();
+
+    // the chain continues from the previous commitment
+    let output = circuit.output().to_vec();
+    circuit.next();
+    assert_eq!(circuit.input(), output.as_slice());
+    MockProver::run(9, &Folded(circuit.clone()), vec![circuit.output().to_vec()]).unwrap().assert_satisfied();
+
+    // a commitment folded with another challenge
+    let mut other = circuit.clone();
+    other.rbits[1][0] = !other.rbits[1][0];
+    assert!(MockProver::run(9, &Folded(other), vec![circuit.output().to_vec()]).unwrap().verify().is_err());
+}
@@ -0,0 +1,848 @@
+//! SHA-256 hash chain for Protostar, the lookup-based counterpart of the bit decomposed Nova and
+//! Sonobe circuits: each step hashes the running digest a configurable number of times,
+//! `h_{j+1} = SHA256(h_j)`. A 32-byte message fits in a single block, so every hash is one
+//! compression from the IV. The digest is carried across steps as two 128-bit big-endian halves.
+//!
+//! Words are assigned dense and converted to a sparse base-8 representation with one digit per
+//! bit, as in the Keccak circuit, by looking up chunks of at most [`CHUNK_DIGITS`] bits in a single
+//! table. The same chunks recompose the sum of the rotations and shifts of a sigma function, split
+//! where these wrap around, so the XOR of the three terms is a digit sum normalized back to bits
+//! by a parity lookup. Majority and choice look up the majority of the digits of `a + b + c`,
+//! `e + f` and `(1 - e) + g`, whose results are dense, and the additions mod `2^32` subtract a
+//! carry range checked by the same table. All gates have degree at most 3.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use halo2_proofs::circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::{Field, PrimeField};
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Selector, TableColumn};
+use halo2_proofs::poly::Rotation;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use std::ops::RangeInclusive;
+
+pub const WORD_BITS: usize = 32;
+pub const DIGEST_WORDS: usize = 8;
+pub const NUM_ROUNDS: usize = 64;
+pub const CHUNK_DIGITS: usize = 4;
+
+const SPARSE_BASE: u64 = 8;
+const NUM_TERMS: usize = 5;
+// the additions mod 2^32 sum at most 7 words, their carries are below 8
+const CARRY_BITS: usize = 3;
+
+const IV: [u32; DIGEST_WORDS] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const K: [u32; NUM_ROUNDS] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const IDENTITY: [Map; 1] = [Map::Rotr(0)];
+const BIG_SIGMA0: [Map; 3] = [Map::Rotr(2), Map::Rotr(13), Map::Rotr(22)];
+const BIG_SIGMA1: [Map; 3] = [Map::Rotr(6), Map::Rotr(11), Map::Rotr(25)];
+const SMALL_SIGMA0: [Map; 3] = [Map::Rotr(7), Map::Rotr(18), Map::Shr(3)];
+const SMALL_SIGMA1: [Map; 3] = [Map::Rotr(17), Map::Rotr(19), Map::Shr(10)];
+
+// a 256-bit message padded to a single block
+fn message_block(digest: &[u32; DIGEST_WORDS]) -> [u32; 16] {
+    let mut block = [0; 16];
+    block[..DIGEST_WORDS].copy_from_slice(digest);
+    block[DIGEST_WORDS] = 0x8000_0000;
+    block[15] = 256;
+    block
+}
+
+fn big_sigma0(x: u32) -> u32 {
+    x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+}
+
+fn big_sigma1(x: u32) -> u32 {
+    x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+}
+
+fn small_sigma0(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+fn small_sigma1(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+/// Native reference: compresses `block` from the IV.
+pub fn compress(block: [u32; 16]) -> [u32; DIGEST_WORDS] {
+    compress_from(IV, block)
+}
+
+/// Compresses `block` from the intermediate digest `state`, as [`compress`] does from the IV.
+pub fn compress_from(state: [u32; DIGEST_WORDS], block: [u32; 16]) -> [u32; DIGEST_WORDS] {
+    let mut w = [0u32; NUM_ROUNDS];
+    w[..16].copy_from_slice(&block);
+    for t in 16..NUM_ROUNDS {
+        w[t] = small_sigma1(w[t - 2])
+            .wrapping_add(w[t - 7])
+            .wrapping_add(small_sigma0(w[t - 15]))
+            .wrapping_add(w[t - 16]);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for t in 0..NUM_ROUNDS {
+        let t1 = h
+            .wrapping_add(big_sigma1(e))
+            .wrapping_add((e & f) ^ (!e & g))
+            .wrapping_add(K[t])
+            .wrapping_add(w[t]);
+        let t2 = big_sigma0(a).wrapping_add((a & b) ^ (a & c) ^ (b & c));
+        (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+    }
+
+    let output = [a, b, c, d, e, f, g, h];
+    std::array::from_fn(|j| state[j].wrapping_add(output[j]))
+}
+
+pub(crate) fn digest_to_scalars(digest: &[u32; DIGEST_WORDS]) -> Vec<Fr> {
+    digest
+        .chunks(4)
+        .map(|half| Fr::from_u128(half.iter().fold(0u128, |acc, word| (acc << 32) | *word as u128)))
+        .collect()
+}
+
+// each half always fits in the lowest 16 bytes of its representation
+pub(crate) fn scalars_to_digest(z: &[Fr]) -> [u32; DIGEST_WORDS] {
+    let mut digest = [0u32; DIGEST_WORDS];
+    for (half, z) in digest.chunks_mut(4).zip(z) {
+        let value = u128::from_le_bytes(z.to_repr()[..16].try_into().unwrap());
+        for (k, word) in half.iter_mut().enumerate() {
+            *word = (value >> (32 * (3 - k))) as u32;
+        }
+    }
+    digest
+}
+
+/// The `num_digits` lowest bits of `value`, from the least significant one.
+pub(crate) fn to_digits(value: u64, num_digits: usize) -> Vec<u8> {
+    (0..num_digits).map(|i| ((value >> i) & 1) as u8).collect()
+}
+
+fn compose(digits: &[u8], base: u64) -> Fr {
+    digits.iter().rev().fold(Fr::ZERO, |acc, digit| acc * Fr::from(base) + Fr::from(*digit as u64))
+}
+
+pub(crate) fn pow(base: u64, exp: usize) -> Fr {
+    Fr::from(base).pow_vartime([exp as u64])
+}
+
+fn signed(coef: i64) -> Fr {
+    if coef < 0 {
+        -Fr::from(coef.unsigned_abs())
+    } else {
+        Fr::from(coef as u64)
+    }
+}
+
+/// Kind of normalization performed by a looked up chunk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Tag {
+    Spread = 1,
+    Dense,
+    Parity,
+    Majority,
+    /// Rows added by the user of the chip, see [`Sha256Config::assign_table`].
+    Extra,
+}
+
+const TAGS: [Tag; 4] = [Tag::Spread, Tag::Dense, Tag::Parity, Tag::Majority];
+
+impl Tag {
+    // base of the input digits, largest input digit and base of the output digits
+    fn bases(self) -> (u64, u8, u64) {
+        match self {
+            Tag::Spread => (2, 1, SPARSE_BASE),
+            Tag::Dense => (2, 1, 2),
+            Tag::Parity | Tag::Majority => (SPARSE_BASE, 3, 2),
+            Tag::Extra => unreachable!("extra rows are looked up as a whole"),
+        }
+    }
+
+    // the majority of the digit sum of at most three bits, every other tag keeps its parity
+    fn apply(self, digit: u8) -> u8 {
+        match self {
+            Tag::Majority => (digit >= 2) as u8,
+            _ => digit & 1,
+        }
+    }
+
+    // dense words are converted at the boundaries of their maps, which leave shorter chunks
+    fn chunk_sizes(self) -> RangeInclusive<usize> {
+        match self {
+            Tag::Spread | Tag::Dense => 1..=CHUNK_DIGITS,
+            _ => CHUNK_DIGITS..=CHUNK_DIGITS,
+        }
+    }
+}
+
+/// Where the digits of a word go in an output.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Map {
+    Rotr(usize),
+    Shr(usize),
+    SwapBytes,
+}
+
+impl Map {
+    // position of the digit `i` in the output, if it is kept
+    fn position(self, i: usize) -> Option<usize> {
+        match self {
+            Map::Rotr(n) => Some((i + WORD_BITS - n) % WORD_BITS),
+            Map::Shr(n) => i.checked_sub(n),
+            Map::SwapBytes => Some(8 * (3 - i / 8) + i % 8),
+        }
+    }
+
+    // digit from which consecutive digits stop being consecutive in the output, the byte
+    // boundaries of a swap are also chunk boundaries
+    fn boundary(self) -> usize {
+        match self {
+            Map::Rotr(n) | Map::Shr(n) => n,
+            Map::SwapBytes => 0,
+        }
+    }
+}
+
+// (start, size) of the chunks of `num_digits` digits, aligned on CHUNK_DIGITS and split at the
+// boundaries of the maps
+fn chunks(num_digits: usize, boundaries: &[usize]) -> Vec<(usize, usize)> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < num_digits {
+        let aligned = (start / CHUNK_DIGITS + 1) * CHUNK_DIGITS;
+        let end = boundaries
+            .iter()
+            .filter(|boundary| **boundary > start)
+            .fold(aligned.min(num_digits), |end, boundary| end.min(*boundary));
+        chunks.push((start, end - start));
+        start = end;
+    }
+    chunks
+}
+
+/// An assigned word together with its digits, one per bit, in the base of the context it is used
+/// in.
+#[derive(Clone, Debug)]
+pub struct Word {
+    pub cell: AssignedCell<Fr, Fr>,
+    pub(crate) digits: [u8; WORD_BITS],
+}
+
+impl Word {
+    fn dense(cell: AssignedCell<Fr, Fr>, value: u32) -> Self {
+        let digits = to_digits(value as u64, WORD_BITS).try_into().unwrap();
+        Self { cell, digits }
+    }
+
+    /// The value of a dense word.
+    pub fn value(&self) -> u32 {
+        self.digits.iter().rev().fold(0, |acc, digit| (acc << 1) | *digit as u32)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sha256Config {
+    chunk_in: Column<Advice>,
+    chunk_out: Column<Advice>,
+    acc_in: Column<Advice>,
+    acc_out: [Column<Advice>; 2],
+    tag: Column<Fixed>,
+    size: Column<Fixed>,
+    coef_in: Column<Fixed>,
+    coef_out: [Column<Fixed>; 2],
+    pub(crate) terms: [Column<Advice>; NUM_TERMS],
+    coefs: [Column<Fixed>; NUM_TERMS],
+    constant: Column<Fixed>,
+    pub(crate) sum: Column<Advice>,
+    table: [TableColumn; 4],
+    first: Selector,
+    next: Selector,
+    lookup: Selector,
+    linear: Selector,
+}
+
+impl Sha256Config {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let [chunk_in, chunk_out, acc_in, sum] = [(); 4].map(|_| meta.advice_column());
+        let acc_out = [(); 2].map(|_| meta.advice_column());
+        let terms = [(); NUM_TERMS].map(|_| meta.advice_column());
+        for column in [acc_in, sum].into_iter().chain(acc_out).chain(terms) {
+            meta.enable_equality(column);
+        }
+        let [tag, size, coef_in, constant] = [(); 4].map(|_| meta.fixed_column());
+        let coef_out = [(); 2].map(|_| meta.fixed_column());
+        let coefs = [(); NUM_TERMS].map(|_| meta.fixed_column());
+        // the IV and the padding are constants
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+        let table = [(); 4].map(|_| meta.lookup_table_column());
+        let [first, next, linear] = [(); 3].map(|_| meta.selector());
+        // lookup inputs can't use simple selectors, disabled rows look up zero
+        let lookup = meta.complex_selector();
+
+        meta.lookup("normalize chunk", |meta| {
+            let s = meta.query_selector(lookup);
+            let [tag, size] = [tag, size].map(|column| meta.query_fixed(column, Rotation::cur()));
+            let [chunk_in, chunk_out] = [chunk_in, chunk_out].map(|column| meta.query_advice(column, Rotation::cur()));
+            [tag, size, chunk_in, chunk_out]
+                .into_iter()
+                .zip(table)
+                .map(|(input, column)| (s.clone() * input, column))
+                .collect()
+        });
+
+        // the accumulators recompose the chunks from the least significant one
+        meta.create_gate("normalize", |meta| {
+            let [first, next] = [first, next].map(|selector| meta.query_selector(selector));
+            let mut constraints = Vec::new();
+            let accs = [(acc_in, chunk_in, coef_in), (acc_out[0], chunk_out, coef_out[0]), (acc_out[1], chunk_out, coef_out[1])];
+            for (acc, chunk, coef) in accs {
+                let [acc_cur, acc_prev] = [0, -1].map(|rotation| meta.query_advice(acc, Rotation(rotation)));
+                let term = meta.query_fixed(coef, Rotation::cur()) * meta.query_advice(chunk, Rotation::cur());
+                constraints.push(first.clone() * (term.clone() - acc_cur.clone()));
+                constraints.push(next.clone() * (acc_prev + term - acc_cur));
+            }
+            constraints
+        });
+
+        meta.create_gate("linear", |meta| {
+            let s = meta.query_selector(linear);
+            let constant = meta.query_fixed(constant, Rotation::cur());
+            let combination = terms.iter().zip(coefs).fold(constant, |acc, (term, coef)| {
+                acc + meta.query_fixed(coef, Rotation::cur()) * meta.query_advice(*term, Rotation::cur())
+            });
+            vec![s * (combination - meta.query_advice(sum, Rotation::cur()))]
+        });
+
+        Self {
+            chunk_in,
+            chunk_out,
+            acc_in,
+            acc_out,
+            tag,
+            size,
+            coef_in,
+            coef_out,
+            terms,
+            coefs,
+            constant,
+            sum,
+            table,
+            first,
+            next,
+            lookup,
+            linear,
+        }
+    }
+
+    /// Assigns the lookup table, with `extra` rows `(input, output)` for [`Sha256Config::lookup`].
+    pub fn assign_table(&self, layouter: &mut impl Layouter<Fr>, extra: &[(Fr, Fr)]) -> Result<(), Error> {
+        // disabled rows look up the all-zero row, which also pads the rest of the table
+        let mut rows = vec![[Fr::ZERO; 4]];
+        for tag in TAGS {
+            let (base_in, max_digit, base_out) = tag.bases();
+            let radix = max_digit as usize + 1;
+            for size in tag.chunk_sizes() {
+                for index in 0..radix.pow(size as u32) {
+                    let digits = (0..size).map(|k| (index / radix.pow(k as u32) % radix) as u8).collect::<Vec<_>>();
+                    let out = digits.iter().map(|digit| tag.apply(*digit)).collect::<Vec<_>>();
+                    rows.push([Fr::from(tag as u64), Fr::from(size as u64), compose(&digits, base_in), compose(&out, base_out)]);
+                }
+            }
+        }
+        rows.extend(extra.iter().map(|(input, output)| [Fr::from(Tag::Extra as u64), Fr::ONE, *input, *output]));
+
+        layouter.assign_table(
+            || "sha256 table",
+            |mut table| {
+                for (offset, row) in rows.iter().enumerate() {
+                    for (column, value) in self.table.iter().zip(row) {
+                        table.assign_cell(|| "sha256 table", *column, offset, || Value::known(*value))?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    // assigns a looked up chunk and the accumulators up to it, the first one of its word or the
+    // next one
+    #[allow(clippy::too_many_arguments)]
+    fn assign_chunk(
+        &self,
+        region: &mut Region<'_, Fr>,
+        row: usize,
+        tag: Tag,
+        size: usize,
+        first: bool,
+        (chunk_in, chunk_out): (Fr, Fr),
+        coefs: [Fr; 3],
+        accs: [Fr; 3],
+    ) -> Result<Vec<AssignedCell<Fr, Fr>>, Error> {
+        self.lookup.enable(region, row)?;
+        if first {
+            self.first.enable(region, row)?;
+        } else {
+            self.next.enable(region, row)?;
+        }
+
+        let fixed = [
+            (self.tag, Fr::from(tag as u64)),
+            (self.size, Fr::from(size as u64)),
+            (self.coef_in, coefs[0]),
+            (self.coef_out[0], coefs[1]),
+            (self.coef_out[1], coefs[2]),
+        ];
+        for (column, value) in fixed {
+            region.assign_fixed(|| "normalize", column, row, || Value::known(value))?;
+        }
+        region.assign_advice(|| "chunk_in", self.chunk_in, row, || Value::known(chunk_in))?;
+        region.assign_advice(|| "chunk_out", self.chunk_out, row, || Value::known(chunk_out))?;
+        [self.acc_in, self.acc_out[0], self.acc_out[1]]
+            .into_iter()
+            .zip(accs)
+            .map(|(column, value)| region.assign_advice(|| "acc", column, row, || Value::known(value)))
+            .collect()
+    }
+
+    // looks up the chunks of `digits` from `offset` on, returning the recomposed input and the two
+    // outputs, each the sum of the normalized digits moved by its maps
+    pub(crate) fn normalize(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        tag: Tag,
+        digits: &[u8],
+        maps: [&[Map]; 2],
+    ) -> Result<(AssignedCell<Fr, Fr>, [Word; 2]), Error> {
+        let (base_in, _, base_out) = tag.bases();
+        let out = digits.iter().map(|digit| tag.apply(*digit)).collect::<Vec<_>>();
+        let moved = maps.map(|maps| {
+            let mut moved = [0u8; WORD_BITS];
+            for map in maps {
+                for (i, digit) in out.iter().enumerate() {
+                    if let Some(position) = map.position(i) {
+                        moved[position] += digit;
+                    }
+                }
+            }
+            moved
+        });
+        let boundaries = maps.iter().flat_map(|maps| maps.iter().map(|map| map.boundary())).collect::<Vec<_>>();
+
+        let (mut acc_in, mut acc_out) = (Fr::ZERO, [Fr::ZERO; 2]);
+        let mut cells = Vec::new();
+        for (start, size) in chunks(digits.len(), &boundaries) {
+            let coef_in = pow(base_in, start);
+            let coef_out = maps.map(|maps| {
+                maps.iter()
+                    .filter_map(|map| map.position(start))
+                    .fold(Fr::ZERO, |coef, position| coef + pow(base_out, position))
+            });
+            let chunk_in = compose(&digits[start..start + size], base_in);
+            let chunk_out = compose(&out[start..start + size], base_out);
+            acc_in += coef_in * chunk_in;
+            for (acc, coef) in acc_out.iter_mut().zip(coef_out) {
+                *acc += coef * chunk_out;
+            }
+
+            cells = self.assign_chunk(
+                region,
+                *offset,
+                tag,
+                size,
+                start == 0,
+                (chunk_in, chunk_out),
+                [coef_in, coef_out[0], coef_out[1]],
+                [acc_in, acc_out[0], acc_out[1]],
+            )?;
+            *offset += 1;
+        }
+
+        let [input, first, second]: [AssignedCell<Fr, Fr>; 3] = cells.try_into().unwrap();
+        let [first_digits, second_digits] = moved;
+        Ok((input, [Word { cell: first, digits: first_digits }, Word { cell: second, digits: second_digits }]))
+    }
+
+    /// Looks up `(input, output)` among the extra rows of the table, returning their cells.
+    pub(crate) fn lookup(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        (input, output): (Fr, Fr),
+    ) -> Result<(AssignedCell<Fr, Fr>, AssignedCell<Fr, Fr>), Error> {
+        let cells = self.assign_chunk(region, *offset, Tag::Extra, 1, true, (input, output), [Fr::ONE, Fr::ONE, Fr::ZERO], [input, output, Fr::ZERO])?;
+        *offset += 1;
+        Ok((cells[0].clone(), cells[1].clone()))
+    }
+
+    // a single row with `sum_k coef_k * term_k + constant`
+    pub(crate) fn linear(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        terms: &[(Fr, &AssignedCell<Fr, Fr>)],
+        constant: Fr,
+    ) -> Result<AssignedCell<Fr, Fr>, Error> {
+        debug_assert!(terms.len() <= NUM_TERMS);
+        let row = *offset;
+        self.linear.enable(region, row)?;
+
+        let mut sum = Value::known(constant);
+        for j in 0..NUM_TERMS {
+            let coef = match terms.get(j) {
+                Some((coef, cell)) => {
+                    cell.copy_advice(|| "term", region, self.terms[j], row)?;
+                    sum = sum + cell.value().map(|value| *coef * value);
+                    *coef
+                }
+                None => {
+                    region.assign_advice(|| "term", self.terms[j], row, || Value::known(Fr::ZERO))?;
+                    Fr::ZERO
+                }
+            };
+            region.assign_fixed(|| "coef", self.coefs[j], row, || Value::known(coef))?;
+        }
+        region.assign_fixed(|| "constant", self.constant, row, || Value::known(constant))?;
+        let cell = region.assign_advice(|| "sum", self.sum, row, || sum)?;
+        *offset += 1;
+
+        Ok(cell)
+    }
+
+    // `sum_k coef_k * word_k + constant` of sparse words, the digits of the combination must stay
+    // below the sparse base so that no carries occur
+    fn sparse_sum(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        terms: &[(i64, &Word)],
+        constant: u8,
+    ) -> Result<Word, Error> {
+        let mut digits = [constant as i64; WORD_BITS];
+        for (coef, word) in terms {
+            for (digit, term) in digits.iter_mut().zip(word.digits) {
+                *digit += coef * term as i64;
+            }
+        }
+        let digits = digits.map(|digit| {
+            debug_assert!((0..SPARSE_BASE as i64).contains(&digit));
+            digit as u8
+        });
+
+        let terms = terms.iter().map(|(coef, word)| (signed(*coef), &word.cell)).collect::<Vec<_>>();
+        let cell = self.linear(region, offset, &terms, compose(&[constant; WORD_BITS], SPARSE_BASE))?;
+        Ok(Word { cell, digits })
+    }
+
+    // a dense word, range checked by its conversion to sparse, with the outputs of `maps`
+    fn word(&self, region: &mut Region<'_, Fr>, offset: &mut usize, value: u32, maps: [&[Map]; 2]) -> Result<(Word, [Word; 2]), Error> {
+        let (cell, outputs) = self.normalize(region, offset, Tag::Spread, &to_digits(value as u64, WORD_BITS), maps)?;
+        Ok((Word::dense(cell, value), outputs))
+    }
+
+    // the dense word of the digits of the sparse `word` normalized by `tag`
+    fn to_dense(&self, region: &mut Region<'_, Fr>, offset: &mut usize, tag: Tag, word: &Word) -> Result<Word, Error> {
+        let (input, [dense, _]) = self.normalize(region, offset, tag, &word.digits, [&IDENTITY, &[]])?;
+        region.constrain_equal(input.cell(), word.cell.cell())?;
+        Ok(dense)
+    }
+
+    // the sum mod 2^32 of terms with their values, as a word with the outputs of `maps`
+    fn add(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        terms: &[(u64, &AssignedCell<Fr, Fr>)],
+        maps: [&[Map]; 2],
+    ) -> Result<(Word, [Word; 2]), Error> {
+        let sum = terms.iter().map(|(value, _)| value).sum::<u64>();
+        let (carry, _) = self.normalize(region, offset, Tag::Spread, &to_digits(sum >> WORD_BITS, CARRY_BITS), [&[], &[]])?;
+        let mut terms = terms.iter().map(|(_, cell)| (Fr::ONE, *cell)).collect::<Vec<_>>();
+        terms.push((-pow(2, WORD_BITS), &carry));
+        let cell = self.linear(region, offset, &terms, Fr::ZERO)?;
+
+        let (word, outputs) = self.word(region, offset, sum as u32, maps)?;
+        region.constrain_equal(word.cell.cell(), cell.cell())?;
+        Ok((word, outputs))
+    }
+
+    /// Assigns `half`, a 128-bit big-endian half of a digest, as the packing of four dense words.
+    pub fn pack(&self, region: &mut Region<'_, Fr>, offset: &mut usize, words: &[Word], half: Fr) -> Result<AssignedCell<Fr, Fr>, Error> {
+        let terms = words
+            .iter()
+            .enumerate()
+            .map(|(j, word)| (pow(2, WORD_BITS * (words.len() - 1 - j)), &word.cell))
+            .collect::<Vec<_>>();
+        let packed = self.linear(region, offset, &terms, Fr::ZERO)?;
+        let cell = region.assign_advice(|| "half", self.terms[0], *offset, || Value::known(half))?;
+        region.constrain_equal(cell.cell(), packed.cell())?;
+        *offset += 1;
+        Ok(cell)
+    }
+
+    /// Assigns a compression of `block` from the digest words `state` of a previous one, or from
+    /// the IV, with the first `message_words` words of the block as witnesses and the rest as
+    /// constant padding. Returns the message words and the digest words.
+    pub fn assign_block(
+        &self,
+        region: &mut Region<'_, Fr>,
+        offset: &mut usize,
+        state: Option<&[Word]>,
+        block: &[u32; 16],
+        message_words: usize,
+    ) -> Result<(Vec<Word>, Vec<Word>), Error> {
+        // a, b, c, d then e, f, g, h in reverse order, so that round t reads its state from index t
+        let (mut a, mut e) = (Vec::new(), Vec::new());
+        for j in (0..4).rev() {
+            for (seq, j, sigma) in [(&mut a, j, &BIG_SIGMA0[..]), (&mut e, 4 + j, &BIG_SIGMA1[..])] {
+                let word = self.word(region, offset, state.map_or(IV[j], |state| state[j].value()), [&IDENTITY, sigma])?;
+                match state {
+                    Some(state) => region.constrain_equal(word.0.cell.cell(), state[j].cell.cell())?,
+                    None => region.constrain_constant(word.0.cell.cell(), Fr::from(IV[j] as u64))?,
+                }
+                seq.push(word);
+            }
+        }
+
+        // the message words with the sparse sums of their small sigmas
+        let mut w = Vec::with_capacity(NUM_ROUNDS);
+        for (t, value) in block.iter().enumerate() {
+            let word = self.word(region, offset, *value, [&SMALL_SIGMA0, &SMALL_SIGMA1])?;
+            if t >= message_words {
+                region.constrain_constant(word.0.cell.cell(), Fr::from(*value as u64))?;
+            }
+            w.push(word);
+        }
+
+        for t in 0..NUM_ROUNDS {
+            if t >= 16 {
+                let sigma1 = self.to_dense(region, offset, Tag::Parity, &w[t - 2].1[1])?;
+                let sigma0 = self.to_dense(region, offset, Tag::Parity, &w[t - 15].1[0])?;
+                let terms = [&sigma1, &w[t - 7].0, &sigma0, &w[t - 16].0].map(|word| (word.value() as u64, &word.cell));
+                let word = self.add(region, offset, &terms, [&SMALL_SIGMA0, &SMALL_SIGMA1])?;
+                w.push(word);
+            }
+
+            let (d, c, b, a_t) = (&a[t].0, &a[t + 1].1[0], &a[t + 2].1[0], &a[t + 3].1);
+            let (h, g, f, e_t) = (&e[t].0, &e[t + 1].1[0], &e[t + 2].1[0], &e[t + 3].1);
+            let sigma0 = self.to_dense(region, offset, Tag::Parity, &a_t[1])?;
+            let sigma1 = self.to_dense(region, offset, Tag::Parity, &e_t[1])?;
+            let maj = self.sparse_sum(region, offset, &[(1, &a_t[0]), (1, b), (1, c)], 0)?;
+            let maj = self.to_dense(region, offset, Tag::Majority, &maj)?;
+            // ch(e, f, g) = (e & f) ^ (!e & g), whose terms never share a bit
+            let ch_f = self.sparse_sum(region, offset, &[(1, &e_t[0]), (1, f)], 0)?;
+            let ch_f = self.to_dense(region, offset, Tag::Majority, &ch_f)?;
+            let ch_g = self.sparse_sum(region, offset, &[(-1, &e_t[0]), (1, g)], 1)?;
+            let ch_g = self.to_dense(region, offset, Tag::Majority, &ch_g)?;
+
+            // t1 is not reduced, the two sums using it drop its carry
+            let t1 = [h, &sigma1, &ch_f, &ch_g, &w[t].0];
+            let t1_value = t1.iter().map(|word| word.value() as u64).sum::<u64>() + K[t] as u64;
+            let t1 = self.linear(region, offset, &t1.map(|word| (Fr::ONE, &word.cell)), Fr::from(K[t] as u64))?;
+            let terms = [(t1_value, &t1), (sigma0.value() as u64, &sigma0.cell), (maj.value() as u64, &maj.cell)];
+            let a_next = self.add(region, offset, &terms, [&IDENTITY, &BIG_SIGMA0])?;
+            let e_next = self.add(region, offset, &[(d.value() as u64, &d.cell), (t1_value, &t1)], [&IDENTITY, &BIG_SIGMA1])?;
+            a.push(a_next);
+            e.push(e_next);
+        }
+
+        // H_j is the initial word j added to the final one, both at the same index from the end of
+        // their half of the state
+        let mut digest = Vec::with_capacity(DIGEST_WORDS);
+        for j in 0..DIGEST_WORDS {
+            let (seq, k) = if j < 4 { (&a, 3 - j) } else { (&e, 7 - j) };
+            let (initial, output) = (&seq[k].0, &seq[NUM_ROUNDS + k].0);
+            let terms = [(initial.value() as u64, &initial.cell), (output.value() as u64, &output.cell)];
+            digest.push(self.add(region, offset, &terms, [&[], &[]])?.0);
+        }
+
+        let message = w.into_iter().take(message_words).map(|(word, _)| word).collect();
+        Ok((message, digest))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sha256ChainCircuit {
+    pub num_hashes_per_step: usize,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+}
+
+impl Sha256ChainCircuit {
+    // hashes the input digest `num_hashes_per_step` times
+    pub fn new(initial_input: Vec<Fr>, num_hashes_per_step: usize) -> Self {
+        let digest = (0..num_hashes_per_step).fold(scalars_to_digest(&initial_input), |digest, _| compress(message_block(&digest)));
+
+        Self {
+            num_hashes_per_step,
+            step_idx: 0,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output: digest_to_scalars(&digest),
+        }
+    }
+}
+
+impl Circuit<Fr> for Sha256ChainCircuit {
+    type Config = Sha256Config;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        Sha256Config::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fr> for Sha256ChainCircuit {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C1> for Sha256ChainCircuit {
+    fn arity() -> usize {
+        2
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        // the chain continues from the current digest
+        *self = Self {
+            step_idx: self.step_idx + 1,
+            initial_input: self.initial_input.clone(),
+            ..Self::new(self.output.clone(), self.num_hashes_per_step)
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        config.assign_table(&mut layouter, &[])?;
+
+        layouter.assign_region(
+            || "sha256",
+            |mut region| {
+                let mut offset = 0;
+                let mut z_in = Vec::new();
+                let mut prev_digest: Option<Vec<Word>> = None;
+                for _ in 0..self.num_hashes_per_step {
+                    let digest = prev_digest.as_ref().map_or(scalars_to_digest(&self.input), |digest| {
+                        std::array::from_fn(|j| digest[j].value())
+                    });
+                    let (message, digest) = config.assign_block(&mut region, &mut offset, None, &message_block(&digest), DIGEST_WORDS)?;
+
+                    // the message is the digest of the previous compression, or the step input
+                    match &prev_digest {
+                        Some(prev_digest) => {
+                            for (word, prev_word) in message.iter().zip(prev_digest) {
+                                region.constrain_equal(word.cell.cell(), prev_word.cell.cell())?;
+                            }
+                        }
+                        None => {
+                            z_in = message
+                                .chunks(4)
+                                .zip(&self.input)
+                                .map(|(words, half)| config.pack(&mut region, &mut offset, words, *half))
+                                .collect::<Result<Vec<_>, _>>()?;
+                        }
+                    }
+                    prev_digest = Some(digest);
+                }
+
+                let z_out = prev_digest
+                    .expect("a step has at least one hash")
+                    .chunks(4)
+                    .zip(&self.output)
+                    .map(|(words, half)| config.pack(&mut region, &mut offset, words, *half))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok((z_in, z_out))
+            },
+        )
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_sha256_preprocess(
+    num_hashes_per_step: usize,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<Sha256ChainCircuit>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = Sha256ChainCircuit::new(vec![Fr::ZERO; 2], num_hashes_per_step);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn sha256_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+    use sha2::{Digest, Sha256};
+
+    let mut circuit = Sha256ChainCircuit::new(vec![Fr::ZERO; 2], 2);
+    MockProver::run(14, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // the chain continues from the previous output
+    let output = circuit.output().to_vec();
+    circuit.next();
+    assert_eq!(circuit.input(), output.as_slice());
+    MockProver::run(14, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // four hashes of 32 zero bytes
+    let expected = (0..4).fold([0u8; 32], |digest, _| Sha256::digest(digest).into());
+    let words = scalars_to_digest(circuit.output());
+    assert_eq!(words.iter().flat_map(|word| word.to_be_bytes()).collect::<Vec<_>>(), expected);
+
+    // an output that is not the hash of the input
+    circuit.output[0] += Fr::ONE;
+    assert!(MockProver::run(14, &circuit, Vec::new()).unwrap().verify().is_err());
+}
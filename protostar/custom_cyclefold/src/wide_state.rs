@@ -0,0 +1,174 @@
+//! Wide state counter for Protostar, the IO hashing workload: each step increments every element
+//! of a state of arity `ARITY`, `z_{i+1}[k] = z_i[k] + 1`, one row per element. The step circuit
+//! is trivial, so the growth of the per-step cost with the arity is dominated by the hashing of
+//! the state into the public IO of the recursive circuit. The arity of a step circuit is fixed by
+//! its type, hence the const generic.
+use crate::ivc::{
+    run_protostar_hyperplonk_ivc_preprocess, IvcProverParam, IvcVerifierParam, PrimaryCircuit,
+    SecondaryCircuit, Fq, Fr, C1, P1, P2,
+};
+use halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector};
+use halo2_proofs::poly::Rotation;
+use plonkish_backend::accumulation::protostar::ivc::halo2::StepCircuit;
+use plonkish_backend::frontend::halo2::CircuitExt;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+
+pub const MAX_ARITY: usize = 1024;
+
+#[derive(Clone, Debug)]
+pub struct WideStateConfig {
+    z: Column<Advice>,
+    z_next: Column<Advice>,
+    selector: Selector,
+}
+
+impl WideStateConfig {
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let [z, z_next] = [(); 2].map(|_| meta.advice_column());
+        for column in [z, z_next] {
+            meta.enable_equality(column);
+        }
+        let selector = meta.selector();
+
+        meta.create_gate("increment", |meta| {
+            let s = meta.query_selector(selector);
+            let [z, z_next] = [z, z_next].map(|column| meta.query_advice(column, Rotation::cur()));
+            vec![s * (z + Expression::Constant(Fr::ONE) - z_next)]
+        });
+
+        Self { z, z_next, selector }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WideStateCircuit<const ARITY: usize> {
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+}
+
+impl<const ARITY: usize> WideStateCircuit<ARITY> {
+    pub fn new(initial_input: Vec<Fr>) -> Self {
+        assert!((1..=MAX_ARITY).contains(&ARITY), "the arity ranges from 1 to {}", MAX_ARITY);
+        assert_eq!(initial_input.len(), ARITY);
+        let output = initial_input.iter().map(|z| *z + Fr::ONE).collect();
+
+        Self {
+            step_idx: 0,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output,
+        }
+    }
+}
+
+impl<const ARITY: usize> Circuit<Fr> for WideStateCircuit<ARITY> {
+    type Config = WideStateConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        WideStateConfig::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl<const ARITY: usize> CircuitExt<Fr> for WideStateCircuit<ARITY> {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl<const ARITY: usize> StepCircuit<C1> for WideStateCircuit<ARITY> {
+    fn arity() -> usize {
+        ARITY
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        *self = Self {
+            step_idx: self.step_idx + 1,
+            initial_input: self.initial_input.clone(),
+            ..Self::new(self.output.clone())
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        layouter.assign_region(
+            || "increment",
+            |mut region| {
+                let (mut z_in, mut z_out) = (Vec::with_capacity(ARITY), Vec::with_capacity(ARITY));
+                for (offset, (z, z_next)) in self.input.iter().zip(&self.output).enumerate() {
+                    config.selector.enable(&mut region, offset)?;
+                    z_in.push(region.assign_advice(|| "z", config.z, offset, || Value::known(*z))?);
+                    z_out.push(region.assign_advice(|| "z_next", config.z_next, offset, || Value::known(*z_next))?);
+                }
+                Ok((z_in, z_out))
+            },
+        )
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_wide_state_preprocess<const ARITY: usize>(
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit<WideStateCircuit<ARITY>>, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = WideStateCircuit::<ARITY>::new(vec![Fr::ZERO; ARITY]);
+    run_protostar_hyperplonk_ivc_preprocess(
+        step_circuit,
+        primary_num_vars,
+        primary_param,
+        cyclefold_num_vars,
+        cyclefold_param,
+    )
+}
+
+#[test]
+fn wide_state_step_circuit_test() {
+    use halo2_proofs::dev::MockProver;
+
+    let mut circuit = WideStateCircuit::<64>::new(vec![Fr::ZERO; 64]);
+    MockProver::run(8, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // the chain continues from the previous output
+    let output = circuit.output().to_vec();
+    circuit.next();
+    assert_eq!(circuit.input(), output.as_slice());
+    assert_eq!(circuit.output(), vec![Fr::from(2); 64].as_slice());
+    MockProver::run(8, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // an output that skips an increment
+    circuit.output[0] += Fr::ONE;
+    assert!(MockProver::run(8, &circuit, Vec::new()).unwrap().verify().is_err());
+}
@@ -1 +1 @@
-
+pub mod minroot;
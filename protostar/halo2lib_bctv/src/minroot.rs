@@ -0,0 +1,312 @@
+//! MinRoot step circuit for the BCTV Protostar IVC, executing a configurable number of iterations
+//! of the `MinRoot` function per step with the halo2-base gadget of `halo2lib_common`. The
+//! secondary chain only folds the primary one, its step circuit is trivial.
+use halo2_base::gates::circuit::{builder::BaseCircuitBuilder, BaseCircuitParams, BaseConfig};
+use halo2_base::gates::GateChip;
+use halo2_base::halo2_proofs::circuit::{AssignedCell, Layouter, Region, SimpleFloorPlanner, Value};
+use halo2_base::halo2_proofs::halo2curves::bn256::{self, Bn256};
+use halo2_base::halo2_proofs::halo2curves::ff::{Field, PrimeField};
+use halo2_base::halo2_proofs::halo2curves::grumpkin;
+use halo2_base::halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error};
+use plonkish_backend::accumulation::protostar::ivc::halo2::{
+    preprocess, prove_steps, ProtostarIvcProverParam, ProtostarIvcVerifierParam, RecursiveCircuit,
+    StepCircuit,
+};
+use plonkish_backend::frontend::halo2::{CircuitExt, Halo2Circuit};
+use plonkish_backend::pcs::multilinear::{Gemini, MultilinearIpa};
+use plonkish_backend::pcs::univariate::UnivariateKzg;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::util::test::seeded_std_rng;
+use plonkish_backend::util::transcript::PoseidonTranscript;
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use halo2lib_common::minroot::{minroot_constraints, minroot_native};
+
+type C1 = bn256::G1Affine;
+type C2 = grumpkin::G1Affine;
+type Fr = bn256::Fr;
+type Fq = bn256::Fq;
+type P1 = Gemini<UnivariateKzg<Bn256>>;
+type P2 = MultilinearIpa<C2>;
+type AT1 = PoseidonTranscript<Fr, Cursor<Vec<u8>>>;
+type AT2 = PoseidonTranscript<Fq, Cursor<Vec<u8>>>;
+
+pub type PrimaryCircuit = Halo2Circuit<Fr, RecursiveCircuit<C1, MinRootCircuit>>;
+pub type SecondaryCircuit = Halo2Circuit<Fq, RecursiveCircuit<C2, TrivialCircuit>>;
+pub type IvcProverParam = ProtostarIvcProverParam<C1, P1, P2, AT1, AT2>;
+pub type IvcVerifierParam = ProtostarIvcVerifierParam<C1, P1, P2>;
+
+// assigns `values` to consecutive rows of `column` from `offset`
+fn assign_state<F: PrimeField>(
+    region: &mut Region<F>,
+    column: Column<Advice>,
+    offset: usize,
+    values: &[F],
+) -> Result<Vec<AssignedCell<F, F>>, Error> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(k, value)| region.assign_advice(|| "z", column, offset + k, || Value::known(*value)))
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct MinRootCircuit {
+    pub num_iters_per_step: usize,
+    pub params: BaseCircuitParams,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+}
+
+impl MinRootCircuit {
+    pub fn new(initial_input: Vec<Fr>, num_iters_per_step: usize, params: BaseCircuitParams) -> Self {
+        assert_eq!(initial_input.len(), 3);
+        let output = minroot_native(num_iters_per_step, &initial_input);
+
+        Self {
+            num_iters_per_step,
+            params,
+            step_idx: 0,
+            initial_input: initial_input.clone(),
+            input: initial_input,
+            output,
+        }
+    }
+}
+
+impl Circuit<Fr> for MinRootCircuit {
+    type Config = BaseConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = BaseCircuitParams;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn params(&self) -> Self::Params {
+        self.params.clone()
+    }
+
+    fn configure_with_params(meta: &mut ConstraintSystem<Fr>, params: Self::Params) -> Self::Config {
+        BaseConfig::configure(meta, params)
+    }
+
+    fn configure(_: &mut ConstraintSystem<Fr>) -> Self::Config {
+        unreachable!("the columns are configured from BaseCircuitParams")
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fr> for MinRootCircuit {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C1> for MinRootCircuit {
+    fn arity() -> usize {
+        3
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        self.step_idx += 1;
+        self.input = self.output.clone();
+        self.output = minroot_native(self.num_iters_per_step, &self.input);
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        let column = config.gate().basic_gates[0][0].value;
+        let (z_in, z_out) = layouter.assign_region(
+            || "state",
+            |mut region| {
+                let z_in = assign_state(&mut region, column, 0, &self.input)?;
+                let z_out = assign_state(&mut region, column, 3, &self.output)?;
+                Ok((z_in, z_out))
+            },
+        )?;
+
+        // the state cells are loaded into the builder so that its copy constraints tie them
+        let mut builder = BaseCircuitBuilder::new(false).use_params(self.params.clone());
+        let [cells_in, cells_out] = [&z_in, &z_out].map(|cells| {
+            let mut copy_manager = builder.core().phase_manager[0].copy_manager.lock().unwrap();
+            cells.iter().map(|cell| copy_manager.load_external_assigned(cell.clone())).collect::<Vec<_>>()
+        });
+        let ctx = builder.main(0);
+        let z = minroot_constraints(ctx, &GateChip::default(), self.num_iters_per_step, [cells_in[0], cells_in[1], cells_in[2]]);
+        for (z, cell) in z.iter().zip(cells_out.iter()) {
+            ctx.constrain_equal(z, cell);
+        }
+        Circuit::synthesize(&builder, config, layouter.namespace(|| "minroot"))?;
+
+        Ok((z_in, z_out))
+    }
+}
+
+/// Step circuit of the secondary chain, which keeps its single state element.
+#[derive(Clone, Debug)]
+pub struct TrivialCircuit {
+    pub params: BaseCircuitParams,
+    step_idx: usize,
+    input: Vec<Fq>,
+}
+
+impl TrivialCircuit {
+    pub fn new(params: BaseCircuitParams) -> Self {
+        Self { params, step_idx: 0, input: vec![Fq::ZERO] }
+    }
+}
+
+impl Circuit<Fq> for TrivialCircuit {
+    type Config = BaseConfig<Fq>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = BaseCircuitParams;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn params(&self) -> Self::Params {
+        self.params.clone()
+    }
+
+    fn configure_with_params(meta: &mut ConstraintSystem<Fq>, params: Self::Params) -> Self::Config {
+        BaseConfig::configure(meta, params)
+    }
+
+    fn configure(_: &mut ConstraintSystem<Fq>) -> Self::Config {
+        unreachable!("the columns are configured from BaseCircuitParams")
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fq>) -> Result<(), Error> {
+        <Self as StepCircuit<C2>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fq> for TrivialCircuit {
+    fn instances(&self) -> Vec<Vec<Fq>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C2> for TrivialCircuit {
+    fn arity() -> usize {
+        1
+    }
+
+    fn initial_input(&self) -> &[Fq] {
+        &self.input
+    }
+
+    fn input(&self) -> &[Fq] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fq] {
+        &self.input
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        self.step_idx += 1;
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(Vec<AssignedCell<Fq, Fq>>, Vec<AssignedCell<Fq, Fq>>), Error> {
+        let column = config.gate().basic_gates[0][0].value;
+        let z = layouter.assign_region(|| "state", |mut region| assign_state(&mut region, column, 0, &self.input))?;
+        Ok((z.clone(), z))
+    }
+}
+
+/// Preprocesses the IVC of MinRoot steps of `num_iters_per_step` iterations, both chains laid out
+/// with `params` in `2^num_vars` rows.
+pub fn run_protostar_hyperplonk_ivc_minroot_preprocess(
+    num_iters_per_step: usize,
+    params: BaseCircuitParams,
+    num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    secondary_param: <P2 as PolynomialCommitmentScheme<Fq>>::Param,
+) -> (PrimaryCircuit, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let primary_step_circuit = MinRootCircuit::new(vec![Fr::ZERO, Fr::ZERO, Fr::ONE], num_iters_per_step, params.clone());
+    let secondary_step_circuit = TrivialCircuit::new(params);
+    preprocess::<C1, P1, P2, _, _, AT1, AT2>(
+        num_vars,
+        primary_param,
+        primary_step_circuit,
+        num_vars,
+        secondary_param,
+        secondary_step_circuit,
+        seeded_std_rng(),
+    )
+    .unwrap()
+}
+
+/// Proves `num_steps` IVC steps and returns the proving time.
+pub fn run_protostar_hyperplonk_ivc_minroot_prove(
+    primary_circuit: &mut PrimaryCircuit,
+    secondary_circuit: &mut SecondaryCircuit,
+    ivc_pp: &IvcProverParam,
+    num_steps: usize,
+) -> Duration {
+    let start = Instant::now();
+    prove_steps::<C1, P1, P2, MinRootCircuit, TrivialCircuit, AT1, AT2>(
+        ivc_pp,
+        primary_circuit,
+        secondary_circuit,
+        num_steps,
+        seeded_std_rng(),
+    )
+    .unwrap();
+    start.elapsed()
+}
+
+#[test]
+fn minroot_step_circuit_test() {
+    use halo2_base::halo2_proofs::dev::MockProver;
+    use halo2lib_common::minroot::circuit_params;
+
+    // the lookup table of the folding verifier takes 2^LOOKUP_BITS rows
+    let k = 14;
+    let mut circuit = MinRootCircuit::new(vec![Fr::ZERO, Fr::ZERO, Fr::ONE], 64, circuit_params(64, k));
+    MockProver::run(k as u32, &circuit, Vec::new()).unwrap().assert_satisfied();
+    circuit.next();
+    MockProver::run(k as u32, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // the output cells are tied to the iterations
+    circuit.output[1] += Fr::ONE;
+    assert!(MockProver::run(k as u32, &circuit, Vec::new()).unwrap().verify().is_err());
+}
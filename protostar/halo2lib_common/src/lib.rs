@@ -0,0 +1,2 @@
+pub mod minroot;
+pub mod params;
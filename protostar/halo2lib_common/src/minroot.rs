@@ -0,0 +1,86 @@
+//! MinRoot on halo2-base, shared by the BCTV and CycleFold step circuits: a configurable number of
+//! iterations `x_{i+1} = (x_i + y_i)^{1/5}`, `y_{i+1} = x_i + i` per step, checked as
+//! `x_{i+1}^5 = x_i + y_i` through two squarings and a multiplication. The state is `(i, x_i, y_i)`.
+use halo2_base::gates::circuit::builder::BaseCircuitBuilder;
+use halo2_base::gates::circuit::{BaseCircuitParams, CircuitBuilderStage};
+use halo2_base::gates::{GateChip, GateInstructions};
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use halo2_base::halo2_proofs::halo2curves::ff::Field;
+use halo2_base::utils::modulus;
+use halo2_base::{AssignedValue, Context, QuantumCell::Constant};
+
+use crate::params::MINIMUM_ROWS;
+
+/// Lookup bits of the range-check table the folding verifier of the recursive circuit uses.
+pub const LOOKUP_BITS: usize = 13;
+
+// x^(1/5), the inverse of 5 modulo p - 1 as exponent
+fn fifth_root(x: Fr) -> Fr {
+    let exp = circuit_dsl::minroot::fifth_root_exponent(&modulus::<Fr>());
+    x.pow_vartime(exp.to_u64_digits())
+}
+
+/// Runs `num_iters` iterations natively from the state `z`, returns the next state.
+pub fn minroot_native(num_iters: usize, z: &[Fr]) -> Vec<Fr> {
+    let (mut i, mut x, mut y) = (z[0], z[1], z[2]);
+    for _ in 0..num_iters {
+        (i, x, y) = (i + Fr::ONE, fifth_root(x + y), x + i);
+    }
+    vec![i, x, y]
+}
+
+/// Constrains `num_iters` iterations from the state cells `z`, returns the cells of the next state.
+pub fn minroot_constraints(
+    ctx: &mut Context<Fr>,
+    gate: &impl GateInstructions<Fr>,
+    num_iters: usize,
+    z: [AssignedValue<Fr>; 3],
+) -> [AssignedValue<Fr>; 3] {
+    let [mut i, mut x, mut y] = z;
+    for _ in 0..num_iters {
+        let x_next = ctx.load_witness(fifth_root(*x.value() + y.value()));
+
+        // x_next^5 = x + y
+        let sq = gate.mul(ctx, x_next, x_next);
+        let quad = gate.mul(ctx, sq, sq);
+        let fifth = gate.mul(ctx, quad, x_next);
+        let sum = gate.add(ctx, x, y);
+        ctx.constrain_equal(&fifth, &sum);
+
+        let y_next = gate.add(ctx, x, i);
+        i = gate.add(ctx, i, Constant(Fr::ONE));
+        x = x_next;
+        y = y_next;
+    }
+    [i, x, y]
+}
+
+/// Columns of a step of `num_iters` iterations in `2^k` rows: the cells of the iterations are
+/// spread over as many advice columns as they need, next to a single lookup advice column.
+pub fn circuit_params(num_iters: usize, k: usize) -> BaseCircuitParams {
+    let mut builder = BaseCircuitBuilder::<Fr>::from_stage(CircuitBuilderStage::Mock);
+    let ctx = builder.main(0);
+    let z = [Fr::ZERO, Fr::ZERO, Fr::ONE].map(|value| ctx.load_witness(value));
+    minroot_constraints(ctx, &GateChip::default(), num_iters, z);
+    let num_rows = ctx.advice.len();
+
+    BaseCircuitParams {
+        k,
+        num_advice_per_phase: vec![num_rows.div_ceil((1 << k) - MINIMUM_ROWS)],
+        num_lookup_advice_per_phase: vec![1],
+        num_fixed: 1,
+        lookup_bits: Some(LOOKUP_BITS),
+        num_instance_columns: 1,
+    }
+}
+
+#[test]
+fn minroot_native_test() {
+    let z = minroot_native(16, &[Fr::ZERO, Fr::ZERO, Fr::ONE]);
+    assert_eq!(z[0], Fr::from(16));
+
+    // every iteration inverts the fifth power
+    let z_15 = minroot_native(15, &[Fr::ZERO, Fr::ZERO, Fr::ONE]);
+    assert_eq!(z[1].pow_vartime([5u64]), z_15[1] + z_15[2]);
+    assert_eq!(z[2], z_15[1] + z_15[0]);
+}
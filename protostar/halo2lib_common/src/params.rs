@@ -0,0 +1,65 @@
+//! Picks the smallest halo2 `k` the IVC circuits fit in, from the number of rows the
+//! preprocessed primary and secondary circuits actually use.
+use halo2_base::gates::circuit::BaseCircuitParams;
+use std::fmt;
+
+/// Smallest `k` considered when fitting a circuit.
+pub const MIN_K: usize = 10;
+
+/// Largest `k` the benches are allowed to use. Circuits are first preprocessed at this `k`
+/// to measure their row usage, so raise it if a configuration does not fit.
+pub const MAX_K: usize = 21;
+
+/// Rows at the end of every column that halo2-base keeps free for blinding factors.
+pub const MINIMUM_ROWS: usize = 9;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamsError {
+    /// The circuit needs more rows than `2^max_k` can provide.
+    DoesNotFit { num_rows: usize, max_k: usize },
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamsError::DoesNotFit { num_rows, max_k } => write!(
+                f,
+                "circuit uses {} rows (+{} unusable) which does not fit in 2^{} rows, raise MAX_K",
+                num_rows, MINIMUM_ROWS, max_k
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+/// Returns the minimal `k` such that `num_rows` advice rows and the `2^lookup_bits` rows of the
+/// range-check table fit in a single column.
+pub fn min_k(num_rows: usize, lookup_bits: Option<usize>) -> Result<usize, ParamsError> {
+    let lower = lookup_bits.map_or(MIN_K, |bits| MIN_K.max(bits + 1));
+    (lower..=MAX_K)
+        .find(|&k| num_rows + MINIMUM_ROWS <= 1 << k)
+        .ok_or(ParamsError::DoesNotFit { num_rows, max_k: MAX_K })
+}
+
+/// Returns `params` with `k` shrunk to the minimal value for a circuit using `num_rows` rows.
+pub fn fit_circuit_params(
+    params: &BaseCircuitParams,
+    num_rows: usize,
+) -> Result<BaseCircuitParams, ParamsError> {
+    let k = min_k(num_rows, params.lookup_bits)?;
+    Ok(BaseCircuitParams { k, ..params.clone() })
+}
+
+#[test]
+fn min_k_test() {
+    // the cyclefold circuit used to be hand-fitted into k = 17
+    assert_eq!(min_k(126425, Some(1)), Ok(17));
+    assert_eq!(min_k(344077, Some(13)), Ok(19));
+    // the lookup table alone needs 2^13 rows
+    assert_eq!(min_k(10, Some(13)), Ok(14));
+    assert_eq!(
+        min_k(1 << MAX_K, None),
+        Err(ParamsError::DoesNotFit { num_rows: 1 << MAX_K, max_k: MAX_K })
+    );
+}
@@ -0,0 +1,91 @@
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+use std::time::Duration;
+use criterion::black_box;
+use criterion::BenchmarkId;
+use std::collections::HashMap;
+use plonkish_backend::util::test::seeded_std_rng;
+use halo2_base::halo2_proofs::halo2curves::{bn256::Bn256, grumpkin};
+use halo2lib_cyclefold::ecdsa::{run_protostar_hyperplonk_ivc_ecdsa_preprocess, run_protostar_hyperplonk_ivc_ecdsa_prove, EcdsaChainCircuit};
+use criterion::{criterion_group, criterion_main, Criterion};
+use plonkish_backend::pcs::multilinear::MultilinearIpa;
+use plonkish_backend::pcs::univariate::UnivariateKzg;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+
+// the step circuit is spread over as many advice columns as 2^PRIMARY_K rows require
+const PRIMARY_K: usize = 19;
+// the cyclefold circuit fits in 2^17 rows, see `halo2lib_common::params::min_k`
+const CYCLEFOLD_K: usize = 17;
+// same headroom for the Protostar cross-term polynomials as the custom_cyclefold benches
+const PCS_EXTRA_VARS: usize = 4;
+
+fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
+    let num_steps = 10;
+    // a signature costs a fixed and a variable base scalar multiplication over secp256k1
+    let num_sigs_per_step = vec![1, 2, 4];
+    let (mut primary_circuits, mut secondary_circuits, mut pp_vec, mut columns)
+        = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+
+    let primary_params = UnivariateKzg::<Bn256>::setup(1 << (PRIMARY_K + PCS_EXTRA_VARS), 0, &mut seeded_std_rng()).unwrap();
+    let cyclefold_params = MultilinearIpa::<grumpkin::G1Affine>::setup(1 << (CYCLEFOLD_K + PCS_EXTRA_VARS), 0, &mut seeded_std_rng()).unwrap();
+
+    for &num_sigs in num_sigs_per_step.iter() {
+        let circuit_params = EcdsaChainCircuit::circuit_params(num_sigs, PRIMARY_K);
+        columns.push(circuit_params.num_advice_per_phase[0]);
+        let (primary_circuit, secondary_circuit, ivc_pp, _ivc_vp)
+            = run_protostar_hyperplonk_ivc_ecdsa_preprocess(num_sigs, circuit_params, PRIMARY_K, primary_params.clone(), CYCLEFOLD_K, cyclefold_params.clone());
+
+        primary_circuits.push(primary_circuit);
+        secondary_circuits.push(secondary_circuit);
+        pp_vec.push(ivc_pp);
+    }
+
+    let mut group = c.benchmark_group("Halo2lib Protostar Cyclefold IVC");
+    group.sample_size(10);
+
+    let mut results = Vec::new();
+    for (i, num_sigs) in num_sigs_per_step.iter().enumerate() {
+      let mut time = HashMap::new();
+        let test_name = format!("entire_process_{}", num_sigs);
+        let benchmark_id = BenchmarkId::new(test_name, num_sigs);
+        group.bench_function(benchmark_id, |b| {
+          b.iter_custom(|iters| {
+              let start = Instant::now();
+              for _ in 0..iters {
+                  black_box(run_protostar_hyperplonk_ivc_ecdsa_prove(&mut primary_circuits[i], &mut secondary_circuits[i], &pp_vec[i], num_steps));
+              }
+              let elapsed = start.elapsed();
+              let _ = *time.entry(i)
+                  .and_modify(|e| *e += elapsed)
+                  .or_insert(elapsed);
+              elapsed
+          })
+      });
+
+      let iterations = 10; // Replace this with the actual iteration count used in iter_custom.
+      let total_duration = time.entry(i).or_insert(Duration::ZERO).as_millis();
+      let average_execution_time = total_duration / iterations;
+      results.push((num_sigs, average_execution_time));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../../benchmark_results/halo2lib_ecdsa_protostar_cyclefold.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Primary K  | Cyclefold K | Advice Columns | Num Sigs per step  | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|------------|-------------|----------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (i, (num_sigs, duration)) in results.iter().enumerate() {
+        writeln!(
+            file,
+            "| {}         | {}         | {}          | {}             | {}                 | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, PRIMARY_K, CYCLEFOLD_K, columns[i], num_sigs, duration, (pp_vec[i].primary_pp.witness_count - pp_vec[i].primary_pp.copy_count), (pp_vec[i].cyclefold_pp.witness_count - pp_vec[i].cyclefold_pp.copy_count)
+        ).expect("Failed to write to file");
+    }
+}
+
+fn ecdsa_protostar_cyclefold(c: &mut Criterion) {
+    bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c);
+}
+
+criterion_group!(benches, ecdsa_protostar_cyclefold);
+criterion_main!(benches);
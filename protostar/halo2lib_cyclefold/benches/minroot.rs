@@ -1,62 +1,60 @@
 use std::fs::File;
 use std::io::Write;
-use halo2_base::gates::circuit::BaseCircuitParams;
-use halo2_base::halo2_proofs::halo2curves::{bn256::{self, Bn256}, grumpkin};
-use plonkish_backend::accumulation::protostar::ivc::halo2::test::{run_protostar_hyperplonk_ivc_minroot_preprocess, run_protostar_hyperplonk_ivc_prove};
+use halo2_base::halo2_proofs::halo2curves::{bn256::Bn256, grumpkin};
+use halo2lib_cyclefold::minroot::{run_protostar_hyperplonk_ivc_minroot_preprocess, run_protostar_hyperplonk_ivc_minroot_prove};
+use halo2lib_common::minroot::circuit_params;
 use criterion::{criterion_group, criterion_main, Criterion};
-use plonkish_backend::pcs::multilinear::{Gemini, MultilinearIpa};
+use plonkish_backend::pcs::multilinear::MultilinearIpa;
 use plonkish_backend::pcs::univariate::UnivariateKzg;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::util::test::seeded_std_rng;
+
+// the iterations are spread over as many advice columns as 2^PRIMARY_K rows require
+const PRIMARY_K: usize = 19;
+// the cyclefold circuit fits in 2^17 rows, see `halo2lib_common::params::min_k`
+const CYCLEFOLD_K: usize = 17;
+// same headroom for the Protostar cross-term polynomials as the custom_cyclefold benches
+const PCS_EXTRA_VARS: usize = 4;
 
 fn bench_gemini_kzg_ipa_protostar_hyperplonk_ivc(c: &mut Criterion) {
-    let primary_circuit_params = BaseCircuitParams {
-        k: 19,
-        num_advice_per_phase: vec![1],
-        num_lookup_advice_per_phase: vec![1],
-        num_fixed: 1,
-        lookup_bits: Some(13),
-        num_instance_columns: 1,
-    };
-    let cyclefold_circuit_params = BaseCircuitParams {
-        k: 17,
-        num_advice_per_phase: vec![1],
-        num_lookup_advice_per_phase: vec![0],
-        num_fixed: 1,
-        lookup_bits: Some(1),
-        num_instance_columns: 1,
-    };
-    let (primary_circuit, secondary_circuit, ivc_pp, ivc_vp, primary_size, secondary_size)
-        = run_protostar_hyperplonk_ivc_minroot_preprocess::<
-            bn256::G1Affine,
-            Gemini<UnivariateKzg<Bn256>>,
-            MultilinearIpa<grumpkin::G1Affine>,
-        >(primary_circuit_params, cyclefold_circuit_params);
+    let primary_params = UnivariateKzg::<Bn256>::setup(1 << (PRIMARY_K + PCS_EXTRA_VARS), 0, &mut seeded_std_rng()).unwrap();
+    let cyclefold_params = MultilinearIpa::<grumpkin::G1Affine>::setup(1 << (CYCLEFOLD_K + PCS_EXTRA_VARS), 0, &mut seeded_std_rng()).unwrap();
 
+    let num_iters_per_step = vec![1024, 2048, 4096, 8192];
     let num_steps_values = vec![10, 20]; //, 100, 1000, 10000];
     let mut group = c.benchmark_group("Halo2lib Protostar Cyclefold IVC");
 
     group.sample_size(10);
 
     let mut results = Vec::new();
-    for &num_steps in &num_steps_values {
-        let test_name = format!("entire_process_{}", num_steps);
-        group.bench_function(&test_name, |b| {
-            b.iter_custom(|_iters| run_protostar_hyperplonk_ivc_prove(primary_circuit.clone(), secondary_circuit.clone(), ivc_pp.clone(), ivc_vp.clone(), num_steps))
-        });
+    for &num_iters in &num_iters_per_step {
+        let circuit_params = circuit_params(num_iters, PRIMARY_K);
+        let num_columns = circuit_params.num_advice_per_phase[0];
+        let (mut primary_circuit, mut secondary_circuit, ivc_pp, _ivc_vp)
+            = run_protostar_hyperplonk_ivc_minroot_preprocess(num_iters, circuit_params, PRIMARY_K, primary_params.clone(), CYCLEFOLD_K, cyclefold_params.clone());
+        let primary_size = ivc_pp.primary_pp.witness_count - ivc_pp.primary_pp.copy_count;
+        let secondary_size = ivc_pp.cyclefold_pp.witness_count - ivc_pp.cyclefold_pp.copy_count;
+
+        for &num_steps in &num_steps_values {
+            let test_name = format!("entire_process_{}_{}", num_iters, num_steps);
+            group.bench_function(&test_name, |b| {
+                b.iter_custom(|_iters| run_protostar_hyperplonk_ivc_minroot_prove(&mut primary_circuit, &mut secondary_circuit, &ivc_pp, num_steps))
+            });
 
-        let exec_time = run_protostar_hyperplonk_ivc_prove(primary_circuit.clone(), secondary_circuit.clone(), ivc_pp.clone(), ivc_vp.clone(), num_steps);
-        results.push((num_steps, exec_time));
+            let exec_time = run_protostar_hyperplonk_ivc_minroot_prove(&mut primary_circuit, &mut secondary_circuit, &ivc_pp, num_steps);
+            results.push((num_steps, num_columns, num_iters, exec_time, primary_size, secondary_size));
+        }
     }
 
     group.finish();
-    let num_iters_per_step = primary_circuit.circuit().step_circuit.clone().into_inner().num_iters_per_step;
     let mut file = File::create("../../benchmark_results/halo2lib_minroot_protostar_cyclefold.md").expect("Failed to create file");
-    writeln!(file, "| Num Steps  | Num Iters per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
-    writeln!(file, "|------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
-    for (num_steps, duration) in results {
+    writeln!(file, "| Num Steps  | Primary K  | Cyclefold K | Advice Columns | Num Iters per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|------------|-------------|----------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (num_steps, num_columns, num_iters, duration, primary_size, secondary_size) in results {
         writeln!(
             file,
-            "| {}         | {}               | {:?} ms             | {:?}                | {:?}                  |",
-            num_steps, num_iters_per_step, duration.as_millis(), primary_size, secondary_size
+            "| {}         | {}         | {}          | {}             | {}               | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, PRIMARY_K, CYCLEFOLD_K, num_columns, num_iters, duration.as_millis(), primary_size, secondary_size
         ).expect("Failed to write to file");
     }
 }
@@ -0,0 +1,337 @@
+//! ECDSA verification chain over secp256k1 for Protostar, built with the non-native field and
+//! elliptic curve chips of halo2-ecc. Each step verifies a configurable number of signatures and
+//! absorbs every verified message hash into the running accumulator `acc' = ALPHA * acc + z`,
+//! where `z` is the message hash reduced into the native field.
+//!
+//! The step circuit lays its cells out through a halo2-base `BaseCircuitBuilder`, whose columns
+//! are auto-configured for a given `k` by [`EcdsaChainCircuit::circuit_params`]. The accumulators
+//! are cells of the step circuit, loaded into the builder as external cells so that its copy
+//! constraints tie them to the values it computes.
+use halo2_base::gates::circuit::{builder::BaseCircuitBuilder, BaseCircuitParams, BaseConfig, CircuitBuilderStage};
+use halo2_base::gates::{GateInstructions, RangeChip};
+use halo2_base::halo2_proofs::circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value};
+use halo2_base::halo2_proofs::halo2curves::bn256::{self, Bn256};
+use halo2_base::halo2_proofs::halo2curves::ff::Field;
+use halo2_base::halo2_proofs::halo2curves::group::{prime::PrimeCurveAffine, Curve};
+use halo2_base::halo2_proofs::halo2curves::secp256k1::{Fp, Fq, Secp256k1Affine};
+use halo2_base::halo2_proofs::halo2curves::{grumpkin, CurveAffine};
+use halo2_base::halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use halo2_base::utils::{biguint_to_fe, fe_to_biguint, modulus, BigPrimeField, ScalarField};
+use halo2_base::{AssignedValue, Context, QuantumCell::Constant};
+use halo2_ecc::ecc::{ecdsa::ecdsa_verify_no_pubkey_check, EccChip};
+use halo2_ecc::fields::{fp::FpChip, FieldChip};
+use plonkish_backend::accumulation::protostar::ivc::halo2::{
+    preprocess, prove_steps, CycleFoldCircuit, ProtostarIvcProverParam, ProtostarIvcVerifierParam,
+    RecursiveCircuit, StepCircuit,
+};
+use plonkish_backend::backend::hyperplonk::HyperPlonk;
+use plonkish_backend::frontend::halo2::{CircuitExt, Halo2Circuit};
+use plonkish_backend::pcs::multilinear::{Gemini, MultilinearIpa};
+use plonkish_backend::pcs::univariate::UnivariateKzg;
+use plonkish_backend::pcs::PolynomialCommitmentScheme;
+use plonkish_backend::util::test::seeded_std_rng;
+use plonkish_backend::util::transcript::PoseidonTranscript;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use std::time::{Duration, Instant};
+
+use halo2lib_common::params::MINIMUM_ROWS;
+
+type C1 = bn256::G1Affine;
+type C2 = grumpkin::G1Affine;
+type Fr = bn256::Fr;
+type P1 = Gemini<UnivariateKzg<Bn256>>;
+type P2 = MultilinearIpa<C2>;
+
+pub type PrimaryCircuit = Halo2Circuit<Fr, RecursiveCircuit<C1, EcdsaChainCircuit>>;
+pub type SecondaryCircuit = Halo2Circuit<grumpkin::Fr, CycleFoldCircuit<C2>>;
+pub type IvcProverParam = ProtostarIvcProverParam<C1, HyperPlonk<P1>, HyperPlonk<P2>>;
+pub type IvcVerifierParam = ProtostarIvcVerifierParam<C1, HyperPlonk<P1>, HyperPlonk<P2>>;
+
+/// secp256k1 elements are split into 3 limbs of 88 bits, the halo2-ecc default.
+pub const LIMB_BITS: usize = 88;
+pub const NUM_LIMBS: usize = 3;
+
+// window sizes of the variable and fixed base scalar multiplications
+const VAR_WINDOW_BITS: usize = 4;
+const FIXED_WINDOW_BITS: usize = 4;
+
+const ALPHA: u64 = 0x5eed;
+
+// reduces an element of one prime field into another
+fn reduce<F: ScalarField, G: BigPrimeField>(value: &F) -> G {
+    biguint_to_fe(&(fe_to_biguint(value) % modulus::<G>()))
+}
+
+/// A signature `(r, s)` on the message hash `msg_hash` under the public key `pk`.
+#[derive(Clone, Debug)]
+pub struct Signature {
+    pub pk: Secp256k1Affine,
+    pub msg_hash: Fq,
+    pub r: Fq,
+    pub s: Fq,
+}
+
+impl Signature {
+    // signs a random message hash under a fresh random key
+    pub fn random(rng: &mut impl RngCore) -> Self {
+        let g = Secp256k1Affine::generator();
+        let sk = Fq::random(&mut *rng);
+        let k = Fq::random(&mut *rng);
+        let msg_hash = Fq::random(&mut *rng);
+
+        let r_point = (g * k).to_affine();
+        let r = reduce::<Fp, Fq>(r_point.coordinates().unwrap().x());
+        let s = k.invert().unwrap() * (msg_hash + r * sk);
+
+        Self { pk: (g * sk).to_affine(), msg_hash, r, s }
+    }
+
+    pub fn verify(&self) -> bool {
+        let s_inv = self.s.invert().unwrap();
+        let (u1, u2) = (self.msg_hash * s_inv, self.r * s_inv);
+        let r_point = (Secp256k1Affine::generator() * u1 + self.pk * u2).to_affine();
+        reduce::<Fp, Fq>(r_point.coordinates().unwrap().x()) == self.r
+    }
+}
+
+fn absorb(acc: Fr, sig: &Signature) -> Fr {
+    acc * Fr::from(ALPHA) + reduce::<Fq, Fr>(&sig.msg_hash)
+}
+
+#[derive(Clone, Debug)]
+pub struct EcdsaChainCircuit {
+    pub num_sigs_per_step: usize,
+    pub params: BaseCircuitParams,
+    step_idx: usize,
+    initial_input: Vec<Fr>,
+    input: Vec<Fr>,
+    output: Vec<Fr>,
+    pub signatures: Vec<Signature>,
+}
+
+impl EcdsaChainCircuit {
+    // produces the signatures of step `step_idx`, drawn from a rng seeded with it, to be verified
+    // on top of the accumulator `acc`
+    pub fn new(acc: Fr, step_idx: usize, num_sigs_per_step: usize, params: BaseCircuitParams) -> Self {
+        let mut rng = StdRng::seed_from_u64(step_idx as u64);
+        let signatures = (0..num_sigs_per_step).map(|_| Signature::random(&mut rng)).collect::<Vec<_>>();
+        let output = signatures.iter().fold(acc, absorb);
+
+        Self {
+            num_sigs_per_step,
+            params,
+            step_idx,
+            initial_input: vec![acc],
+            input: vec![acc],
+            output: vec![output],
+            signatures,
+        }
+    }
+
+    /// Auto-configures the columns of a step verifying `num_sigs_per_step` signatures in `2^k`
+    /// rows, using all but one row of the range-check table.
+    pub fn circuit_params(num_sigs_per_step: usize, k: usize) -> BaseCircuitParams {
+        let circuit = Self::new(Fr::ZERO, 0, num_sigs_per_step, BaseCircuitParams::default());
+        let mut builder = BaseCircuitBuilder::from_stage(CircuitBuilderStage::Mock)
+            .use_k(k)
+            .use_lookup_bits(k - 1);
+        let range = builder.range_chip();
+        // the external accumulator takes no row of the builder, a witness cell stands for it
+        let ctx = builder.main(0);
+        let acc = ctx.load_witness(circuit.input[0]);
+        circuit.verify_signatures(ctx, &range, acc);
+        builder.calculate_params(Some(MINIMUM_ROWS))
+    }
+
+    // returns the accumulator after the signatures of this step
+    fn verify_signatures(&self, ctx: &mut Context<Fr>, range: &RangeChip<Fr>, mut acc: AssignedValue<Fr>) -> AssignedValue<Fr> {
+        let fp_chip = FpChip::<Fr, Fp>::new(range, LIMB_BITS, NUM_LIMBS);
+        let fq_chip = FpChip::<Fr, Fq>::new(range, LIMB_BITS, NUM_LIMBS);
+        let ecc_chip = EccChip::new(&fp_chip);
+
+        for sig in self.signatures.iter() {
+            let pk = ecc_chip.load_private_unchecked(ctx, (sig.pk.x, sig.pk.y));
+            ecc_chip.assert_is_on_curve::<Secp256k1Affine>(ctx, &pk);
+            let [msg_hash, r, s] = [sig.msg_hash, sig.r, sig.s].map(|value| fq_chip.load_private(ctx, value));
+
+            let valid = ecdsa_verify_no_pubkey_check::<Fr, Fp, Fq, Secp256k1Affine>(
+                &ecc_chip,
+                ctx,
+                pk,
+                r,
+                s,
+                msg_hash.clone(),
+                VAR_WINDOW_BITS,
+                FIXED_WINDOW_BITS,
+            );
+            range.gate().assert_is_const(ctx, &valid, &Fr::ONE);
+
+            // the CRT representation carries the value reduced into the native field
+            acc = range.gate().mul_add(ctx, acc, Constant(Fr::from(ALPHA)), *msg_hash.native());
+        }
+
+        acc
+    }
+}
+
+impl Circuit<Fr> for EcdsaChainCircuit {
+    type Config = BaseConfig<Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = BaseCircuitParams;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn params(&self) -> Self::Params {
+        self.params.clone()
+    }
+
+    fn configure_with_params(meta: &mut ConstraintSystem<Fr>, params: Self::Params) -> Self::Config {
+        BaseConfig::configure(meta, params)
+    }
+
+    fn configure(_: &mut ConstraintSystem<Fr>) -> Self::Config {
+        unreachable!("the columns are configured from BaseCircuitParams")
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fr>) -> Result<(), Error> {
+        <Self as StepCircuit<C1>>::synthesize(self, config, layouter).map(|_| ())
+    }
+}
+
+impl CircuitExt<Fr> for EcdsaChainCircuit {
+    fn instances(&self) -> Vec<Vec<Fr>> {
+        Vec::new()
+    }
+}
+
+impl StepCircuit<C1> for EcdsaChainCircuit {
+    fn arity() -> usize {
+        1
+    }
+
+    fn initial_input(&self) -> &[Fr] {
+        &self.initial_input
+    }
+
+    fn input(&self) -> &[Fr] {
+        &self.input
+    }
+
+    fn output(&self) -> &[Fr] {
+        &self.output
+    }
+
+    fn step_idx(&self) -> usize {
+        self.step_idx
+    }
+
+    fn next(&mut self) {
+        // the signatures of the next step on top of the current accumulator
+        *self = Self {
+            initial_input: self.initial_input.clone(),
+            ..Self::new(self.output[0], self.step_idx + 1, self.num_sigs_per_step, self.params.clone())
+        };
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(Vec<AssignedCell<Fr, Fr>>, Vec<AssignedCell<Fr, Fr>>), Error> {
+        let column = config.gate().basic_gates[0][0].value;
+        let (z_in, z_out) = layouter.assign_region(
+            || "accumulators",
+            |mut region| {
+                let z_in = region.assign_advice(|| "acc", column, 0, || Value::known(self.input[0]))?;
+                let z_out = region.assign_advice(|| "acc", column, 1, || Value::known(self.output[0]))?;
+                Ok((z_in, z_out))
+            },
+        )?;
+
+        // witness generation alone skips the copy constraints, which the accumulators rely on
+        let mut builder = BaseCircuitBuilder::new(false).use_params(self.params.clone());
+        let [acc_in, acc_out] = [&z_in, &z_out].map(|cell| {
+            let mut copy_manager = builder.core().phase_manager[0].copy_manager.lock().unwrap();
+            copy_manager.load_external_assigned(cell.clone())
+        });
+        let range = builder.range_chip();
+        let ctx = builder.main(0);
+        let acc = self.verify_signatures(ctx, &range, acc_in);
+        ctx.constrain_equal(&acc, &acc_out);
+        Circuit::synthesize(&builder, config, layouter.namespace(|| "ecdsa"))?;
+
+        Ok((vec![z_in], vec![z_out]))
+    }
+}
+
+pub fn run_protostar_hyperplonk_ivc_ecdsa_preprocess(
+    num_sigs_per_step: usize,
+    params: BaseCircuitParams,
+    primary_num_vars: usize,
+    primary_param: <P1 as PolynomialCommitmentScheme<Fr>>::Param,
+    cyclefold_num_vars: usize,
+    cyclefold_param: <P2 as PolynomialCommitmentScheme<grumpkin::Fr>>::Param,
+) -> (PrimaryCircuit, SecondaryCircuit, IvcProverParam, IvcVerifierParam) {
+    let step_circuit = EcdsaChainCircuit::new(Fr::ZERO, 0, num_sigs_per_step, params);
+    preprocess::<
+        C1,
+        HyperPlonk<P1>,
+        HyperPlonk<P2>,
+        EcdsaChainCircuit,
+        PoseidonTranscript<Fr, _>,
+        PoseidonTranscript<grumpkin::Fr, _>,
+    >(
+        primary_num_vars,
+        primary_param,
+        step_circuit,
+        cyclefold_num_vars,
+        cyclefold_param,
+        seeded_std_rng(),
+    )
+    .unwrap()
+}
+
+/// Proves `num_steps` IVC steps and returns the proving time.
+pub fn run_protostar_hyperplonk_ivc_ecdsa_prove(
+    primary_circuit: &mut PrimaryCircuit,
+    secondary_circuit: &mut SecondaryCircuit,
+    ivc_pp: &IvcProverParam,
+    num_steps: usize,
+) -> Duration {
+    let start = Instant::now();
+    prove_steps::<
+        C1,
+        HyperPlonk<P1>,
+        HyperPlonk<P2>,
+        EcdsaChainCircuit,
+        PoseidonTranscript<Fr, _>,
+        PoseidonTranscript<grumpkin::Fr, _>,
+    >(ivc_pp, primary_circuit, secondary_circuit, num_steps, seeded_std_rng())
+    .unwrap();
+    start.elapsed()
+}
+
+#[test]
+fn ecdsa_step_circuit_test() {
+    use halo2_base::halo2_proofs::dev::MockProver;
+
+    let k = 18;
+    let params = EcdsaChainCircuit::circuit_params(1, k);
+    let mut circuit = EcdsaChainCircuit::new(Fr::ZERO, 0, 1, params);
+    assert!(circuit.signatures.iter().all(Signature::verify));
+    MockProver::run(k as u32, &circuit, Vec::new()).unwrap().assert_satisfied();
+
+    // the output cell is tied to the accumulator the builder computes
+    let mut wrong_output = circuit.clone();
+    wrong_output.output[0] += Fr::ONE;
+    assert!(MockProver::run(k as u32, &wrong_output, Vec::new()).unwrap().verify().is_err());
+
+    // a forged signature doesn't verify
+    circuit.signatures[0].s += Fq::ONE;
+    assert!(!circuit.signatures[0].verify());
+    assert!(MockProver::run(k as u32, &circuit, Vec::new()).unwrap().verify().is_err());
+}
@@ -0,0 +1,2 @@
+pub mod ecdsa;
+pub mod minroot;
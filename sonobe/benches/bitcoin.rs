@@ -0,0 +1,91 @@
+use folding_schemes::folding::nova::Nova;
+use sonobe::bitcoin::HeaderChainCircuit;
+use std::{fs::File, time::Instant};
+use std::io::Write;
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::FoldingScheme;
+use sonobe::utils::test_nova_setup;
+
+use ark_pallas::{constraints::GVar, Fr, Projective};
+use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+type NOVA = Nova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    HeaderChainCircuit<Fr>,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    false,
+    >;
+
+fn bench_nova_ivc(c: &mut Criterion) {
+    let mut primary_circuits = Vec::new();
+    let mut pp_vec = Vec::new();
+    // same configurations as the Nova runs, each proves the first 10 headers of the fixture
+    let configs = vec![(1, 10), (2, 5), (5, 2)];
+    for (num_headers, _) in &configs {
+        let circuit_primary = HeaderChainCircuit::<Fr>::new(*num_headers);
+        let params =
+        test_nova_setup::<HeaderChainCircuit<Fr>>(circuit_primary.clone());
+        primary_circuits.push(circuit_primary);
+        pp_vec.push(params);
+    }
+
+    let mut group = c.benchmark_group("NOVA IVC");
+
+    group.sample_size(10);
+
+    let mut rng = ark_std::test_rng();
+    let mut results = Vec::new();
+    for (i, (num_headers, num_steps)) in configs.iter().enumerate() {
+        // the fixture is finite, so every run restarts from the genesis block
+        let initial_state = primary_circuits[i].state();
+        let init = || NOVA::init(&pp_vec[i], primary_circuits[i].clone(), initial_state.clone()).unwrap();
+        let test_name = format!("entire_process_{}", num_headers);
+        group.bench_function(&test_name, |b| {
+            b.iter_custom(|_iters| {
+              let mut folding_scheme = init();
+              let start = Instant::now();
+              for _i in 0..*num_steps {
+                folding_scheme.prove_step(&mut rng, vec![], None).unwrap();
+            }
+              start.elapsed()
+            })
+        });
+
+        let mut folding_scheme = init();
+        let (primary_size, secondary_size) = (pp_vec[i].1.r1cs.A.n_rows, pp_vec[i].1.cf_r1cs.A.n_rows);
+        let exec_time =
+        {
+            let start = Instant::now();
+            for _i in 0..*num_steps {
+                folding_scheme.prove_step(&mut rng, vec![], None).unwrap();
+            }
+            start.elapsed()
+        };
+        results.push((num_steps, num_headers, exec_time, primary_size, secondary_size));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/sonobe_nova_bitcoin.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Num Headers per step| Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|---------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (num_steps, num_headers, duration, primary_size, secondary_size) in results.iter() {
+        writeln!(
+            file,
+            "| {}         | {}                | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, num_headers, duration.as_millis(), primary_size, secondary_size
+        ).expect("Failed to write to file");
+    }
+}
+
+fn bitcoin_nova(c: &mut Criterion) {
+    bench_nova_ivc(c);
+}
+
+criterion_group!(benches, bitcoin_nova);
+criterion_main!(benches);
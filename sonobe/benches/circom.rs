@@ -0,0 +1,82 @@
+use folding_schemes::folding::nova::Nova;
+use sonobe::circom::{fixture, CircomCircuit, FIXTURE_STEPS};
+use std::{fs::File, sync::Arc, time::Instant};
+use std::io::Write;
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::FoldingScheme;
+use sonobe::utils::test_nova_setup;
+
+use ark_pallas::{constraints::GVar, Fr, Projective};
+use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+type NOVA = Nova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    CircomCircuit<Fr>,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    false,
+    >;
+
+fn bench_nova_ivc(c: &mut Criterion) {
+    // the bundled multiplier, point `fixture` to the .r1cs and witnesses of another circuit
+    // compiled with `--prime pallas` to benchmark it instead
+    let (r1cs, generator) = fixture::<Fr>().expect("failed to load the circom fixture");
+    let r1cs = Arc::new(r1cs);
+    let circuit_primary = CircomCircuit::<Fr>::new(r1cs.clone(), generator);
+    let params =
+    test_nova_setup::<CircomCircuit<Fr>>(circuit_primary.clone());
+
+    let num_steps = FIXTURE_STEPS;
+    let mut group = c.benchmark_group("NOVA IVC");
+
+    group.sample_size(10);
+
+    let mut rng = ark_std::test_rng();
+    // the witnesses are finite, so every run restarts from the initial state
+    let initial_state = vec![Fr::from(3_u32)];
+    let init = || NOVA::init(&params, circuit_primary.clone(), initial_state.clone()).unwrap();
+    let test_name = format!("entire_process_{}", r1cs.constraints.len());
+    group.bench_function(&test_name, |b| {
+        b.iter_custom(|_iters| {
+          let mut folding_scheme = init();
+          let start = Instant::now();
+          for _i in 0..num_steps {
+            folding_scheme.prove_step(&mut rng, vec![], None).unwrap();
+        }
+          start.elapsed()
+        })
+    });
+
+    let mut folding_scheme = init();
+    let (primary_size, secondary_size) = (params.1.r1cs.A.n_rows, params.1.cf_r1cs.A.n_rows);
+    let exec_time =
+    {
+        let start = Instant::now();
+        for _i in 0..num_steps {
+            folding_scheme.prove_step(&mut rng, vec![], None).unwrap();
+        }
+        start.elapsed()
+    };
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/sonobe_nova_circom.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Num Constraints | Num Wires | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-----------------|-----------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    writeln!(
+        file,
+        "| {}         | {}               | {}         | {:?} ms             | {:?}                | {:?}                  |",
+        num_steps, r1cs.constraints.len(), r1cs.num_wires, exec_time.as_millis(), primary_size, secondary_size
+    ).expect("Failed to write to file");
+}
+
+fn circom_nova(c: &mut Criterion) {
+    bench_nova_ivc(c);
+}
+
+criterion_group!(benches, circom_nova);
+criterion_main!(benches);
@@ -0,0 +1,90 @@
+use folding_schemes::folding::nova::Nova;
+use circuit_dsl::hashchain::HashChain;
+use folding_schemes::frontend::FCircuit;
+use sonobe::dsl::DslCircuit;
+use std::{fs::File, time::Instant};
+use std::io::Write;
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::FoldingScheme;
+use sonobe::utils::test_nova_setup;
+
+use ark_pallas::{constraints::GVar, Fr, Projective};
+use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+type NOVA = Nova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    DslCircuit<Fr, HashChain>,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    false,
+    >;
+
+fn bench_nova_ivc(c: &mut Criterion) {
+    let mut primary_circuits = Vec::new();
+    let mut pp_vec = Vec::new();
+    let initial_state = vec![Fr::from(0_u32)];
+    let num_elts_per_step = vec![1400, 3200, 6600, 14000, 28000];
+    for num_elts in &num_elts_per_step {
+        let circuit_primary = DslCircuit::<Fr, HashChain>::new(HashChain { num_elts_per_step: *num_elts, seed: 0 }).unwrap();
+        let params =
+        test_nova_setup::<DslCircuit<Fr, HashChain>>(circuit_primary.clone());
+        primary_circuits.push(circuit_primary);
+        pp_vec.push(params);
+    }
+
+    let num_steps = 10;
+    let mut group = c.benchmark_group("NOVA IVC");
+
+    group.sample_size(10);
+
+    let mut rng = ark_std::test_rng();
+    let mut folding_scheme_vec = Vec::new();
+    let mut results = Vec::new();
+    for (i, num_elts) in num_elts_per_step.iter().enumerate() {
+        folding_scheme_vec.push(NOVA::init(&pp_vec[i], primary_circuits[i].clone(), initial_state.clone()).unwrap());
+        let test_name = format!("entire_process_{}", num_elts);
+        group.bench_function(&test_name, |b| {
+            b.iter_custom(|_iters| {
+              let start = Instant::now();
+              for _i in 0..num_steps {
+                folding_scheme_vec[i].prove_step(&mut rng, vec![], None).unwrap();
+            }
+              start.elapsed()
+            })
+        });
+
+        let exec_time = 
+        {
+            let start = Instant::now();
+            for _i in 0..num_steps {
+                folding_scheme_vec[i].prove_step(&mut rng, vec![], None).unwrap();
+            }
+            start.elapsed()
+        };
+        results.push((num_elts, exec_time));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/sonobe_nova_hashchain.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Num Elts per step  | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (i, (num_elts, duration)) in results.iter().enumerate() {
+        writeln!(
+            file,
+            "| {}         | {}               | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, num_elts, duration.as_millis(), pp_vec[i].1.r1cs.A.n_rows, pp_vec[i].1.cf_r1cs.A.n_rows
+        ).expect("Failed to write to file");
+    }
+}
+
+fn hashchain_nova(c: &mut Criterion) {
+    bench_nova_ivc(c);
+}
+
+criterion_group!(benches, hashchain_nova);
+criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,90 @@
+use folding_schemes::folding::nova::Nova;
+use sonobe::matvec::DenseLayerCircuit;
+use std::{fs::File, time::Instant};
+use std::io::Write;
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::FoldingScheme;
+use sonobe::utils::test_nova_setup;
+
+use ark_pallas::{constraints::GVar, Fr, Projective};
+use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+type NOVA = Nova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    DenseLayerCircuit<Fr>,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    false,
+    >;
+
+fn bench_nova_ivc(c: &mut Criterion) {
+    let mut primary_circuits = Vec::new();
+    let mut pp_vec = Vec::new();
+    // layer widths, same as the Nova runs
+    let widths = vec![16, 32, 64, 128];
+    for width in &widths {
+        let circuit_primary = DenseLayerCircuit::<Fr>::new(*width);
+        let params =
+        test_nova_setup::<DenseLayerCircuit<Fr>>(circuit_primary.clone());
+        primary_circuits.push(circuit_primary);
+        pp_vec.push(params);
+    }
+
+    let num_steps = 10;
+    let mut group = c.benchmark_group("NOVA IVC");
+
+    group.sample_size(10);
+
+    let mut rng = ark_std::test_rng();
+    let mut folding_scheme_vec = Vec::new();
+    let mut results = Vec::new();
+    for (i, width) in widths.iter().enumerate() {
+        // every run starts from the same input activations
+        let initial_state = primary_circuits[i].initial_input();
+        folding_scheme_vec.push(NOVA::init(&pp_vec[i], primary_circuits[i].clone(), initial_state).unwrap());
+        let test_name = format!("entire_process_{}", width);
+        group.bench_function(&test_name, |b| {
+            b.iter_custom(|_iters| {
+              let start = Instant::now();
+              for _i in 0..num_steps {
+                folding_scheme_vec[i].prove_step(&mut rng, vec![], None).unwrap();
+            }
+              start.elapsed()
+            })
+        });
+
+        let exec_time =
+        {
+            let start = Instant::now();
+            for _i in 0..num_steps {
+                folding_scheme_vec[i].prove_step(&mut rng, vec![], None).unwrap();
+            }
+            start.elapsed()
+        };
+        results.push((width, exec_time));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/sonobe_nova_matvec.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Width | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (i, (width, duration)) in results.iter().enumerate() {
+        writeln!(
+            file,
+            "| {}         | {}    | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, width, duration.as_millis(), pp_vec[i].1.r1cs.A.n_rows, pp_vec[i].1.cf_r1cs.A.n_rows
+        ).expect("Failed to write to file");
+    }
+}
+
+fn matvec_nova(c: &mut Criterion) {
+    bench_nova_ivc(c);
+}
+
+criterion_group!(benches, matvec_nova);
+criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,98 @@
+use folding_schemes::folding::nova::Nova;
+use sonobe::merkle::{MerkleTree, MerkleUpdateCircuit};
+use std::{fs::File, time::Instant};
+use std::io::Write;
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::FoldingScheme;
+use sonobe::utils::test_nova_setup;
+
+use ark_pallas::{constraints::GVar, Fr, Projective};
+use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+type NOVA = Nova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    MerkleUpdateCircuit<Fr>,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    false,
+    >;
+
+fn bench_nova_ivc(c: &mut Criterion) {
+    let mut primary_circuits = Vec::new();
+    let mut pp_vec = Vec::new();
+    // (tree depth, updates per step), same as the Nova runs
+    let configs = vec![(16, 1), (16, 4), (32, 1), (32, 4), (32, 16)];
+    for (depth, num_updates) in &configs {
+        let circuit_primary = MerkleUpdateCircuit::<Fr>::new(*depth, *num_updates);
+        let params =
+        test_nova_setup::<MerkleUpdateCircuit<Fr>>(circuit_primary.clone());
+        primary_circuits.push(circuit_primary);
+        pp_vec.push(params);
+    }
+
+    let num_steps = 10;
+    let mut group = c.benchmark_group("NOVA IVC");
+
+    group.sample_size(10);
+
+    let mut rng = ark_std::test_rng();
+    let mut folding_scheme_vec = Vec::new();
+    let mut results = Vec::new();
+    for (i, (depth, num_updates)) in configs.iter().enumerate() {
+        // every run starts from the empty tree, which the prover then updates along the folding
+        let mut tree = MerkleTree::<Fr>::new(*depth);
+        let initial_state = vec![tree.root()];
+        folding_scheme_vec.push(NOVA::init(&pp_vec[i], primary_circuits[i].clone(), initial_state).unwrap());
+        let (circuit, folding_scheme) = (&primary_circuits[i], &mut folding_scheme_vec[i]);
+        let mut step = 0;
+        let mut prove_step = || {
+            let external_inputs = circuit.external_inputs(&mut tree, step);
+            folding_scheme.prove_step(&mut rng, external_inputs, None).unwrap();
+            step += 1;
+        };
+        let test_name = format!("entire_process_{}_{}", depth, num_updates);
+        group.bench_function(&test_name, |b| {
+            b.iter_custom(|_iters| {
+              let start = Instant::now();
+              for _i in 0..num_steps {
+                prove_step();
+            }
+              start.elapsed()
+            })
+        });
+
+        let exec_time =
+        {
+            let start = Instant::now();
+            for _i in 0..num_steps {
+                prove_step();
+            }
+            start.elapsed()
+        };
+        results.push((depth, num_updates, exec_time));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/sonobe_nova_merkle.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Depth | Num Updates per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-------|----------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (i, (depth, num_updates, duration)) in results.iter().enumerate() {
+        writeln!(
+            file,
+            "| {}         | {}    | {}                   | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, depth, num_updates, duration.as_millis(), pp_vec[i].1.r1cs.A.n_rows, pp_vec[i].1.cf_r1cs.A.n_rows
+        ).expect("Failed to write to file");
+    }
+}
+
+fn merkle_nova(c: &mut Criterion) {
+    bench_nova_ivc(c);
+}
+
+criterion_group!(benches, merkle_nova);
+criterion_main!(benches);
\ No newline at end of file
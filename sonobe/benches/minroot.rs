@@ -1,10 +1,15 @@
 use folding_schemes::folding::nova::Nova;
-use sonobe::minroot::MinRootCircuit;
-use std::{fs::File, time::Instant};
+use circuit_dsl::minroot::MinRoot;
+use folding_schemes::frontend::FCircuit;
+use sonobe::dsl::DslCircuit;
+use std::{fs::File, time::{Duration, Instant}};
 use std::io::Write;
 use folding_schemes::commitment::pedersen::Pedersen;
 use folding_schemes::FoldingScheme;
-use sonobe::utils::test_nova_setup;
+use sonobe::utils::{
+    protogalaxy_prove_step, test_hypernova_setup, test_nova_setup, test_protogalaxy_setup,
+    HyperNovaPedersen, OtherChains, ProtoGalaxyPedersen,
+};
 
 use ark_pallas::{constraints::GVar, Fr, Projective};
 use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
@@ -15,29 +20,51 @@ type NOVA = Nova<
     GVar,
     Projective2,
     GVar2,
-    MinRootCircuit<Fr>,
+    DslCircuit<Fr, MinRoot>,
     Pedersen<Projective>,
     Pedersen<Projective2>,
+    false,
     >;
 
-fn bench_nova_ivc(c: &mut Criterion) {
+// one running and one incoming instance per step, the same 2-to-1 folding as Nova
+type HYPERNOVA = HyperNovaPedersen<DslCircuit<Fr, MinRoot>, 1, 1>;
+
+type PROTOGALAXY = ProtoGalaxyPedersen<DslCircuit<Fr, MinRoot>>;
+
+const NUM_STEPS: usize = 10;
+
+// (scheme, instances folded per step, num iters per step, execution time, primary size, secondary size)
+type ResultRow = (&'static str, usize, usize, Duration, usize, usize);
+
+fn initial_state() -> Vec<Fr> {
+    vec![Fr::from(0_u32), Fr::from(0_u32), Fr::from(1_u32)]
+}
+
+// the k - 1 chains folded next to the IVC one start from distinct states
+fn other_initial_states(k: usize) -> Vec<Vec<Fr>> {
+    (1..k)
+        .map(|j| vec![Fr::from(0_u32), Fr::from(j as u32), Fr::from(1_u32)])
+        .collect()
+}
+
+fn bench_nova_ivc(c: &mut Criterion, num_iters_per_step: &[usize]) -> Vec<ResultRow> {
     let mut primary_circuits = Vec::new();
     let mut pp_vec = Vec::new();
-    let initial_state = vec![Fr::from(0_u32), Fr::from(0_u32), Fr::from(1_u32)];
-    let num_iters_per_step = vec![1024, 2048, 4096, 8192];
-    for num_iters in &num_iters_per_step {
-        let circuit_primary = MinRootCircuit::<Fr>::new(vec![Fr::from(0_u32), Fr::from(0_u32), Fr::from(1_u32)], *num_iters);
-        let (prover_params, _verifier_params) =
-        test_nova_setup::<MinRootCircuit<Fr>>(circuit_primary.clone());
+    let initial_state = initial_state();
+    for num_iters in num_iters_per_step {
+        let circuit_primary = DslCircuit::<Fr, MinRoot>::new(MinRoot { num_iters_per_step: *num_iters }).unwrap();
+        let params =
+        test_nova_setup::<DslCircuit<Fr, MinRoot>>(circuit_primary.clone());
         primary_circuits.push(circuit_primary);
-        pp_vec.push(prover_params);
+        pp_vec.push(params);
     }
 
-    let num_steps = 10;
+    let num_steps = NUM_STEPS;
     let mut group = c.benchmark_group("NOVA IVC");
 
     group.sample_size(10);
 
+    let mut rng = ark_std::test_rng();
     let mut folding_scheme_vec = Vec::new();
     let mut results = Vec::new();
     for (i, num_iters) in num_iters_per_step.iter().enumerate() {
@@ -47,40 +74,135 @@ fn bench_nova_ivc(c: &mut Criterion) {
             b.iter_custom(|_iters| {
               let start = Instant::now();
               for _i in 0..num_steps {
-                folding_scheme_vec[i].0.prove_step().unwrap();
+                folding_scheme_vec[i].prove_step(&mut rng, vec![], None).unwrap();
             }
               start.elapsed()
             })
         });
 
-        let exec_time = 
+        let exec_time =
         {
             let start = Instant::now();
             for _i in 0..num_steps {
-                folding_scheme_vec[i].0.prove_step().unwrap();
+                folding_scheme_vec[i].prove_step(&mut rng, vec![], None).unwrap();
             }
             start.elapsed()
         };
-        results.push((num_iters, exec_time));
+        results.push(("Nova", 1, *num_iters, exec_time, pp_vec[i].1.r1cs.A.n_rows, pp_vec[i].1.cf_r1cs.A.n_rows));
     }
 
     group.finish();
+    results
+}
+
+fn bench_hypernova_ivc(c: &mut Criterion, num_iters_per_step: &[usize]) -> Vec<ResultRow> {
+    let mut rng = ark_std::test_rng();
+    let initial_state = initial_state();
+
+    let num_steps = NUM_STEPS;
+    let mut group = c.benchmark_group("HYPERNOVA IVC");
+
+    group.sample_size(10);
+
+    let mut results = Vec::new();
+    for num_iters in num_iters_per_step {
+        let circuit_primary = DslCircuit::<Fr, MinRoot>::new(MinRoot { num_iters_per_step: *num_iters }).unwrap();
+        let (prover_params, verifier_params) =
+        test_hypernova_setup::<DslCircuit<Fr, MinRoot>, 1, 1>(circuit_primary.clone());
+        // the CCS of the augmented circuit keeps the R1CS number of constraints
+        let (primary_size, secondary_size) = (verifier_params.ccs.m, verifier_params.cf_r1cs.A.n_rows);
+        let mut folding_scheme = HYPERNOVA::init(&(prover_params, verifier_params), circuit_primary, initial_state.clone()).unwrap();
+
+        let test_name = format!("entire_process_{}", num_iters);
+        group.bench_function(&test_name, |b| {
+            b.iter_custom(|_iters| {
+              let start = Instant::now();
+              for _i in 0..num_steps {
+                folding_scheme.prove_step(&mut rng, vec![], None).unwrap();
+            }
+              start.elapsed()
+            })
+        });
+
+        let exec_time =
+        {
+            let start = Instant::now();
+            for _i in 0..num_steps {
+                folding_scheme.prove_step(&mut rng, vec![], None).unwrap();
+            }
+            start.elapsed()
+        };
+        results.push(("HyperNova", 1, *num_iters, exec_time, primary_size, secondary_size));
+    }
+
+    group.finish();
+    results
+}
+
+fn bench_protogalaxy_ivc(c: &mut Criterion, num_iters_per_step: &[usize], k: usize) -> Vec<ResultRow> {
+    let mut rng = ark_std::test_rng();
+    let initial_state = initial_state();
+
+    let num_steps = NUM_STEPS;
+    let mut group = c.benchmark_group(format!("PROTOGALAXY IVC k = {}", k));
+
+    group.sample_size(10);
+
+    let mut results = Vec::new();
+    for num_iters in num_iters_per_step {
+        let circuit_primary = DslCircuit::<Fr, MinRoot>::new(MinRoot { num_iters_per_step: *num_iters }).unwrap();
+        let (prover_params, verifier_params) =
+        test_protogalaxy_setup::<DslCircuit<Fr, MinRoot>>(circuit_primary.clone());
+        let (primary_size, secondary_size) = (verifier_params.r1cs.A.n_rows, verifier_params.cf_r1cs.A.n_rows);
+        let mut other_chains = OtherChains::new(circuit_primary.clone(), other_initial_states(k));
+        let mut folding_scheme = PROTOGALAXY::init(&(prover_params, verifier_params), circuit_primary, initial_state.clone()).unwrap();
+
+        let test_name = format!("entire_process_{}", num_iters);
+        group.bench_function(&test_name, |b| {
+            b.iter_custom(|_iters| {
+              let start = Instant::now();
+              for _i in 0..num_steps {
+                protogalaxy_prove_step(&mut folding_scheme, &mut other_chains, &mut rng).unwrap();
+            }
+              start.elapsed()
+            })
+        });
+
+        let exec_time =
+        {
+            let start = Instant::now();
+            for _i in 0..num_steps {
+                protogalaxy_prove_step(&mut folding_scheme, &mut other_chains, &mut rng).unwrap();
+            }
+            start.elapsed()
+        };
+        results.push(("ProtoGalaxy", k, *num_iters, exec_time, primary_size, secondary_size));
+    }
+
+    group.finish();
+    results
+}
+
+fn minroot_sonobe(c: &mut Criterion) {
+    let num_iters_per_step = vec![1024, 2048, 4096, 8192];
+    let mut results = bench_nova_ivc(c, &num_iters_per_step);
+    results.extend(bench_hypernova_ivc(c, &num_iters_per_step));
+    // 2-to-1 folding as with Nova, and folding several MinRoot instances at once
+    for k in [1, 4] {
+        results.extend(bench_protogalaxy_ivc(c, &num_iters_per_step, k));
+    }
 
     let mut file = File::create("../benchmark_results/sonobe_nova_minroot.md").expect("Failed to create file");
-    writeln!(file, "| Num Steps  | Num Iters per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
-    writeln!(file, "|------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
-    for (i, (num_iters, duration)) in results.iter().enumerate() {
+    writeln!(file, "| Scheme     | Instances per step | Num Steps  | Num Iters per step | Execution Time (ms) | Per Step Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|--------------------|------------|--------------------|---------------------|--------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (scheme, k, num_iters, duration, primary_size, secondary_size) in results {
         writeln!(
             file,
-            "| {}         | {}               | {:?} ms             | {:?}                | {:?}                  |",
-            num_steps, num_iters, duration.as_millis(), folding_scheme_vec[i].1, folding_scheme_vec[i].2
+            "| {}       | {}                  | {}         | {}               | {:?} ms             | {:?} ms            | {:?}                | {:?}                  |",
+            scheme, k, NUM_STEPS, num_iters, duration.as_millis(), duration.as_millis() / NUM_STEPS as u128, primary_size, secondary_size
         ).expect("Failed to write to file");
     }
 }
 
-fn minroot_nova(c: &mut Criterion) {
-    bench_nova_ivc(c);
-}
-
-criterion_group!(benches, minroot_nova);
-criterion_main!(benches);
\ No newline at end of file
+criterion_group!(benches, minroot_sonobe);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+use sonobe::monolithic::{groth16_prove, groth16_setup, minroot_steps, MonolithicCircuit};
+use sonobe::range_check::RangeCheckCircuit;
+use std::{fs::File, time::{Duration, Instant}};
+use std::io::Write;
+use folding_schemes::frontend::FCircuit;
+
+use ark_bn254::Fr;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const NUM_STEPS: usize = 10;
+
+// (workload, num iters per step, execution time, circuit size)
+type ResultRow = (&'static str, usize, Duration, usize);
+
+// proves `NUM_STEPS` steps of a workload as a single Groth16 proof
+fn bench_groth16<FC: FCircuit<Fr>>(
+    c: &mut Criterion,
+    workload: &'static str,
+    num_iters_per_step: &[usize],
+    circuit: impl Fn(usize) -> MonolithicCircuit<Fr, FC>,
+) -> Vec<ResultRow> {
+    let mut group = c.benchmark_group(format!("GROTH16 {}", workload));
+
+    group.sample_size(10);
+
+    let mut results = Vec::new();
+    for num_iters in num_iters_per_step {
+        let monolithic_circuit = circuit(*num_iters);
+        let size = monolithic_circuit.num_constraints();
+        let pk = groth16_setup(monolithic_circuit.clone());
+
+        let test_name = format!("entire_process_{}", num_iters);
+        group.bench_function(&test_name, |b| {
+            b.iter_custom(|_iters| groth16_prove(&pk, monolithic_circuit.clone()))
+        });
+
+        let exec_time =
+        {
+            let start = Instant::now();
+            groth16_prove(&pk, monolithic_circuit.clone());
+            start.elapsed()
+        };
+        results.push((workload, *num_iters, exec_time, size));
+    }
+
+    group.finish();
+    results
+}
+
+fn monolithic_sonobe(c: &mut Criterion) {
+    let mut results = bench_groth16(c, "MinRoot", &[1024, 2048, 4096, 8192], |num_iters| {
+        minroot_steps::<Fr>(NUM_STEPS, num_iters)
+    });
+    results.extend(bench_groth16(c, "RangeCheck", &[128, 512, 2048], |num_adds| {
+        // the addends depend on the step index, so the same circuit is used for every step
+        let steps = vec![RangeCheckCircuit::<Fr>::new(num_adds); NUM_STEPS];
+        MonolithicCircuit::new(vec![Fr::from(0_u32)], steps)
+    }));
+
+    // same schema as the IVC tables, a monolithic proof has no secondary circuit
+    let mut file = File::create("../benchmark_results/sonobe_monolithic_groth16.md").expect("Failed to create file");
+    writeln!(file, "| Workload   | Num Steps  | Num Iters per step | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|------------|--------------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (workload, num_iters, duration, size) in results {
+        writeln!(
+            file,
+            "| {}    | {}         | {}               | {:?} ms             | {:?}                | -                      |",
+            workload, NUM_STEPS, num_iters, duration.as_millis(), size
+        ).expect("Failed to write to file");
+    }
+}
+
+criterion_group!(benches, monolithic_sonobe);
+criterion_main!(benches);
@@ -0,0 +1,142 @@
+use folding_schemes::folding::nova::Nova;
+use sonobe::minroot::MinRootCircuit;
+use std::{fs::File, time::{Duration, Instant}};
+use std::io::Write;
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::FoldingScheme;
+use sonobe::utils::{
+    hypernova_prove_step, protogalaxy_prove_step, test_hypernova_setup, test_nova_setup,
+    test_protogalaxy_setup, HyperNovaPedersen, OtherChains, ProtoGalaxyPedersen,
+};
+
+use ark_pallas::{constraints::GVar, Fr, Projective};
+use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
+use criterion::{criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, Criterion};
+
+type NOVA = Nova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    MinRootCircuit<Fr>,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    false,
+    >;
+
+type PROTOGALAXY = ProtoGalaxyPedersen<MinRootCircuit<Fr>>;
+
+const NUM_STEPS: usize = 10;
+const NUM_ITERS_PER_STEP: usize = 1024;
+
+// (scheme, instances folded per step, execution time, primary size, secondary size)
+type ResultRow = (&'static str, usize, Duration, usize, usize);
+
+fn initial_state() -> Vec<Fr> {
+    vec![Fr::from(0_u32), Fr::from(0_u32), Fr::from(1_u32)]
+}
+
+// the k - 1 chains folded next to the IVC one start from distinct states
+fn other_initial_states(k: usize) -> Vec<Vec<Fr>> {
+    (1..k)
+        .map(|j| vec![Fr::from(0_u32), Fr::from(j as u32), Fr::from(1_u32)])
+        .collect()
+}
+
+// benchmarks NUM_STEPS calls of `prove_step`, then times them once more for the result table
+fn measure(group: &mut BenchmarkGroup<'_, WallTime>, test_name: &str, mut prove_step: impl FnMut()) -> Duration {
+    group.bench_function(test_name, |b| {
+        b.iter_custom(|_iters| {
+          let start = Instant::now();
+          for _i in 0..NUM_STEPS {
+            prove_step();
+        }
+          start.elapsed()
+        })
+    });
+
+    let start = Instant::now();
+    for _i in 0..NUM_STEPS {
+        prove_step();
+    }
+    start.elapsed()
+}
+
+// k sequential Nova steps take k times the time of a single one
+fn bench_nova_ivc(group: &mut BenchmarkGroup<'_, WallTime>) -> ResultRow {
+    let mut rng = ark_std::test_rng();
+    let circuit_primary = MinRootCircuit::<Fr>::new(NUM_ITERS_PER_STEP);
+    let params = test_nova_setup::<MinRootCircuit<Fr>>(circuit_primary.clone());
+    let (primary_size, secondary_size) = (params.1.r1cs.A.n_rows, params.1.cf_r1cs.A.n_rows);
+    let mut folding_scheme = NOVA::init(&params, circuit_primary, initial_state()).unwrap();
+
+    let exec_time = measure(group, "nova", || folding_scheme.prove_step(&mut rng, vec![], None).unwrap());
+    ("Nova", 1, exec_time, primary_size, secondary_size)
+}
+
+// one running instance and K incoming ones per step
+fn bench_hypernova_ivc<const K: usize>(group: &mut BenchmarkGroup<'_, WallTime>) -> ResultRow {
+    let mut rng = ark_std::test_rng();
+    let circuit_primary = MinRootCircuit::<Fr>::new(NUM_ITERS_PER_STEP);
+    let (prover_params, verifier_params) =
+        test_hypernova_setup::<MinRootCircuit<Fr>, 1, K>(circuit_primary.clone());
+    let (primary_size, secondary_size) = (verifier_params.ccs.m, verifier_params.cf_r1cs.A.n_rows);
+    let mut other_chains = OtherChains::new(circuit_primary.clone(), other_initial_states(K));
+    let mut folding_scheme = HyperNovaPedersen::<MinRootCircuit<Fr>, 1, K>::init(
+        &(prover_params, verifier_params),
+        circuit_primary,
+        initial_state(),
+    )
+    .unwrap();
+
+    let exec_time = measure(group, &format!("hypernova_k_{}", K), || {
+        hypernova_prove_step(&mut folding_scheme, &mut other_chains, &mut rng).unwrap()
+    });
+    ("HyperNova", K, exec_time, primary_size, secondary_size)
+}
+
+fn bench_protogalaxy_ivc(group: &mut BenchmarkGroup<'_, WallTime>, k: usize) -> ResultRow {
+    let mut rng = ark_std::test_rng();
+    let circuit_primary = MinRootCircuit::<Fr>::new(NUM_ITERS_PER_STEP);
+    let (prover_params, verifier_params) = test_protogalaxy_setup::<MinRootCircuit<Fr>>(circuit_primary.clone());
+    let (primary_size, secondary_size) = (verifier_params.r1cs.A.n_rows, verifier_params.cf_r1cs.A.n_rows);
+    let mut other_chains = OtherChains::new(circuit_primary.clone(), other_initial_states(k));
+    let mut folding_scheme = PROTOGALAXY::init(&(prover_params, verifier_params), circuit_primary, initial_state()).unwrap();
+
+    let exec_time = measure(group, &format!("protogalaxy_k_{}", k), || {
+        protogalaxy_prove_step(&mut folding_scheme, &mut other_chains, &mut rng).unwrap()
+    });
+    ("ProtoGalaxy", k, exec_time, primary_size, secondary_size)
+}
+
+fn multi_instance_sonobe(c: &mut Criterion) {
+    let mut group = c.benchmark_group("MULTI INSTANCE IVC");
+    group.sample_size(10);
+
+    let mut results = vec![bench_nova_ivc(&mut group)];
+    // the number of HyperNova instances is a const generic
+    results.push(bench_hypernova_ivc::<1>(&mut group));
+    results.push(bench_hypernova_ivc::<2>(&mut group));
+    results.push(bench_hypernova_ivc::<4>(&mut group));
+    results.push(bench_hypernova_ivc::<8>(&mut group));
+    results.push(bench_hypernova_ivc::<16>(&mut group));
+    for k in [1, 2, 4, 8, 16] {
+        results.push(bench_protogalaxy_ivc(&mut group, k));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/sonobe_multi_instance_minroot.md").expect("Failed to create file");
+    writeln!(file, "| Scheme     | Instances per step | Num Steps  | Num Iters per step | Execution Time (ms) | Per Instance Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|--------------------|------------|--------------------|---------------------|------------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (scheme, k, duration, primary_size, secondary_size) in results {
+        writeln!(
+            file,
+            "| {}       | {}                  | {}         | {}               | {:?} ms             | {:.3} ms              | {:?}                | {:?}                  |",
+            scheme, k, NUM_STEPS, NUM_ITERS_PER_STEP, duration.as_millis(), duration.as_secs_f64() * 1000.0 / (NUM_STEPS * k) as f64, primary_size, secondary_size
+        ).expect("Failed to write to file");
+    }
+}
+
+criterion_group!(benches, multi_instance_sonobe);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+use folding_schemes::folding::nova::Nova;
+use sonobe::random_circuit::RandomCircuit;
+use std::{fs::File, time::Instant};
+use std::io::Write;
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::FoldingScheme;
+use sonobe::utils::test_nova_setup;
+
+use ark_pallas::{constraints::GVar, Fr, Projective};
+use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+type NOVA = Nova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    RandomCircuit<Fr>,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    false,
+    >;
+
+fn bench_nova_ivc(c: &mut Criterion) {
+    let mut primary_circuits = Vec::new();
+    let mut pp_vec = Vec::new();
+    // (num_constraints, num_variables, density), the same shapes as the Nova runs; the generator
+    // goes up to 2^22 constraints, extend the sweep for scaling studies
+    let configs = (10..=16)
+        .step_by(2)
+        .map(|log_constraints| (1 << log_constraints, 2 << log_constraints, 3))
+        .collect::<Vec<(usize, usize, usize)>>();
+    for (num_constraints, num_variables, density) in &configs {
+        let circuit_primary = RandomCircuit::<Fr>::new(*num_constraints, *num_variables, *density, 0);
+        let params =
+        test_nova_setup::<RandomCircuit<Fr>>(circuit_primary.clone());
+        primary_circuits.push(circuit_primary);
+        pp_vec.push(params);
+    }
+
+    let num_steps = 10;
+    let mut group = c.benchmark_group("NOVA IVC");
+
+    group.sample_size(10);
+
+    let mut rng = ark_std::test_rng();
+    let mut folding_scheme_vec = Vec::new();
+    let mut results = Vec::new();
+    for (i, config) in configs.iter().enumerate() {
+        let initial_state = vec![Fr::from(1_u32)];
+        folding_scheme_vec.push(NOVA::init(&pp_vec[i], primary_circuits[i].clone(), initial_state).unwrap());
+        let test_name = format!("entire_process_{}_{}_{}", config.0, config.1, config.2);
+        group.bench_function(&test_name, |b| {
+            b.iter_custom(|_iters| {
+              let start = Instant::now();
+              for _i in 0..num_steps {
+                folding_scheme_vec[i].prove_step(&mut rng, vec![], None).unwrap();
+            }
+              start.elapsed()
+            })
+        });
+
+        let exec_time =
+        {
+            let start = Instant::now();
+            for _i in 0..num_steps {
+                folding_scheme_vec[i].prove_step(&mut rng, vec![], None).unwrap();
+            }
+            start.elapsed()
+        };
+        results.push((config, exec_time));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/sonobe_nova_random_circuit.md").expect("Failed to create file");
+    writeln!(file, "| Num Steps  | Num Constraints | Num Variables | Density | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|------------|-----------------|---------------|---------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (i, ((num_constraints, num_variables, density), duration)) in results.iter().enumerate() {
+        writeln!(
+            file,
+            "| {}         | {}            | {}          | {}      | {:?} ms             | {:?}                | {:?}                  |",
+            num_steps, num_constraints, num_variables, density, duration.as_millis(), pp_vec[i].1.r1cs.A.n_rows, pp_vec[i].1.cf_r1cs.A.n_rows
+        ).expect("Failed to write to file");
+    }
+}
+
+fn random_circuit_nova(c: &mut Criterion) {
+    bench_nova_ivc(c);
+}
+
+criterion_group!(benches, random_circuit_nova);
+criterion_main!(benches);
\ No newline at end of file
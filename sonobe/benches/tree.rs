@@ -0,0 +1,108 @@
+use circuit_dsl::hashchain::HashChain;
+use folding_schemes::frontend::FCircuit;
+use sonobe::dsl::DslCircuit;
+use sonobe::tree::{native_chain, num_tree_steps, tree_ivc, TreeParams};
+use std::{fs::File, time::{Duration, Instant}};
+use std::io::Write;
+use folding_schemes::FoldingScheme;
+use sonobe::utils::{test_hypernova_setup, HyperNovaPedersen};
+
+use ark_pallas::Fr;
+use criterion::{criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, Criterion};
+
+type SEQUENTIAL = HyperNovaPedersen<DslCircuit<Fr, HashChain>, 1, 1>;
+
+// leaf steps shared by the segments of every tree, so that trees only differ by their merges
+const NUM_LEAF_STEPS: usize = 16;
+const NUM_ELTS_PER_STEP: usize = 32;
+
+// (mode, segments, num steps, execution time)
+type ResultRow = (&'static str, usize, usize, Duration);
+
+fn step() -> HashChain {
+    HashChain { num_elts_per_step: NUM_ELTS_PER_STEP, seed: 0 }
+}
+
+fn initial_state() -> Vec<Fr> {
+    vec![Fr::from(0_u32)]
+}
+
+// benchmarks a whole run, then times it once more for the result table
+fn measure(group: &mut BenchmarkGroup<'_, WallTime>, test_name: &str, mut run: impl FnMut()) -> Duration {
+    group.bench_function(test_name, |b| {
+        b.iter_custom(|_iters| {
+          let start = Instant::now();
+          run();
+          start.elapsed()
+        })
+    });
+
+    let start = Instant::now();
+    run();
+    start.elapsed()
+}
+
+// sequential HyperNova IVC over the steps of a tree, the baseline of its speedup
+fn bench_sequential_ivc(group: &mut BenchmarkGroup<'_, WallTime>, num_steps: usize) -> ResultRow {
+    let circuit_primary = DslCircuit::<Fr, HashChain>::new(step()).unwrap();
+    let params = test_hypernova_setup::<DslCircuit<Fr, HashChain>, 1, 1>(circuit_primary.clone());
+    let expected = native_chain(&step(), initial_state(), 0, num_steps);
+
+    let exec_time = measure(group, &format!("sequential_{}", num_steps), || {
+        let mut rng = ark_std::test_rng();
+        let mut folding_scheme = SEQUENTIAL::init(&params, circuit_primary.clone(), initial_state()).unwrap();
+        for _i in 0..num_steps {
+            folding_scheme.prove_step(&mut rng, vec![], None).unwrap();
+        }
+        assert_eq!(folding_scheme.state(), expected);
+    });
+    ("Sequential", 1, num_steps, exec_time)
+}
+
+fn bench_tree_ivc(group: &mut BenchmarkGroup<'_, WallTime>, params: &TreeParams, num_segments: usize) -> ResultRow {
+    let segment_len = NUM_LEAF_STEPS / num_segments;
+    let num_steps = num_tree_steps(num_segments, segment_len);
+    let expected = native_chain(&step(), initial_state(), 0, num_steps);
+
+    let exec_time = measure(group, &format!("tree_{}", num_segments), || {
+        let root = tree_ivc(params, &step(), initial_state(), num_segments, segment_len).unwrap();
+        assert_eq!(root.state(), expected);
+    });
+    ("Tree", num_segments, num_steps, exec_time)
+}
+
+fn tree_sonobe(c: &mut Criterion) {
+    let mut group = c.benchmark_group("TREE IVC");
+    group.sample_size(10);
+
+    let circuit_primary = DslCircuit::<Fr, HashChain>::new(step()).unwrap();
+    let params = test_hypernova_setup::<DslCircuit<Fr, HashChain>, 2, 1>(circuit_primary);
+    let (primary_size, secondary_size) = (params.1.ccs.m, params.1.cf_r1cs.A.n_rows);
+
+    // one leaf per thread, each tree against sequential IVC over as many steps
+    let mut results = Vec::new();
+    for num_segments in [1, 2, 4, 8] {
+        let tree = bench_tree_ivc(&mut group, &params, num_segments);
+        results.push((bench_sequential_ivc(&mut group, tree.2), tree));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/sonobe_tree_hashchain.md").expect("Failed to create file");
+    writeln!(file, "Hash chain of {} elements per step, tree circuit sizes: primary {}, secondary {}", NUM_ELTS_PER_STEP, primary_size, secondary_size).expect("Failed to write to file");
+    writeln!(file).expect("Failed to write to file");
+    writeln!(file, "| Mode       | Segments | Num Steps  | Execution Time (ms) | Speedup |").expect("Failed to write to file");
+    writeln!(file, "|------------|----------|------------|---------------------|---------|").expect("Failed to write to file");
+    for (sequential, tree) in results {
+        for (mode, num_segments, num_steps, duration) in [sequential, tree] {
+            writeln!(
+                file,
+                "| {}       | {}        | {}         | {:?} ms             | {:.2}    |",
+                mode, num_segments, num_steps, duration.as_millis(), sequential.3.as_secs_f64() / duration.as_secs_f64()
+            ).expect("Failed to write to file");
+        }
+    }
+}
+
+criterion_group!(benches, tree_sonobe);
+criterion_main!(benches);
@@ -0,0 +1,101 @@
+use folding_schemes::folding::nova::Nova;
+use sonobe::zkvm::{Machine, Program, ZkvmCircuit};
+use std::{fs::File, time::Instant};
+use std::io::Write;
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::FoldingScheme;
+use sonobe::utils::test_nova_setup;
+
+use ark_pallas::{constraints::GVar, Fr, Projective};
+use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+type NOVA = Nova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    ZkvmCircuit<Fr>,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    false,
+    >;
+
+fn bench_nova_ivc(c: &mut Criterion) {
+    let mut primary_circuits = Vec::new();
+    let mut pp_vec = Vec::new();
+    // same programs as the Nova runs
+    let programs = vec![
+        ("fibonacci", Program::fibonacci(16)),
+        ("bubble_sort", Program::bubble_sort(&[7, 3, 9, 1, 5, 2])),
+    ];
+    for (_, program) in &programs {
+        let circuit_primary = ZkvmCircuit::<Fr>::new(program.clone());
+        let params =
+        test_nova_setup::<ZkvmCircuit<Fr>>(circuit_primary.clone());
+        primary_circuits.push(circuit_primary);
+        pp_vec.push(params);
+    }
+
+    let mut group = c.benchmark_group("NOVA IVC");
+
+    group.sample_size(10);
+
+    let mut rng = ark_std::test_rng();
+    let mut folding_scheme_vec = Vec::new();
+    let mut results = Vec::new();
+    for (i, (name, program)) in programs.iter().enumerate() {
+        // every program is proven up to and including its final Halt, a halted machine stays
+        // in place so repeated runs keep folding valid steps
+        let num_steps = Machine::run(program, 10_000).1;
+        let initial_state = primary_circuits[i].state();
+        folding_scheme_vec.push(NOVA::init(&pp_vec[i], primary_circuits[i].clone(), initial_state).unwrap());
+        // the prover runs the machine along the folding, for the memory accesses of the steps
+        let mut execution = primary_circuits[i].execution();
+        let folding_scheme = &mut folding_scheme_vec[i];
+        let mut prove_step = || {
+            let external_inputs = execution.step(program);
+            folding_scheme.prove_step(&mut rng, external_inputs, None).unwrap();
+        };
+        let test_name = format!("entire_process_{}", name);
+        group.bench_function(&test_name, |b| {
+            b.iter_custom(|_iters| {
+              let start = Instant::now();
+              for _i in 0..num_steps {
+                prove_step();
+            }
+              start.elapsed()
+            })
+        });
+
+        let exec_time =
+        {
+            let start = Instant::now();
+            for _i in 0..num_steps {
+                prove_step();
+            }
+            start.elapsed()
+        };
+        results.push((name, num_steps, exec_time));
+    }
+
+    group.finish();
+
+    let mut file = File::create("../benchmark_results/sonobe_nova_zkvm.md").expect("Failed to create file");
+    writeln!(file, "| Program      | Num Steps  | Execution Time (ms) | Primary_circuit_size | Secondary_circuit_size |").expect("Failed to write to file");
+    writeln!(file, "|--------------|------------|---------------------|----------------------|------------------------|").expect("Failed to write to file");
+    for (i, (name, num_steps, duration)) in results.iter().enumerate() {
+        writeln!(
+            file,
+            "| {}  | {}         | {:?} ms             | {:?}                | {:?}                  |",
+            name, num_steps, duration.as_millis(), pp_vec[i].1.r1cs.A.n_rows, pp_vec[i].1.cf_r1cs.A.n_rows
+        ).expect("Failed to write to file");
+    }
+}
+
+fn zkvm_nova(c: &mut Criterion) {
+    bench_nova_ivc(c);
+}
+
+criterion_group!(benches, zkvm_nova);
+criterion_main!(benches);
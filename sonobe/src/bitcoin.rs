@@ -0,0 +1,256 @@
+//! Bitcoin header chain, the light-client workload: each step takes a configurable number of
+//! 80-byte block headers, checks that every header commits to the previous block hash, hashes it
+//! with double SHA-256 and checks that the hash, read as a little-endian integer, is at most the
+//! target encoded by the header's compact `nBits` field. The state is the tip hash, as two
+//! 128-bit big-endian halves of the raw digest, and the compact target.
+//!
+//! Headers come from the bundled fixture of mainnet headers, step `i` takes the ones following the
+//! headers of the previous steps. As in the Nova version, `nBits` is carried unchanged, so
+//! difficulty retargeting is out of scope.
+
+use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
+use ark_ff::PrimeField;
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_r1cs_std::uint8::UInt8;
+use ark_r1cs_std::{R1CSVar, ToBitsGadget};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use crate::sha256::{digest_to_field, field_to_digest, pack_bytes, DIGEST_BYTES};
+
+pub const HEADER_BYTES: usize = 80;
+pub const MIN_EXPONENT: usize = 3;
+pub const MAX_EXPONENT: usize = 29;
+// targets are below 2^TARGET_BITS, so are the hashes meeting them
+const TARGET_BITS: usize = 8 * MAX_EXPONENT;
+
+const PREV_HASH: usize = 4;
+const BITS: usize = 72;
+
+pub type Header = [u8; HEADER_BYTES];
+
+/// Bitcoin mainnet headers from the genesis block on, bundled so the workload runs offline.
+pub fn fixture() -> Vec<Header> {
+    include_str!("../../fixtures/bitcoin_headers.txt")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let bytes = (0..line.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&line[i..i + 2], 16).expect("invalid hex"))
+                .collect::<Vec<_>>();
+            bytes.try_into().expect("a header is 80 bytes")
+        })
+        .collect()
+}
+
+pub fn block_hash(header: &Header) -> [u8; DIGEST_BYTES] {
+    Sha256::digest(Sha256::digest(header)).into()
+}
+
+pub fn prev_hash(header: &Header) -> [u8; DIGEST_BYTES] {
+    header[PREV_HASH..PREV_HASH + DIGEST_BYTES].try_into().unwrap()
+}
+
+pub fn compact_target(header: &Header) -> u32 {
+    u32::from_le_bytes(header[BITS..BITS + 4].try_into().unwrap())
+}
+
+/// Expands `nBits`, a 24-bit mantissa scaled by `256^(exponent - 3)`.
+pub fn target(bits: u32) -> BigUint {
+    let exponent = (bits >> 24) as usize;
+    assert!((MIN_EXPONENT..=MAX_EXPONENT).contains(&exponent), "unsupported target exponent");
+    assert_eq!(bits & 0x0080_0000, 0, "negative target");
+    BigUint::from(bits & 0x007f_ffff) << (8 * (exponent - MIN_EXPONENT))
+}
+
+/// Native reference of the checks of a single header.
+pub fn check_header(header: &Header, tip: &[u8; DIGEST_BYTES], bits: u32) -> bool {
+    prev_hash(header) == *tip
+        && compact_target(header) == bits
+        && BigUint::from_bytes_le(&block_hash(header)) <= target(bits)
+}
+
+// little-endian bytes to the field element they encode
+fn pack_bytes_le<F: PrimeField>(bytes: &[UInt8<F>]) -> Result<FpVar<F>, SynthesisError> {
+    let bits = bytes
+        .iter()
+        .map(|byte| byte.to_bits_le())
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    Boolean::le_bits_to_fp_var(&bits)
+}
+
+#[derive(Clone, Debug)]
+pub struct HeaderChainCircuit<F: PrimeField> {
+    pub num_headers_per_step: usize,
+    pub headers: Vec<Header>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> HeaderChainCircuit<F> {
+    pub fn new(num_headers_per_step: usize) -> Self {
+        Self {
+            num_headers_per_step,
+            headers: fixture(),
+            _f: PhantomData,
+        }
+    }
+
+    /// The headers of step `i`.
+    pub fn step_headers(&self, i: usize) -> &[Header] {
+        let start = i * self.num_headers_per_step;
+        assert!(start + self.num_headers_per_step <= self.headers.len(), "not enough headers in the fixture");
+        &self.headers[start..start + self.num_headers_per_step]
+    }
+
+    /// The state before the genesis block, at the target of the fixture.
+    pub fn state(&self) -> Vec<F> {
+        let mut z = digest_to_field(&[0u8; DIGEST_BYTES]);
+        z.push(F::from(compact_target(&self.headers[0]) as u64));
+        z
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for HeaderChainCircuit<F> {
+    type Params = usize; // number of headers per step
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params))
+    }
+
+    fn state_len(&self) -> usize {
+        3
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        let headers = self.step_headers(i);
+        let mut tip = field_to_digest(&z_i[..2]);
+        let bits = compact_target(&headers[0]);
+        for header in headers {
+            debug_assert!(check_header(header, &tip, bits));
+            tip = block_hash(header);
+        }
+
+        let mut z = digest_to_field(&tip);
+        z.push(z_i[2]);
+        Ok(z)
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let headers = self.step_headers(i);
+        let tip = z_i[..2].value().map(|z| field_to_digest(&z)).unwrap_or_default();
+        let bits = compact_target(&headers[0]);
+
+        let mut tip = UInt8::new_witness_vec(cs.clone(), &tip)?;
+        for (half, z) in tip.chunks(DIGEST_BYTES / 2).zip(&z_i) {
+            pack_bytes(half)?.enforce_equal(z)?;
+        }
+
+        let mut target_var = None;
+        for header in headers {
+            let mut bytes = UInt8::new_witness_vec(cs.clone(), &header[..PREV_HASH])?;
+            bytes.extend(tip);
+            bytes.extend(UInt8::new_witness_vec(cs.clone(), &header[PREV_HASH + DIGEST_BYTES..])?);
+
+            pack_bytes_le(&bytes[BITS..BITS + 4])?.enforce_equal(&z_i[2])?;
+
+            // the target is expanded once, the following headers share it
+            if target_var.is_none() {
+                // one-hot exponent flags select the scale 256^(exponent - 3)
+                let exponent = (bits >> 24) as usize;
+                let flags = (MIN_EXPONENT..=MAX_EXPONENT)
+                    .map(|e| Boolean::new_witness(cs.clone(), || Ok(e == exponent)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                flags
+                    .iter()
+                    .fold(FpVar::zero(), |acc, flag| acc + FpVar::from(flag.clone()))
+                    .enforce_equal(&FpVar::one())?;
+                flags
+                    .iter()
+                    .zip(MIN_EXPONENT..)
+                    .fold(FpVar::zero(), |acc, (flag, e)| acc + FpVar::from(flag.clone()) * F::from(e as u64))
+                    .enforce_equal(&pack_bytes_le(&bytes[BITS + 3..BITS + 4])?)?;
+
+                // the sign bit of the mantissa is clear
+                bytes[BITS + 2].to_bits_le()?[7].enforce_equal(&Boolean::FALSE)?;
+
+                let scale = flags.iter().zip(MIN_EXPONENT..).fold(FpVar::zero(), |acc, (flag, e)| {
+                    acc + FpVar::from(flag.clone()) * F::from(2u64).pow([(8 * (e - MIN_EXPONENT)) as u64])
+                });
+                target_var = Some(pack_bytes_le(&bytes[BITS..BITS + 3])? * scale);
+            }
+            let target_var = target_var.as_ref().unwrap();
+
+            let hash = Sha256Gadget::digest(&bytes)?.0;
+            let hash = Sha256Gadget::digest(&hash)?.0;
+
+            // the hash is a little-endian integer, its bytes past the target size are zero and
+            // the rest is at most the target, which the decomposition of target - hash shows
+            for byte in &hash[TARGET_BITS / 8..] {
+                byte.enforce_equal(&UInt8::constant(0))?;
+            }
+            let hash_value = BigUint::from_bytes_le(&block_hash(header)[..TARGET_BITS / 8]);
+            let target_value = target(bits);
+            let slack = if hash_value <= target_value { target_value - hash_value } else { BigUint::from(0u32) };
+            let slack_bits = (0..TARGET_BITS)
+                .map(|k| Boolean::new_witness(cs.clone(), || Ok(slack.bit(k as u64))))
+                .collect::<Result<Vec<_>, _>>()?;
+            Boolean::le_bits_to_fp_var(&slack_bits)?
+                .enforce_equal(&(target_var - pack_bytes_le(&hash[..TARGET_BITS / 8])?))?;
+
+            tip = hash;
+        }
+
+        let mut z = tip.chunks(DIGEST_BYTES / 2).map(pack_bytes).collect::<Result<Vec<_>, _>>()?;
+        z.push(z_i[2].clone());
+        Ok(z)
+    }
+}
+
+#[test]
+fn header_chain_fold_test() {
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    // headers 0 to 10 of the fixture, one per step
+    let circuit = HeaderChainCircuit::<Fr>::new(1);
+    assert_eq!(circuit.headers.len(), 11);
+    let mut z_i = circuit.state();
+    for (i, header) in circuit.headers.iter().enumerate() {
+        assert!(check_header(header, &field_to_digest(&z_i[..2]), compact_target(&circuit.headers[0])));
+        let z_i_plus_1 = circuit.step_native(i, z_i.clone(), vec![]).unwrap();
+
+        if i == 0 {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+            let z_out = circuit.generate_step_constraints(cs.clone(), i, z_in, vec![]).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            assert_eq!(z_out.value().unwrap(), z_i_plus_1);
+        }
+        z_i = z_i_plus_1;
+    }
+
+    // the tip is the hash of block 10, displayed byte-reversed
+    let tip = field_to_digest(&z_i[..2]).iter().rev().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    assert_eq!(tip, "000000002c05cc2e78923c34df87fd108b22221ac6076c18f3ade378a4d915e9");
+    assert_eq!(z_i[2], Fr::from(compact_target(&circuit.headers[0]) as u64));
+}
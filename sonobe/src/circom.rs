@@ -0,0 +1,408 @@
+//! Circom frontend: loads a step circuit compiled by circom, its `.r1cs`, and wraps it as an
+//! `FCircuit`, so existing Circom circuits can be folded without an arkworks rewrite.
+//!
+//! As in the Nova version, the main component takes the state `z_i` as its first input signal and
+//! outputs `z_{i+1}` as its only output signal, so that after the constant one come the wires of
+//! `z_{i+1}`, then the ones of `z_i`. The circuit must be compiled for the scalar field of the
+//! folding curve, `circom --prime pallas` for the benches. The witness of every step comes from a
+//! precomputed `.wtns` file or from the circuit's wasm run by node, both the native step and the
+//! constraints generate the one of their step from its index and state.
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::fields::fp::{AllocatedFp, FpVar};
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+use num_bigint::BigUint;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+/// Directory of the bundled multiplier step circuit, see its README.
+pub const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../fixtures/circom");
+/// Number of steps the bundled witnesses cover.
+pub const FIXTURE_STEPS: usize = 10;
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+const HEADER: u32 = 1;
+const CONSTRAINTS: u32 = 2;
+const WITNESS: u32 = 2;
+
+/// Terms of a linear combination, as wire indices and coefficients.
+pub type Terms<F> = Vec<(usize, F)>;
+
+#[derive(Clone, Debug)]
+pub struct R1CS<F: PrimeField> {
+    pub num_wires: usize,
+    pub num_pub_out: usize,
+    pub num_pub_in: usize,
+    pub num_prv_in: usize,
+    pub constraints: Vec<(Terms<F>, Terms<F>, Terms<F>)>,
+}
+
+impl<F: PrimeField> R1CS<F> {
+    pub fn arity(&self) -> usize {
+        self.num_pub_out
+    }
+
+    /// Checks that `witness` is a witness of a step from `z`, the values of the constraints
+    /// themselves are left to the folding.
+    pub fn check_witness(&self, witness: &[F], z: &[F]) -> io::Result<()> {
+        if witness.len() != self.num_wires {
+            return Err(invalid("the witness does not match the number of wires"));
+        }
+        if witness[0] != F::one() {
+            return Err(invalid("wire 0 of the witness is not one"));
+        }
+        if witness[1 + self.arity()..1 + 2 * self.arity()] != *z {
+            return Err(invalid("the witness does not start from the state"));
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn read_u32(reader: &mut &[u8]) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut &[u8]) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+// field elements are stored in little-endian canonical form on `n8` bytes
+fn read_field<F: PrimeField>(reader: &mut &[u8], n8: usize) -> io::Result<F> {
+    let mut bytes = vec![0u8; n8];
+    reader.read_exact(&mut bytes)?;
+    F::BigInt::try_from(BigUint::from_bytes_le(&bytes))
+        .ok()
+        .and_then(F::from_bigint)
+        .ok_or_else(|| invalid("field element out of range"))
+}
+
+// the prime of the header, which must be the modulus of `F`, returns the size of an element
+fn read_prime<F: PrimeField>(reader: &mut &[u8]) -> io::Result<usize> {
+    let n8 = read_u32(reader)? as usize;
+    let mut prime = vec![0u8; n8];
+    reader.read_exact(&mut prime)?;
+    if BigUint::from_bytes_le(&prime) != BigUint::from_bytes_le(&F::MODULUS.to_bytes_le()) {
+        return Err(invalid("the file is over another field"));
+    }
+    Ok(n8)
+}
+
+// sections of an iden3 binary file by type, they may come in any order
+fn read_sections<'a>(bytes: &'a [u8], magic: &[u8; 4]) -> io::Result<HashMap<u32, &'a [u8]>> {
+    let mut reader = bytes;
+    let mut file_magic = [0u8; 4];
+    reader.read_exact(&mut file_magic)?;
+    if file_magic != *magic {
+        return Err(invalid("invalid magic number"));
+    }
+    let _version = read_u32(&mut reader)?;
+    let num_sections = read_u32(&mut reader)?;
+
+    let mut sections = HashMap::new();
+    for _ in 0..num_sections {
+        let section_type = read_u32(&mut reader)?;
+        let size = read_u64(&mut reader)? as usize;
+        if size > reader.len() {
+            return Err(invalid("truncated section"));
+        }
+        sections.insert(section_type, &reader[..size]);
+        reader = &reader[size..];
+    }
+    Ok(sections)
+}
+
+fn section<'a>(sections: &HashMap<u32, &'a [u8]>, section_type: u32) -> io::Result<&'a [u8]> {
+    sections.get(&section_type).copied().ok_or_else(|| invalid("missing section"))
+}
+
+/// Loads a `.r1cs` file compiled by circom over the field `F`.
+pub fn load_r1cs<F: PrimeField>(path: impl AsRef<Path>) -> io::Result<R1CS<F>> {
+    let bytes = fs::read(path)?;
+    let sections = read_sections(&bytes, R1CS_MAGIC)?;
+
+    let mut header = section(&sections, HEADER)?;
+    let n8 = read_prime::<F>(&mut header)?;
+    let num_wires = read_u32(&mut header)? as usize;
+    let num_pub_out = read_u32(&mut header)? as usize;
+    let num_pub_in = read_u32(&mut header)? as usize;
+    let num_prv_in = read_u32(&mut header)? as usize;
+    let _num_labels = read_u64(&mut header)?;
+    let num_constraints = read_u32(&mut header)? as usize;
+    if num_pub_out == 0 || num_pub_in + num_prv_in < num_pub_out {
+        return Err(invalid("the step circuit takes the state as its first input and outputs the next one"));
+    }
+
+    let mut reader = section(&sections, CONSTRAINTS)?;
+    let mut read_terms = || -> io::Result<Terms<F>> {
+        let num_terms = read_u32(&mut reader)?;
+        (0..num_terms)
+            .map(|_| {
+                let wire = read_u32(&mut reader)? as usize;
+                if wire >= num_wires {
+                    return Err(invalid("wire out of range"));
+                }
+                Ok((wire, read_field(&mut reader, n8)?))
+            })
+            .collect()
+    };
+    let constraints = (0..num_constraints)
+        .map(|_| Ok((read_terms()?, read_terms()?, read_terms()?)))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    Ok(R1CS {
+        num_wires,
+        num_pub_out,
+        num_pub_in,
+        num_prv_in,
+        constraints,
+    })
+}
+
+/// Loads a `.wtns` file over the field `F`.
+pub fn load_wtns<F: PrimeField>(path: impl AsRef<Path>) -> io::Result<Vec<F>> {
+    let bytes = fs::read(path)?;
+    let sections = read_sections(&bytes, WTNS_MAGIC)?;
+
+    let mut header = section(&sections, HEADER)?;
+    let n8 = read_prime::<F>(&mut header)?;
+    let num_witnesses = read_u32(&mut header)?;
+
+    let mut reader = section(&sections, WITNESS)?;
+    (0..num_witnesses).map(|_| read_field(&mut reader, n8)).collect()
+}
+
+#[derive(Clone, Debug)]
+pub enum WitnessGenerator<F: PrimeField> {
+    /// One precomputed `.wtns` file per step.
+    Wtns(Vec<PathBuf>),
+    /// The wasm of the circuit run by node through `script`, the `generate_witness.js` circom
+    /// emits. The state goes to the input signal `step_in`, `inputs` holds the private inputs of
+    /// every step by signal name.
+    Wasm {
+        script: PathBuf,
+        wasm: PathBuf,
+        step_in: String,
+        inputs: Vec<Vec<(String, Vec<F>)>>,
+    },
+}
+
+impl<F: PrimeField> WitnessGenerator<F> {
+    pub fn num_steps(&self) -> usize {
+        match self {
+            WitnessGenerator::Wtns(paths) => paths.len(),
+            WitnessGenerator::Wasm { inputs, .. } => inputs.len(),
+        }
+    }
+
+    /// Witness of step `step` of `r1cs` from the state `z`.
+    pub fn generate(&self, r1cs: &R1CS<F>, step: usize, z: &[F]) -> io::Result<Vec<F>> {
+        let witness = match self {
+            WitnessGenerator::Wtns(paths) => load_wtns(&paths[step])?,
+            WitnessGenerator::Wasm {
+                script,
+                wasm,
+                step_in,
+                inputs,
+            } => {
+                let signals = std::iter::once((step_in, z))
+                    .chain(inputs[step].iter().map(|(name, values)| (name, &values[..])));
+                let json = signals
+                    .map(|(name, values)| {
+                        let values = values
+                            .iter()
+                            .map(|value| format!("\"{}\"", Into::<BigUint>::into(value.into_bigint())))
+                            .collect::<Vec<_>>();
+                        format!("\"{}\": [{}]", name, values.join(", "))
+                    })
+                    .collect::<Vec<_>>();
+
+                let file = std::env::temp_dir().join(format!("circom_{}_{}", std::process::id(), step));
+                let (input, output) = (file.with_extension("json"), file.with_extension("wtns"));
+                fs::write(&input, format!("{{{}}}", json.join(", ")))?;
+                let status = Command::new("node")
+                    .arg(script)
+                    .arg(wasm)
+                    .arg(&input)
+                    .arg(&output)
+                    .status()
+                    .map_err(|err| match err.kind() {
+                        ErrorKind::NotFound => io::Error::new(ErrorKind::NotFound, "node is needed to run the wasm witness generator"),
+                        _ => err,
+                    });
+                let witness = match status {
+                    Ok(status) if status.success() => load_wtns(&output),
+                    Ok(_) => Err(io::Error::new(ErrorKind::Other, "witness generation failed")),
+                    Err(err) => Err(err),
+                };
+                let _ = fs::remove_file(input);
+                let _ = fs::remove_file(output);
+                witness?
+            }
+        };
+        r1cs.check_witness(&witness, z)?;
+        Ok(witness)
+    }
+}
+
+/// The bundled multiplier step circuit over the Pallas scalar field and the witnesses of its
+/// first steps from `z_0 = 3`.
+pub fn fixture<F: PrimeField>() -> io::Result<(R1CS<F>, WitnessGenerator<F>)> {
+    let dir = Path::new(FIXTURE_DIR);
+    let r1cs = load_r1cs(dir.join("multiplier2_pallas.r1cs"))?;
+    let paths = (0..FIXTURE_STEPS)
+        .map(|i| dir.join(format!("witness/pallas_{}.wtns", i)))
+        .collect();
+    Ok((r1cs, WitnessGenerator::Wtns(paths)))
+}
+
+#[derive(Clone, Debug)]
+pub struct CircomCircuit<F: PrimeField> {
+    pub r1cs: Arc<R1CS<F>>,
+    pub generator: WitnessGenerator<F>,
+}
+
+impl<F: PrimeField> CircomCircuit<F> {
+    pub fn new(r1cs: Arc<R1CS<F>>, generator: WitnessGenerator<F>) -> Self {
+        Self { r1cs, generator }
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for CircomCircuit<F> {
+    type Params = (Arc<R1CS<F>>, WitnessGenerator<F>);
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params.0, params.1))
+    }
+
+    fn state_len(&self) -> usize {
+        self.r1cs.arity()
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        assert!(i < self.generator.num_steps(), "no witness for step {}", i);
+        let witness = self
+            .generator
+            .generate(&self.r1cs, i, &z_i)
+            .unwrap_or_else(|err| panic!("witness of step {}: {}", i, err));
+        Ok(witness[1..1 + self.r1cs.arity()].to_vec())
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        // the R1CS shape is computed from a zero state, which has no witness: the wires then take
+        // zeros, only the shape of that system is used
+        let witness = z_i
+            .value()
+            .ok()
+            .and_then(|z| self.generator.generate(&self.r1cs, i, &z).ok())
+            .unwrap_or_else(|| vec![F::zero(); self.r1cs.num_wires]);
+        let value = |wire: usize| Ok(witness[wire]);
+        let arity = self.r1cs.arity();
+
+        let mut wires = Vec::with_capacity(self.r1cs.num_wires);
+        wires.push(LinearCombination::from(Variable::One));
+        let z_out = (0..arity)
+            .map(|k| AllocatedFp::new_witness(cs.clone(), || value(1 + k)))
+            .collect::<Result<Vec<_>, _>>()?;
+        wires.extend(z_out.iter().map(|z| LinearCombination::from(z.variable)));
+        wires.extend(z_i.iter().map(|z| match z {
+            FpVar::Constant(z) => LinearCombination::from((*z, Variable::One)),
+            FpVar::Var(z) => LinearCombination::from(z.variable),
+        }));
+        for wire in wires.len()..self.r1cs.num_wires {
+            wires.push(LinearCombination::from(cs.new_witness_variable(|| value(wire))?));
+        }
+
+        let lc = |terms: &Terms<F>| {
+            terms
+                .iter()
+                .fold(LinearCombination::zero(), |lc, (wire, coeff)| lc + (*coeff, &wires[*wire]))
+        };
+        for (a, b, c) in &self.r1cs.constraints {
+            cs.enforce_constraint(lc(a), lc(b), lc(c))?;
+        }
+
+        Ok(z_out.into_iter().map(FpVar::Var).collect())
+    }
+}
+
+#[test]
+fn circom_step_circuit_test() {
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let (r1cs, generator) = fixture::<Fr>().unwrap();
+    assert_eq!((r1cs.arity(), r1cs.num_wires, r1cs.constraints.len()), (1, 4, 1));
+    let circuit = CircomCircuit::new(Arc::new(r1cs), generator);
+
+    // c = a * b, with b = i + 2 at step i
+    let mut z_i = vec![Fr::from(3u32)];
+    for i in 0..3 {
+        let z_i_plus_1 = circuit.step_native(i, z_i.clone(), vec![]).unwrap();
+        assert_eq!(z_i_plus_1[0], z_i[0] * Fr::from(i as u32 + 2));
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+        let z_out = circuit.generate_step_constraints(cs.clone(), i, z_in, vec![]).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(z_out.value().unwrap(), z_i_plus_1);
+        z_i = z_i_plus_1;
+    }
+
+    // a witness of another step
+    assert!(circuit.generator.generate(&circuit.r1cs, 3, &[Fr::from(3u32)]).is_err());
+    // a witness over another field
+    assert!(load_wtns::<Fr>(Path::new(FIXTURE_DIR).join("witness/bn254_0.wtns")).is_err());
+}
+
+#[test]
+fn circom_wasm_witness_test() {
+    use ark_bn254::Fr;
+
+    if Command::new("node").arg("--version").output().is_err() {
+        eprintln!("node is not installed, skipping the wasm witness generation");
+        return;
+    }
+
+    // the wasm is compiled over the BN254 scalar field, whose precomputed witnesses it reproduces
+    let dir = Path::new(FIXTURE_DIR);
+    let r1cs = load_r1cs::<Fr>(dir.join("multiplier2.r1cs")).unwrap();
+    let generator = WitnessGenerator::Wasm {
+        script: dir.join("generate_witness.js"),
+        wasm: dir.join("multiplier2.wasm"),
+        step_in: "a".to_string(),
+        inputs: (0..FIXTURE_STEPS).map(|i| vec![("b".to_string(), vec![Fr::from(i as u32 + 2)])]).collect(),
+    };
+    let mut z_i = vec![Fr::from(3u32)];
+    for i in 0..3 {
+        let witness = generator.generate(&r1cs, i, &z_i).unwrap();
+        assert_eq!(witness, load_wtns::<Fr>(dir.join(format!("witness/bn254_{}.wtns", i))).unwrap());
+        z_i = witness[1..2].to_vec();
+    }
+}
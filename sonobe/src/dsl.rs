@@ -0,0 +1,285 @@
+//! Lowering of the `circuit_dsl` step circuits to arkworks: [`ArkBuilder`] generates the
+//! constraints of a step, [`NativeBuilder`] computes its next state, and [`DslCircuit`] wraps a
+//! step as an `FCircuit`. Poseidon is the sponge of `ark-crypto-primitives` with the canonical
+//! config of the folding schemes.
+
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::{PoseidonConfig, PoseidonSponge};
+use ark_crypto_primitives::sponge::CryptographicSponge;
+use ark_ff::PrimeField;
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use circuit_dsl::{Builder, Step};
+use num_bigint::BigUint;
+
+use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+use folding_schemes::{frontend::FCircuit, Error};
+
+/// Generates the constraints of the operations of a step in `cs`.
+pub struct ArkBuilder<'a, F: PrimeField> {
+    cs: ConstraintSystemRef<F>,
+    poseidon_config: &'a PoseidonConfig<F>,
+}
+
+impl<'a, F: PrimeField> ArkBuilder<'a, F> {
+    pub fn new(cs: ConstraintSystemRef<F>, poseidon_config: &'a PoseidonConfig<F>) -> Self {
+        Self { cs, poseidon_config }
+    }
+}
+
+impl<'a, F: PrimeField> Builder for ArkBuilder<'a, F> {
+    type Var = FpVar<F>;
+    type Error = SynthesisError;
+
+    fn modulus(&self) -> BigUint {
+        F::MODULUS.into()
+    }
+
+    fn constant(&mut self, value: &BigUint) -> Result<Self::Var, Self::Error> {
+        Ok(FpVar::constant(F::from(value.clone())))
+    }
+
+    fn alloc(&mut self, value: impl FnOnce() -> BigUint) -> Result<Self::Var, Self::Error> {
+        FpVar::new_witness(self.cs.clone(), || Ok(F::from(value())))
+    }
+
+    fn hint(
+        &mut self,
+        inputs: &[Self::Var],
+        f: impl FnOnce(&[BigUint]) -> BigUint,
+    ) -> Result<Self::Var, Self::Error> {
+        FpVar::new_witness(self.cs.clone(), || {
+            let values = inputs
+                .iter()
+                .map(|x| x.value().map(Into::into))
+                .collect::<Result<Vec<BigUint>, _>>()?;
+            Ok(F::from(f(&values)))
+        })
+    }
+
+    fn add(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error> {
+        Ok(a + b)
+    }
+
+    fn mul(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error> {
+        Ok(a * b)
+    }
+
+    fn enforce_eq(&mut self, a: &Self::Var, b: &Self::Var) -> Result<(), Self::Error> {
+        a.enforce_equal(b)
+    }
+
+    fn poseidon(&mut self, inputs: &[Self::Var]) -> Result<Self::Var, Self::Error> {
+        let mut sponge = PoseidonSpongeVar::new(self.cs.clone(), self.poseidon_config);
+        sponge.absorb(&inputs.to_vec())?;
+        Ok(sponge.squeeze_field_elements(1)?.remove(0))
+    }
+
+    // the bit decomposition of the range check workload
+    fn range_check(&mut self, a: &Self::Var, num_bits: usize) -> Result<(), Self::Error> {
+        let bits = (0..num_bits)
+            .map(|k| {
+                Boolean::new_witness(self.cs.clone(), || {
+                    a.value().map(|value| Into::<BigUint>::into(value).bit(k as u64))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(a)
+    }
+}
+
+/// Runs the operations of a step on values, failing on an unsatisfied equality.
+pub struct NativeBuilder<'a, F: PrimeField> {
+    poseidon_config: &'a PoseidonConfig<F>,
+}
+
+impl<'a, F: PrimeField> NativeBuilder<'a, F> {
+    pub fn new(poseidon_config: &'a PoseidonConfig<F>) -> Self {
+        Self { poseidon_config }
+    }
+}
+
+impl<'a, F: PrimeField> Builder for NativeBuilder<'a, F> {
+    type Var = F;
+    type Error = SynthesisError;
+
+    fn modulus(&self) -> BigUint {
+        F::MODULUS.into()
+    }
+
+    fn constant(&mut self, value: &BigUint) -> Result<F, SynthesisError> {
+        Ok(F::from(value.clone()))
+    }
+
+    fn alloc(&mut self, value: impl FnOnce() -> BigUint) -> Result<F, SynthesisError> {
+        Ok(F::from(value()))
+    }
+
+    fn hint(&mut self, inputs: &[F], f: impl FnOnce(&[BigUint]) -> BigUint) -> Result<F, SynthesisError> {
+        let values = inputs.iter().map(|x| (*x).into()).collect::<Vec<BigUint>>();
+        Ok(F::from(f(&values)))
+    }
+
+    fn add(&mut self, a: &F, b: &F) -> Result<F, SynthesisError> {
+        Ok(*a + b)
+    }
+
+    fn mul(&mut self, a: &F, b: &F) -> Result<F, SynthesisError> {
+        Ok(*a * b)
+    }
+
+    fn enforce_eq(&mut self, a: &F, b: &F) -> Result<(), SynthesisError> {
+        if a == b {
+            Ok(())
+        } else {
+            Err(SynthesisError::Unsatisfiable)
+        }
+    }
+
+    fn poseidon(&mut self, inputs: &[F]) -> Result<F, SynthesisError> {
+        let mut sponge = PoseidonSponge::new(self.poseidon_config);
+        sponge.absorb(&inputs.to_vec());
+        Ok(sponge.squeeze_field_elements::<F>(1)[0])
+    }
+
+    fn range_check(&mut self, a: &F, num_bits: usize) -> Result<(), SynthesisError> {
+        if Into::<BigUint>::into(*a).bits() <= num_bits as u64 {
+            Ok(())
+        } else {
+            Err(SynthesisError::Unsatisfiable)
+        }
+    }
+}
+
+/// A DSL step circuit as an `FCircuit`.
+#[derive(Clone, Debug)]
+pub struct DslCircuit<F: PrimeField, S: Step> {
+    pub step: S,
+    poseidon_config: PoseidonConfig<F>,
+    // offset added to the step index of the folding scheme
+    first_step: usize,
+}
+
+impl<F: PrimeField, S: Step> DslCircuit<F, S> {
+    /// Starts the circuit at step `first_step` of the chain, for a scheme folding a segment of it.
+    pub fn starting_at(mut self, first_step: usize) -> Self {
+        self.first_step = first_step;
+        self
+    }
+}
+
+impl<F: PrimeField, S: Step> FCircuit<F> for DslCircuit<F, S> {
+    type Params = S;
+
+    fn new(step: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            step,
+            poseidon_config: poseidon_canonical_config::<F>(),
+            first_step: 0,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        S::ARITY
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        let i = self.first_step + i;
+        let z_out = self
+            .step
+            .synthesize(&mut NativeBuilder::new(&self.poseidon_config), i, &z_i)
+            .unwrap_or_else(|err| panic!("step {} is not satisfied: {}", i, err));
+        Ok(z_out)
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        self.step
+            .synthesize(&mut ArkBuilder::new(cs, &self.poseidon_config), self.first_step + i, &z_i)
+    }
+}
+
+#[test]
+fn dsl_step_circuit_test() {
+    use crate::{minroot::MinRootCircuit, range_check::RangeCheckCircuit};
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+    use circuit_dsl::{hashchain::HashChain, minroot::MinRoot, range_check::RangeCheck};
+
+    fn run<S: Step>(step: S, i: usize, z_i: Vec<Fr>) -> Vec<Fr> {
+        let circuit = DslCircuit::<Fr, S>::new(step).unwrap();
+        let z_i_plus_1 = circuit.step_native(i, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+        let z_out = circuit.generate_step_constraints(cs.clone(), i, z_in, vec![]).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(z_out.value().unwrap(), z_i_plus_1);
+
+        // a segment starting further down the chain sees the same step
+        let shifted = circuit.starting_at(i).step_native(0, z_i, vec![]).unwrap();
+        assert_eq!(shifted, z_i_plus_1);
+        z_i_plus_1
+    }
+
+    // the same chains as the hand-written circuits
+    let z_0 = vec![Fr::from(0u32), Fr::from(0u32), Fr::from(1u32)];
+    let z_1 = run(MinRoot { num_iters_per_step: 16 }, 1, z_0.clone());
+    assert_eq!(z_1, MinRootCircuit::<Fr>::new(16).step_native(1, z_0, vec![]).unwrap());
+
+    let z_0 = vec![Fr::from(u64::MAX)];
+    let z_1 = run(RangeCheck { num_adds_per_step: 4 }, 3, z_0.clone());
+    assert_eq!(z_1, RangeCheckCircuit::<Fr>::new(4).step_native(3, z_0, vec![]).unwrap());
+
+    let step = HashChain { num_elts_per_step: 5, seed: 7 };
+    let z_1 = run(step, 2, vec![Fr::from(3u32)]);
+    let mut sponge = PoseidonSponge::new(&poseidon_canonical_config::<Fr>());
+    let preimage = (0..5).map(|k| Fr::from(step.preimage(2, k)));
+    sponge.absorb(&[vec![Fr::from(3u32)], preimage.collect()].concat());
+    assert_eq!(z_1, sponge.squeeze_field_elements::<Fr>(1));
+}
+
+#[test]
+fn dsl_baseline_test() {
+    use crate::{minroot::MinRootCircuit, range_check::RangeCheckCircuit};
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+    use circuit_dsl::{minroot::MinRoot, range_check::RangeCheck};
+
+    fn num_constraints<FC: FCircuit<Fr>>(circuit: &FC, z_i: Vec<Fr>) -> usize {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        circuit.generate_step_constraints(cs.clone(), 0, z_in, vec![]).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        cs.num_constraints()
+    }
+
+    // the hand-written circuits are the baselines of the DSL lowerings: the DSL MinRoot takes its
+    // next state from the additions instead of re-checking witnesses, the DSL range check spends a
+    // constraint on range checking its carry
+    let n = 16;
+    let z_0 = vec![Fr::from(0u32), Fr::from(0u32), Fr::from(1u32)];
+    assert_eq!(num_constraints(&MinRootCircuit::<Fr>::new(n), z_0.clone()), 6 * n);
+    let circuit = DslCircuit::<Fr, MinRoot>::new(MinRoot { num_iters_per_step: n }).unwrap();
+    assert_eq!(num_constraints(&circuit, z_0), 4 * n);
+
+    let z_0 = vec![Fr::from(0u32)];
+    assert_eq!(num_constraints(&RangeCheckCircuit::<Fr>::new(n), z_0.clone()), 132 * n);
+    let circuit = DslCircuit::<Fr, RangeCheck>::new(RangeCheck { num_adds_per_step: n }).unwrap();
+    assert_eq!(num_constraints(&circuit, z_0), 133 * n);
+}
@@ -0,0 +1,242 @@
+//! Export of step circuits to the iden3 binary formats, to compare against tools outside this
+//! repo: the constraints of a step go to a `.r1cs` file and its witness to a `.wtns` file, which
+//! `check_files` re-loads to validate the witness against the constraints.
+//!
+//! Any `FCircuit` exports, the wires follow the layout of the Circom frontend, the constant one,
+//! `z_{i+1}`, `z_i`, then the other witnesses of the step, so an exported step can be folded back
+//! through `circom::CircomCircuit`. Each output gets its own wire, tied to the variable the step
+//! returns by an equality constraint. MinRoot is the only workload of the benches with an arkworks
+//! version in this crate, the hash chain and scalar multiplication ones are exported by the Nova
+//! crate.
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{ConstraintSystem, SynthesisError};
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use folding_schemes::frontend::FCircuit;
+
+use crate::circom::{load_r1cs, load_wtns, Terms, R1CS};
+use crate::minroot::MinRootCircuit;
+
+const HEADER: u32 = 1;
+const CONSTRAINTS: u32 = 2;
+const WIRE_TO_LABEL: u32 = 3;
+const WITNESS: u32 = 2;
+
+/// Constraints of step `i` of `circuit` and its witness on the state `z` and the external inputs
+/// of the step, which are private inputs of the exported step.
+pub fn export_step<F: PrimeField, FC: FCircuit<F>>(
+    circuit: &FC,
+    i: usize,
+    z: &[F],
+    external_inputs: &[F],
+) -> Result<(R1CS<F>, Vec<F>), SynthesisError> {
+    let arity = z.len();
+    let cs = ConstraintSystem::<F>::new_ref();
+    let z_in = Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(z.to_vec()))?;
+    let external_inputs = Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(external_inputs.to_vec()))?;
+    let z_out = circuit.generate_step_constraints(cs.clone(), i, z_in, external_inputs)?;
+    assert_eq!(z_out.len(), arity);
+    // the outputs come last among the witnesses
+    for z in &z_out {
+        FpVar::new_witness(cs.clone(), || z.value())?.enforce_equal(z)?;
+    }
+    cs.finalize();
+
+    let matrices = cs.to_matrices().ok_or(SynthesisError::MissingCS)?;
+    if matrices.num_instance_variables != 1 {
+        panic!("step circuits have no public inputs");
+    }
+    let num_witnesses = matrices.num_witness_variables;
+    // column 0 is the constant one, then come the witnesses
+    let wire = |column: usize| match column {
+        0 => 0,
+        column if column > num_witnesses - arity => column - (num_witnesses - arity),
+        column => arity + column,
+    };
+    let terms = |row: &Vec<(F, usize)>| {
+        row.iter()
+            .map(|(coeff, column)| (wire(*column), *coeff))
+            .collect::<Terms<F>>()
+    };
+    let constraints = (0..matrices.num_constraints)
+        .map(|k| (terms(&matrices.a[k]), terms(&matrices.b[k]), terms(&matrices.c[k])))
+        .collect::<Vec<_>>();
+
+    let assignment = cs.borrow().ok_or(SynthesisError::MissingCS)?.witness_assignment.clone();
+    let mut witness = vec![F::one()];
+    witness.extend(&assignment[num_witnesses - arity..]);
+    witness.extend(&assignment[..num_witnesses - arity]);
+    let r1cs = R1CS {
+        num_wires: witness.len(),
+        num_pub_out: arity,
+        num_pub_in: 0,
+        num_prv_in: arity,
+        constraints,
+    };
+    Ok((r1cs, witness))
+}
+
+/// Index of the first constraint of `r1cs` that `witness` does not satisfy, if any.
+pub fn first_unsatisfied<F: PrimeField>(r1cs: &R1CS<F>, witness: &[F]) -> Option<usize> {
+    let eval = |terms: &Terms<F>| terms.iter().map(|(wire, coeff)| witness[*wire] * coeff).sum::<F>();
+    r1cs.constraints
+        .iter()
+        .position(|(a, b, c)| eval(a) * eval(b) != eval(c))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn push_u32(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend((value as u32).to_le_bytes());
+}
+
+// field elements are stored in little-endian canonical form on as many bytes as the prime
+fn push_field<F: PrimeField>(bytes: &mut Vec<u8>, value: &F) {
+    bytes.extend(value.into_bigint().to_bytes_le());
+}
+
+// the size of a field element and the prime, as in the headers of both formats
+fn push_prime<F: PrimeField>(bytes: &mut Vec<u8>) {
+    let prime = F::MODULUS.to_bytes_le();
+    push_u32(bytes, prime.len());
+    bytes.extend(prime);
+}
+
+fn write_sections(path: impl AsRef<Path>, magic: &[u8; 4], version: u32, sections: &[(u32, Vec<u8>)]) -> io::Result<()> {
+    let mut bytes = magic.to_vec();
+    bytes.extend(version.to_le_bytes());
+    push_u32(&mut bytes, sections.len());
+    for (section_type, content) in sections {
+        bytes.extend(section_type.to_le_bytes());
+        bytes.extend((content.len() as u64).to_le_bytes());
+        bytes.extend(content);
+    }
+    fs::write(path, bytes)
+}
+
+/// Writes `r1cs` as a `.r1cs` file, every wire labelled by its index.
+pub fn write_r1cs<F: PrimeField>(path: impl AsRef<Path>, r1cs: &R1CS<F>) -> io::Result<()> {
+    let mut header = Vec::new();
+    push_prime::<F>(&mut header);
+    for count in [r1cs.num_wires, r1cs.num_pub_out, r1cs.num_pub_in, r1cs.num_prv_in] {
+        push_u32(&mut header, count);
+    }
+    header.extend((r1cs.num_wires as u64).to_le_bytes());
+    push_u32(&mut header, r1cs.constraints.len());
+
+    let mut constraints = Vec::new();
+    for (a, b, c) in &r1cs.constraints {
+        for terms in [a, b, c] {
+            push_u32(&mut constraints, terms.len());
+            for (wire, coeff) in terms {
+                push_u32(&mut constraints, *wire);
+                push_field(&mut constraints, coeff);
+            }
+        }
+    }
+
+    let labels = (0..r1cs.num_wires as u64).flat_map(u64::to_le_bytes).collect();
+    write_sections(path, b"r1cs", 1, &[(HEADER, header), (CONSTRAINTS, constraints), (WIRE_TO_LABEL, labels)])
+}
+
+/// Writes `witness` as a `.wtns` file.
+pub fn write_wtns<F: PrimeField>(path: impl AsRef<Path>, witness: &[F]) -> io::Result<()> {
+    let mut header = Vec::new();
+    push_prime::<F>(&mut header);
+    push_u32(&mut header, witness.len());
+    let mut values = Vec::new();
+    for value in witness {
+        push_field(&mut values, value);
+    }
+    write_sections(path, b"wtns", 2, &[(HEADER, header), (WITNESS, values)])
+}
+
+/// Re-loads an exported step and checks that the witness satisfies its constraints.
+pub fn check_files<F: PrimeField>(r1cs_path: impl AsRef<Path>, wtns_path: impl AsRef<Path>) -> io::Result<()> {
+    let r1cs = load_r1cs::<F>(r1cs_path)?;
+    let witness = load_wtns::<F>(wtns_path)?;
+    if witness.len() != r1cs.num_wires || witness[0] != F::one() {
+        return Err(invalid("the witness does not match the wires".to_string()));
+    }
+    match first_unsatisfied(&r1cs, &witness) {
+        Some(i) => Err(invalid(format!("constraint {} is not satisfied", i))),
+        None => Ok(()),
+    }
+}
+
+/// Runs `circuit`, which takes no external inputs, from `z0` up to step `step` and exports that
+/// step, as `<name>.r1cs` and `<name>_<step>.wtns` in `dir`.
+pub fn export_chain<F: PrimeField, FC: FCircuit<F>>(
+    dir: impl AsRef<Path>,
+    name: &str,
+    circuit: FC,
+    z0: &[F],
+    step: usize,
+) -> io::Result<()> {
+    assert_eq!(circuit.external_inputs_len(), 0, "the chain is run without external inputs");
+    let to_io = |err: String| io::Error::new(ErrorKind::Other, err);
+    let mut z = z0.to_vec();
+    for i in 0..step {
+        z = circuit.step_native(i, z, vec![]).map_err(|err| to_io(format!("{:?}", err)))?;
+    }
+    let (r1cs, witness) = export_step(&circuit, step, &z, &[]).map_err(|err| to_io(err.to_string()))?;
+
+    fs::create_dir_all(&dir)?;
+    write_r1cs(dir.as_ref().join(format!("{}.r1cs", name)), &r1cs)?;
+    write_wtns(dir.as_ref().join(format!("{}_{}.wtns", name, step)), &witness)
+}
+
+/// Exports step `step` of the MinRoot workload, at the smallest configuration of its bench, to
+/// `dir`.
+pub fn export_workloads(dir: impl AsRef<Path>, step: usize) -> io::Result<()> {
+    type F = ark_pallas::Fr;
+
+    let z0 = vec![F::from(0u64), F::from(0u64), F::from(1u64)];
+    let circuit = MinRootCircuit::<F>::new(1024);
+    export_chain(dir, "minroot", circuit, &z0, step)
+}
+
+#[test]
+fn export_workloads_test() {
+    use crate::circom::{CircomCircuit, WitnessGenerator};
+    use ark_relations::r1cs::ConstraintSystem;
+    use std::sync::Arc;
+
+    type F = ark_pallas::Fr;
+
+    let dir = std::env::temp_dir().join(format!("sonobe_export_{}", std::process::id()));
+    export_workloads(&dir, 1).unwrap();
+    let (r1cs_path, wtns_path) = (dir.join("minroot.r1cs"), dir.join("minroot_1.wtns"));
+    check_files::<F>(&r1cs_path, &wtns_path).unwrap();
+
+    let r1cs = load_r1cs::<F>(&r1cs_path).unwrap();
+    let mut witness = load_wtns::<F>(&wtns_path).unwrap();
+    assert_eq!(first_unsatisfied(&r1cs, &witness), None);
+
+    // the export folds back through the Circom frontend, from the state of step 1
+    let arity = r1cs.arity();
+    let z_1 = witness[1 + arity..1 + 2 * arity].to_vec();
+    let minroot = MinRootCircuit::<F>::new(1024);
+    assert_eq!(witness[1..1 + arity], minroot.step_native(1, z_1.clone(), vec![]).unwrap()[..]);
+    let circuit = CircomCircuit::new(Arc::new(r1cs.clone()), WitnessGenerator::Wtns(vec![wtns_path.clone(); 2]));
+    let cs = ConstraintSystem::<F>::new_ref();
+    let z_in = Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(z_1)).unwrap();
+    circuit.generate_step_constraints(cs.clone(), 1, z_in, vec![]).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+
+    // an output off by one
+    witness[1] += F::from(1u64);
+    assert!(first_unsatisfied(&r1cs, &witness).is_some());
+    write_wtns(&wtns_path, &witness).unwrap();
+    assert!(check_files::<F>(&r1cs_path, &wtns_path).is_err());
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,216 @@
+//! Keccak-f[1600] permutation chain: each step applies the permutation a configurable number of
+//! times to the running state. The 25 lanes are carried across steps as 64-bit field elements
+//! and decomposed into bits, so every XOR and AND of the permutation is an R1CS constraint.
+
+use ark_ff::PrimeField;
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+pub const NUM_LANES: usize = 25;
+pub const LANE_BITS: usize = 64;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808A, 0x8000000080008000,
+    0x000000000000808B, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008A, 0x0000000000000088, 0x0000000080008009, 0x000000008000000A,
+    0x000000008000808B, 0x800000000000008B, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800A, 0x800000008000000A,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+// rotation offsets of the lane (x, y), at index x + 5y
+const RHO: [usize; NUM_LANES] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+// lane (x, y) moves to (y, 2x + 3y) in the pi step
+fn pi(x: usize, y: usize) -> usize {
+    y + 5 * ((2 * x + 3 * y) % 5)
+}
+
+// native reference, the lane (x, y) is at index x + 5y
+pub fn keccak_f(mut state: [u64; NUM_LANES]) -> [u64; NUM_LANES] {
+    for rc in ROUND_CONSTANTS {
+        let c: [u64; 5] = std::array::from_fn(|x| (0..5).fold(0, |acc, y| acc ^ state[x + 5 * y]));
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+
+        let mut b = [0u64; NUM_LANES];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[pi(x, y)] = state[x + 5 * y].rotate_left(RHO[x + 5 * y] as u32);
+            }
+        }
+
+        for x in 0..5 {
+            for y in 0..5 {
+                state[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+        state[0] ^= rc;
+    }
+    state
+}
+
+// every lane always fits in the lowest 8 bytes of its representation
+fn to_u64<F: PrimeField>(x: F) -> u64 {
+    x.into_bigint().as_ref()[0]
+}
+
+type Lane<F> = Vec<Boolean<F>>;
+
+fn rotate_left<F: PrimeField>(lane: &Lane<F>, n: usize) -> Lane<F> {
+    (0..LANE_BITS).map(|i| lane[(i + LANE_BITS - n) % LANE_BITS].clone()).collect()
+}
+
+fn xor<F: PrimeField>(a: &Lane<F>, b: &Lane<F>) -> Result<Lane<F>, SynthesisError> {
+    a.iter().zip(b).map(|(a, b)| a.xor(b)).collect()
+}
+
+fn keccak_round<F: PrimeField>(a: Vec<Lane<F>>, rc: u64) -> Result<Vec<Lane<F>>, SynthesisError> {
+    // theta
+    let c = (0..5)
+        .map(|x| (1..5).try_fold(a[x].clone(), |column, y| xor(&column, &a[x + 5 * y])))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut a_theta = a;
+    for x in 0..5 {
+        let d = xor(&c[(x + 4) % 5], &rotate_left(&c[(x + 1) % 5], 1))?;
+        for y in 0..5 {
+            a_theta[x + 5 * y] = xor(&a_theta[x + 5 * y], &d)?;
+        }
+    }
+
+    // rho and pi only move bits around
+    let mut b = vec![Vec::new(); NUM_LANES];
+    for x in 0..5 {
+        for y in 0..5 {
+            b[pi(x, y)] = rotate_left(&a_theta[x + 5 * y], RHO[x + 5 * y]);
+        }
+    }
+
+    // chi
+    let mut a_chi = Vec::with_capacity(NUM_LANES);
+    for y in 0..5 {
+        for x in 0..5 {
+            let lane = (0..LANE_BITS)
+                .map(|i| {
+                    let not_and = b[(x + 1) % 5 + 5 * y][i].not().and(&b[(x + 2) % 5 + 5 * y][i])?;
+                    b[x + 5 * y][i].xor(&not_and)
+                })
+                .collect::<Result<Lane<F>, _>>()?;
+            a_chi.push(lane);
+        }
+    }
+
+    // iota, XOR with constants is free
+    a_chi[0] = a_chi[0]
+        .iter()
+        .enumerate()
+        .map(|(i, bit)| if (rc >> i) & 1 == 1 { bit.not() } else { bit.clone() })
+        .collect();
+
+    Ok(a_chi)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct KeccakChainCircuit<F: PrimeField> {
+    pub num_perms_per_step: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> KeccakChainCircuit<F> {
+    pub fn new(num_perms_per_step: usize) -> Self {
+        Self {
+            num_perms_per_step,
+            _f: PhantomData,
+        }
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for KeccakChainCircuit<F> {
+    type Params = usize; // number of permutations per step
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params))
+    }
+
+    fn state_len(&self) -> usize {
+        NUM_LANES
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, _i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        let state = std::array::from_fn(|l| to_u64(z_i[l]));
+        let state = (0..self.num_perms_per_step).fold(state, |state, _| keccak_f(state));
+
+        Ok(state.map(F::from).to_vec())
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut state = Vec::with_capacity(NUM_LANES);
+        for lane in z_i.iter() {
+            // the value is missing while computing the R1CS shape, any state will do there
+            let value = lane.value().map(to_u64).unwrap_or_default();
+            let bits = (0..LANE_BITS)
+                .map(|i| Boolean::new_witness(cs.clone(), || Ok((value >> i) & 1 == 1)))
+                .collect::<Result<Lane<F>, _>>()?;
+            Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(lane)?;
+            state.push(bits);
+        }
+
+        for _ in 0..self.num_perms_per_step {
+            for rc in ROUND_CONSTANTS {
+                state = keccak_round(state, rc)?;
+            }
+        }
+
+        state.iter().map(|bits| Boolean::le_bits_to_fp_var(bits)).collect()
+    }
+}
+
+#[test]
+fn keccak_chain_step_test() {
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // the first lane of the permutation of the zero state, from the Keccak reference
+    assert_eq!(keccak_f([0; NUM_LANES])[0], 0xF1258F7940E1DDE7);
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let state: [u64; NUM_LANES] = std::array::from_fn(|_| rng.gen());
+    let mut expected = state;
+    tiny_keccak::keccakf(&mut expected);
+    assert_eq!(keccak_f(state), expected);
+
+    let circuit = KeccakChainCircuit::<Fr>::new(1);
+    let z_i = state.map(Fr::from).to_vec();
+    let z_i_plus_1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+    assert_eq!(z_i_plus_1, expected.map(Fr::from).to_vec());
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+    let z_out = circuit.generate_step_constraints(cs.clone(), 0, z_in, vec![]).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(z_out.iter().map(|z| z.value().unwrap()).collect::<Vec<_>>(), z_i_plus_1);
+}
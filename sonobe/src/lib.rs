@@ -1,2 +1,18 @@
 pub mod minroot;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod range_check;
+pub mod monolithic;
+pub mod sha256;
+pub mod keccak;
+pub mod merkle;
+pub mod schnorr;
+pub mod matvec;
+pub mod zkvm;
+pub mod bitcoin;
+pub mod wide_state;
+pub mod synthetic;
+pub mod random_circuit;
+pub mod circom;
+pub mod export;
+pub mod dsl;
+pub mod tree;
\ No newline at end of file
@@ -0,0 +1,184 @@
+//! Fixed-point dense layer chain, the streaming ML inference workload: each step applies one
+//! layer `y = ReLU((W * x + b) >> FRAC_BITS)` of a configurable width to the state `x`, with the
+//! weights and biases of the layer as private witnesses. Values are signed integers scaled by
+//! `2^FRAC_BITS`, the rescaled pre-activations are range checked to [`VALUE_BITS`] bits by bit
+//! decomposition, whose sign bit then selects the ReLU output.
+//!
+//! There is a layer per step, its weights and biases are hashed from the step index and their
+//! position in the layer.
+
+use ark_ff::PrimeField;
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use circuit_dsl::mix;
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+pub const FRAC_BITS: usize = 8;
+// pre-activations live in [-2^(VALUE_BITS - 1), 2^(VALUE_BITS - 1))
+pub const VALUE_BITS: usize = 16;
+
+// activations are non-negative and fit in the lowest limb
+fn to_i64<F: PrimeField>(x: F) -> i64 {
+    x.into_bigint().as_ref()[0] as i64
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DenseLayerCircuit<F: PrimeField> {
+    pub width: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> DenseLayerCircuit<F> {
+    pub fn new(width: usize) -> Self {
+        Self {
+            width,
+            _f: PhantomData,
+        }
+    }
+
+    /// Weights and biases of the layer applied at step `i`. The weights of a row sum to at most a
+    /// half in absolute value, so activations stay in range.
+    pub fn layer(&self, i: usize) -> (Vec<Vec<i64>>, Vec<i64>) {
+        let bound = ((1 << (FRAC_BITS - 1)) / self.width as i64).max(1);
+        let seed = mix(i as u64);
+        let weights = (0..self.width)
+            .map(|row| {
+                (0..self.width)
+                    .map(|col| (mix(seed ^ (row * self.width + col) as u64) % (2 * bound as u64 + 1)) as i64 - bound)
+                    .collect()
+            })
+            .collect();
+        let bias_bound = 1u64 << (2 * FRAC_BITS);
+        let bias = (0..self.width)
+            .map(|row| (mix(!seed ^ row as u64) % (2 * bias_bound + 1)) as i64 - bias_bound as i64)
+            .collect();
+        (weights, bias)
+    }
+
+    /// A layer input, the activations of a previous layer are non-negative.
+    pub fn initial_input(&self) -> Vec<F> {
+        (0..self.width)
+            .map(|j| F::from(mix(!(j as u64)) % (1 << (VALUE_BITS - 2))))
+            .collect()
+    }
+}
+
+/// Rescaled pre-activations and the remainders of the rescaling, `W * x + b = q * 2^FRAC_BITS + r`.
+pub fn pre_activations(weights: &[Vec<i64>], bias: &[i64], input: &[i64]) -> (Vec<i64>, Vec<i64>) {
+    weights
+        .iter()
+        .zip(bias)
+        .map(|(row, b)| {
+            let acc = row.iter().zip(input).map(|(w, x)| w * x).sum::<i64>() + b;
+            (acc.div_euclid(1 << FRAC_BITS), acc.rem_euclid(1 << FRAC_BITS))
+        })
+        .unzip()
+}
+
+impl<F: PrimeField> FCircuit<F> for DenseLayerCircuit<F> {
+    type Params = usize; // layer width
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params))
+    }
+
+    fn state_len(&self) -> usize {
+        self.width
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        let (weights, bias) = self.layer(i);
+        let input = z_i.into_iter().map(to_i64).collect::<Vec<_>>();
+        let (q, _) = pre_activations(&weights, &bias, &input);
+
+        Ok(q.into_iter().map(|q| F::from(q.max(0))).collect())
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let offset = 1i64 << (VALUE_BITS - 1);
+        let (weights, bias) = self.layer(i);
+        // the values are missing while computing the R1CS shape, any input will do there
+        let input = z_i.iter().map(|x| x.value().map(to_i64).unwrap_or_default()).collect::<Vec<_>>();
+        let (q, r) = pre_activations(&weights, &bias, &input);
+
+        let mut output = Vec::with_capacity(self.width);
+        for row in 0..self.width {
+            let w = Vec::<FpVar<F>>::new_witness(cs.clone(), || {
+                Ok(weights[row].iter().map(|w| F::from(*w)).collect::<Vec<_>>())
+            })?;
+            let b = FpVar::new_witness(cs.clone(), || Ok(F::from(bias[row])))?;
+            let acc = w.iter().zip(&z_i).fold(b, |acc, (w, x)| acc + w * x);
+
+            // q + 2^(VALUE_BITS - 1) and r are decomposed into VALUE_BITS and FRAC_BITS bits
+            let q_shifted = (q[row] + offset) as u64;
+            let q_bits = (0..VALUE_BITS)
+                .map(|k| Boolean::new_witness(cs.clone(), || Ok((q_shifted >> k) & 1 == 1)))
+                .collect::<Result<Vec<_>, _>>()?;
+            let r_bits = (0..FRAC_BITS)
+                .map(|k| Boolean::new_witness(cs.clone(), || Ok((r[row] >> k) & 1 == 1)))
+                .collect::<Result<Vec<_>, _>>()?;
+            let q_var = Boolean::le_bits_to_fp_var(&q_bits)? - F::from(offset);
+
+            // W_i * x + b = q * 2^FRAC_BITS + r
+            acc.enforce_equal(&(&q_var * F::from(1u64 << FRAC_BITS) + Boolean::le_bits_to_fp_var(&r_bits)?))?;
+
+            // the top bit of the shifted value is set iff q is non-negative
+            output.push(q_var * FpVar::from(q_bits[VALUE_BITS - 1].clone()));
+        }
+
+        Ok(output)
+    }
+}
+
+#[test]
+fn dense_layer_step_test() {
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let circuit = DenseLayerCircuit::<Fr>::new(8);
+    let mut x = (0..8).map(|j| (mix(!(j as u64)) % (1 << (VALUE_BITS - 2))) as i64).collect::<Vec<_>>();
+    let mut z_i = circuit.initial_input();
+    for i in 0..3 {
+        // the layer applied on integers, with a floor division by 2^FRAC_BITS
+        let (weights, bias) = circuit.layer(i);
+        let q = weights
+            .iter()
+            .zip(&bias)
+            .map(|(row, b)| (row.iter().zip(&x).map(|(w, x)| w * x).sum::<i64>() + b).div_euclid(1 << FRAC_BITS))
+            .collect::<Vec<_>>();
+        // the layers of these steps clamp some neurons and pass others through
+        assert!(q.iter().any(|q| *q < 0) && q.iter().any(|q| *q > 0));
+        x = q.into_iter().map(|q| q.max(0)).collect();
+        let expected = x.iter().map(|x| Fr::from(*x as u64)).collect::<Vec<_>>();
+        assert_eq!(circuit.step_native(i, z_i.clone(), vec![]).unwrap(), expected);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+        let z_out = circuit.generate_step_constraints(cs.clone(), i, z_in, vec![]).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        z_i = z_out.iter().map(|z| z.value().unwrap()).collect();
+        assert_eq!(z_i, expected);
+    }
+
+    // pre-activations out of the fixed-point range have no decomposition
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(vec![Fr::from(1u64 << 30); 8])).unwrap();
+    circuit.generate_step_constraints(cs.clone(), 0, z_in, vec![]).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
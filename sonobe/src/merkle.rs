@@ -0,0 +1,288 @@
+//! Merkle tree update chain, the state transition of a rollup: each step updates a configurable
+//! number of leaves of a Poseidon Merkle tree of depth `d` and outputs the new root. Nodes are
+//! hashed with the Poseidon two-to-one CRH of `ark-crypto-primitives`.
+//!
+//! The leaves are the history of the chain, not its state, so the prover keeps the native tree:
+//! [`MerkleUpdateCircuit::external_inputs`] applies the updates of a step to it and returns their
+//! advice, which the step takes as its external inputs.
+
+use ark_crypto_primitives::crh::poseidon::constraints::{CRHParametersVar, TwoToOneCRHGadget};
+use ark_crypto_primitives::crh::poseidon::TwoToOneCRH;
+use ark_crypto_primitives::crh::{TwoToOneCRHScheme, TwoToOneCRHSchemeGadget};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::select::CondSelectGadget;
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use circuit_dsl::mix;
+use std::collections::HashMap;
+
+use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+use folding_schemes::{frontend::FCircuit, Error};
+
+/// Sparse Poseidon Merkle tree with zero leaves, only the nodes that differ from an empty subtree
+/// are stored. Level 0 holds the leaves and level `depth` the root.
+#[derive(Clone, Debug)]
+pub struct MerkleTree<F: PrimeField + Absorb> {
+    pub depth: usize,
+    nodes: HashMap<(usize, u64), F>,
+    // root of an empty subtree at each level
+    empty: Vec<F>,
+    config: PoseidonConfig<F>,
+}
+
+/// Advice of a single leaf update, the siblings go from the leaf level up to the root.
+#[derive(Clone, Debug)]
+pub struct MerkleUpdate<F: PrimeField> {
+    pub index: u64,
+    pub old_leaf: F,
+    pub new_leaf: F,
+    pub siblings: Vec<F>,
+}
+
+impl<F: PrimeField> MerkleUpdate<F> {
+    /// Number of external inputs of an update in a tree of depth `depth`.
+    pub fn num_external_inputs(depth: usize) -> usize {
+        3 + depth
+    }
+
+    /// The update as external inputs, `(index, old_leaf, new_leaf, siblings)`.
+    pub fn to_external_inputs(&self) -> Vec<F> {
+        let mut inputs = vec![F::from(self.index), self.old_leaf, self.new_leaf];
+        inputs.extend(&self.siblings);
+        inputs
+    }
+
+    pub fn from_external_inputs(inputs: &[F]) -> Self {
+        Self {
+            index: inputs[0].into_bigint().as_ref()[0],
+            old_leaf: inputs[1],
+            new_leaf: inputs[2],
+            siblings: inputs[3..].to_vec(),
+        }
+    }
+}
+
+impl<F: PrimeField + Absorb> MerkleTree<F> {
+    pub fn new(depth: usize) -> Self {
+        assert!(depth < 64, "leaf indices are u64");
+        let config = poseidon_canonical_config::<F>();
+        let mut empty = vec![F::zero()];
+        for level in 0..depth {
+            empty.push(hash(&config, empty[level], empty[level]));
+        }
+
+        Self {
+            depth,
+            nodes: HashMap::new(),
+            empty,
+            config,
+        }
+    }
+
+    pub fn hash(&self, left: F, right: F) -> F {
+        hash(&self.config, left, right)
+    }
+
+    pub fn node(&self, level: usize, index: u64) -> F {
+        self.nodes.get(&(level, index)).copied().unwrap_or(self.empty[level])
+    }
+
+    pub fn root(&self) -> F {
+        self.node(self.depth, 0)
+    }
+
+    /// Root of the tree with `leaf` at `index`, from the siblings of its path.
+    pub fn path_root(&self, leaf: F, index: u64, siblings: &[F]) -> F {
+        path_root(&self.config, leaf, index, siblings)
+    }
+
+    pub fn siblings(&self, index: u64) -> Vec<F> {
+        (0..self.depth).map(|level| self.node(level, (index >> level) ^ 1)).collect()
+    }
+
+    /// Sets the leaf at `index`, returning the advice proving the update.
+    pub fn update(&mut self, index: u64, leaf: F) -> MerkleUpdate<F> {
+        let update = MerkleUpdate {
+            index,
+            old_leaf: self.node(0, index),
+            new_leaf: leaf,
+            siblings: self.siblings(index),
+        };
+
+        let mut node = leaf;
+        for level in 0..self.depth {
+            let position = index >> level;
+            self.nodes.insert((level, position), node);
+            let sibling = update.siblings[level];
+            node = if position & 1 == 0 { self.hash(node, sibling) } else { self.hash(sibling, node) };
+        }
+        self.nodes.insert((self.depth, 0), node);
+
+        update
+    }
+}
+
+fn hash<F: PrimeField + Absorb>(config: &PoseidonConfig<F>, left: F, right: F) -> F {
+    TwoToOneCRH::<F>::evaluate(config, left, right).unwrap()
+}
+
+// native version of `compute_root`
+fn path_root<F: PrimeField + Absorb>(config: &PoseidonConfig<F>, leaf: F, index: u64, siblings: &[F]) -> F {
+    siblings.iter().enumerate().fold(leaf, |node, (level, sibling)| {
+        if (index >> level) & 1 == 0 {
+            hash(config, node, *sibling)
+        } else {
+            hash(config, *sibling, node)
+        }
+    })
+}
+
+// the little-endian bits of the value of `x`, which `x` must equal
+pub(crate) fn decompose<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    x: &FpVar<F>,
+    num_bits: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let bits = (0..num_bits)
+        .map(|k| Boolean::new_witness(cs.clone(), || x.value().map(|x| x.into_bigint().get_bit(k))))
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(x)?;
+    Ok(bits)
+}
+
+// hashes `leaf` up to the root, the bits of the leaf index pick the side of every sibling
+pub(crate) fn compute_root<F: PrimeField + Absorb>(
+    params: &CRHParametersVar<F>,
+    leaf: &FpVar<F>,
+    index_bits: &[Boolean<F>],
+    siblings: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    let mut node = leaf.clone();
+    for (bit, sibling) in index_bits.iter().zip(siblings) {
+        let left = FpVar::conditionally_select(bit, sibling, &node)?;
+        let right = FpVar::conditionally_select(bit, &node, sibling)?;
+        node = TwoToOneCRHGadget::<F>::evaluate(params, &left, &right)?;
+    }
+    Ok(node)
+}
+
+#[derive(Clone, Debug)]
+pub struct MerkleUpdateCircuit<F: PrimeField + Absorb> {
+    pub depth: usize,
+    pub num_updates_per_step: usize,
+    config: PoseidonConfig<F>,
+}
+
+impl<F: PrimeField + Absorb> MerkleUpdateCircuit<F> {
+    pub fn new(depth: usize, num_updates_per_step: usize) -> Self {
+        Self {
+            depth,
+            num_updates_per_step,
+            config: poseidon_canonical_config::<F>(),
+        }
+    }
+
+    /// Applies the updates of step `i` to `tree`, returning their advice as the external inputs of
+    /// the step.
+    pub fn external_inputs(&self, tree: &mut MerkleTree<F>, i: usize) -> Vec<F> {
+        (0..self.num_updates_per_step)
+            .flat_map(|j| {
+                let seed = mix((i * self.num_updates_per_step + j) as u64);
+                let index = seed & ((1 << self.depth) - 1);
+                tree.update(index, F::from(mix(seed))).to_external_inputs()
+            })
+            .collect()
+    }
+}
+
+impl<F: PrimeField + Absorb> FCircuit<F> for MerkleUpdateCircuit<F> {
+    type Params = (usize, usize); // tree depth and number of updates per step
+
+    fn new((depth, num_updates_per_step): Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(depth, num_updates_per_step))
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        self.num_updates_per_step * MerkleUpdate::<F>::num_external_inputs(self.depth)
+    }
+
+    fn step_native(&self, _i: usize, z_i: Vec<F>, external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        let root = external_inputs
+            .chunks(MerkleUpdate::<F>::num_external_inputs(self.depth))
+            .map(MerkleUpdate::from_external_inputs)
+            .fold(z_i[0], |root, update| {
+                debug_assert_eq!(path_root(&self.config, update.old_leaf, update.index, &update.siblings), root);
+                path_root(&self.config, update.new_leaf, update.index, &update.siblings)
+            });
+
+        Ok(vec![root])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let params = CRHParametersVar::new_constant(cs.clone(), &self.config)?;
+        let mut root = z_i[0].clone();
+        for update in external_inputs.chunks(MerkleUpdate::<F>::num_external_inputs(self.depth)) {
+            let (index, old_leaf, new_leaf, siblings) = (&update[0], &update[1], &update[2], &update[3..]);
+            let index_bits = decompose(cs.clone(), index, self.depth)?;
+
+            compute_root(&params, old_leaf, &index_bits, siblings)?.enforce_equal(&root)?;
+            root = compute_root(&params, new_leaf, &index_bits, siblings)?;
+        }
+
+        Ok(vec![root])
+    }
+}
+
+#[test]
+fn merkle_update_step_test() {
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let (depth, num_updates_per_step) = (4, 3);
+    let circuit = MerkleUpdateCircuit::<Fr>::new(depth, num_updates_per_step);
+    let mut tree = MerkleTree::<Fr>::new(depth);
+    let z_i = vec![tree.root()];
+    let external_inputs = circuit.external_inputs(&mut tree, 0);
+
+    // dense reference: every leaf of the tree, hashed level by level
+    let mut level = vec![Fr::from(0_u32); 1 << depth];
+    for update in external_inputs.chunks(MerkleUpdate::<Fr>::num_external_inputs(depth)) {
+        let update = MerkleUpdate::from_external_inputs(update);
+        level[update.index as usize] = update.new_leaf;
+    }
+    while level.len() > 1 {
+        level = level.chunks(2).map(|pair| tree.hash(pair[0], pair[1])).collect();
+    }
+    assert_eq!(tree.root(), level[0]);
+    assert_eq!(circuit.step_native(0, z_i.clone(), external_inputs.clone()).unwrap(), vec![level[0]]);
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+    let inputs = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs.clone())).unwrap();
+    let z_out = circuit.generate_step_constraints(cs.clone(), 0, z_in, inputs).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(z_out[0].value().unwrap(), level[0]);
+
+    // a stale root doesn't open the updated leaves
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(vec![z_i[0] + Fr::from(1_u32)])).unwrap();
+    let inputs = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+    circuit.generate_step_constraints(cs.clone(), 0, z_in, inputs).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
@@ -9,8 +9,10 @@ use ark_r1cs_std::fields::fp::FpVar;
 use ark_r1cs_std::fields::FieldVar;
 use ark_r1cs_std::boolean::Boolean;
 use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::R1CSVar;
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
 use num_bigint::BigUint;
+use std::marker::PhantomData;
 
 use folding_schemes::{
     frontend::FCircuit,
@@ -28,26 +30,28 @@ pub struct MinRootIteration<F: PrimeField> {
   pub y_i_plus_1: F,
 }
 
+// exp = (p - 3 / 5), where p is the order of the group
+// x^{exp} mod p provides the fifth root of x
+fn fifth_root_exponent<F: PrimeField>() -> Vec<u64> {
+  let p: BigUint = F::MODULUS.into(); //G::group_params().2.to_biguint().unwrap();
+  let two = BigUint::parse_bytes(b"2", 10).unwrap();
+  let three = BigUint::parse_bytes(b"3", 10).unwrap();
+  let five = BigUint::parse_bytes(b"5", 10).unwrap();
+  let five_inv = five.modpow(&(&p - &two), &p);
+  ((&five_inv * (&p - &three)) % &p).to_u64_digits()
+}
+
 impl<F: PrimeField> MinRootIteration<F> {
   // produces a sample non-deterministic advice, executing one invocation of MinRoot per step
   fn new(num_iters: usize, i_0: &F, x_0: &F, y_0: &F) -> (Vec<F>, Vec<Self>) {
-    // exp = (p - 3 / 5), where p is the order of the group
-    // x^{exp} mod p provides the fifth root of x
-    let exp = {
-      let p: BigUint = F::MODULUS.into(); //G::group_params().2.to_biguint().unwrap();
-      let two = BigUint::parse_bytes(b"2", 10).unwrap();
-      let three = BigUint::parse_bytes(b"3", 10).unwrap();
-      let five = BigUint::parse_bytes(b"5", 10).unwrap();
-      let five_inv = five.modpow(&(&p - &two), &p);
-      (&five_inv * (&p - &three)) % &p
-    };
+    let exp = fifth_root_exponent::<F>();
 
     let mut res = Vec::new();
     let mut i = *i_0;
     let mut x_i = *x_0;
     let mut y_i = *y_0;
     for _i in 0..num_iters {
-      let x_i_plus_1 = (x_i + y_i).pow(&exp.to_u64_digits()); // computes the fifth root of x_i + y_i
+      let x_i_plus_1 = (x_i + y_i).pow(&exp); // computes the fifth root of x_i + y_i
 
       // sanity check
       if cfg!(debug_assertions) {
@@ -81,67 +85,59 @@ impl<F: PrimeField> MinRootIteration<F> {
 }
 
 
+/// The advice of a step is derived from the state it is given, so the circuit only holds the
+/// number of iterations.
 #[derive(Clone, Debug)]
 pub struct MinRootCircuit<F: PrimeField> {
   pub num_iters_per_step: usize,
-  input: Vec<F>,
-  pub seq: Vec<MinRootIteration<F>>,
+  _f: PhantomData<F>,
 }
 
 impl<F: PrimeField> MinRootCircuit<F> {
-    pub fn new(initial_input: Vec<F>, num_iters_per_step: usize) -> Self {
-        let (_output, seq) = 
-            MinRootIteration::new(num_iters_per_step, &initial_input[0], &initial_input[1], &initial_input[2]);
-
+    pub fn new(num_iters_per_step: usize) -> Self {
         Self { 
             num_iters_per_step,
-            input: initial_input.clone(),
-            seq, 
+            _f: PhantomData,
         }
     }
 }
 
 impl<F: PrimeField> FCircuit<F> for MinRootCircuit<F> {
 
-  type Params = (Vec<F>, usize); // initial input and number of iterations per step
+  type Params = usize; // number of iterations per step
   
-  fn new(params: Self::Params) -> Self {
-    let (_output, seq) = 
-    MinRootIteration::new(params.1, &params.0[0], &params.0[1], &params.0[2]);
-
-    Self { 
-        num_iters_per_step: params.1,
-        input: params.0.clone(),
-        seq, 
-    }
+  fn new(params: Self::Params) -> Result<Self, Error> {
+    Ok(Self::new(params))
   }
 
   fn state_len(&self) -> usize {
       3
   }
 
-  fn step_native(&mut self, _i: usize, _z_i: Vec<F>) -> Result<Vec<F>, Error> {
+  fn external_inputs_len(&self) -> usize {
+      0
+  }
+
+  fn step_native(&self, _i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
         // produces a sample non-deterministic advice, executing one invocation of MinRoot per step
         let (_output, seq) = 
-        MinRootIteration::new(self.num_iters_per_step, &self.input[0], &self.input[1], &self.input[2]);
-  
-        self.seq = seq;
+        MinRootIteration::new(self.num_iters_per_step, &z_i[0], &z_i[1], &z_i[2]);
   
         // use the provided inputs
-        let i_0 = self.input[0];
-        let x_0 = self.input[1];
-        let y_0 = self.input[2];
+        let i_0 = z_i[0];
+        let x_0 = z_i[1];
+        let y_0 = z_i[2];
         let mut z_out: Vec<F> = Vec::new();
   
         // variables to hold running x_i and y_i
         let mut x_i = x_0;
         let mut y_i = y_0;
         let mut i = i_0;
-        for ii in 0..self.seq.len() {
+        for ii in 0..seq.len() {
         // non deterministic advice
-        let i_plus_1 = self.seq[ii].i_plus_1;
-        let x_i_plus_1 = self.seq[ii].x_i_plus_1;
-        let y_i_plus_1 = self.seq[ii].y_i_plus_1;
+        let i_plus_1 = seq[ii].i_plus_1;
+        let x_i_plus_1 = seq[ii].x_i_plus_1;
+        let y_i_plus_1 = seq[ii].y_i_plus_1;
         // check the following conditions hold:
         // (i) x_i_plus_1 = (x_i + y_i)^{1/5}, which can be more easily checked with x_i_plus_1^5 = x_i + y_i
         // (ii) y_i_plus_1 = x_i
@@ -152,7 +148,7 @@ impl<F: PrimeField> FCircuit<F> for MinRootCircuit<F> {
         assert_eq!(y_i_plus_1, x_i + i);
         assert_eq!(i_plus_1, i + F::ONE);
 
-        if ii == self.seq.len() - 1 {
+        if ii == seq.len() - 1 {
             z_out = vec![i_plus_1, x_i_plus_1, y_i_plus_1];
         }
   
@@ -170,8 +166,10 @@ impl<F: PrimeField> FCircuit<F> for MinRootCircuit<F> {
       cs: ConstraintSystemRef<F>,
       _i: usize,
       z_i: Vec<FpVar<F>>,
+      _external_inputs: Vec<FpVar<F>>,
   ) -> Result<Vec<FpVar<F>>, SynthesisError> {
       
+    let exp = fifth_root_exponent::<F>();
     let mut z_out: Result<Vec<FpVar<F>>, SynthesisError> =
       Err(SynthesisError::AssignmentMissing);
 
@@ -185,16 +183,16 @@ impl<F: PrimeField> FCircuit<F> for MinRootCircuit<F> {
     let mut x_i = x_0;
     let mut y_i = y_0;
     let mut i = i_0;
-    for ii in 0..self.seq.len() {
-      // non deterministic advice
+    for ii in 0..self.num_iters_per_step {
+      // non deterministic advice, computed from the values of the state
       let i_plus_1 = FpVar::new_variable(cs.clone(),
-       || Ok(self.seq[ii].i_plus_1), AllocationMode::Witness).unwrap();
+       || Ok(i.value()? + F::ONE), AllocationMode::Witness)?;
 
       let x_i_plus_1 = FpVar::new_variable(cs.clone(),
-       || Ok(self.seq[ii].x_i_plus_1), AllocationMode::Witness).unwrap();
+       || Ok((x_i.value()? + y_i.value()?).pow(&exp)), AllocationMode::Witness)?;
       
       let y_i_plus_1 = FpVar::new_variable(cs.clone(),
-       || Ok(self.seq[ii].y_i_plus_1), AllocationMode::Witness).unwrap();
+       || Ok(x_i.value()? + i.value()?), AllocationMode::Witness)?;
       
       // check the following conditions hold:
       // (i) x_i_plus_1 = (x_i + y_i)^{1/5}, which can be more easily checked with x_i_plus_1^5 = x_i + y_i
@@ -210,7 +208,7 @@ impl<F: PrimeField> FCircuit<F> for MinRootCircuit<F> {
       i_plus_1.conditional_enforce_equal(&(i.clone() + F::ONE), &Boolean::<F>::TRUE)?;
       y_i_plus_1.conditional_enforce_equal(&(x_i.clone() + i.clone()), &Boolean::<F>::TRUE)?;
 
-      if ii == self.seq.len() - 1 {
+      if ii == self.num_iters_per_step - 1 {
         z_out = Ok(vec![i_plus_1.clone(), x_i_plus_1.clone(), y_i_plus_1.clone()]);
       }
 
@@ -222,4 +220,29 @@ impl<F: PrimeField> FCircuit<F> for MinRootCircuit<F> {
 
     z_out
   }
-}
\ No newline at end of file
+}
+
+#[test]
+fn minroot_state_advances_test() {
+  use ark_pallas::Fr;
+  use ark_relations::r1cs::ConstraintSystem;
+
+  let num_iters_per_step = 4;
+  let circuit = MinRootCircuit::<Fr>::new(num_iters_per_step);
+  let mut z_i = vec![Fr::from(0_u32), Fr::from(0_u32), Fr::from(1_u32)];
+  for i in 0..3 {
+    let z_i_plus_1 = circuit.step_native(i, z_i.clone(), vec![]).unwrap();
+    // each step continues from the state it is given, not from the initial one
+    assert_eq!(z_i_plus_1[0], z_i[0] + Fr::from(num_iters_per_step as u32));
+    assert_ne!(z_i_plus_1[1..], z_i[1..]);
+
+    // the constraints of the step take their advice from the same state
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+    let z_out = circuit.generate_step_constraints(cs.clone(), i, z_in, vec![]).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(z_out.iter().map(|z| z.value().unwrap()).collect::<Vec<_>>(), z_i_plus_1);
+
+    z_i = z_i_plus_1;
+  }
+}
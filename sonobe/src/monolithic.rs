@@ -0,0 +1,93 @@
+//! Non-folding baseline: the step constraints of a whole IVC run are generated into a single R1CS
+//! and proven at once with Groth16 over BN254.
+
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, ProvingKey};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError};
+use std::time::{Duration, Instant};
+
+use folding_schemes::frontend::FCircuit;
+
+use crate::minroot::MinRootCircuit;
+
+/// Chains `steps` from `z_0`, with the initial and final states as public inputs. The steps take
+/// no external inputs.
+#[derive(Clone, Debug)]
+pub struct MonolithicCircuit<F: PrimeField, FC: FCircuit<F>> {
+    pub z_0: Vec<F>,
+    pub z_n: Vec<F>,
+    pub steps: Vec<FC>,
+}
+
+impl<F: PrimeField, FC: FCircuit<F>> MonolithicCircuit<F, FC> {
+    pub fn new(z_0: Vec<F>, steps: Vec<FC>) -> Self {
+        assert!(steps.iter().all(|step| step.external_inputs_len() == 0));
+        let z_n = steps
+            .iter()
+            .enumerate()
+            .fold(z_0.clone(), |z_i, (i, step)| step.step_native(i, z_i, vec![]).unwrap());
+
+        Self { z_0, z_n, steps }
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        let cs = ConstraintSystem::<F>::new_ref();
+        self.clone().generate_constraints(cs.clone()).unwrap();
+        cs.num_constraints()
+    }
+}
+
+impl<F: PrimeField, FC: FCircuit<F>> ConstraintSynthesizer<F> for MonolithicCircuit<F, FC> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let mut z_i = Vec::<FpVar<F>>::new_input(cs.clone(), || Ok(self.z_0.clone()))?;
+        for (i, step) in self.steps.iter().enumerate() {
+            z_i = step.generate_step_constraints(cs.clone(), i, z_i, vec![])?;
+        }
+
+        let z_n = Vec::<FpVar<F>>::new_input(cs.clone(), || Ok(self.z_n.clone()))?;
+        z_i.enforce_equal(&z_n)
+    }
+}
+
+// splits a MinRoot chain starting from (0, 0, 1) into `num_steps` step circuits
+pub fn minroot_steps<F: PrimeField>(num_steps: usize, num_iters_per_step: usize) -> MonolithicCircuit<F, MinRootCircuit<F>> {
+    let z_0 = vec![F::from(0_u32), F::from(0_u32), F::from(1_u32)];
+    MonolithicCircuit::new(z_0, vec![MinRootCircuit::<F>::new(num_iters_per_step); num_steps])
+}
+
+pub fn groth16_setup<FC: FCircuit<Fr>>(circuit: MonolithicCircuit<Fr, FC>) -> ProvingKey<Bn254> {
+    let mut rng = ark_std::test_rng();
+    Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit, &mut rng).unwrap()
+}
+
+// proves the monolithic circuit, witness generation included as for the IVC provers
+pub fn groth16_prove<FC: FCircuit<Fr>>(pk: &ProvingKey<Bn254>, circuit: MonolithicCircuit<Fr, FC>) -> Duration {
+    let mut rng = ark_std::test_rng();
+    let start = Instant::now();
+    let res = Groth16::<Bn254>::create_random_proof_with_reduction(circuit, pk, &mut rng);
+    assert!(res.is_ok());
+    start.elapsed()
+}
+
+#[test]
+fn groth16_round_trip_test() {
+    use ark_groth16::prepare_verifying_key;
+
+    let circuit = minroot_steps::<Fr>(2, 4);
+    let pk = groth16_setup(circuit.clone());
+    let mut rng = ark_std::test_rng();
+    let proof = Groth16::<Bn254>::create_random_proof_with_reduction(circuit.clone(), &pk, &mut rng).unwrap();
+
+    let pvk = prepare_verifying_key(&pk.vk);
+    let public_inputs = [circuit.z_0.clone(), circuit.z_n.clone()].concat();
+    assert!(Groth16::<Bn254>::verify_proof(&pvk, &proof, &public_inputs).unwrap());
+
+    // the proof is bound to the final state
+    let mut other_inputs = public_inputs;
+    other_inputs[3] += Fr::from(1_u32);
+    assert!(!Groth16::<Bn254>::verify_proof(&pvk, &proof, &other_inputs).unwrap());
+}
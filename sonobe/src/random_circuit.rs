@@ -0,0 +1,191 @@
+//! Random satisfiable R1CS, for scaling studies that skip hand-written circuits: given a number of
+//! constraints, a number of witness variables, a density and a seed, a step has exactly
+//! `num_constraints` constraints over exactly `num_variables` fresh witness variables, each row of
+//! `A`, `B` and `C` having `density` non-zero entries.
+//!
+//! The rows are derived on the fly from the seed by the generator of the Nova version, so both
+//! frameworks are swept on identical matrices: variable 0 is `z_i`, then come the free advice
+//! variables, then one variable per constraint, which enters `C` with coefficient 1 next to terms
+//! over the previous variables only. The state is the last defined variable.
+
+use ark_ff::PrimeField;
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::fields::fp::{AllocatedFp, FpVar};
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+use circuit_dsl::mix;
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+pub const MAX_LOG_CONSTRAINTS: usize = 22;
+
+const A: usize = 0;
+const B: usize = 1;
+const C: usize = 2;
+
+/// `n`-th random word of the stream of `seed`.
+pub fn word(seed: u64, n: u64) -> u64 {
+    mix(mix(seed) ^ n)
+}
+
+#[derive(Clone, Debug)]
+pub struct RandomCircuit<F: PrimeField> {
+    pub num_constraints: usize,
+    pub num_variables: usize,
+    pub density: usize,
+    pub seed: u64,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> RandomCircuit<F> {
+    pub fn new(num_constraints: usize, num_variables: usize, density: usize, seed: u64) -> Self {
+        assert!(
+            (1..=1 << MAX_LOG_CONSTRAINTS).contains(&num_constraints),
+            "the number of constraints ranges from 1 to 2^{}",
+            MAX_LOG_CONSTRAINTS
+        );
+        assert!(num_variables >= num_constraints, "every constraint defines a variable");
+        assert!(density > 0);
+        Self {
+            num_constraints,
+            num_variables,
+            density,
+            seed,
+            _f: PhantomData,
+        }
+    }
+
+    pub fn num_advice(&self) -> usize {
+        self.num_variables - self.num_constraints
+    }
+
+    /// The advice of step `i`.
+    pub fn advice(&self, i: usize) -> Vec<F> {
+        (0..self.num_advice())
+            .map(|k| F::from(word(self.seed, (i * self.num_advice() + k) as u64)))
+            .collect()
+    }
+
+    /// Terms of the row of `matrix` of constraint `k`, as variable indices and coefficients.
+    pub fn row(&self, k: usize, matrix: usize) -> Vec<(usize, u64)> {
+        let num_vars = (1 + self.num_advice() + k) as u64;
+        let random_term = |t: usize| {
+            let n = (((k * 3 + matrix) * self.density + t) * 2) as u64;
+            ((word(self.seed, n) % num_vars) as usize, 1 + word(self.seed, n + 1) % 0xffff)
+        };
+        if matrix == C {
+            // the defined variable, then terms over the previous ones
+            let mut row = vec![(num_vars as usize, 1)];
+            row.extend((1..self.density).map(random_term));
+            row
+        } else {
+            (0..self.density).map(random_term).collect()
+        }
+    }
+
+    /// Values of all the variables of a step from `z_i` and the advice.
+    pub fn evaluate(&self, z: F, advice: &[F]) -> Vec<F> {
+        assert_eq!(advice.len(), self.num_advice());
+        let eval = |row: &[(usize, u64)], values: &[F]| -> F {
+            row.iter().map(|(var, coeff)| values[*var] * F::from(*coeff)).sum()
+        };
+
+        let mut values = Vec::with_capacity(1 + self.num_variables);
+        values.push(z);
+        values.extend(advice);
+        for k in 0..self.num_constraints {
+            let value = eval(&self.row(k, A), &values) * eval(&self.row(k, B), &values)
+                - eval(&self.row(k, C)[1..], &values);
+            values.push(value);
+        }
+        values
+    }
+}
+
+fn lc<F: PrimeField>(row: &[(usize, u64)], vars: &[LinearCombination<F>]) -> LinearCombination<F> {
+    row.iter()
+        .fold(LinearCombination::zero(), |lc, (var, coeff)| lc + (F::from(*coeff), &vars[*var]))
+}
+
+impl<F: PrimeField> FCircuit<F> for RandomCircuit<F> {
+    type Params = (usize, usize, usize, u64); // number of constraints, number of variables, density, seed
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params.0, params.1, params.2, params.3))
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        Ok(vec![*self.evaluate(z_i[0], &self.advice(i)).last().unwrap()])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let values = z_i[0].value().ok().map(|z| self.evaluate(z, &self.advice(i)));
+        let value = |var: usize| values.as_ref().map(|values| values[var]).ok_or(SynthesisError::AssignmentMissing);
+
+        let mut vars = Vec::with_capacity(1 + self.num_variables);
+        vars.push(match &z_i[0] {
+            FpVar::Constant(z) => LinearCombination::from((*z, Variable::One)),
+            FpVar::Var(z) => LinearCombination::from(z.variable),
+        });
+        for k in 0..self.num_advice() {
+            vars.push(LinearCombination::from(cs.new_witness_variable(|| value(1 + k))?));
+        }
+
+        let mut out = z_i[0].clone();
+        for k in 0..self.num_constraints {
+            let var = AllocatedFp::new_witness(cs.clone(), || value(vars.len()))?;
+            vars.push(LinearCombination::from(var.variable));
+            cs.enforce_constraint(
+                lc(&self.row(k, A), &vars),
+                lc(&self.row(k, B), &vars),
+                lc(&self.row(k, C), &vars),
+            )?;
+            out = FpVar::Var(var);
+        }
+
+        Ok(vec![out])
+    }
+}
+
+#[test]
+fn random_step_test() {
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    for (num_constraints, num_variables, density) in [(64, 64, 1), (64, 256, 3), (128, 128, 16)] {
+        let circuit = RandomCircuit::<Fr>::new(num_constraints, num_variables, density, 7);
+        let z_i = vec![Fr::from(3u64)];
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+        let z_out = circuit.generate_step_constraints(cs.clone(), 2, z_in, vec![]).unwrap();
+
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.num_constraints(), num_constraints);
+        assert_eq!(cs.num_witness_variables(), 1 + num_variables);
+        assert_eq!(vec![z_out[0].value().unwrap()], circuit.step_native(2, z_i, vec![]).unwrap());
+        for k in 0..num_constraints {
+            assert!([A, B, C].iter().all(|matrix| circuit.row(k, *matrix).len() == density));
+        }
+    }
+
+    // the rows follow from the seed as in the Nova version, whose test pins the same row
+    let [circuit, same, other] = [7, 7, 8].map(|seed| RandomCircuit::<Fr>::new(32, 32, 4, seed));
+    assert_eq!(circuit.row(31, A), vec![(6, 57388), (19, 13810), (25, 2184), (1, 26751)]);
+    assert_eq!(circuit.row(31, A), same.row(31, A));
+    assert_ne!(circuit.row(31, A), other.row(31, A));
+}
@@ -0,0 +1,138 @@
+//! Range-check chain: each step performs a configurable number of u64 additions with carry,
+//! `acc + a_j = acc_{j+1} + carry_j * 2^64`, range-checking every `a_j` and `acc_{j+1}` to 64 bits
+//! by bit decomposition. The state is the running accumulator.
+//!
+//! The addends of step `i` are a function of `i` alone, which both the native step and its
+//! constraints are given.
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use circuit_dsl::range_check::RangeCheck;
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+pub const NUM_BITS: usize = 64;
+
+fn to_u64<F: PrimeField>(x: F) -> u64 {
+    x.into_bigint().as_ref()[0]
+}
+
+// allocates `value` together with its 64-bit little-endian decomposition
+fn alloc_range_checked<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    value: u64,
+) -> Result<FpVar<F>, SynthesisError> {
+    let num = FpVar::new_witness(cs.clone(), || Ok(F::from(value)))?;
+    let bits = (0..NUM_BITS)
+        .map(|i| Boolean::new_witness(cs.clone(), || Ok((value >> i) & 1 == 1)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(&num)?;
+
+    Ok(num)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RangeCheckCircuit<F: PrimeField> {
+    pub num_adds_per_step: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> RangeCheckCircuit<F> {
+    pub fn new(num_adds_per_step: usize) -> Self {
+        Self {
+            num_adds_per_step,
+            _f: PhantomData,
+        }
+    }
+
+    /// `j`-th addend of step `i`, the one of the DSL version.
+    pub fn addend(&self, i: usize, j: usize) -> u64 {
+        RangeCheck {
+            num_adds_per_step: self.num_adds_per_step,
+        }
+        .addend(i, j)
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for RangeCheckCircuit<F> {
+    type Params = usize; // number of additions per step
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params))
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        let acc = (0..self.num_adds_per_step)
+            .fold(to_u64(z_i[0]), |acc, j| acc.wrapping_add(self.addend(i, j)));
+
+        Ok(vec![F::from(acc)])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let two_pow_64 = F::from(u64::MAX) + F::ONE;
+
+        // the value is missing while computing the R1CS shape, any accumulator will do there
+        let mut acc_value = z_i[0].value().map(to_u64).unwrap_or_default();
+        let mut acc = z_i[0].clone();
+        for j in 0..self.num_adds_per_step {
+            let a_value = self.addend(i, j);
+            let (acc_next_value, carry_value) = acc_value.overflowing_add(a_value);
+
+            let a = alloc_range_checked(cs.clone(), a_value)?;
+            let acc_next = alloc_range_checked(cs.clone(), acc_next_value)?;
+            let carry = Boolean::new_witness(cs.clone(), || Ok(carry_value))?;
+
+            (&acc + &a).enforce_equal(&(&acc_next + FpVar::from(carry) * two_pow_64))?;
+
+            acc_value = acc_next_value;
+            acc = acc_next;
+        }
+
+        Ok(vec![acc])
+    }
+}
+
+#[test]
+fn range_check_step_test() {
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    // starting from u64::MAX, the first addition carries
+    let circuit = RangeCheckCircuit::<Fr>::new(4);
+    let z_i = vec![Fr::from(u64::MAX)];
+    let z_i_plus_1 = circuit.step_native(3, z_i.clone(), vec![]).unwrap();
+    let acc = (0..4).fold(u64::MAX, |acc, j| acc.wrapping_add(circuit.addend(3, j)));
+    assert_eq!(z_i_plus_1, vec![Fr::from(acc)]);
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+    let z_out = circuit.generate_step_constraints(cs.clone(), 3, z_in, vec![]).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(z_out.iter().map(|z| z.value().unwrap()).collect::<Vec<_>>(), z_i_plus_1);
+
+    // an accumulator out of the 64-bit range doesn't fit a single carry
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(vec![z_i[0] + Fr::from(4) * (z_i[0] + Fr::from(1u64))])).unwrap();
+    circuit.generate_step_constraints(cs.clone(), 3, z_in, vec![]).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
@@ -0,0 +1,175 @@
+//! Schnorr signature verification chain over Vesta, whose base field is the Pallas scalar field
+//! the folded circuit works in. Each step verifies a configurable number of signatures,
+//! `s * G = R + e * P` with the challenge `e = H(R, P, m)`, and absorbs every verified public key
+//! and message into the running accumulator `acc' = H(acc, P, m)`. Hashes are the Poseidon CRH of
+//! `ark-crypto-primitives`.
+//!
+//! Keys, nonces and messages are hashes of the step index and of the position of the signature in
+//! the step, so that `step_native` and the constraints of a step sign the same messages.
+
+use ark_crypto_primitives::crh::poseidon::constraints::{CRHGadget, CRHParametersVar};
+use ark_crypto_primitives::crh::poseidon::CRH;
+use ark_crypto_primitives::crh::{CRHScheme, CRHSchemeGadget};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ec::{CurveGroup, Group};
+use ark_ff::{BigInteger, PrimeField};
+use ark_pallas::Fr;
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::CurveVar;
+use ark_r1cs_std::ToBitsGadget;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_vesta::{constraints::GVar as VestaVar, Affine, Fr as VestaScalar, Projective};
+use circuit_dsl::mix;
+
+use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+use folding_schemes::{frontend::FCircuit, Error};
+
+/// A signature on `m` under the public key `pk`.
+#[derive(Clone, Debug)]
+pub struct Signature {
+    pub pk: Affine,
+    pub r: Affine,
+    pub s: VestaScalar,
+    pub m: Fr,
+}
+
+fn challenge(config: &PoseidonConfig<Fr>, r: &Affine, pk: &Affine, m: Fr) -> VestaScalar {
+    let e = CRH::<Fr>::evaluate(config, vec![r.x, r.y, pk.x, pk.y, m]).unwrap();
+    VestaScalar::from_le_bytes_mod_order(&e.into_bigint().to_bytes_le())
+}
+
+impl Signature {
+    pub fn sign(config: &PoseidonConfig<Fr>, sk: VestaScalar, k: VestaScalar, m: Fr) -> Self {
+        let g = Projective::generator();
+        let pk = (g * sk).into_affine();
+        let r = (g * k).into_affine();
+        let e = challenge(config, &r, &pk, m);
+
+        Self { pk, r, s: k + e * sk, m }
+    }
+
+    pub fn verify(&self, config: &PoseidonConfig<Fr>) -> bool {
+        let e = challenge(config, &self.r, &self.pk, self.m);
+        Projective::generator() * self.s == Projective::from(self.r) + self.pk * e
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SchnorrChainCircuit {
+    pub num_sigs_per_step: usize,
+    config: PoseidonConfig<Fr>,
+}
+
+impl SchnorrChainCircuit {
+    pub fn new(num_sigs_per_step: usize) -> Self {
+        Self {
+            num_sigs_per_step,
+            config: poseidon_canonical_config::<Fr>(),
+        }
+    }
+
+    // the j-th signature of step i, under a key of its own
+    pub fn signature(&self, i: usize, j: usize) -> Signature {
+        let seed = mix((i * self.num_sigs_per_step + j) as u64);
+        let [sk, k, m] = [1, 2, 3].map(|n| mix(seed ^ n));
+        Signature::sign(&self.config, VestaScalar::from(sk), VestaScalar::from(k), Fr::from(m))
+    }
+}
+
+impl FCircuit<Fr> for SchnorrChainCircuit {
+    type Params = usize; // number of signatures per step
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params))
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, i: usize, z_i: Vec<Fr>, _external_inputs: Vec<Fr>) -> Result<Vec<Fr>, Error> {
+        let acc = (0..self.num_sigs_per_step).fold(z_i[0], |acc, j| {
+            let sig = self.signature(i, j);
+            debug_assert!(sig.verify(&self.config));
+            CRH::<Fr>::evaluate(&self.config, vec![acc, sig.pk.x, sig.pk.y, sig.m]).unwrap()
+        });
+
+        Ok(vec![acc])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<Fr>,
+        i: usize,
+        z_i: Vec<FpVar<Fr>>,
+        _external_inputs: Vec<FpVar<Fr>>,
+    ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        let params = CRHParametersVar::new_constant(cs.clone(), &self.config)?;
+        let generator = VestaVar::new_constant(cs.clone(), Projective::generator())?;
+
+        let mut acc = z_i[0].clone();
+        for j in 0..self.num_sigs_per_step {
+            let sig = self.signature(i, j);
+
+            let pk = VestaVar::new_witness(cs.clone(), || Ok(Projective::from(sig.pk)))?;
+            let r = VestaVar::new_witness(cs.clone(), || Ok(Projective::from(sig.r)))?;
+            let m = FpVar::new_witness(cs.clone(), || Ok(sig.m))?;
+            let (pk_affine, r_affine) = (pk.to_affine()?, r.to_affine()?);
+
+            // e = H(R, P, m), decomposed into its canonical bits
+            let e = CRHGadget::<Fr>::evaluate(
+                &params,
+                &[r_affine.x, r_affine.y, pk_affine.x.clone(), pk_affine.y.clone(), m.clone()],
+            )?;
+            let e_bits = e.to_bits_le()?;
+            let s_bits = sig
+                .s
+                .into_bigint()
+                .to_bits_le()
+                .into_iter()
+                .map(|bit| Boolean::new_witness(cs.clone(), || Ok(bit)))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            // s * G = R + e * P
+            let lhs = generator.scalar_mul_le(s_bits.iter())?;
+            let rhs = r + pk.scalar_mul_le(e_bits.iter())?;
+            lhs.enforce_equal(&rhs)?;
+
+            acc = CRHGadget::<Fr>::evaluate(&params, &[acc, pk_affine.x, pk_affine.y, m])?;
+        }
+
+        Ok(vec![acc])
+    }
+}
+
+#[test]
+fn schnorr_chain_step_test() {
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let circuit = SchnorrChainCircuit::new(2);
+    assert!(circuit.signature(0, 0).verify(&circuit.config));
+    let mut sig = circuit.signature(0, 1);
+    sig.m += Fr::from(1u32);
+    assert!(!sig.verify(&circuit.config));
+
+    let mut z_i = vec![Fr::from(0u32)];
+    for i in 0..2 {
+        let z_i_plus_1 = circuit.step_native(i, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+        let z_out = circuit.generate_step_constraints(cs.clone(), i, z_in, vec![]).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(z_out.iter().map(|z| z.value().unwrap()).collect::<Vec<_>>(), z_i_plus_1);
+
+        z_i = z_i_plus_1;
+    }
+}
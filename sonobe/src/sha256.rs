@@ -0,0 +1,138 @@
+//! SHA-256 hash chain: each step hashes the running digest a configurable number of times,
+//! `h_{j+1} = SHA256(h_j)`, with the SHA-256 constraints of `ark-crypto-primitives`. The digest is
+//! carried across steps as two 128-bit big-endian halves, since it does not fit in a single
+//! field element.
+
+use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::uint8::UInt8;
+use ark_r1cs_std::{R1CSVar, ToBitsGadget};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use sha2::{Digest, Sha256};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+pub const DIGEST_BYTES: usize = 32;
+const HALF_BYTES: usize = DIGEST_BYTES / 2;
+
+// native reference, hashes `digest` `num_hashes` times
+pub fn sha256_chain(digest: [u8; DIGEST_BYTES], num_hashes: usize) -> [u8; DIGEST_BYTES] {
+    (0..num_hashes).fold(digest, |digest, _| Sha256::digest(digest).into())
+}
+
+pub fn digest_to_field<F: PrimeField>(digest: &[u8; DIGEST_BYTES]) -> Vec<F> {
+    digest
+        .chunks(HALF_BYTES)
+        .map(|half| F::from(u128::from_be_bytes(half.try_into().unwrap())))
+        .collect()
+}
+
+// each half always fits in the lowest 16 bytes of its representation
+pub fn field_to_digest<F: PrimeField>(z: &[F]) -> [u8; DIGEST_BYTES] {
+    let mut digest = [0u8; DIGEST_BYTES];
+    for (half, z) in digest.chunks_mut(HALF_BYTES).zip(z) {
+        let bytes = z.into_bigint().to_bytes_le();
+        let value = u128::from_le_bytes(bytes[..HALF_BYTES].try_into().unwrap());
+        half.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+// big-endian bytes to the field element they encode
+pub(crate) fn pack_bytes<F: PrimeField>(bytes: &[UInt8<F>]) -> Result<FpVar<F>, SynthesisError> {
+    let bits = bytes
+        .iter()
+        .rev()
+        .map(|byte| byte.to_bits_le())
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    Boolean::le_bits_to_fp_var(&bits)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sha256ChainCircuit<F: PrimeField> {
+    pub num_hashes_per_step: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> Sha256ChainCircuit<F> {
+    pub fn new(num_hashes_per_step: usize) -> Self {
+        Self {
+            num_hashes_per_step,
+            _f: PhantomData,
+        }
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for Sha256ChainCircuit<F> {
+    type Params = usize; // number of hashes per step
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params))
+    }
+
+    fn state_len(&self) -> usize {
+        2
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, _i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        let digest = sha256_chain(field_to_digest(&z_i), self.num_hashes_per_step);
+        Ok(digest_to_field(&digest))
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        // the value is missing while computing the R1CS shape, any digest will do there
+        let digest = z_i.value().map(|z| field_to_digest(&z)).unwrap_or_default();
+
+        let mut bytes = UInt8::new_witness_vec(cs.clone(), &digest)?;
+        for (half, z) in bytes.chunks(HALF_BYTES).zip(&z_i) {
+            pack_bytes(half)?.enforce_equal(z)?;
+        }
+
+        for _ in 0..self.num_hashes_per_step {
+            bytes = Sha256Gadget::digest(&bytes)?.0;
+        }
+
+        bytes.chunks(HALF_BYTES).map(pack_bytes).collect()
+    }
+}
+
+
+#[test]
+fn sha256_chain_step_test() {
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    // SHA256 of 32 zero bytes
+    let expected = "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925";
+    let digest = sha256_chain([0u8; DIGEST_BYTES], 1);
+    let hex = digest.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    assert_eq!(hex, expected);
+
+    let circuit = Sha256ChainCircuit::<Fr>::new(2);
+    let z_i = digest_to_field::<Fr>(&digest);
+    assert_eq!(field_to_digest(&z_i), digest);
+    let expected: [u8; DIGEST_BYTES] = Sha256::digest(Sha256::digest(digest)).into();
+    assert_eq!(circuit.step_native(0, z_i.clone(), vec![]).unwrap(), digest_to_field::<Fr>(&expected));
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+    let z_out = circuit.generate_step_constraints(cs.clone(), 0, z_in, vec![]).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(z_out.iter().map(|z| z.value().unwrap()).collect::<Vec<_>>(), digest_to_field::<Fr>(&expected));
+}
@@ -0,0 +1,178 @@
+//! Synthetic R1CS workload, to separate the costs driven by the witness size from the ones driven
+//! by the constraints: a step has exactly `num_constraints` constraints over exactly
+//! `num_witnesses` fresh witness variables, each row of `A` and `B` having `density` non-zero
+//! entries.
+//!
+//! The shape is the one of the Nova version: the first `min(num_witnesses, num_constraints)`
+//! constraints are products `out_j = A_j * B_j` over `z_i`, the advice and the previous products,
+//! the remaining witnesses are free advice and the remaining constraints re-check earlier products
+//! scaled by a random factor, `(s * A_k) * B_k = s * out_k`. The state is the last product.
+
+use ark_ff::PrimeField;
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use circuit_dsl::mix;
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+/// Terms of a row, as variable indices and coefficients. Variable 0 is `z_i`, the advice comes
+/// next, then the products.
+pub type Row = Vec<(usize, u64)>;
+
+fn eval<F: PrimeField>(row: &Row, values: &[F]) -> F {
+    row.iter().map(|(var, coeff)| values[*var] * F::from(*coeff)).sum()
+}
+
+fn lc<F: PrimeField>(row: &Row, vars: &[FpVar<F>]) -> FpVar<F> {
+    row.iter()
+        .fold(FpVar::zero(), |acc, (var, coeff)| acc + &vars[*var] * F::from(*coeff))
+}
+
+#[derive(Clone, Debug)]
+pub struct SyntheticCircuit<F: PrimeField> {
+    pub num_constraints: usize,
+    pub num_witnesses: usize,
+    pub density: usize,
+    pub num_advice: usize,
+    // rows of A and B of the products
+    pub products: Vec<(Row, Row)>,
+    // product re-checked by each of the remaining constraints, with its scale
+    pub rechecks: Vec<(usize, u64)>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> SyntheticCircuit<F> {
+    pub fn new(num_constraints: usize, num_witnesses: usize, density: usize) -> Self {
+        assert!(num_constraints > 0 && num_witnesses > 0 && density > 0);
+        let num_products = num_constraints.min(num_witnesses);
+        let num_advice = num_witnesses - num_products;
+
+        let mut counter = 0u64;
+        let mut next = || {
+            counter += 1;
+            mix(counter)
+        };
+        let products = (0..num_products)
+            .map(|j| {
+                // every variable allocated so far is available
+                let num_vars = (1 + num_advice + j) as u64;
+                let mut row = || {
+                    (0..density)
+                        .map(|_| ((next() % num_vars) as usize, 1 + next() % 0xffff))
+                        .collect::<Row>()
+                };
+                (row(), row())
+            })
+            .collect();
+        let rechecks = (num_products..num_constraints)
+            .map(|_| ((next() % num_products as u64) as usize, 2 + next() % 0xfffe))
+            .collect();
+
+        Self {
+            num_constraints,
+            num_witnesses,
+            density,
+            num_advice,
+            products,
+            rechecks,
+            _f: PhantomData,
+        }
+    }
+
+    /// The advice of step `i`.
+    pub fn advice(&self, i: usize) -> Vec<F> {
+        (0..self.num_advice)
+            .map(|k| F::from(mix((i * self.num_advice + k) as u64)))
+            .collect()
+    }
+
+    /// Values of all the variables of a step from `z_i` and the advice.
+    pub fn evaluate(&self, z: F, advice: &[F]) -> Vec<F> {
+        let mut values = vec![z];
+        values.extend(advice);
+        for (a, b) in &self.products {
+            let product = eval(a, &values) * eval(b, &values);
+            values.push(product);
+        }
+        values
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for SyntheticCircuit<F> {
+    type Params = (usize, usize, usize); // number of constraints, number of witnesses, density
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params.0, params.1, params.2))
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        Ok(vec![*self.evaluate(z_i[0], &self.advice(i)).last().unwrap()])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut vars = vec![z_i[0].clone()];
+        for value in self.advice(i) {
+            vars.push(FpVar::new_witness(cs.clone(), || Ok(value))?);
+        }
+
+        // each product allocates its output and enforces a single constraint
+        let mut out = z_i[0].clone();
+        for (a, b) in &self.products {
+            out = lc(a, &vars) * lc(b, &vars);
+            vars.push(out.clone());
+        }
+
+        let first_product = 1 + self.num_advice;
+        for (k, scale) in &self.rechecks {
+            let (a, b) = &self.products[*k];
+            let scale = F::from(*scale);
+            (lc(a, &vars) * scale).mul_equals(&lc(b, &vars), &(&vars[first_product + k] * scale))?;
+        }
+
+        Ok(vec![out])
+    }
+}
+
+#[test]
+fn synthetic_step_test() {
+    use ark_pallas::Fr;
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    // witness-heavy, balanced and constraint-heavy shapes
+    for (num_constraints, num_witnesses, density) in [(16, 64, 1), (32, 32, 4), (64, 16, 8)] {
+        let circuit = SyntheticCircuit::<Fr>::new(num_constraints, num_witnesses, density);
+        assert!(circuit
+            .products
+            .iter()
+            .all(|(a, b)| a.len() == density && b.len() == density));
+
+        let z_i = vec![Fr::from(3u64)];
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+        let z_out = circuit.generate_step_constraints(cs.clone(), 2, z_in, vec![]).unwrap();
+
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.num_constraints(), num_constraints);
+        // the step allocates its witnesses on top of z_i
+        assert_eq!(cs.num_witness_variables(), 1 + num_witnesses);
+        assert_eq!(vec![z_out[0].value().unwrap()], circuit.step_native(2, z_i, vec![]).unwrap());
+    }
+}
@@ -0,0 +1,209 @@
+//! Tree-structured (PCD-style) folding of a DSL step chain with HyperNova.
+//!
+//! The chain is split into `num_segments` (a power of two) leaf segments of `segment_len` steps.
+//! Each leaf is folded sequentially on its own thread, from its initial state computed natively,
+//! and adjacent accumulators are then merged level by level, the merges of a level also running
+//! in parallel. A merge is a step of the right prover that folds the running instance of the left
+//! one as a second LCCCS (`MU = 2`), so steps are laid out in order as
+//! `left ++ right ++ [merge step]` and a tree of `n` leaves covers `n * segment_len + n - 1` steps.
+//!
+//! The augmented circuit of this HyperNova folds the LCCCS of the left accumulator without
+//! linking its state to the one of the right prover, nor carrying its CycleFold instance, so a
+//! merge first checks the left accumulator with the HyperNova verifier, its CycleFold instance
+//! included, and that its last state is the first one of the right prover. The root is then a
+//! HyperNova IVC from the first state of the right half, the left halves having been verified at
+//! their merges: the tree is checked, but the final proof is not a succinct proof of the chain.
+
+use ark_pallas::{Fr, Projective};
+use ark_vesta::Projective as Projective2;
+use circuit_dsl::Step;
+use std::thread;
+
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::folding::hypernova::{
+    ProverParams as HyperNovaProverParams, VerifierParams as HyperNovaVerifierParams,
+};
+use folding_schemes::frontend::FCircuit;
+use folding_schemes::{Error, FoldingScheme, MultiFolding};
+
+use crate::dsl::DslCircuit;
+use crate::utils::HyperNovaPedersen;
+
+/// HyperNova folding one extra running instance per step, the left accumulator at merges.
+pub type TreeHyperNova<S> = HyperNovaPedersen<DslCircuit<Fr, S>, 2, 1>;
+
+pub type TreeParams = (
+    HyperNovaProverParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>, false>,
+    HyperNovaVerifierParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>, false>,
+);
+
+/// Index of the first step of leaf `s`, after the steps of the leaves and merges before it.
+pub fn leaf_first_step(s: usize, segment_len: usize) -> usize {
+    s * segment_len + s - s.count_ones() as usize
+}
+
+/// Number of steps covered by a tree of `num_segments` leaves of `segment_len` steps.
+pub fn num_tree_steps(num_segments: usize, segment_len: usize) -> usize {
+    num_segments * segment_len + num_segments - 1
+}
+
+/// Runs `num_steps` steps of the chain natively from `z_0`, starting at step `first_step`.
+pub fn native_chain<S: Step>(step: &S, z_0: Vec<Fr>, first_step: usize, num_steps: usize) -> Vec<Fr> {
+    let circuit = DslCircuit::<Fr, S>::new(step.clone()).unwrap().starting_at(first_step);
+    (0..num_steps).fold(z_0, |z_i, i| circuit.step_native(i, z_i, vec![]).unwrap())
+}
+
+// folds a leaf segment, the extra running instance being a fixed padding one built at its start
+fn prove_segment<S: Step>(
+    params: &TreeParams,
+    step: &S,
+    z_start: Vec<Fr>,
+    first_step: usize,
+    segment_len: usize,
+) -> Result<TreeHyperNova<S>, Error> {
+    let mut rng = ark_std::test_rng();
+    let circuit = DslCircuit::<Fr, S>::new(step.clone())?.starting_at(first_step);
+    let mut folding_scheme = TreeHyperNova::<S>::init(params, circuit, z_start)?;
+    let padding = folding_scheme.new_running_instance(&mut rng, folding_scheme.state(), vec![])?;
+
+    for _ in 0..segment_len {
+        folding_scheme.prove_step(&mut rng, vec![], Some((vec![padding.clone()], vec![])))?;
+    }
+    Ok(folding_scheme)
+}
+
+// folds the accumulator of `left` in the next step of `right`, once it is verified and its last
+// state is the first one of `right`
+fn merge<S: Step>(
+    params: &TreeParams,
+    left: TreeHyperNova<S>,
+    mut right: TreeHyperNova<S>,
+) -> Result<TreeHyperNova<S>, Error> {
+    let (running, incoming, cyclefold) = left.instances();
+    TreeHyperNova::<S>::verify(
+        params.1.clone(),
+        left.z_0.clone(),
+        left.state(),
+        left.i,
+        running,
+        incoming,
+        cyclefold,
+    )?;
+    if left.state() != right.z_0 {
+        return Err(Error::IVCVerificationFail);
+    }
+
+    let mut rng = ark_std::test_rng();
+    let running = (left.U_i.clone(), left.W_i.clone());
+    right.prove_step(&mut rng, vec![], Some((vec![running], vec![])))?;
+    Ok(right)
+}
+
+/// Folds the chain from `z_0` as a tree of `num_segments` leaves of `segment_len` steps, one thread
+/// per leaf then per merge, and returns the root scheme, whose state ends the chain and whose
+/// initial state starts the right half of the tree.
+pub fn tree_ivc<S: Step>(
+    params: &TreeParams,
+    step: &S,
+    z_0: Vec<Fr>,
+    num_segments: usize,
+    segment_len: usize,
+) -> Result<TreeHyperNova<S>, Error> {
+    assert!(num_segments.is_power_of_two(), "the number of segments must be a power of two");
+
+    // the initial states of the leaves, cheap next to folding
+    let mut z_start = z_0;
+    let mut leaves = Vec::with_capacity(num_segments);
+    for s in 0..num_segments {
+        let first_step = leaf_first_step(s, segment_len);
+        leaves.push((first_step, z_start.clone()));
+        // the leaf then the merges ending at it, one per trailing one bit of `s`
+        let num_steps = segment_len + s.trailing_ones() as usize;
+        z_start = native_chain(step, z_start, first_step, num_steps);
+    }
+
+    thread::scope(|scope| {
+        let mut level = leaves
+            .into_iter()
+            .map(|(first_step, z_start)| {
+                scope.spawn(move || prove_segment(params, step, z_start, first_step, segment_len))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().expect("segment prover panicked"))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        while level.len() > 1 {
+            let mut nodes = level.into_iter();
+            let mut handles = Vec::new();
+            while let (Some(left), Some(right)) = (nodes.next(), nodes.next()) {
+                handles.push(scope.spawn(move || merge(params, left, right)));
+            }
+            level = handles
+                .into_iter()
+                .map(|handle| handle.join().expect("merge prover panicked"))
+                .collect::<Result<Vec<_>, _>>()?;
+        }
+        Ok(level.remove(0))
+    })
+}
+
+#[test]
+fn leaf_first_step_test() {
+    // leaves and merges of 4 leaves of 2 steps: [0, 1] [2, 3] m4 [5, 6] [7, 8] m9 m10
+    let first_steps = (0..4).map(|s| leaf_first_step(s, 2)).collect::<Vec<_>>();
+    assert_eq!(first_steps, vec![0, 2, 5, 7]);
+    assert_eq!(num_tree_steps(4, 2), 11);
+
+    for num_segments in [1, 2, 4, 8] {
+        for segment_len in [1, 3] {
+            // a leaf starts after the previous one and the merges ending at it
+            let mut next_step = 0;
+            for s in 0..num_segments {
+                assert_eq!(leaf_first_step(s, segment_len), next_step);
+                next_step += segment_len + s.trailing_ones() as usize;
+            }
+            assert_eq!(next_step, num_tree_steps(num_segments, segment_len));
+        }
+    }
+}
+
+#[test]
+fn tree_ivc_test() {
+    use crate::utils::test_hypernova_setup;
+    use circuit_dsl::hashchain::HashChain;
+
+    let step = HashChain { num_elts_per_step: 2, seed: 0 };
+    let circuit = DslCircuit::<Fr, HashChain>::new(step).unwrap();
+    let params = test_hypernova_setup::<DslCircuit<Fr, HashChain>, 2, 1>(circuit);
+    let z_0 = vec![Fr::from(0u32)];
+
+    for num_segments in [2, 4] {
+        let segment_len = 2;
+        let root = tree_ivc(&params, &step, z_0.clone(), num_segments, segment_len).unwrap();
+        let num_steps = num_tree_steps(num_segments, segment_len);
+        assert_eq!(root.state(), native_chain(&step, z_0.clone(), 0, num_steps));
+
+        // the root proves the right half, the left one was verified at the last merge
+        let first_step = leaf_first_step(num_segments / 2, segment_len);
+        assert_eq!(root.z_0, native_chain(&step, z_0.clone(), 0, first_step));
+        let (running, incoming, cyclefold) = root.instances();
+        TreeHyperNova::<HashChain>::verify(
+            params.1.clone(),
+            root.z_0.clone(),
+            root.state(),
+            root.i,
+            running,
+            incoming,
+            cyclefold,
+        )
+        .unwrap();
+    }
+
+    // leaves that are not adjacent in the chain are not merged
+    let segment_len = 2;
+    let z_2 = native_chain(&step, z_0.clone(), 0, leaf_first_step(2, segment_len));
+    let left = prove_segment(&params, &step, z_0.clone(), 0, segment_len).unwrap();
+    let right = prove_segment(&params, &step, z_2, leaf_first_step(2, segment_len), segment_len).unwrap();
+    assert!(merge(&params, left, right).is_err());
+}
@@ -6,10 +6,60 @@
 use ark_pallas::{constraints::GVar, Fr, Projective};
 use ark_vesta::{constraints::GVar as GVar2, Projective as Projective2};
 
-use folding_schemes::commitment::{pedersen::Pedersen, CommitmentScheme};
-use folding_schemes::folding::nova::{get_r1cs, ProverParams, VerifierParams};
+use folding_schemes::commitment::pedersen::Pedersen;
+use folding_schemes::folding::hypernova::{
+    HyperNova, PreprocessorParam as HyperNovaPreprocessorParam,
+    ProverParams as HyperNovaProverParams, VerifierParams as HyperNovaVerifierParams,
+};
+use folding_schemes::folding::nova::{
+    Nova, PreprocessorParam as NovaPreprocessorParam, ProverParams, VerifierParams,
+};
+use folding_schemes::folding::protogalaxy::{
+    PreprocessorParam as ProtoGalaxyPreprocessorParam, ProtoGalaxy,
+    ProverParams as ProtoGalaxyProverParams, VerifierParams as ProtoGalaxyVerifierParams,
+};
 use folding_schemes::frontend::FCircuit;
-use folding_schemes::transcript::poseidon::poseidon_test_config;
+use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+use folding_schemes::{Error, FoldingScheme, MultiFolding};
+use ark_std::rand::RngCore;
+
+/// Nova over Pallas and Vesta with Pedersen commitments, as set up by [`test_nova_setup`].
+pub type NovaPedersen<FC> = Nova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    FC,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    false,
+>;
+
+/// HyperNova over the same curves and commitments as [`test_nova_setup`], folding `MU` running
+/// (LCCCS) and `NU` incoming (CCCS) instances per step.
+pub type HyperNovaPedersen<FC, const MU: usize, const NU: usize> = HyperNova<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    FC,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+    MU,
+    NU,
+    false,
+>;
+
+/// ProtoGalaxy over the same curves and commitments as [`test_nova_setup`].
+pub type ProtoGalaxyPedersen<FC> = ProtoGalaxy<
+    Projective,
+    GVar,
+    Projective2,
+    GVar2,
+    FC,
+    Pedersen<Projective>,
+    Pedersen<Projective2>,
+>;
 
 // This method computes the Nova's Prover & Verifier parameters for the example.
 // Warning: this method is only for testing purposes. For a real world use case those parameters
@@ -18,32 +68,121 @@ use folding_schemes::transcript::poseidon::poseidon_test_config;
 pub fn test_nova_setup<FC: FCircuit<Fr>>(
     F_circuit: FC,
 ) -> (
-    ProverParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>>,
-    VerifierParams<Projective, Projective2>,
+    ProverParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>, false>,
+    VerifierParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>, false>,
+) {
+    let mut rng = ark_std::test_rng();
+    let poseidon_config = poseidon_canonical_config::<Fr>();
+
+    let prep_param = NovaPreprocessorParam::new(poseidon_config, F_circuit);
+    NovaPedersen::<FC>::preprocess(&mut rng, &prep_param).unwrap()
+}
+
+// This method computes the HyperNova's Prover & Verifier parameters for the example, sizing the
+// commitment keys from the CCS of its augmented circuit.
+// Warning: this method is only for testing purposes, see `test_nova_setup`.
+#[allow(clippy::type_complexity)]
+pub fn test_hypernova_setup<FC: FCircuit<Fr>, const MU: usize, const NU: usize>(
+    F_circuit: FC,
+) -> (
+    HyperNovaProverParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>, false>,
+    HyperNovaVerifierParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>, false>,
+) {
+    let mut rng = ark_std::test_rng();
+    let poseidon_config = poseidon_canonical_config::<Fr>();
+
+    let prep_param = HyperNovaPreprocessorParam::new(poseidon_config, F_circuit);
+    HyperNovaPedersen::<FC, MU, NU>::preprocess(&mut rng, &prep_param).unwrap()
+}
+
+// This method computes the ProtoGalaxy's Prover & Verifier parameters for the example.
+// Warning: this method is only for testing purposes, see `test_nova_setup`.
+#[allow(clippy::type_complexity)]
+pub fn test_protogalaxy_setup<FC: FCircuit<Fr>>(
+    F_circuit: FC,
+) -> (
+    ProtoGalaxyProverParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>>,
+    ProtoGalaxyVerifierParams<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>>,
 ) {
     let mut rng = ark_std::test_rng();
-    let poseidon_config = poseidon_test_config::<Fr>();
-
-    // get the CM & CF_CM len
-    let (r1cs, cf_r1cs) =
-        get_r1cs::<Projective, GVar, Projective2, GVar2, FC>(&poseidon_config, F_circuit).unwrap();
-    let cf_len = r1cs.A.n_rows;
-    let cf_cf_len = cf_r1cs.A.n_rows;
-
-    let (pedersen_params, _) = Pedersen::<Projective>::setup(&mut rng, cf_len).unwrap();
-    let (cf_pedersen_params, _) = Pedersen::<Projective2>::setup(&mut rng, cf_cf_len).unwrap();
-
-    let prover_params =
-        ProverParams::<Projective, Projective2, Pedersen<Projective>, Pedersen<Projective2>> {
-            poseidon_config: poseidon_config.clone(),
-            cs_params: pedersen_params,
-            cf_cs_params: cf_pedersen_params,
-        };
-    let verifier_params = VerifierParams::<Projective, Projective2> {
-        poseidon_config: poseidon_config.clone(),
-        r1cs,
-        cf_r1cs,
-    };
-    (prover_params, verifier_params)
+    let poseidon_config = poseidon_canonical_config::<Fr>();
+
+    let prep_param = ProtoGalaxyPreprocessorParam::new(poseidon_config, F_circuit);
+    ProtoGalaxyPedersen::<FC>::preprocess(&mut rng, &prep_param).unwrap()
+}
+
+/// Chains of the step circuit folded next to the IVC one. Each is advanced natively from its own
+/// state, so the extra instances of a step are independent of the IVC state and of each other.
+#[derive(Clone, Debug)]
+pub struct OtherChains<FC: FCircuit<Fr>> {
+    circuit: FC,
+    i: usize,
+    pub states: Vec<Vec<Fr>>,
+}
+
+impl<FC: FCircuit<Fr>> OtherChains<FC> {
+    pub fn new(circuit: FC, initial_states: Vec<Vec<Fr>>) -> Self {
+        Self {
+            circuit,
+            i: 0,
+            states: initial_states,
+        }
+    }
+
+    // hands out the state of every chain at the current step and advances them to the next one
+    fn next(&mut self) -> Result<Vec<Vec<Fr>>, Error> {
+        let next = self
+            .states
+            .iter()
+            .map(|z| self.circuit.step_native(self.i, z.clone(), vec![]))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.i += 1;
+        Ok(std::mem::replace(&mut self.states, next))
+    }
+}
+
+// Proves one ProtoGalaxy step folding `k` instances into the running one: the IVC step itself and
+// one instance of each of the `k - 1` other chains.
+pub fn protogalaxy_prove_step<FC: FCircuit<Fr>>(
+    folding_scheme: &mut ProtoGalaxyPedersen<FC>,
+    other_chains: &mut OtherChains<FC>,
+    rng: &mut impl RngCore,
+) -> Result<(), Error> {
+    let other_instances = (!other_chains.states.is_empty())
+        .then(|| {
+            other_chains
+                .next()?
+                .into_iter()
+                .map(|z| folding_scheme.new_incoming_instance(&mut *rng, z, vec![]))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?;
+
+    folding_scheme.prove_step(rng, vec![], other_instances)
+}
+
+// Proves one HyperNova step folding `MU` running and `NU` incoming instances: the IVC step itself
+// and one instance of each of the `MU - 1 + NU - 1` other chains, the first `MU - 1` as running
+// instances.
+pub fn hypernova_prove_step<FC: FCircuit<Fr>, const MU: usize, const NU: usize>(
+    folding_scheme: &mut HyperNovaPedersen<FC, MU, NU>,
+    other_chains: &mut OtherChains<FC>,
+    rng: &mut impl RngCore,
+) -> Result<(), Error> {
+    assert_eq!(other_chains.states.len(), MU - 1 + NU - 1);
+    let mut states = other_chains.next()?;
+    let incoming_states = states.split_off(MU - 1);
+
+    let running_instances = states
+        .into_iter()
+        .map(|z| folding_scheme.new_running_instance(&mut *rng, z, vec![]))
+        .collect::<Result<Vec<_>, _>>()?;
+    let incoming_instances = incoming_states
+        .into_iter()
+        .map(|z| folding_scheme.new_incoming_instance(&mut *rng, z, vec![]))
+        .collect::<Result<Vec<_>, _>>()?;
+    let other_instances = (MU > 1 || NU > 1).then_some((running_instances, incoming_instances));
+
+    folding_scheme.prove_step(rng, vec![], other_instances)
 }
 fn main() {}
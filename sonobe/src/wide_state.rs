@@ -0,0 +1,89 @@
+//! Wide state counter, the IO hashing workload: each step increments every element of a state of
+//! configurable arity, `z_{i+1}[k] = z_i[k] + 1`. Every next element is a witness tied to its
+//! increment by a single constraint, as in the Nova version, so the growth of the per-step cost
+//! with the arity is dominated by the hashing of the state into the public IO of the augmented
+//! circuit.
+
+use ark_ff::PrimeField;
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+pub const MAX_ARITY: usize = 1024;
+
+#[derive(Clone, Copy, Debug)]
+pub struct WideStateCircuit<F: PrimeField> {
+    pub arity: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> WideStateCircuit<F> {
+    pub fn new(arity: usize) -> Self {
+        assert!((1..=MAX_ARITY).contains(&arity), "the arity ranges from 1 to {}", MAX_ARITY);
+        Self {
+            arity,
+            _f: PhantomData,
+        }
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for WideStateCircuit<F> {
+    type Params = usize; // state arity
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params))
+    }
+
+    fn state_len(&self) -> usize {
+        self.arity
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(&self, _i: usize, z_i: Vec<F>, _external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        Ok(z_i.into_iter().map(|z| z + F::one()).collect())
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        z_i.into_iter()
+            .map(|z| {
+                let z_next = FpVar::new_witness(cs.clone(), || Ok(z.value()? + F::one()))?;
+                z_next.enforce_equal(&(z + F::one()))?;
+                Ok(z_next)
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn wide_state_step_test() {
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    for arity in [1, 7, 64] {
+        let circuit = WideStateCircuit::<Fr>::new(arity);
+        let z_i = (0..arity).map(|k| Fr::from(k as u64)).collect::<Vec<_>>();
+        let z_i_plus_1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let z_out = circuit.generate_step_constraints(cs.clone(), 0, z_in, vec![]).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(z_out.value().unwrap(), z_i_plus_1);
+        // a single constraint per element, as in the Nova version
+        assert_eq!(cs.num_constraints(), arity);
+    }
+}
@@ -0,0 +1,602 @@
+//! Tiny register machine, the zkVM workload: every step fetches the instruction at `pc` from a
+//! program committed as a Poseidon Merkle tree, decodes it and executes it on eight 32-bit
+//! registers and a word-addressed memory, which is also committed as a Merkle tree and opened at
+//! every access. The state is `(program_root, pc, r0, ..., r7, memory_root)`.
+//!
+//! The step is uniform: it decodes every opcode and selects the result of the fetched one. The
+//! instruction and its authentication path follow from `pc` and the fixed program, while memory
+//! is history: as for the Merkle chain, the prover runs an [`Execution`] alongside the folding,
+//! which gives the opened memory word and its path as the external inputs of each step.
+
+use ark_crypto_primitives::crh::poseidon::constraints::CRHParametersVar;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::alloc::AllocVar;
+use ark_r1cs_std::boolean::Boolean;
+use ark_r1cs_std::eq::EqGadget;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_r1cs_std::select::CondSelectGadget;
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use folding_schemes::transcript::poseidon::poseidon_canonical_config;
+use folding_schemes::{frontend::FCircuit, Error};
+
+use crate::merkle::{compute_root, decompose, MerkleTree};
+
+pub const NUM_REGS: usize = 8;
+const REG_BITS: usize = 3;
+pub const WORD_BITS: usize = 32;
+/// Programs hold up to `2^PROGRAM_DEPTH` instructions.
+pub const PROGRAM_DEPTH: usize = 6;
+/// Memory holds `2^MEMORY_DEPTH` words, addresses wrap around.
+pub const MEMORY_DEPTH: usize = 10;
+pub const NUM_OPCODES: usize = 10;
+/// `(program_root, pc, registers, memory_root)`
+pub const STATE_LEN: usize = NUM_REGS + 3;
+
+// an instruction is encoded as `op | rd << 4 | rs1 << 7 | rs2 << 10 | imm << 13`
+const OPCODE_BITS: usize = 4;
+const RD_SHIFT: usize = OPCODE_BITS;
+const RS1_SHIFT: usize = RD_SHIFT + REG_BITS;
+const RS2_SHIFT: usize = RS1_SHIFT + REG_BITS;
+const IMM_SHIFT: usize = RS2_SHIFT + REG_BITS;
+
+/// `Halt` is encoded as zero, so the empty leaves past the end of a program halt the machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opcode {
+    Halt,
+    Add,
+    Sub,
+    Mul,
+    Addi,
+    Slt,
+    Load,
+    Store,
+    Bnz,
+    Jmp,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; NUM_OPCODES] = [
+        Opcode::Halt,
+        Opcode::Add,
+        Opcode::Sub,
+        Opcode::Mul,
+        Opcode::Addi,
+        Opcode::Slt,
+        Opcode::Load,
+        Opcode::Store,
+        Opcode::Bnz,
+        Opcode::Jmp,
+    ];
+}
+
+/// `imm` is an immediate for `Addi`, an address offset for `Load` and `Store` and a jump target
+/// for `Bnz` and `Jmp`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub op: Opcode,
+    pub rd: usize,
+    pub rs1: usize,
+    pub rs2: usize,
+    pub imm: u32,
+}
+
+impl Instruction {
+    fn new(op: Opcode, rd: usize, rs1: usize, rs2: usize, imm: u32) -> Self {
+        assert!(rd < NUM_REGS && rs1 < NUM_REGS && rs2 < NUM_REGS);
+        Self { op, rd, rs1, rs2, imm }
+    }
+
+    pub fn halt() -> Self {
+        Self::new(Opcode::Halt, 0, 0, 0, 0)
+    }
+
+    pub fn add(rd: usize, rs1: usize, rs2: usize) -> Self {
+        Self::new(Opcode::Add, rd, rs1, rs2, 0)
+    }
+
+    pub fn sub(rd: usize, rs1: usize, rs2: usize) -> Self {
+        Self::new(Opcode::Sub, rd, rs1, rs2, 0)
+    }
+
+    pub fn mul(rd: usize, rs1: usize, rs2: usize) -> Self {
+        Self::new(Opcode::Mul, rd, rs1, rs2, 0)
+    }
+
+    pub fn addi(rd: usize, rs1: usize, imm: i32) -> Self {
+        Self::new(Opcode::Addi, rd, rs1, 0, imm as u32)
+    }
+
+    // rd = rs1 < rs2, as unsigned words
+    pub fn slt(rd: usize, rs1: usize, rs2: usize) -> Self {
+        Self::new(Opcode::Slt, rd, rs1, rs2, 0)
+    }
+
+    // rd = mem[rs1 + offset]
+    pub fn load(rd: usize, rs1: usize, offset: u32) -> Self {
+        Self::new(Opcode::Load, rd, rs1, 0, offset)
+    }
+
+    // mem[rs1 + offset] = rs2
+    pub fn store(rs2: usize, rs1: usize, offset: u32) -> Self {
+        Self::new(Opcode::Store, 0, rs1, rs2, offset)
+    }
+
+    // jumps to `target` if rs1 != 0
+    pub fn bnz(rs1: usize, target: u32) -> Self {
+        Self::new(Opcode::Bnz, 0, rs1, 0, target)
+    }
+
+    pub fn jmp(target: u32) -> Self {
+        Self::new(Opcode::Jmp, 0, 0, 0, target)
+    }
+
+    pub fn encode(&self) -> u64 {
+        (self.op as u64)
+            | ((self.rd as u64) << RD_SHIFT)
+            | ((self.rs1 as u64) << RS1_SHIFT)
+            | ((self.rs2 as u64) << RS2_SHIFT)
+            | ((self.imm as u64) << IMM_SHIFT)
+    }
+
+    /// Executes the instruction at `pc` on `regs`, `word` being the memory word at its address,
+    /// and returns the next `pc` and the word written back to that address.
+    pub fn execute(&self, pc: u32, regs: &mut [u32; NUM_REGS], word: u32) -> (u32, u32) {
+        let (a, b) = (regs[self.rs1], regs[self.rs2]);
+        let result = match self.op {
+            Opcode::Add => Some(a.wrapping_add(b)),
+            Opcode::Sub => Some(a.wrapping_sub(b)),
+            Opcode::Mul => Some(a.wrapping_mul(b)),
+            Opcode::Addi => Some(a.wrapping_add(self.imm)),
+            Opcode::Slt => Some((a < b) as u32),
+            Opcode::Load => Some(word),
+            Opcode::Halt | Opcode::Store | Opcode::Bnz | Opcode::Jmp => None,
+        };
+        let next_pc = self.next_pc(pc, regs);
+        if let Some(result) = result {
+            regs[self.rd] = result;
+        }
+        (next_pc, if self.op == Opcode::Store { b } else { word })
+    }
+
+    pub fn next_pc(&self, pc: u32, regs: &[u32]) -> u32 {
+        match self.op {
+            Opcode::Halt => pc,
+            Opcode::Jmp => self.imm,
+            Opcode::Bnz if regs[self.rs1] != 0 => self.imm,
+            _ => pc + 1,
+        }
+    }
+}
+
+fn address(base: u32, offset: u32) -> usize {
+    base.wrapping_add(offset) as usize & ((1 << MEMORY_DEPTH) - 1)
+}
+
+/// A program together with its inputs, the initial registers and memory.
+#[derive(Clone, Debug)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+    pub registers: [u32; NUM_REGS],
+    pub memory: Vec<u32>,
+}
+
+impl Program {
+    /// The instruction at `pc`, past the end of the program the machine halts.
+    pub fn fetch(&self, pc: u32) -> Instruction {
+        self.instructions.get(pc as usize).copied().unwrap_or_else(Instruction::halt)
+    }
+
+    /// Stores the `n`-th Fibonacci number at address 0.
+    pub fn fibonacci(n: u32) -> Self {
+        let instructions = vec![
+            Instruction::addi(2, 0, 0),
+            Instruction::addi(3, 0, 1),
+            Instruction::bnz(1, 5),
+            Instruction::store(2, 0, 0),
+            Instruction::halt(),
+            // loop body, (r2, r3) = (r3, r2 + r3)
+            Instruction::add(4, 2, 3),
+            Instruction::addi(2, 3, 0),
+            Instruction::addi(3, 4, 0),
+            Instruction::addi(1, 1, -1),
+            Instruction::jmp(2),
+        ];
+        let mut registers = [0; NUM_REGS];
+        registers[1] = n;
+
+        Self {
+            instructions,
+            registers,
+            memory: Vec::new(),
+        }
+    }
+
+    /// Sorts `values`, which are laid out from address 0, in place.
+    pub fn bubble_sort(values: &[u32]) -> Self {
+        assert!(!values.is_empty());
+        let instructions = vec![
+            // r2 = i, the end of the unsorted prefix
+            Instruction::addi(2, 1, -1),
+            Instruction::bnz(2, 3),
+            Instruction::halt(),
+            // r3 = j, the inner loop runs while j < i
+            Instruction::addi(3, 0, 0),
+            Instruction::slt(4, 3, 2),
+            Instruction::bnz(4, 8),
+            Instruction::addi(2, 2, -1),
+            Instruction::jmp(1),
+            Instruction::load(5, 3, 0),
+            Instruction::load(6, 3, 1),
+            Instruction::slt(7, 6, 5),
+            Instruction::bnz(7, 14),
+            Instruction::addi(3, 3, 1),
+            Instruction::jmp(4),
+            // swap mem[j] and mem[j + 1]
+            Instruction::store(6, 3, 0),
+            Instruction::store(5, 3, 1),
+            Instruction::jmp(12),
+        ];
+        let mut registers = [0; NUM_REGS];
+        registers[1] = values.len() as u32;
+
+        Self {
+            instructions,
+            registers,
+            memory: values.to_vec(),
+        }
+    }
+}
+
+/// Native interpreter, the reference the circuit is checked against.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Machine {
+    pub pc: u32,
+    pub regs: [u32; NUM_REGS],
+    pub memory: Vec<u32>,
+}
+
+impl Machine {
+    pub fn new(program: &Program) -> Self {
+        assert!(program.instructions.len() <= 1 << PROGRAM_DEPTH);
+        assert!(program.memory.len() <= 1 << MEMORY_DEPTH);
+        let mut memory = program.memory.clone();
+        memory.resize(1 << MEMORY_DEPTH, 0);
+
+        Self {
+            pc: 0,
+            regs: program.registers,
+            memory,
+        }
+    }
+
+    pub fn fetch(&self, program: &Program) -> Instruction {
+        program.fetch(self.pc)
+    }
+
+    /// Executes one instruction and returns it, a halted machine stays in place.
+    pub fn step(&mut self, program: &Program) -> Instruction {
+        let ins = self.fetch(program);
+        let index = address(self.regs[ins.rs1], ins.imm);
+        let (pc, word) = ins.execute(self.pc, &mut self.regs, self.memory[index]);
+        self.pc = pc;
+        self.memory[index] = word;
+        ins
+    }
+
+    /// Runs `program` to completion, returning the final machine and the number of steps taken,
+    /// including the final `Halt`.
+    pub fn run(program: &Program, max_steps: usize) -> (Self, usize) {
+        let mut machine = Self::new(program);
+        for num_steps in 1..=max_steps {
+            if machine.step(program).op == Opcode::Halt {
+                return (machine, num_steps);
+            }
+        }
+        panic!("program did not halt within {} steps", max_steps);
+    }
+}
+
+/// The native machine and its memory tree, which the prover runs alongside the folding.
+#[derive(Clone, Debug)]
+pub struct Execution<F: PrimeField + Absorb> {
+    pub machine: Machine,
+    pub memory_tree: MerkleTree<F>,
+}
+
+impl<F: PrimeField + Absorb> Execution<F> {
+    /// Executes the instruction of the machine and returns the external inputs of its step, the
+    /// memory word at the accessed address before and after the step and its siblings. Steps that
+    /// don't write to memory rewrite the accessed word with itself.
+    pub fn step(&mut self, program: &Program) -> Vec<F> {
+        let ins = self.machine.fetch(program);
+        let index = address(self.machine.regs[ins.rs1], ins.imm);
+        self.machine.step(program);
+        let update = self
+            .memory_tree
+            .update(index as u64, F::from(self.machine.memory[index] as u64));
+
+        let mut inputs = vec![update.old_leaf, update.new_leaf];
+        inputs.extend(update.siblings);
+        inputs
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ZkvmCircuit<F: PrimeField + Absorb> {
+    pub program: Program,
+    program_tree: MerkleTree<F>,
+    // the machine and memory before the first step
+    initial: Execution<F>,
+}
+
+impl<F: PrimeField + Absorb> ZkvmCircuit<F> {
+    pub fn new(program: Program) -> Self {
+        let mut program_tree = MerkleTree::new(PROGRAM_DEPTH);
+        for (i, ins) in program.instructions.iter().enumerate() {
+            program_tree.update(i as u64, F::from(ins.encode()));
+        }
+        let machine = Machine::new(&program);
+        let mut memory_tree = MerkleTree::new(MEMORY_DEPTH);
+        for (i, word) in machine.memory.iter().enumerate().filter(|(_, word)| **word != 0) {
+            memory_tree.update(i as u64, F::from(*word as u64));
+        }
+
+        Self {
+            program,
+            program_tree,
+            initial: Execution { machine, memory_tree },
+        }
+    }
+
+    /// The execution of the program from its start, giving the external inputs of every step.
+    pub fn execution(&self) -> Execution<F> {
+        self.initial.clone()
+    }
+
+    /// `(program_root, pc, registers, memory_root)` before the first step.
+    pub fn state(&self) -> Vec<F> {
+        let machine = &self.initial.machine;
+        let mut z = vec![self.program_tree.root(), F::from(machine.pc as u64)];
+        z.extend(machine.regs.iter().map(|r| F::from(*r as u64)));
+        z.push(self.initial.memory_tree.root());
+        z
+    }
+}
+
+fn to_u32<F: PrimeField>(x: F) -> u32 {
+    x.into_bigint().as_ref()[0] as u32
+}
+
+// bits of a value known outside of the circuit, if known
+fn alloc_bits<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    value: Option<u64>,
+    num_bits: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    (0..num_bits)
+        .map(|k| {
+            Boolean::new_witness(cs.clone(), || {
+                value.map(|value| (value >> k) & 1 == 1).ok_or(SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect()
+}
+
+// returns `regs[index]`, with a tree of selections over the little-endian index bits
+fn read_register<F: PrimeField>(regs: &[FpVar<F>], index_bits: &[Boolean<F>]) -> Result<FpVar<F>, SynthesisError> {
+    let mut values = regs.to_vec();
+    for bit in index_bits {
+        values = values
+            .chunks(2)
+            .map(|pair| FpVar::conditionally_select(bit, &pair[1], &pair[0]))
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+    Ok(values.remove(0))
+}
+
+impl<F: PrimeField + Absorb> FCircuit<F> for ZkvmCircuit<F> {
+    type Params = Program;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::new(params))
+    }
+
+    fn state_len(&self) -> usize {
+        STATE_LEN
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        2 + MEMORY_DEPTH
+    }
+
+    fn step_native(&self, _i: usize, z_i: Vec<F>, external_inputs: Vec<F>) -> Result<Vec<F>, Error> {
+        let (pc, memory_root) = (to_u32(z_i[1]), z_i[STATE_LEN - 1]);
+        let mut regs = [0; NUM_REGS];
+        for (reg, z) in regs.iter_mut().zip(&z_i[2..2 + NUM_REGS]) {
+            *reg = to_u32(*z);
+        }
+        let (old_word, new_word, siblings) = (external_inputs[0], external_inputs[1], &external_inputs[2..]);
+
+        let ins = self.program.fetch(pc);
+        let index = address(regs[ins.rs1], ins.imm) as u64;
+        let memory_tree = &self.initial.memory_tree;
+        debug_assert_eq!(memory_tree.path_root(old_word, index, siblings), memory_root);
+        let (next_pc, word) = ins.execute(pc, &mut regs, to_u32(old_word));
+        debug_assert_eq!(F::from(word as u64), new_word);
+
+        let mut z = vec![z_i[0], F::from(next_pc as u64)];
+        z.extend(regs.iter().map(|r| F::from(*r as u64)));
+        z.push(memory_tree.path_root(new_word, index, siblings));
+        Ok(z)
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let (program_root, pc, regs, memory_root) =
+            (&z_i[0], &z_i[1], &z_i[2..2 + NUM_REGS], &z_i[STATE_LEN - 1]);
+        let (old_leaf, new_leaf, siblings) = (&external_inputs[0], &external_inputs[1], &external_inputs[2..]);
+        let params = CRHParametersVar::new_constant(cs.clone(), &poseidon_canonical_config::<F>())?;
+
+        // the instruction follows from pc, while computing the R1CS shape it may have no value
+        let pc_value = pc.value().ok().map(to_u32);
+        let ins = pc_value.map(|pc| self.program.fetch(pc));
+
+        // one-hot opcode flags
+        let flags = Opcode::ALL
+            .iter()
+            .map(|op| Boolean::new_witness(cs.clone(), || ins.map(|ins| ins.op == *op).ok_or(SynthesisError::AssignmentMissing)))
+            .collect::<Result<Vec<_>, _>>()?;
+        flags
+            .iter()
+            .fold(FpVar::zero(), |acc, flag| acc + FpVar::from(flag.clone()))
+            .enforce_equal(&FpVar::one())?;
+        let flag = |op: Opcode| flags[op as usize].clone();
+
+        // fetch the instruction at pc, its decomposition also range checks the fields
+        let pc_bits = decompose(cs.clone(), pc, PROGRAM_DEPTH)?;
+        let rd_bits = alloc_bits(cs.clone(), ins.map(|ins| ins.rd as u64), REG_BITS)?;
+        let rs1_bits = alloc_bits(cs.clone(), ins.map(|ins| ins.rs1 as u64), REG_BITS)?;
+        let rs2_bits = alloc_bits(cs.clone(), ins.map(|ins| ins.rs2 as u64), REG_BITS)?;
+        let imm_bits = alloc_bits(cs.clone(), ins.map(|ins| ins.imm as u64), WORD_BITS)?;
+        let imm = Boolean::le_bits_to_fp_var(&imm_bits)?;
+        let instruction = Opcode::ALL
+            .iter()
+            .fold(FpVar::zero(), |acc, op| acc + FpVar::from(flag(*op)) * F::from(*op as u64))
+            + Boolean::le_bits_to_fp_var(&rd_bits)? * F::from(1u64 << RD_SHIFT)
+            + Boolean::le_bits_to_fp_var(&rs1_bits)? * F::from(1u64 << RS1_SHIFT)
+            + Boolean::le_bits_to_fp_var(&rs2_bits)? * F::from(1u64 << RS2_SHIFT)
+            + &imm * F::from(1u64 << IMM_SHIFT);
+        let program_siblings = pc_value.map(|pc| self.program_tree.siblings(pc as u64));
+        let program_siblings = (0..PROGRAM_DEPTH)
+            .map(|level| {
+                FpVar::new_witness(cs.clone(), || {
+                    program_siblings.as_ref().map(|siblings| siblings[level]).ok_or(SynthesisError::AssignmentMissing)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        compute_root(&params, &instruction, &pc_bits, &program_siblings)?.enforce_equal(program_root)?;
+
+        let a = read_register(regs, &rs1_bits)?;
+        let b = read_register(regs, &rs2_bits)?;
+        let word = |bits: &[Boolean<F>]| Boolean::le_bits_to_fp_var(&bits[..WORD_BITS]);
+
+        // words are reduced by decomposing the full result and keeping its low bits
+        let sum_bits = decompose(cs.clone(), &(&a + &b), WORD_BITS + 1)?;
+        // the top bit of a - b + 2^32 is set iff a >= b
+        let diff_bits = decompose(cs.clone(), &(&a - &b + F::from(1u64 << WORD_BITS)), WORD_BITS + 1)?;
+        let product_bits = decompose(cs.clone(), &(&a * &b), 2 * WORD_BITS)?;
+        // a + imm is both the result of Addi and the address of Load and Store
+        let offset_bits = decompose(cs.clone(), &(&a + &imm), WORD_BITS + 1)?;
+
+        // every step opens memory at a + imm, only Store writes to it
+        let address_bits = &offset_bits[..MEMORY_DEPTH];
+        compute_root(&params, old_leaf, address_bits, siblings)?.enforce_equal(memory_root)?;
+        (new_leaf - old_leaf).enforce_equal(&(FpVar::from(flag(Opcode::Store)) * (&b - old_leaf)))?;
+        let new_memory_root = compute_root(&params, new_leaf, address_bits, siblings)?;
+
+        // write back the result of the executed opcode
+        let results = [
+            (Opcode::Add, word(&sum_bits)?),
+            (Opcode::Sub, word(&diff_bits)?),
+            (Opcode::Mul, word(&product_bits)?),
+            (Opcode::Addi, word(&offset_bits)?),
+            (Opcode::Slt, FpVar::one() - FpVar::from(diff_bits[WORD_BITS].clone())),
+            (Opcode::Load, old_leaf.clone()),
+        ];
+        let value = results
+            .iter()
+            .fold(FpVar::zero(), |acc, (op, result)| acc + FpVar::from(flag(*op)) * result);
+        let enable = results.iter().try_fold(Boolean::FALSE, |acc, (op, _)| acc.or(&flag(*op)))?;
+        let mut z_next = vec![program_root.clone()];
+        let new_regs = regs
+            .iter()
+            .enumerate()
+            .map(|(i, reg)| {
+                let literals = rd_bits
+                    .iter()
+                    .enumerate()
+                    .map(|(k, bit)| if (i >> k) & 1 == 1 { bit.clone() } else { bit.not() })
+                    .chain([enable.clone()])
+                    .collect::<Vec<_>>();
+                FpVar::conditionally_select(&Boolean::kary_and(&literals)?, &value, reg)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // pc moves to imm on Jmp and on Bnz with a non-zero register, and stays on Halt
+        let taken = flag(Opcode::Bnz).and(&a.is_neq(&FpVar::zero())?)?.or(&flag(Opcode::Jmp))?;
+        let next_pc = FpVar::conditionally_select(&taken, &imm, &(pc + FpVar::one()))?
+            - FpVar::from(flag(Opcode::Halt));
+
+        z_next.push(next_pc);
+        z_next.extend(new_regs);
+        z_next.push(new_memory_root);
+        Ok(z_next)
+    }
+}
+
+#[test]
+fn zkvm_step_test() {
+    use ark_pallas::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    let synthesize = |circuit: &ZkvmCircuit<Fr>, i: usize, z_i: &[Fr], external_inputs: &[Fr]| {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_in = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.to_vec())).unwrap();
+        let inputs = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs.to_vec())).unwrap();
+        let z_out = circuit.generate_step_constraints(cs.clone(), i, z_in, inputs).unwrap();
+        (cs.is_satisfied().unwrap(), z_out.iter().map(|z| z.value().unwrap()).collect::<Vec<_>>())
+    };
+
+    let values = [3, 1, 2];
+    for program in [Program::fibonacci(5), Program::bubble_sort(&values)] {
+        let (machine, num_steps) = Machine::run(&program, 1000);
+        let circuit = ZkvmCircuit::<Fr>::new(program.clone());
+        let mut execution = circuit.execution();
+        let mut z_i = circuit.state();
+        for i in 0..num_steps {
+            // the step circuit agrees with the native interpreter
+            let external_inputs = execution.step(&program);
+            let z_native = circuit.step_native(i, z_i.clone(), external_inputs.clone()).unwrap();
+            assert_eq!(z_native[1], Fr::from(execution.machine.pc as u64));
+            assert_eq!(z_native[2..2 + NUM_REGS], execution.machine.regs.map(|r| Fr::from(r as u64)));
+            assert_eq!(z_native[STATE_LEN - 1], execution.memory_tree.root());
+
+            let (satisfied, z_out) = synthesize(&circuit, i, &z_i, &external_inputs);
+            assert!(satisfied);
+            assert_eq!(z_out, z_native);
+            z_i = z_native;
+        }
+        assert_eq!(execution.machine, machine);
+    }
+
+    let (machine, _) = Machine::run(&Program::fibonacci(5), 1000);
+    assert_eq!(machine.memory[0], 5);
+    let (machine, _) = Machine::run(&Program::bubble_sort(&values), 1000);
+    assert_eq!(&machine.memory[..values.len()], &[1, 2, 3]);
+
+    // a load of a word that is not in the committed memory fails
+    let program = Program::bubble_sort(&values);
+    let circuit = ZkvmCircuit::<Fr>::new(program.clone());
+    let mut execution = circuit.execution();
+    let mut z_i = circuit.state();
+    let mut i = 0;
+    while execution.machine.fetch(&program).op != Opcode::Load {
+        let external_inputs = execution.step(&program);
+        z_i = circuit.step_native(i, z_i, external_inputs).unwrap();
+        i += 1;
+    }
+    let mut external_inputs = execution.step(&program);
+    assert!(synthesize(&circuit, i, &z_i, &external_inputs).0);
+    external_inputs[0] += Fr::from(1u64);
+    external_inputs[1] += Fr::from(1u64);
+    assert!(!synthesize(&circuit, i, &z_i, &external_inputs).0);
+}